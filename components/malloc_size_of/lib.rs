@@ -231,6 +231,12 @@ impl MallocSizeOf for String {
     }
 }
 
+impl MallocSizeOf for std::ffi::CString {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        unsafe { ops.malloc_size_of(self.as_ptr()) }
+    }
+}
+
 impl<'a, T: ?Sized> MallocSizeOf for &'a T {
     fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
         // Zero makes sense for a non-owning reference.
@@ -709,6 +715,17 @@ where
     }
 }
 
+impl<Impl: selectors::parser::SelectorImpl> MallocSizeOf
+    for selectors::parser::RelativeSelector<Impl>
+where
+    Impl::NonTSPseudoClass: MallocSizeOf,
+    Impl::PseudoElement: MallocSizeOf,
+{
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.selector.size_of(ops)
+    }
+}
+
 impl<Impl: selectors::parser::SelectorImpl> MallocSizeOf for selectors::parser::Component<Impl>
 where
     Impl::NonTSPseudoClass: MallocSizeOf,
@@ -724,9 +741,8 @@ where
             Component::Slotted(ref selector) | Component::Host(Some(ref selector)) => {
                 selector.size_of(ops)
             },
-            Component::Is(ref list) |
-            Component::Where(ref list) |
-            Component::Has(ref list) => list.size_of(ops),
+            Component::Is(ref list) | Component::Where(ref list) => list.size_of(ops),
+            Component::Has(ref relative_selectors) => relative_selectors.size_of(ops),
             Component::NthOf(ref nth_of_data) => nth_of_data.size_of(ops),
             Component::PseudoElement(ref pseudo) => (*pseudo).size_of(ops),
             Component::Combinator(..) |
@@ -745,6 +761,7 @@ where
             Component::Empty |
             Component::Scope |
             Component::ParentSelector |
+            Component::RelativeSelectorAnchor |
             Component::Nth(..) |
             Component::Host(None) => 0,
         }
@@ -813,7 +830,11 @@ malloc_size_of_is_0!(Range<f32>, Range<f64>);
 
 malloc_size_of_is_0!(app_units::Au);
 
-malloc_size_of_is_0!(cssparser::RGBA, cssparser::TokenSerializationType);
+malloc_size_of_is_0!(
+    cssparser::RGBA,
+    cssparser::TokenSerializationType,
+    cssparser::UnicodeRange
+);
 
 malloc_size_of_is_0!(dom::ElementState, dom::DocumentState);
 
@@ -17,7 +17,7 @@
 //! is non-trivial. This module encapsulates those details and presents an
 //! easy-to-use API for the parser.
 
-use crate::parser::{Combinator, Component, Selector, SelectorImpl};
+use crate::parser::{Combinator, Component, RelativeSelector, Selector, SelectorImpl};
 use crate::sink::Push;
 use servo_arc::{Arc, HeaderWithLength, ThinArc};
 use smallvec::{self, SmallVec};
@@ -90,6 +90,33 @@ impl<Impl: SelectorImpl> SelectorBuilder<Impl> {
         !self.combinators.is_empty()
     }
 
+    /// Returns true if a parent selector (`&`) has been pushed to this
+    /// builder, either directly or inside a nested selector list.
+    #[inline]
+    pub fn has_parent_selector(&self) -> bool {
+        specificity_and_flags(self.simple_selectors.iter()).has_parent_selector()
+    }
+
+    /// Returns true if a `:scope` pseudo-class has been pushed to the
+    /// compound selectors of this builder.
+    #[inline]
+    pub fn has_scope_selector(&self) -> bool {
+        self.simple_selectors
+            .iter()
+            .any(|s| matches!(*s, Component::Scope))
+    }
+
+    /// Prepends an implicit `& ` (a parent selector followed by a descendant
+    /// combinator) to the selector being built, as required for nested style
+    /// rules without an explicit parent selector.
+    pub fn prepend_implicit_parent_selector(&mut self) {
+        self.simple_selectors.insert(0, Component::ParentSelector);
+        // The length of the compound selector to the left of every existing
+        // combinator is unaffected, so we only need to account for the new
+        // leading compound.
+        self.combinators.insert(0, (Combinator::Descendant, 1));
+    }
+
     /// Consumes the builder, producing a Selector.
     #[inline(always)]
     pub fn build(&mut self) -> ThinArc<SpecificityAndFlags, Component<Impl>> {
@@ -274,7 +301,7 @@ where
         Impl: SelectorImpl,
     {
         match *simple_selector {
-            Component::Combinator(..) => {},
+            Component::Combinator(..) | Component::RelativeSelectorAnchor => {},
             Component::ParentSelector => flags.insert(SelectorFlags::HAS_PARENT),
             Component::Part(..) => {
                 flags.insert(SelectorFlags::HAS_PART);
@@ -335,13 +362,22 @@ where
                 *specificity += Specificity::from(sf.specificity);
                 flags.insert(sf.flags);
             },
+            Component::Has(ref relative_selectors) => {
+                // https://drafts.csswg.org/selectors/#specificity-rules:
+                //
+                //     The specificity of a :has() pseudo-class is replaced by
+                //     the specificity of the most specific complex selector in
+                //     its selector list argument.
+                let sf = relative_selector_list_specificity_and_flags(relative_selectors);
+                *specificity += Specificity::from(sf.specificity);
+                flags.insert(sf.flags);
+            },
             Component::Where(ref list) |
             Component::Negation(ref list) |
-            Component::Is(ref list) |
-            Component::Has(ref list) => {
+            Component::Is(ref list) => {
                 // https://drafts.csswg.org/selectors/#specificity-rules:
                 //
-                //     The specificity of an :is(), :not(), or :has() pseudo-class
+                //     The specificity of an :is() or :not() pseudo-class
                 //     is replaced by the specificity of the most specific complex
                 //     selector in its selector list argument.
                 let sf = selector_list_specificity_and_flags(list);
@@ -385,3 +421,20 @@ pub(crate) fn selector_list_specificity_and_flags<Impl: SelectorImpl>(
     }
     SpecificityAndFlags { specificity, flags }
 }
+
+/// Finds the maximum specificity of the relative selectors in the list and
+/// returns it.
+pub(crate) fn relative_selector_list_specificity_and_flags<Impl: SelectorImpl>(
+    list: &[RelativeSelector<Impl>],
+) -> SpecificityAndFlags {
+    let mut specificity = 0;
+    let mut flags = SelectorFlags::empty();
+    for relative_selector in list.iter() {
+        let selector = &relative_selector.selector;
+        specificity = std::cmp::max(specificity, selector.specificity());
+        if selector.has_parent_selector() {
+            flags.insert(SelectorFlags::HAS_PARENT);
+        }
+    }
+    SpecificityAndFlags { specificity, flags }
+}
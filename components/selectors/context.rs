@@ -6,6 +6,7 @@ use crate::attr::CaseSensitivity;
 use crate::bloom::BloomFilter;
 use crate::nth_index_cache::{NthIndexCache, NthIndexCacheInner};
 use crate::parser::{Selector, SelectorImpl};
+use crate::relative_selector::cache::RelativeSelectorCache;
 use crate::tree::{Element, OpaqueElement};
 
 /// What kind of selector matching mode we should use.
@@ -76,6 +77,24 @@ pub enum NeedsSelectorFlags {
     Yes,
 }
 
+/// Whether we're matching in the context of invalidation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchingForInvalidation {
+    No,
+    Yes,
+}
+
+/// Whether rules inside `@starting-style` should be matched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IncludeStartingStyle {
+    /// Only match the rules outside `@starting-style`, which is what we do for
+    /// regular styling.
+    No,
+    /// Match the rules inside `@starting-style` too, in order to compute the
+    /// starting style of an element.
+    Yes,
+}
+
 /// Which quirks mode is this document in.
 ///
 /// See: https://quirks.spec.whatwg.org/
@@ -99,6 +118,16 @@ impl QuirksMode {
     }
 }
 
+/// A set of caches (and cache-likes) that speed up expensive selector matches.
+#[derive(Default)]
+pub struct SelectorCaches {
+    /// A cache to speed up nth-index-like selectors.
+    pub nth_index: NthIndexCache,
+    /// A cache to speed up relative selector matches. See the comment on
+    /// `RelativeSelectorCache` for more details.
+    pub relative_selector: RelativeSelectorCache,
+}
+
 /// Data associated with the matching process for a element.  This context is
 /// used across many selectors for an element, so it's not appropriate for
 /// transient data that applies to only a single selector.
@@ -110,8 +139,8 @@ where
     matching_mode: MatchingMode,
     /// Input with the bloom filter used to fast-reject selectors.
     pub bloom_filter: Option<&'a BloomFilter>,
-    /// A cache to speed up nth-index-like selectors.
-    pub nth_index_cache: &'a mut NthIndexCache,
+    /// Caches to speed up expensive selector matches.
+    pub selector_caches: &'a mut SelectorCaches,
     /// The element which is going to match :scope pseudo-class. It can be
     /// either one :scope element, or the scoping element.
     ///
@@ -140,6 +169,10 @@ where
     /// Whether we're inside a negation or not.
     in_negation: bool,
 
+    /// The anchor element of the relative selector (the argument of `:has()`)
+    /// that we're matching, if any.
+    current_relative_selector_anchor: Option<OpaqueElement>,
+
     /// An optional hook function for checking whether a pseudo-element
     /// should match when matching_mode is ForStatelessPseudoElement.
     pub pseudo_element_matching_fn: Option<&'a dyn Fn(&Impl::PseudoElement) -> bool>,
@@ -147,8 +180,16 @@ where
     /// Extra implementation-dependent matching data.
     pub extra_data: Impl::ExtraMatchingData<'a>,
 
+    /// Whether rules inside `@starting-style` should be matched.
+    pub include_starting_style: IncludeStartingStyle,
+
+    /// Whether any rule inside `@starting-style` matched, which is set by the
+    /// rule collection code.
+    pub has_starting_style: bool,
+
     quirks_mode: QuirksMode,
     needs_selector_flags: NeedsSelectorFlags,
+    matching_for_invalidation: MatchingForInvalidation,
     classes_and_ids_case_sensitivity: CaseSensitivity,
     _impl: ::std::marker::PhantomData<Impl>,
 }
@@ -161,17 +202,19 @@ where
     pub fn new(
         matching_mode: MatchingMode,
         bloom_filter: Option<&'a BloomFilter>,
-        nth_index_cache: &'a mut NthIndexCache,
+        selector_caches: &'a mut SelectorCaches,
         quirks_mode: QuirksMode,
         needs_selector_flags: NeedsSelectorFlags,
+        matching_for_invalidation: MatchingForInvalidation,
     ) -> Self {
         Self::new_for_visited(
             matching_mode,
             bloom_filter,
-            nth_index_cache,
+            selector_caches,
             VisitedHandlingMode::AllLinksUnvisited,
             quirks_mode,
             needs_selector_flags,
+            matching_for_invalidation,
         )
     }
 
@@ -179,25 +222,30 @@ where
     pub fn new_for_visited(
         matching_mode: MatchingMode,
         bloom_filter: Option<&'a BloomFilter>,
-        nth_index_cache: &'a mut NthIndexCache,
+        selector_caches: &'a mut SelectorCaches,
         visited_handling: VisitedHandlingMode,
         quirks_mode: QuirksMode,
         needs_selector_flags: NeedsSelectorFlags,
+        matching_for_invalidation: MatchingForInvalidation,
     ) -> Self {
         Self {
             matching_mode,
             bloom_filter,
             visited_handling,
-            nth_index_cache,
+            selector_caches,
             quirks_mode,
             classes_and_ids_case_sensitivity: quirks_mode.classes_and_ids_case_sensitivity(),
             needs_selector_flags,
+            matching_for_invalidation,
             scope_element: None,
             current_host: None,
             nesting_level: 0,
             in_negation: false,
+            current_relative_selector_anchor: None,
             pseudo_element_matching_fn: None,
             extra_data: Default::default(),
+            include_starting_style: IncludeStartingStyle::No,
+            has_starting_style: false,
             _impl: ::std::marker::PhantomData,
         }
     }
@@ -210,7 +258,9 @@ where
         is_from_end: bool,
         selectors: &[Selector<Impl>],
     ) -> &mut NthIndexCacheInner {
-        self.nth_index_cache.get(is_of_type, is_from_end, selectors)
+        self.selector_caches
+            .nth_index
+            .get(is_of_type, is_from_end, selectors)
     }
 
     /// Whether we're matching a nested selector.
@@ -243,6 +293,12 @@ where
         self.needs_selector_flags == NeedsSelectorFlags::Yes
     }
 
+    /// Whether we're matching to invalidate.
+    #[inline]
+    pub fn matching_for_invalidation(&self) -> bool {
+        self.matching_for_invalidation == MatchingForInvalidation::Yes
+    }
+
     /// The case-sensitivity for class and ID selectors
     #[inline]
     pub fn classes_and_ids_case_sensitivity(&self) -> CaseSensitivity {
@@ -275,6 +331,31 @@ where
         result
     }
 
+    /// Runs F with a deeper nesting level, with the given element as the
+    /// anchor of the relative selectors being matched, for a :has(..)
+    /// selector.
+    #[inline]
+    pub fn nest_for_relative_selector<F, R>(&mut self, anchor: OpaqueElement, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        debug_assert!(
+            self.current_relative_selector_anchor.is_none(),
+            "Nested relative selector"
+        );
+        self.current_relative_selector_anchor = Some(anchor);
+        let result = self.nest(f);
+        self.current_relative_selector_anchor = None;
+        result
+    }
+
+    /// Returns the current anchor element of the relative selector being
+    /// matched, if any.
+    #[inline]
+    pub fn relative_selector_anchor(&self) -> Option<OpaqueElement> {
+        self.current_relative_selector_anchor
+    }
+
     #[inline]
     pub fn visited_handling(&self) -> VisitedHandlingMode {
         self.visited_handling
@@ -312,6 +393,20 @@ where
         result
     }
 
+    /// Runs F with a given scope element, which `:scope` and `&` resolve to,
+    /// like the scoping root of a scoped style rule.
+    #[inline]
+    pub fn with_scope_element<F, R>(&mut self, scope: Option<OpaqueElement>, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let original_scope_element = self.scope_element.take();
+        self.scope_element = scope;
+        let result = f(self);
+        self.scope_element = original_scope_element;
+        result
+    }
+
     /// Returns the current shadow host whose shadow root we're matching rules
     /// against.
     #[inline]
@@ -9,6 +9,8 @@ use crate::attr::{
 use crate::bloom::{BloomFilter, BLOOM_HASH_MASK};
 use crate::parser::{AncestorHashes, Combinator, Component, LocalName, NthSelectorData};
 use crate::parser::{NonTSPseudoClass, Selector, SelectorImpl, SelectorIter, SelectorList};
+use crate::parser::{RelativeSelector, RelativeSelectorMatchHint};
+use crate::relative_selector::cache::RelativeSelectorCachedMatch;
 use crate::tree::Element;
 use smallvec::SmallVec;
 use std::borrow::Borrow;
@@ -339,23 +341,103 @@ pub fn list_matches_complex_selector<E: Element>(
     false
 }
 
-/// Traverse all descendents of the given element and return true as soon as any of them match
-/// the given list of selectors.
-fn has_children_matching<E: Element>(
-    selectors: &[Selector<E::Impl>],
+/// Matches a list of relative selectors (the argument of `:has()`) with the
+/// given element as their anchor.
+fn matches_relative_selectors<E: Element>(
+    selectors: &[RelativeSelector<E::Impl>],
+    element: &E,
+    context: &mut MatchingContext<E::Impl>,
+) -> bool {
+    // The DOM may be in the middle of changing while invalidating, so the
+    // cache can't be trusted (nor filled) then.
+    let use_cache = !context.matching_for_invalidation();
+    for relative_selector in selectors.iter() {
+        if use_cache {
+            if let Some(cached) = context
+                .selector_caches
+                .relative_selector
+                .lookup(element.opaque(), relative_selector)
+            {
+                if cached.matched() {
+                    return true;
+                }
+                continue;
+            }
+        }
+        let matched = matches_relative_selector(relative_selector, element, context);
+        if use_cache {
+            context.selector_caches.relative_selector.add(
+                element.opaque(),
+                relative_selector,
+                if matched {
+                    RelativeSelectorCachedMatch::Matched
+                } else {
+                    RelativeSelectorCachedMatch::NotMatched
+                },
+            );
+        }
+        if matched {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Matches a single relative selector, only looking at the elements that can
+/// possibly match it according to its match hint.
+fn matches_relative_selector<E: Element>(
+    relative_selector: &RelativeSelector<E::Impl>,
+    element: &E,
+    context: &mut MatchingContext<E::Impl>,
+) -> bool {
+    let hint = relative_selector.match_hint;
+    let mut candidate = if hint.is_descendant_direction() {
+        element.first_element_child()
+    } else {
+        element.next_sibling_element()
+    };
+    // Siblings of the anchor can't match selectors that need to be looked for
+    // in their subtree, e.g. `:has(~ .a .b)`, only their descendants can.
+    let candidate_may_match = !matches!(
+        hint,
+        RelativeSelectorMatchHint::InNextSiblingSubtree |
+            RelativeSelectorMatchHint::InSiblingSubtree
+    );
+
+    while let Some(el) = candidate {
+        if candidate_may_match &&
+            matches_complex_selector(relative_selector.selector.iter(), &el, context)
+        {
+            return true;
+        }
+        if hint.is_subtree() && matches_relative_selector_subtree(relative_selector, &el, context) {
+            return true;
+        }
+        if hint.is_next_sibling() {
+            break;
+        }
+        candidate = el.next_sibling_element();
+    }
+
+    false
+}
+
+/// Matches a relative selector against all the descendants of the given
+/// element.
+fn matches_relative_selector_subtree<E: Element>(
+    relative_selector: &RelativeSelector<E::Impl>,
     element: &E,
     context: &mut MatchingContext<E::Impl>,
 ) -> bool {
     let mut current = element.first_element_child();
 
     while let Some(el) = current {
-        for selector in selectors {
-            if matches_complex_selector(selector.iter(), &el, context) {
-                return true;
-            }
+        if matches_complex_selector(relative_selector.selector.iter(), &el, context) {
+            return true;
         }
 
-        if has_children_matching(selectors, &el, context) {
+        if matches_relative_selector_subtree(relative_selector, &el, context) {
             return true;
         }
 
@@ -808,10 +890,12 @@ where
                         .nest(|context| matches_complex_selector(selector.iter(), element, context))
                 })
         },
-        // These should only work at parse time, should be replaced with :is() at CascadeData build
-        // time.
-        Component::ParentSelector => false,
-        Component::Scope => match context.shared.scope_element {
+        // Nested parent selectors get replaced with :is() at CascadeData
+        // build time, so the only ones left here are those in top-level
+        // rules, which behave like :scope.
+        //
+        // https://drafts.csswg.org/css-nesting/#nest-selector
+        Component::ParentSelector | Component::Scope => match context.shared.scope_element {
             Some(ref scope_element) => element.opaque() == *scope_element,
             None => element.is_root(),
         },
@@ -832,7 +916,18 @@ where
             .nest_for_negation(|context| !list_matches_complex_selector(list, element, context)),
         Component::Has(ref list) => context
             .shared
-            .nest(|context| has_children_matching(list, element, context)),
+            .nest_for_relative_selector(element.opaque(), |context| {
+                matches_relative_selectors(list, element, context)
+            }),
+        Component::RelativeSelectorAnchor => {
+            // Outside of relative selector matching (e.g., when invalidating
+            // from the subject of a relative selector), any element could be
+            // the anchor.
+            context
+                .shared
+                .relative_selector_anchor()
+                .map_or(true, |anchor| anchor == element.opaque())
+        },
         Component::Combinator(_) => unsafe {
             debug_unreachable!("Shouldn't try to selector-match combinators")
         },
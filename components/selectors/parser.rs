@@ -7,8 +7,8 @@ use crate::attr::{NamespaceConstraint, ParsedAttrSelectorOperation};
 use crate::attr::{ParsedCaseSensitivity, SELECTOR_WHITESPACE};
 use crate::bloom::BLOOM_HASH_MASK;
 use crate::builder::{
-    selector_list_specificity_and_flags, SelectorBuilder, SelectorFlags, Specificity,
-    SpecificityAndFlags,
+    relative_selector_list_specificity_and_flags, selector_list_specificity_and_flags,
+    SelectorBuilder, SelectorFlags, Specificity, SpecificityAndFlags,
 };
 use crate::context::QuirksMode;
 use crate::sink::Push;
@@ -113,6 +113,9 @@ bitflags! {
 
         /// Whether we explicitly disallow pseudo-element-like things.
         const DISALLOW_PSEUDOS = 1 << 6;
+
+        /// Whether we explicitly disallow relative selectors (i.e. `:has()`).
+        const DISALLOW_RELATIVE_SELECTOR = 1 << 7;
     }
 }
 
@@ -156,6 +159,11 @@ impl SelectorParsingState {
     fn allows_combinators(self) -> bool {
         !self.intersects(Self::DISALLOW_COMBINATORS)
     }
+
+    #[inline]
+    fn allows_relative_selectors(self) -> bool {
+        !self.intersects(Self::DISALLOW_RELATIVE_SELECTOR)
+    }
 }
 
 pub type SelectorParseError<'i> = ParseError<'i, SelectorParseErrorKind<'i>>;
@@ -348,6 +356,33 @@ pub trait Parser<'i> {
     }
 }
 
+/// Whether the selector being parsed is relative to some other selector.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseRelative {
+    /// Parse a selector relative to the parent selector of a nested style
+    /// rule. A leading combinator is allowed, and a selector without an
+    /// explicit `&` gets an implicit `& ` prepended.
+    ///
+    /// https://drafts.csswg.org/css-nesting/#syntax
+    ForNesting,
+    /// Parse a relative selector, the argument of `:has()`. A leading
+    /// combinator is allowed, and an implicit descendant combinator is
+    /// assumed otherwise. The selector is anchored to the element `:has()` is
+    /// matched against.
+    ///
+    /// https://drafts.csswg.org/selectors/#relative
+    ForHas,
+    /// Parse a selector of a style rule directly inside an `@scope` rule. A
+    /// leading combinator is allowed, and a selector containing neither `&`
+    /// nor `:scope` gets an implicit `& ` prepended, where `&` represents the
+    /// scoping root.
+    ///
+    /// https://drafts.csswg.org/css-cascade-6/#scoped-rules
+    ForScope,
+    /// Treat as a regular, non-relative selector.
+    No,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, ToShmem)]
 #[shmem(no_bounds)]
 pub struct SelectorList<Impl: SelectorImpl>(
@@ -373,6 +408,7 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
     pub fn parse<'i, 't, P>(
         parser: &P,
         input: &mut CssParser<'i, 't>,
+        parse_relative: ParseRelative,
     ) -> Result<Self, ParseError<'i, P::Error>>
     where
         P: Parser<'i, Impl = Impl>,
@@ -382,6 +418,7 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
             input,
             SelectorParsingState::empty(),
             ParseErrorRecovery::DiscardList,
+            parse_relative,
         )
     }
 
@@ -391,6 +428,7 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
         input: &mut CssParser<'i, 't>,
         state: SelectorParsingState,
         recovery: ParseErrorRecovery,
+        parse_relative: ParseRelative,
     ) -> Result<Self, ParseError<'i, P::Error>>
     where
         P: Parser<'i, Impl = Impl>,
@@ -398,7 +436,7 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
         let mut values = SmallVec::new();
         loop {
             let selector = input.parse_until_before(Delimiter::Comma, |input| {
-                parse_selector(parser, input, state)
+                parse_selector(parser, input, state, parse_relative)
             });
 
             let was_ok = selector.is_ok();
@@ -426,6 +464,28 @@ impl<Impl: SelectorImpl> SelectorList<Impl> {
         }
     }
 
+    /// Returns a selector list with a single `&` selector, which is what
+    /// declarations directly inside a nested group rule are wrapped with.
+    pub fn ampersand() -> Self {
+        let mut builder = SelectorBuilder::default();
+        builder.push_simple_selector(Component::ParentSelector);
+        SelectorList(smallvec::smallvec![Selector(builder.build())])
+    }
+
+    /// Replaces the parent selector (`&`) in all the selectors of this list
+    /// by `:is(<parent>)`.
+    pub fn replace_parent_selector(&self, parent: &[Selector<Impl>]) -> Cow<Self> {
+        if !self.0.iter().any(|s| s.has_parent_selector()) {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(SelectorList(
+            self.0
+                .iter()
+                .map(|s| s.replace_parent_selector(parent).into_owned())
+                .collect(),
+        ))
+    }
+
     /// Creates a SelectorList from a Vec of selectors. Used in tests.
     #[allow(dead_code)]
     pub(crate) fn from_vec(v: Vec<Selector<Impl>>) -> Self {
@@ -447,6 +507,7 @@ where
         parser,
         input,
         state | SelectorParsingState::DISALLOW_PSEUDOS | SelectorParsingState::DISALLOW_COMBINATORS,
+        ParseRelative::No,
     )
 }
 
@@ -636,6 +697,16 @@ impl<Impl: SelectorImpl> Selector<Impl> {
         self.0.header.header.has_parent_selector()
     }
 
+    /// Whether this is a relative selector, i.e. the argument of a `:has()`
+    /// pseudo-class, which starts with its anchor.
+    #[inline]
+    pub fn is_relative(&self) -> bool {
+        matches!(
+            self.iter_raw_parse_order_from(0).next(),
+            Some(&Component::RelativeSelectorAnchor)
+        )
+    }
+
     #[inline]
     pub fn is_slotted(&self) -> bool {
         self.0.header.header.is_slotted()
@@ -857,6 +928,38 @@ impl<Impl: SelectorImpl> Selector<Impl> {
             result
         }
 
+        fn replace_parent_on_relative_selector_list<Impl: SelectorImpl>(
+            orig: &[RelativeSelector<Impl>],
+            parent: &[Selector<Impl>],
+            specificity: &mut Specificity,
+        ) -> Vec<RelativeSelector<Impl>> {
+            let mut any = false;
+
+            let result = orig
+                .iter()
+                .map(|s| {
+                    if !s.selector.has_parent_selector() {
+                        return s.clone();
+                    }
+                    any = true;
+                    RelativeSelector {
+                        match_hint: s.match_hint,
+                        selector: s.selector.replace_parent_selector(parent).into_owned(),
+                    }
+                })
+                .collect();
+
+            if !any {
+                return result;
+            }
+
+            *specificity += Specificity::from(
+                relative_selector_list_specificity_and_flags(&result).specificity -
+                    relative_selector_list_specificity_and_flags(orig).specificity,
+            );
+            result
+        }
+
         fn replace_parent_on_selector<Impl: SelectorImpl>(
             orig: &Selector<Impl>,
             parent: &[Selector<Impl>],
@@ -891,7 +994,8 @@ impl<Impl: SelectorImpl> Selector<Impl> {
                 PseudoElement(..) |
                 Combinator(..) |
                 Host(None) |
-                Part(..) => component.clone(),
+                Part(..) |
+                RelativeSelectorAnchor => component.clone(),
                 ParentSelector => {
                     specificity += parent_specificity;
                     Is(parent.to_vec().into_boxed_slice())
@@ -927,15 +1031,12 @@ impl<Impl: SelectorImpl> Selector<Impl> {
                         .into_boxed_slice(),
                     )
                 },
-                Has(ref selectors) => {
-                    Has(replace_parent_on_selector_list(
-                        selectors,
-                        parent,
-                        &mut specificity,
-                        /* with_specificity = */ true,
-                    )
-                    .into_boxed_slice())
-                },
+                Has(ref selectors) => Has(replace_parent_on_relative_selector_list(
+                    selectors,
+                    parent,
+                    &mut specificity,
+                )
+                .into_boxed_slice()),
 
                 Host(Some(ref selector)) => Host(Some(replace_parent_on_selector(
                     selector,
@@ -1357,6 +1458,123 @@ impl<Impl: SelectorImpl> NthOfSelectorData<Impl> {
     }
 }
 
+/// Where to look for elements that may match a relative selector, relative to
+/// its anchor element, as determined by its combinators.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ToShmem)]
+pub enum RelativeSelectorMatchHint {
+    /// Only the children of the anchor, e.g. `:has(> .foo)`.
+    InChild,
+    /// The whole subtree of the anchor, e.g. `:has(.foo)`.
+    InSubtree,
+    /// Only the next sibling of the anchor, e.g. `:has(+ .foo)`.
+    InNextSibling,
+    /// The subtree of the next sibling of the anchor, e.g.
+    /// `:has(+ .foo .bar)`.
+    InNextSiblingSubtree,
+    /// All the later siblings of the anchor, e.g. `:has(~ .foo)`.
+    InSibling,
+    /// The subtrees of all the later siblings of the anchor, e.g.
+    /// `:has(~ .foo .bar)`.
+    InSiblingSubtree,
+}
+
+impl RelativeSelectorMatchHint {
+    /// Computes the match hint of a relative selector, given its leading
+    /// combinator and the rest of its combinators, in parse order.
+    pub fn new(
+        relative_combinator: Combinator,
+        mut combinators: impl Iterator<Item = Combinator>,
+    ) -> Self {
+        // Sibling combinators only widen the search if they appear before any
+        // descendant-like combinator, since past that point we're searching
+        // the whole subtree anyway.
+        let mut sibling = relative_combinator == Combinator::LaterSibling;
+        let subtree = combinators.any(|combinator| {
+            if combinator.is_sibling() {
+                sibling = true;
+                return false;
+            }
+            true
+        });
+        match relative_combinator {
+            Combinator::Descendant => RelativeSelectorMatchHint::InSubtree,
+            Combinator::Child if subtree => RelativeSelectorMatchHint::InSubtree,
+            Combinator::Child => RelativeSelectorMatchHint::InChild,
+            _ => match (sibling, subtree) {
+                (false, false) => RelativeSelectorMatchHint::InNextSibling,
+                (false, true) => RelativeSelectorMatchHint::InNextSiblingSubtree,
+                (true, false) => RelativeSelectorMatchHint::InSibling,
+                (true, true) => RelativeSelectorMatchHint::InSiblingSubtree,
+            },
+        }
+    }
+
+    /// Whether elements matching the relative selector are descendants of the
+    /// anchor.
+    #[inline]
+    pub fn is_descendant_direction(&self) -> bool {
+        matches!(
+            *self,
+            RelativeSelectorMatchHint::InChild | RelativeSelectorMatchHint::InSubtree
+        )
+    }
+
+    /// Whether the elements matching the relative selector may be found
+    /// anywhere in the subtree of the elements the search starts from.
+    #[inline]
+    pub fn is_subtree(&self) -> bool {
+        matches!(
+            *self,
+            RelativeSelectorMatchHint::InSubtree |
+                RelativeSelectorMatchHint::InNextSiblingSubtree |
+                RelativeSelectorMatchHint::InSiblingSubtree
+        )
+    }
+
+    /// Whether the search starts from the next sibling of the anchor only.
+    #[inline]
+    pub fn is_next_sibling(&self) -> bool {
+        matches!(
+            *self,
+            RelativeSelectorMatchHint::InNextSibling |
+                RelativeSelectorMatchHint::InNextSiblingSubtree
+        )
+    }
+}
+
+/// A relative selector, i.e. one of the arguments of `:has()`.
+///
+/// The selector starts with a `Component::RelativeSelectorAnchor`, followed by
+/// the (possibly implicit) leading combinator, so that matching it from a
+/// candidate element leads back to the anchor.
+///
+/// https://drafts.csswg.org/selectors/#relative
+#[derive(Clone, Eq, PartialEq, ToShmem)]
+#[shmem(no_bounds)]
+pub struct RelativeSelector<Impl: SelectorImpl> {
+    /// Where to look for elements matching this selector.
+    pub match_hint: RelativeSelectorMatchHint,
+    /// The selector, including the anchor.
+    #[shmem(field_bound)]
+    pub selector: Selector<Impl>,
+}
+
+impl<Impl: SelectorImpl> RelativeSelector<Impl> {
+    fn from_selector(selector: Selector<Impl>) -> Self {
+        let mut combinators = selector
+            .iter_raw_parse_order_from(0)
+            .filter_map(|component| component.as_combinator());
+        let relative_combinator = combinators
+            .next()
+            .expect("Relative selectors should have a leading combinator");
+        let match_hint = RelativeSelectorMatchHint::new(relative_combinator, combinators);
+        Self {
+            match_hint,
+            selector,
+        }
+    }
+}
+
 /// A CSS simple selector or combinator. We store both in the same enum for
 /// optimal packing and cache performance, see [1].
 ///
@@ -1402,6 +1620,10 @@ pub enum Component<Impl: SelectorImpl> {
     Empty,
     Scope,
     ParentSelector,
+    /// The anchor element of a relative selector, which is the element the
+    /// `:has()` pseudo-class containing it is matched against. This is never
+    /// serialized.
+    RelativeSelectorAnchor,
     Nth(NthSelectorData),
     NthOf(NthOfSelectorData<Impl>),
     NonTSPseudoClass(#[shmem(field_bound)] Impl::NonTSPseudoClass),
@@ -1447,8 +1669,9 @@ pub enum Component<Impl: SelectorImpl> {
     ///
     /// https://drafts.csswg.org/selectors/#has-pseudo
     ///
-    /// Same comment as above re. the argument.
-    Has(Box<[Selector<Impl>]>),
+    /// Same comment as above re. the argument, which is a list of relative
+    /// selectors.
+    Has(Box<[RelativeSelector<Impl>]>),
     /// An implementation-dependent pseudo-element selector.
     PseudoElement(#[shmem(field_bound)] Impl::PseudoElement),
 
@@ -1600,6 +1823,11 @@ impl<Impl: SelectorImpl> Component<Impl> {
                     return false;
                 }
             },
+            Has(ref list) => {
+                if !visitor.visit_relative_selector_list(list) {
+                    return false;
+                }
+            },
             _ => {},
         }
 
@@ -1706,6 +1934,23 @@ impl<Impl: SelectorImpl> ToCss for Selector<Impl> {
                 continue;
             }
 
+            // The anchor of a relative selector isn't serialized, and neither
+            // is the implicit descendant combinator after it.
+            if let Component::RelativeSelectorAnchor = compound[0] {
+                debug_assert_eq!(
+                    compound.len(),
+                    1,
+                    "RelativeSelectorAnchor should be alone in its compound"
+                );
+                match combinators.next() {
+                    Some(Combinator::Child) => dest.write_str("> ")?,
+                    Some(Combinator::NextSibling) => dest.write_str("+ ")?,
+                    Some(Combinator::LaterSibling) => dest.write_str("~ ")?,
+                    _ => {},
+                }
+                continue;
+            }
+
             // 1. If there is only one simple selector in the compound selectors
             //    which is a universal selector, append the result of
             //    serializing the universal selector to s.
@@ -1923,17 +2168,22 @@ impl<Impl: SelectorImpl> ToCss for Component<Impl> {
                 serialize_selector_list(nth_of_data.selectors().iter(), dest)?;
                 dest.write_char(')')
             },
-            Is(ref list) | Where(ref list) | Negation(ref list) | Has(ref list) => {
+            Is(ref list) | Where(ref list) | Negation(ref list) => {
                 match *self {
                     Where(..) => dest.write_str(":where(")?,
                     Is(..) => dest.write_str(":is(")?,
                     Negation(..) => dest.write_str(":not(")?,
-                    Has(..) => dest.write_str(":has(")?,
                     _ => unreachable!(),
                 }
                 serialize_selector_list(list.iter(), dest)?;
                 dest.write_str(")")
             },
+            Has(ref list) => {
+                dest.write_str(":has(")?;
+                serialize_selector_list(list.iter().map(|rel| &rel.selector), dest)?;
+                dest.write_str(")")
+            },
+            RelativeSelectorAnchor => Ok(()),
             NonTSPseudoClass(ref pseudo) => pseudo.to_css(dest),
         }
     }
@@ -1994,6 +2244,7 @@ fn parse_selector<'i, 't, P, Impl>(
     parser: &P,
     input: &mut CssParser<'i, 't>,
     mut state: SelectorParsingState,
+    parse_relative: ParseRelative,
 ) -> Result<Selector<Impl>, ParseError<'i, P::Error>>
 where
     P: Parser<'i, Impl = Impl>,
@@ -2001,6 +2252,26 @@ where
 {
     let mut builder = SelectorBuilder::default();
 
+    match parse_relative {
+        ParseRelative::ForNesting | ParseRelative::ForScope => {
+            // A nested selector may start with a combinator, in which case it
+            // is relative to the parent selector.
+            if let Some(combinator) = try_parse_leading_combinator(input) {
+                builder.push_simple_selector(Component::ParentSelector);
+                builder.push_combinator(combinator);
+            }
+        },
+        ParseRelative::ForHas => {
+            // A relative selector always starts with a combinator, the
+            // descendant one if none is specified.
+            let combinator =
+                try_parse_leading_combinator(input).unwrap_or(Combinator::Descendant);
+            builder.push_simple_selector(Component::RelativeSelectorAnchor);
+            builder.push_combinator(combinator);
+        },
+        ParseRelative::No => {},
+    }
+
     'outer_loop: loop {
         // Parse a sequence of simple selectors.
         let empty = parse_compound_selector(parser, &mut state, input, &mut builder)?;
@@ -2060,9 +2331,31 @@ where
         builder.push_combinator(combinator);
     }
 
+    let needs_implicit_parent_selector = match parse_relative {
+        ParseRelative::ForNesting => !builder.has_parent_selector(),
+        ParseRelative::ForScope => !builder.has_parent_selector() && !builder.has_scope_selector(),
+        ParseRelative::ForHas | ParseRelative::No => false,
+    };
+    if needs_implicit_parent_selector {
+        builder.prepend_implicit_parent_selector();
+    }
+
     Ok(Selector(builder.build()))
 }
 
+/// Tries to parse a combinator at the start of a relative selector, like the
+/// `>` in `> .foo`.
+fn try_parse_leading_combinator<'i, 't>(input: &mut CssParser<'i, 't>) -> Option<Combinator> {
+    input
+        .try_parse(|input| match input.next() {
+            Ok(&Token::Delim('>')) => Ok(Combinator::Child),
+            Ok(&Token::Delim('+')) => Ok(Combinator::NextSibling),
+            Ok(&Token::Delim('~')) => Ok(Combinator::LaterSibling),
+            _ => Err(()),
+        })
+        .ok()
+}
+
 impl<Impl: SelectorImpl> Selector<Impl> {
     /// Parse a selector, without any pseudo-element.
     #[inline]
@@ -2073,7 +2366,7 @@ impl<Impl: SelectorImpl> Selector<Impl> {
     where
         P: Parser<'i, Impl = Impl>,
     {
-        parse_selector(parser, input, SelectorParsingState::empty())
+        parse_selector(parser, input, SelectorParsingState::empty(), ParseRelative::No)
     }
 }
 
@@ -2482,6 +2775,7 @@ where
             SelectorParsingState::SKIP_DEFAULT_NAMESPACE |
             SelectorParsingState::DISALLOW_PSEUDOS,
         ParseErrorRecovery::DiscardList,
+        ParseRelative::No,
     )?;
 
     Ok(Component::Negation(list.0.into_vec().into_boxed_slice()))
@@ -2586,7 +2880,7 @@ where
     Ok(empty)
 }
 
-fn parse_is_where_has<'i, 't, P, Impl>(
+fn parse_is_where<'i, 't, P, Impl>(
     parser: &P,
     input: &mut CssParser<'i, 't>,
     state: SelectorParsingState,
@@ -2609,10 +2903,48 @@ where
             SelectorParsingState::SKIP_DEFAULT_NAMESPACE |
             SelectorParsingState::DISALLOW_PSEUDOS,
         ParseErrorRecovery::IgnoreInvalidSelector,
+        ParseRelative::No,
     )?;
     Ok(component(inner.0.into_vec().into_boxed_slice()))
 }
 
+/// Parses the argument of `:has()`, which is an unforgiving list of relative
+/// selectors.
+///
+/// https://drafts.csswg.org/selectors/#has-pseudo
+fn parse_has<'i, 't, P, Impl>(
+    parser: &P,
+    input: &mut CssParser<'i, 't>,
+    state: SelectorParsingState,
+) -> Result<Component<Impl>, ParseError<'i, P::Error>>
+where
+    P: Parser<'i, Impl = Impl>,
+    Impl: SelectorImpl,
+{
+    debug_assert!(parser.parse_has());
+    // :has() can't be nested, and pseudo-elements aren't valid inside it.
+    if !state.allows_relative_selectors() {
+        return Err(input.new_custom_error(SelectorParseErrorKind::InvalidState));
+    }
+    let inner = SelectorList::parse_with_state(
+        parser,
+        input,
+        state |
+            SelectorParsingState::SKIP_DEFAULT_NAMESPACE |
+            SelectorParsingState::DISALLOW_PSEUDOS |
+            SelectorParsingState::DISALLOW_RELATIVE_SELECTOR,
+        ParseErrorRecovery::DiscardList,
+        ParseRelative::ForHas,
+    )?;
+    Ok(Component::Has(
+        inner
+            .0
+            .into_iter()
+            .map(RelativeSelector::from_selector)
+            .collect(),
+    ))
+}
+
 fn parse_functional_pseudo_class<'i, 't, P, Impl>(
     parser: &P,
     input: &mut CssParser<'i, 't>,
@@ -2628,9 +2960,9 @@ where
         "nth-of-type" => return parse_nth_pseudo_class(parser, input, state, NthType::OfType),
         "nth-last-child" => return parse_nth_pseudo_class(parser, input, state, NthType::LastChild),
         "nth-last-of-type" => return parse_nth_pseudo_class(parser, input, state, NthType::LastOfType),
-        "is" if parser.parse_is_and_where() => return parse_is_where_has(parser, input, state, Component::Is),
-        "where" if parser.parse_is_and_where() => return parse_is_where_has(parser, input, state, Component::Where),
-        "has" if parser.parse_has() => return parse_is_where_has(parser, input, state, Component::Has),
+        "is" if parser.parse_is_and_where() => return parse_is_where(parser, input, state, Component::Is),
+        "where" if parser.parse_is_and_where() => return parse_is_where(parser, input, state, Component::Where),
+        "has" if parser.parse_has() => return parse_has(parser, input, state),
         "host" => {
             if !state.allows_tree_structural_pseudo_classes() {
                 return Err(input.new_custom_error(SelectorParseErrorKind::InvalidState));
@@ -2644,7 +2976,7 @@ where
     }
 
     if parser.parse_is_and_where() && parser.is_is_alias(&name) {
-        return parse_is_where_has(parser, input, state, Component::Is);
+        return parse_is_where(parser, input, state, Component::Is);
     }
 
     if !state.allows_custom_functional_pseudo_classes() {
@@ -2691,6 +3023,7 @@ where
             SelectorParsingState::SKIP_DEFAULT_NAMESPACE |
             SelectorParsingState::DISALLOW_PSEUDOS,
         ParseErrorRecovery::DiscardList,
+        ParseRelative::No,
     )?;
     Ok(Component::NthOf(NthOfSelectorData::new(
         &nth_data,
@@ -3174,7 +3507,11 @@ pub mod tests {
         expected: Option<&'a str>,
     ) -> Result<SelectorList<DummySelectorImpl>, SelectorParseError<'i>> {
         let mut parser_input = ParserInput::new(input);
-        let result = SelectorList::parse(parser, &mut CssParser::new(&mut parser_input));
+        let result = SelectorList::parse(
+            parser,
+            &mut CssParser::new(&mut parser_input),
+            ParseRelative::No,
+        );
         if let Ok(ref selectors) = result {
             // We can't assume that the serialized parsed selector will equal
             // the input; for example, if there is no default namespace, '*|foo'
@@ -3190,6 +3527,17 @@ pub mod tests {
         result
     }
 
+    fn parse_nested<'i>(
+        input: &'i str,
+    ) -> Result<SelectorList<DummySelectorImpl>, SelectorParseError<'i>> {
+        let mut parser_input = ParserInput::new(input);
+        SelectorList::parse(
+            &DummyParser::default(),
+            &mut CssParser::new(&mut parser_input),
+            ParseRelative::ForNesting,
+        )
+    }
+
     fn specificity(a: u32, b: u32, c: u32) -> u32 {
         a << 20 | b << 10 | c
     }
@@ -3197,7 +3545,11 @@ pub mod tests {
     #[test]
     fn test_empty() {
         let mut input = ParserInput::new(":empty");
-        let list = SelectorList::parse(&DummyParser::default(), &mut CssParser::new(&mut input));
+        let list = SelectorList::parse(
+            &DummyParser::default(),
+            &mut CssParser::new(&mut input),
+            ParseRelative::No,
+        );
         assert!(list.is_ok());
     }
 
@@ -3717,6 +4069,123 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn nested_relative_selectors() {
+        assert_eq!(parse_nested(".foo").unwrap().to_css_string(), "& .foo");
+        assert_eq!(parse_nested("> .foo").unwrap().to_css_string(), "& > .foo");
+        assert_eq!(parse_nested("+ .foo").unwrap().to_css_string(), "& + .foo");
+        assert_eq!(parse_nested("~ .foo").unwrap().to_css_string(), "& ~ .foo");
+        assert_eq!(parse_nested(".foo &").unwrap().to_css_string(), ".foo &");
+        assert_eq!(parse_nested("&.foo").unwrap().to_css_string(), "&.foo");
+        assert_eq!(parse_nested(":is(&) .foo").unwrap().to_css_string(), ":is(&) .foo");
+        assert_eq!(
+            parse_nested(".foo, > .bar .baz").unwrap().to_css_string(),
+            "& .foo, & > .bar .baz"
+        );
+        assert!(parse_nested("> > .foo").is_err());
+        assert!(parse_nested(">").is_err());
+
+        let parent = parse(".bar, div .baz").unwrap();
+        let child = parse_nested("> .foo").unwrap();
+        assert_eq!(
+            SelectorList::from_vec(vec![child.0[0].replace_parent_selector(&parent.0).into_owned()]),
+            parse(":is(.bar, div .baz) > .foo").unwrap()
+        );
+
+        let list = SelectorList::<DummySelectorImpl>::ampersand();
+        assert_eq!(list.to_css_string(), "&");
+        assert_eq!(
+            *list.replace_parent_selector(&parent.0),
+            parse(":is(.bar, div .baz)").unwrap()
+        );
+    }
+
+    #[test]
+    fn scoped_relative_selectors() {
+        let parse_scoped = |input| {
+            let mut parser_input = ParserInput::new(input);
+            SelectorList::<DummySelectorImpl>::parse(
+                &DummyParser::default(),
+                &mut CssParser::new(&mut parser_input),
+                ParseRelative::ForScope,
+            )
+            .map(|list| list.to_css_string())
+        };
+        assert_eq!(parse_scoped(".foo"), Ok("& .foo".into()));
+        assert_eq!(parse_scoped("> .foo"), Ok("& > .foo".into()));
+        assert_eq!(parse_scoped(":scope > .foo"), Ok(":scope > .foo".into()));
+        assert_eq!(parse_scoped(".foo :scope"), Ok(".foo :scope".into()));
+        assert_eq!(parse_scoped("&.foo"), Ok("&.foo".into()));
+        assert!(parse_scoped("> > .foo").is_err());
+
+        // The implicit `&` doesn't contribute to the specificity.
+        let mut parser_input = ParserInput::new(".foo");
+        let list = SelectorList::parse(
+            &DummyParser::default(),
+            &mut CssParser::new(&mut parser_input),
+            ParseRelative::ForScope,
+        )
+        .unwrap();
+        assert_eq!(list.0[0].specificity(), specificity(0, 1, 0));
+    }
+
+    #[test]
+    fn relative_selectors() {
+        assert!(parse("div:has(> img)").is_ok());
+        assert!(parse("div:has(+ .x)").is_ok());
+        assert!(parse("div:has(~ .y)").is_ok());
+        assert!(parse("div:has(.a, > .b .c)").is_ok());
+        assert!(parse_expected("div:has( .a)", Some("div:has(.a)")).is_ok());
+        assert!(parse("div:has()").is_err());
+        assert!(parse("div:has(>)").is_err());
+        assert!(parse("div:has(> > img)").is_err());
+        assert!(parse("div:has(::before)").is_err());
+        assert!(parse("div:has(.a, ::before)").is_err());
+        assert!(parse("div:has(:has(.a))").is_err());
+        // :is() is forgiving, so the nested :has() just gets dropped.
+        assert!(parse_expected("div:has(:is(:has(.a)))", Some("div:has(:is())")).is_ok());
+
+        let has = |input: &'static str| -> Vec<RelativeSelectorMatchHint> {
+            let list = parse(input).unwrap();
+            let selector = &list.0[0];
+            match *selector.iter().next().unwrap() {
+                Component::Has(ref list) => list.iter().map(|s| s.match_hint).collect(),
+                ref other => panic!("Expected :has(), got {:?}", other),
+            }
+        };
+        assert_eq!(has(":has(> img)"), vec![RelativeSelectorMatchHint::InChild]);
+        assert_eq!(has(":has(> .a img)"), vec![RelativeSelectorMatchHint::InSubtree]);
+        assert_eq!(has(":has(img)"), vec![RelativeSelectorMatchHint::InSubtree]);
+        assert_eq!(has(":has(+ .x)"), vec![RelativeSelectorMatchHint::InNextSibling]);
+        assert_eq!(
+            has(":has(+ .x > .y)"),
+            vec![RelativeSelectorMatchHint::InNextSiblingSubtree]
+        );
+        assert_eq!(has(":has(~ .y)"), vec![RelativeSelectorMatchHint::InSibling]);
+        assert_eq!(has(":has(+ .x ~ .y)"), vec![RelativeSelectorMatchHint::InSibling]);
+        assert_eq!(
+            has(":has(~ .x .y)"),
+            vec![RelativeSelectorMatchHint::InSiblingSubtree]
+        );
+        assert_eq!(
+            has(":has(+ .x ~ .y .z)"),
+            vec![RelativeSelectorMatchHint::InSiblingSubtree]
+        );
+
+        // The specificity of :has() is the one of its most specific argument.
+        assert_eq!(
+            parse("div:has(> #a, .b .c)").unwrap().0[0].specificity(),
+            specificity(1, 0, 1)
+        );
+
+        let parent = parse(".bar").unwrap();
+        let child = parse(".foo:has(> &)").unwrap();
+        assert_eq!(
+            SelectorList::from_vec(vec![child.0[0].replace_parent_selector(&parent.0).into_owned()]),
+            parse(".foo:has(> :is(.bar))").unwrap()
+        );
+    }
+
     #[test]
     fn test_pseudo_iter() {
         let selector = &parse("q::before").unwrap().0[0];
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use fxhash::FxHashMap;
+use std::os::raw::c_void;
+
+use crate::parser::{RelativeSelector, SelectorImpl};
+use crate::tree::OpaqueElement;
+
+/// The result of matching a relative selector against an anchor element.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RelativeSelectorCachedMatch {
+    /// The relative selector matched.
+    Matched,
+    /// The relative selector didn't match.
+    NotMatched,
+}
+
+impl RelativeSelectorCachedMatch {
+    /// Whether this is a match.
+    #[inline]
+    pub fn matched(self) -> bool {
+        matches!(self, RelativeSelectorCachedMatch::Matched)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Key {
+    element: OpaqueElement,
+    selector: *const c_void,
+}
+
+impl Key {
+    // The heap allocation of the relative selector is shared by all its
+    // clones, and lives as long as the selector does, unlike the address of the
+    // `RelativeSelector` itself, which may move.
+    fn new<Impl: SelectorImpl>(element: OpaqueElement, selector: &RelativeSelector<Impl>) -> Self {
+        Self {
+            element,
+            selector: selector.selector.thin_arc_heap_ptr(),
+        }
+    }
+}
+
+/// A cache of the results of matching relative selectors against anchor
+/// elements.
+///
+/// Without it, matching `:has(.foo)` against every element of a deep tree
+/// would walk the same subtrees over and over again. Like the nth-index cache,
+/// this is only valid as long as the DOM doesn't change.
+#[derive(Default)]
+pub struct RelativeSelectorCache {
+    cache: FxHashMap<Key, RelativeSelectorCachedMatch>,
+}
+
+impl RelativeSelectorCache {
+    /// Records the result of matching `selector` with `anchor` as its anchor.
+    pub fn add<Impl: SelectorImpl>(
+        &mut self,
+        anchor: OpaqueElement,
+        selector: &RelativeSelector<Impl>,
+        matched: RelativeSelectorCachedMatch,
+    ) {
+        self.cache.insert(Key::new(anchor, selector), matched);
+    }
+
+    /// Looks up the result of matching `selector` with `anchor` as its anchor.
+    pub fn lookup<Impl: SelectorImpl>(
+        &self,
+        anchor: OpaqueElement,
+        selector: &RelativeSelector<Impl>,
+    ) -> Option<RelativeSelectorCachedMatch> {
+        self.cache.get(&Key::new(anchor, selector)).cloned()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
@@ -7,7 +7,7 @@
 #![deny(missing_docs)]
 
 use crate::attr::NamespaceConstraint;
-use crate::parser::{Combinator, Component, Selector, SelectorImpl};
+use crate::parser::{Combinator, Component, RelativeSelector, Selector, SelectorImpl};
 
 /// A trait to visit selector properties.
 ///
@@ -47,6 +47,20 @@ pub trait SelectorVisitor: Sized {
         true
     }
 
+    /// Visit a list of relative selectors, i.e. the argument of `:has()`. The
+    /// caller is responsible to call visit into the internal selectors if / as
+    /// needed.
+    ///
+    /// The default implementation does this.
+    fn visit_relative_selector_list(&mut self, list: &[RelativeSelector<Self::Impl>]) -> bool {
+        for nested in list {
+            if !nested.selector.visit(self) {
+                return false;
+            }
+        }
+        true
+    }
+
     /// Visits a complex selector.
     ///
     /// Gets the combinator to the right of the selector, or `None` if the
@@ -8,16 +8,23 @@
 // compile it out so that people remember it exists.
 
 use crate::context::{CascadeInputs, SharedStyleContext};
+use crate::custom_properties::{Name as CustomPropertyName, VariableValue};
 use crate::dom::{OpaqueNode, TDocument, TElement, TNode};
+use crate::logical_geometry::WritingMode;
 use crate::properties::animated_properties::{AnimationValue, AnimationValueMap};
+use crate::properties::longhands::animation_composition::computed_value::single_value::T as AnimationComposition;
 use crate::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
 use crate::properties::longhands::animation_fill_mode::computed_value::single_value::T as AnimationFillMode;
 use crate::properties::longhands::animation_play_state::computed_value::single_value::T as AnimationPlayState;
-use crate::properties::AnimationDeclarations;
+#[cfg(feature = "servo")]
+use crate::properties::longhands::transition_behavior::computed_value::single_value::T as TransitionBehavior;
+use crate::properties::{style_structs, AnimationDeclarations};
 use crate::properties::{
-    ComputedValues, Importance, LonghandId, LonghandIdSet, PropertyDeclarationBlock,
-    PropertyDeclarationId,
+    ComputedValues, CustomDeclaration, CustomDeclarationValue, Importance, LonghandId,
+    LonghandIdSet, PropertyDeclaration, PropertyDeclarationBlock, PropertyDeclarationId,
 };
+use crate::properties_and_values::registry::PropertyRegistration;
+use crate::properties_and_values::value::ComputedValue as RegisteredComputedValue;
 use crate::rule_tree::CascadeLevel;
 use crate::selector_parser::PseudoElement;
 use crate::shared_lock::{Locked, SharedRwLock};
@@ -25,13 +32,21 @@ use crate::style_resolver::StyleResolverForElement;
 use crate::stylesheets::keyframes_rule::{KeyframesAnimation, KeyframesStep, KeyframesStepValue};
 use crate::stylesheets::layer_rule::LayerOrder;
 use crate::values::animated::{Animate, Procedure};
-use crate::values::computed::{Time, TimingFunction};
+use crate::values::computed::AnimationTimeline as AnimationTimelineValue;
+use crate::values::computed::{
+    AnimationRange, Length, LengthPercentageOrAuto, ScrollAxis, Scroller, Time, TimingFunction,
+    TransitionProperty, ViewTimelineInset,
+};
+use crate::values::generics::box_::TimelineRangeName;
 use crate::values::generics::easing::BeforeFlag;
 use crate::Atom;
+use euclid::{Rect, Size2D, Vector2D};
 use fxhash::FxHashMap;
 use parking_lot::RwLock;
 use servo_arc::Arc;
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use style_traits::CSSPixel;
 
 /// Represents an animation for a given property.
 #[derive(Clone, Debug, MallocSizeOf)]
@@ -60,6 +75,7 @@ impl PropertyAnimation {
         longhand: LonghandId,
         timing_function: TimingFunction,
         duration: Time,
+        allow_discrete: bool,
         old_style: &ComputedValues,
         new_style: &ComputedValues,
     ) -> Option<PropertyAnimation> {
@@ -73,6 +89,16 @@ impl PropertyAnimation {
             return None;
         }
 
+        // Discretely animatable properties, and pairs of values that can't be
+        // interpolated, only transition with `transition-behavior: allow-discrete`.
+        // https://drafts.csswg.org/css-transitions-2/#transition-behavior-property
+        if !allow_discrete &&
+            (longhand.is_discrete_animatable() ||
+                from.animate(&to, Procedure::Interpolate { progress: 0.5 }).is_err())
+        {
+            return None;
+        }
+
         Some(PropertyAnimation {
             from,
             to,
@@ -83,20 +109,123 @@ impl PropertyAnimation {
 
     /// The output of the timing function given the progress ration of this animation.
     fn timing_function_output(&self, progress: f64) -> f64 {
-        let epsilon = 1. / (200. * self.duration);
-        // FIXME: Need to set the before flag correctly.
-        // In order to get the before flag, we have to know the current animation phase
-        // and whether the iteration is reversed. For now, we skip this calculation
-        // by treating as if the flag is unset at all times.
-        // https://drafts.csswg.org/css-easing/#step-timing-function-algo
-        self.timing_function
-            .calculate_output(progress, BeforeFlag::Unset, epsilon)
+        timing_function_output(&self.timing_function, self.duration, progress)
     }
 
     /// Update the given animation at a given point of progress.
     fn calculate_value(&self, progress: f64) -> Result<AnimationValue, ()> {
+        let progress = self.timing_function_output(progress);
+
+        // When animating to or from `display: none`, the other value is used
+        // for the whole active interval, so that entry and exit animations of
+        // the element stay visible.
+        // https://drafts.csswg.org/css-display-4/#display-animation
+        if let (&AnimationValue::Display(ref from), &AnimationValue::Display(ref to)) =
+            (&self.from, &self.to)
+        {
+            if from.is_none() != to.is_none() {
+                let use_to = if from.is_none() {
+                    progress > 0.
+                } else {
+                    progress >= 1.
+                };
+                return Ok(if use_to { &self.to } else { &self.from }.clone());
+            }
+        }
+
+        match self.from.animate(&self.to, Procedure::Interpolate { progress }) {
+            Ok(value) => Ok(value),
+            // Values that can't be interpolated flip at the midpoint.
+            // https://drafts.csswg.org/web-animations-1/#discrete
+            Err(()) if progress < 0.5 => Ok(self.from.clone()),
+            Err(()) => Ok(self.to.clone()),
+        }
+    }
+}
+
+/// The output of a timing function for an animation of the given duration, at
+/// the given progress.
+fn timing_function_output(timing_function: &TimingFunction, duration: f64, progress: f64) -> f64 {
+    let epsilon = 1. / (200. * duration);
+    // FIXME: Need to set the before flag correctly.
+    // In order to get the before flag, we have to know the current animation phase
+    // and whether the iteration is reversed. For now, we skip this calculation
+    // by treating as if the flag is unset at all times.
+    // https://drafts.csswg.org/css-easing/#step-timing-function-algo
+    timing_function.calculate_output(progress, BeforeFlag::Unset, epsilon)
+}
+
+/// The animated values of registered custom properties.
+pub type CustomPropertyValueMap = FxHashMap<CustomPropertyName, Arc<VariableValue>>;
+
+/// Represents an animation for a registered custom property.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#animation-behavior-of-custom-properties>
+#[derive(Clone, Debug, MallocSizeOf)]
+pub struct CustomPropertyAnimation {
+    /// The name of the custom property, without the leading `--`.
+    pub name: CustomPropertyName,
+
+    /// The value we are animating from.
+    #[ignore_malloc_size_of = "Registered custom property values"]
+    from: RegisteredComputedValue,
+
+    /// The value we are animating to.
+    #[ignore_malloc_size_of = "Registered custom property values"]
+    to: RegisteredComputedValue,
+
+    /// The timing function of this `CustomPropertyAnimation`.
+    timing_function: TimingFunction,
+
+    /// The duration of this `CustomPropertyAnimation` in seconds.
+    pub duration: f64,
+}
+
+impl CustomPropertyAnimation {
+    fn from_custom_property(
+        name: &CustomPropertyName,
+        registration: &PropertyRegistration,
+        context: &SharedStyleContext,
+        timing_function: TimingFunction,
+        duration: Time,
+        old_style: &ComputedValues,
+        new_style: &ComputedValues,
+    ) -> Option<Self> {
+        // Only registered properties with a syntax other than the universal
+        // one can be interpolated.
+        if !registration.is_typed() {
+            return None;
+        }
+        let from = old_style.custom_properties()?.get(name)?;
+        let to = new_style.custom_properties()?.get(name)?;
+        let duration = duration.seconds() as f64;
+        if from == to || duration == 0.0 {
+            return None;
+        }
+
+        let device = context.stylist.device();
+        let quirks_mode = context.stylist.quirks_mode();
+        let from = registration.parse_computed(from, device, quirks_mode).ok()?;
+        let to = registration.parse_computed(to, device, quirks_mode).ok()?;
+
+        // Values that can't be interpolated with each other, like lists of
+        // different lengths or `<custom-ident>`s, don't transition.
+        from.animate(&to, Procedure::Interpolate { progress: 0.5 })
+            .ok()?;
+
+        Some(Self {
+            name: name.clone(),
+            from,
+            to,
+            timing_function,
+            duration,
+        })
+    }
+
+    /// Update the given animation at a given point of progress.
+    fn calculate_value(&self, progress: f64) -> Result<RegisteredComputedValue, ()> {
         let procedure = Procedure::Interpolate {
-            progress: self.timing_function_output(progress),
+            progress: timing_function_output(&self.timing_function, self.duration, progress),
         };
         self.from.animate(&self.to, procedure)
     }
@@ -145,6 +274,7 @@ pub enum KeyframesIterationState {
 struct IntermediateComputedKeyframe {
     declarations: PropertyDeclarationBlock,
     timing_function: Option<TimingFunction>,
+    composition: Option<AnimationComposition>,
     start_percentage: f32,
 }
 
@@ -153,6 +283,7 @@ impl IntermediateComputedKeyframe {
         IntermediateComputedKeyframe {
             declarations: PropertyDeclarationBlock::new(),
             timing_function: None,
+            composition: None,
             start_percentage,
         }
     }
@@ -198,6 +329,11 @@ impl IntermediateComputedKeyframe {
             self.timing_function = Some(timing_function.to_computed_value_without_context());
         }
 
+        // Likewise for the composite operation.
+        if let Some(composition) = step.get_animation_composition(&guard) {
+            self.composition = Some(composition);
+        }
+
         let block = match step.value {
             KeyframesStepValue::ComputedValues => return,
             KeyframesStepValue::Declarations { ref block } => block,
@@ -277,9 +413,22 @@ struct ComputedKeyframe {
     /// at what point in an animation iteration this step is.
     start_percentage: f32,
 
+    /// The composite operation used to combine the values of this step with
+    /// the underlying values.
+    composition: AnimationComposition,
+
     /// The animation values to transition to and from when processing this
     /// keyframe animation step.
     values: Vec<AnimationValue>,
+
+    /// The properties whose values are not specified by this step, nor by any
+    /// previous one. Their values stand for the underlying value.
+    missing_properties: LonghandIdSet,
+
+    /// The values of the registered custom properties to transition to and
+    /// from when processing this keyframe animation step.
+    #[ignore_malloc_size_of = "Registered custom property values"]
+    custom_values: Vec<(CustomPropertyName, RegisteredComputedValue)>,
 }
 
 impl ComputedKeyframe {
@@ -289,6 +438,7 @@ impl ComputedKeyframe {
         context: &SharedStyleContext,
         base_style: &Arc<ComputedValues>,
         default_timing_function: TimingFunction,
+        default_composition: AnimationComposition,
         resolver: &mut StyleResolverForElement<E>,
     ) -> Vec<Self>
     where
@@ -308,6 +458,30 @@ impl ComputedKeyframe {
             })
             .collect();
 
+        // Only registered custom properties with a syntax other than the
+        // universal one can be interpolated.
+        let animating_custom_properties: Vec<(&CustomPropertyName, &PropertyRegistration)> =
+            animation
+                .custom_properties_changed
+                .iter()
+                .filter_map(|name| {
+                    let registration = context.stylist.get_custom_property_registration(name)?;
+                    if !registration.is_typed() {
+                        return None;
+                    }
+                    Some((name, registration))
+                })
+                .collect();
+        let custom_value = |style: &ComputedValues,
+                            name: &CustomPropertyName,
+                            registration: &PropertyRegistration| {
+            let value = style.custom_properties()?.get(name)?;
+            let device = context.stylist.device();
+            registration
+                .parse_computed(value, device, context.stylist.quirks_mode())
+                .ok()
+        };
+
         let intermediate_steps =
             IntermediateComputedKeyframe::generate_for_keyframes(animation, context, base_style);
 
@@ -315,11 +489,28 @@ impl ComputedKeyframe {
         for (step_index, step) in intermediate_steps.into_iter().enumerate() {
             let start_percentage = step.start_percentage;
             let properties_changed_in_step = step.declarations.longhands().clone();
+            let custom_properties_changed_in_step: Vec<CustomPropertyName> = step
+                .declarations
+                .declarations()
+                .iter()
+                .filter_map(|declaration| match *declaration {
+                    PropertyDeclaration::Custom(ref declaration) => Some(declaration.name.clone()),
+                    _ => None,
+                })
+                .collect();
             let step_timing_function = step.timing_function.clone();
+            let composition = step.composition.unwrap_or(default_composition);
             let step_style = step.resolve_style(element, context, base_style, resolver);
             let timing_function =
                 step_timing_function.unwrap_or_else(|| default_timing_function.clone());
 
+            let mut missing_properties = if start_percentage == 0. || start_percentage == 1.0 {
+                animating_properties
+            } else {
+                computed_steps[step_index - 1].missing_properties
+            };
+            missing_properties.remove_all(&properties_changed_in_step);
+
             let values = {
                 // If a value is not set in a property declaration we use the value from
                 // the style for the first and last keyframe. For intermediate ones, we
@@ -351,14 +542,622 @@ impl ComputedKeyframe {
                     .collect()
             };
 
+            // Custom properties follow the same rules, but those without a
+            // value that matches their registration aren't animated.
+            let custom_values = animating_custom_properties
+                .iter()
+                .filter_map(|&(name, registration)| {
+                    let value = if custom_properties_changed_in_step.contains(name) {
+                        custom_value(&*step_style, name, registration)
+                    } else {
+                        None
+                    };
+                    let value = value.or_else(|| {
+                        if start_percentage == 0. || start_percentage == 1.0 {
+                            custom_value(&**base_style, name, registration)
+                        } else {
+                            computed_steps[step_index - 1]
+                                .custom_values
+                                .iter()
+                                .find(|(previous_name, _)| previous_name == name)
+                                .map(|(_, value)| value.clone())
+                        }
+                    })?;
+                    Some((name.clone(), value))
+                })
+                .collect();
+
             computed_steps.push(ComputedKeyframe {
                 timing_function,
                 start_percentage,
+                composition,
                 values,
+                missing_properties,
+                custom_values,
             });
         }
         computed_steps
     }
+
+    /// Generate the property-specific keyframes of an animation created by the
+    /// embedder, that is, for each animated property, the keyframes which
+    /// specify a value for it, after computing the missing keyframe offsets.
+    /// Properties without a keyframe at offset 0 or 1 get one there which
+    /// uses the underlying value.
+    ///
+    /// Keyframes don't get values for the properties they don't specify, so
+    /// that the easing and composite operation of each interval are the ones
+    /// of the keyframes of that property.
+    ///
+    /// Returns an error if the specified offsets aren't sorted or are out of
+    /// the [0, 1] range.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#compute-missing-keyframe-offsets>
+    /// <https://drafts.csswg.org/web-animations-1/#calculating-computed-keyframes>
+    fn generate_for_script_keyframes(
+        keyframes: &[ScriptKeyframe],
+        default_composition: AnimationComposition,
+        base_style: &ComputedValues,
+    ) -> Result<Vec<Vec<Self>>, ()> {
+        let mut previous_offset = 0.;
+        for offset in keyframes.iter().filter_map(|keyframe| keyframe.offset) {
+            if !(0. ..=1.).contains(&offset) || offset < previous_offset {
+                return Err(());
+            }
+            previous_offset = offset;
+        }
+
+        let mut offsets: Vec<Option<f64>> =
+            keyframes.iter().map(|keyframe| keyframe.offset).collect();
+        if let Some(last) = offsets.last_mut() {
+            last.get_or_insert(1.);
+        }
+        if offsets.len() > 1 {
+            offsets[0].get_or_insert(0.);
+        }
+
+        // Space the keyframes without an offset evenly between the surrounding
+        // keyframes which have one.
+        let mut previous_index = 0;
+        for index in 1..offsets.len() {
+            let end = match offsets[index] {
+                Some(offset) => offset,
+                None => continue,
+            };
+            // The first offset was set above, and `previous_index` only moves
+            // to keyframes with an offset.
+            let start = offsets[previous_index].unwrap();
+            let count = (index - previous_index) as f64;
+            for (i, offset) in offsets[previous_index + 1..index].iter_mut().enumerate() {
+                *offset = Some(start + (end - start) * (i + 1) as f64 / count);
+            }
+            previous_index = index;
+        }
+
+        let mut animating_properties = LonghandIdSet::new();
+        for value in keyframes.iter().flat_map(|keyframe| keyframe.values.iter()) {
+            animating_properties.insert(value.id());
+        }
+
+        let mut property_keyframes = Vec::new();
+        for property in animating_properties.iter() {
+            let underlying_keyframe = |start_percentage| -> Result<Self, ()> {
+                let mut missing_properties = LonghandIdSet::new();
+                missing_properties.insert(property);
+                Ok(ComputedKeyframe {
+                    timing_function: TimingFunction::linear(),
+                    start_percentage,
+                    composition: default_composition,
+                    values: vec![
+                        AnimationValue::from_computed_values(property, base_style).ok_or(())?
+                    ],
+                    missing_properties,
+                    custom_values: vec![],
+                })
+            };
+
+            let mut steps: Vec<Self> = Vec::with_capacity(keyframes.len() + 2);
+            for (keyframe, offset) in keyframes.iter().zip(offsets.iter()) {
+                let value = match keyframe.values.iter().find(|value| value.id() == property) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                steps.push(ComputedKeyframe {
+                    timing_function: keyframe.easing.clone(),
+                    // Every keyframe has an offset by now: the last one always
+                    // gets one, and the loop above fills the ones before it.
+                    start_percentage: offset.unwrap() as f32,
+                    composition: keyframe.composite.unwrap_or(default_composition),
+                    values: vec![value.clone()],
+                    missing_properties: LonghandIdSet::new(),
+                    custom_values: vec![],
+                });
+            }
+
+            // `property` comes from the values of some keyframe, so `steps`
+            // isn't empty.
+            if steps[0].start_percentage != 0. {
+                steps.insert(0, underlying_keyframe(0.)?);
+            }
+            if steps.last().unwrap().start_percentage != 1. {
+                steps.push(underlying_keyframe(1.)?);
+            }
+            property_keyframes.push(steps);
+        }
+        Ok(property_keyframes)
+    }
+
+    /// Returns the value at the given index, composited with the underlying
+    /// value according to the composite operation of this keyframe.
+    ///
+    /// The underlying value is the result of the animations below this one in
+    /// the stack, if any, or the value in the given style otherwise.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#the-effect-value-of-a-keyframe-animation-effect>
+    fn composited_value(
+        &self,
+        index: usize,
+        map: &AnimationValueMap,
+        style: &ComputedValues,
+    ) -> AnimationValue {
+        let value = &self.values[index];
+        let id = value.id();
+        let procedure = match self.composition {
+            _ if self.missing_properties.contains(id) => None,
+            AnimationComposition::Replace => return value.clone(),
+            AnimationComposition::Add => Some(Procedure::Add),
+            AnimationComposition::Accumulate => Some(Procedure::Accumulate { count: 1 }),
+        };
+
+        let underlying = map
+            .get(&id)
+            .cloned()
+            .or_else(|| AnimationValue::from_computed_values(id, style));
+        let underlying = match underlying {
+            Some(underlying) => underlying,
+            None => return value.clone(),
+        };
+
+        match procedure {
+            // Missing keyframe values are neutral values for composition.
+            None => underlying,
+            // Values that can't be added or accumulated behave like replace.
+            Some(procedure) => underlying
+                .animate(value, procedure)
+                .unwrap_or_else(|()| value.clone()),
+        }
+    }
+}
+
+/// A physical axis of a scroll container.
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq)]
+pub enum PhysicalScrollAxis {
+    /// The horizontal axis.
+    Horizontal,
+    /// The vertical axis.
+    Vertical,
+}
+
+impl PhysicalScrollAxis {
+    /// Map a (possibly logical) `<axis>` to a physical one, using the writing
+    /// mode of the scroll container.
+    fn new(axis: ScrollAxis, writing_mode: WritingMode) -> Self {
+        let vertical_writing_mode = writing_mode.is_vertical();
+        match axis {
+            ScrollAxis::Horizontal => PhysicalScrollAxis::Horizontal,
+            ScrollAxis::Vertical => PhysicalScrollAxis::Vertical,
+            ScrollAxis::Block if vertical_writing_mode => PhysicalScrollAxis::Horizontal,
+            ScrollAxis::Block => PhysicalScrollAxis::Vertical,
+            ScrollAxis::Inline if vertical_writing_mode => PhysicalScrollAxis::Vertical,
+            ScrollAxis::Inline => PhysicalScrollAxis::Horizontal,
+        }
+    }
+}
+
+/// The scroll position of a scroll container, as reported by the embedder.
+///
+/// All values are in CSS pixels, and offsets are measured from the scroll
+/// origin, so they are never negative regardless of the writing mode.
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
+pub struct ScrollState {
+    /// The current scroll offset.
+    pub offset: Vector2D<f32, CSSPixel>,
+    /// The maximum scroll offset, that is, the size of the scrollable overflow
+    /// area minus the size of the scrollport.
+    pub scroll_range: Vector2D<f32, CSSPixel>,
+    /// The size of the scrollport.
+    pub scrollport_size: Size2D<f32, CSSPixel>,
+}
+
+impl ScrollState {
+    /// Returns the offset, scroll range and scrollport size along the given axis.
+    fn along(&self, axis: PhysicalScrollAxis) -> (f32, f32, f32) {
+        match axis {
+            PhysicalScrollAxis::Horizontal => (
+                self.offset.x,
+                self.scroll_range.x,
+                self.scrollport_size.width,
+            ),
+            PhysicalScrollAxis::Vertical => (
+                self.offset.y,
+                self.scroll_range.y,
+                self.scrollport_size.height,
+            ),
+        }
+    }
+}
+
+/// The scroll state of the scroll containers of a document and the position of
+/// the subjects of its view progress timelines, which scroll-driven animations
+/// sample their progress from.
+#[derive(Debug, Default)]
+pub struct ScrollStateMap {
+    /// The scroll state of each scroll container. The scroll state of the
+    /// document viewport is stored for the root element.
+    scroll_containers: FxHashMap<OpaqueNode, ScrollState>,
+    /// The border box of each view progress timeline subject, relative to the
+    /// scroll origin of its nearest scroll container.
+    subjects: FxHashMap<OpaqueNode, Rect<f32, CSSPixel>>,
+}
+
+/// The timeline that drives the progress of an `Animation`.
+///
+/// https://drafts.csswg.org/web-animations-1/#timelines
+#[derive(Clone, Debug, MallocSizeOf, PartialEq)]
+pub enum Timeline {
+    /// The document timeline, driven by the animation clock.
+    Document,
+    /// A scroll progress timeline, driven by the scroll position of `source`.
+    ///
+    /// https://drafts.csswg.org/scroll-animations-1/#scroll-timelines
+    Scroll {
+        /// The scroll container of this timeline.
+        source: OpaqueNode,
+        /// The axis of the scroll container that this timeline tracks.
+        axis: PhysicalScrollAxis,
+    },
+    /// A view progress timeline, driven by the visibility of `subject` within
+    /// the scrollport of `source`.
+    ///
+    /// https://drafts.csswg.org/scroll-animations-1/#view-timelines
+    View {
+        /// The nearest scroll container of the subject.
+        source: OpaqueNode,
+        /// The subject element of this timeline.
+        subject: OpaqueNode,
+        /// The axis of the scroll container that this timeline tracks.
+        axis: PhysicalScrollAxis,
+        /// The insets applied to the scrollport.
+        inset: ViewTimelineInset,
+    },
+    /// A timeline that doesn't exist, like `none` or a name that isn't declared
+    /// by the element or any of its ancestors. Animations attached to it have no
+    /// effect.
+    Inactive,
+}
+
+impl Timeline {
+    /// Resolve the computed value of `animation-timeline` for an element with
+    /// the given style.
+    fn resolve<E>(element: E, style: &ComputedValues, value: &AnimationTimelineValue) -> Self
+    where
+        E: TElement,
+    {
+        match *value {
+            AnimationTimelineValue::Auto => Timeline::Document,
+            AnimationTimelineValue::Timeline(ref name) => {
+                if name.is_none() {
+                    return Timeline::Inactive;
+                }
+                Self::named(element, style, name.as_atom()).unwrap_or(Timeline::Inactive)
+            },
+            AnimationTimelineValue::Scroll(axis, scroller) => {
+                let (source, writing_mode) = match scroller {
+                    Scroller::Nearest => nearest_scroll_container(element, style),
+                    Scroller::Root => root_scroll_container(element, style),
+                };
+                Timeline::Scroll {
+                    source,
+                    axis: PhysicalScrollAxis::new(axis, writing_mode),
+                }
+            },
+            #[cfg(feature = "servo")]
+            AnimationTimelineValue::View(ref view) => {
+                let (source, writing_mode) = nearest_scroll_container(element, style);
+                Timeline::View {
+                    source,
+                    subject: element.as_node().opaque(),
+                    axis: PhysicalScrollAxis::new(view.axis, writing_mode),
+                    inset: view.inset.clone(),
+                }
+            },
+        }
+    }
+
+    /// Look up a named scroll or view progress timeline declared by the element
+    /// or its nearest ancestor declaring a timeline with that name.
+    ///
+    /// https://drafts.csswg.org/scroll-animations-1/#timeline-scoping
+    fn named<E>(element: E, style: &ComputedValues, name: &Atom) -> Option<Self>
+    where
+        E: TElement,
+    {
+        let declared_timeline = |element: E, style: &ComputedValues| {
+            let ui = style.get_ui();
+            if let Some(index) = ui
+                .scroll_timeline_name_iter()
+                .position(|timeline_name| timeline_name.as_atom() == Some(name))
+            {
+                return Some(Timeline::Scroll {
+                    source: element.as_node().opaque(),
+                    axis: PhysicalScrollAxis::new(
+                        ui.scroll_timeline_axis_mod(index),
+                        style.writing_mode,
+                    ),
+                });
+            }
+
+            let index = ui
+                .view_timeline_name_iter()
+                .position(|timeline_name| timeline_name.as_atom() == Some(name))?;
+            let (source, writing_mode) = nearest_scroll_container(element, style);
+            Some(Timeline::View {
+                source,
+                subject: element.as_node().opaque(),
+                axis: PhysicalScrollAxis::new(ui.view_timeline_axis_mod(index), writing_mode),
+                inset: ui.view_timeline_inset_mod(index),
+            })
+        };
+
+        declared_timeline(element, style).or_else(|| {
+            find_in_ancestors(element, |ancestor, style| declared_timeline(ancestor, style))
+        })
+    }
+
+    /// The scroll container whose scroll position drives this timeline.
+    fn source(&self) -> Option<OpaqueNode> {
+        match *self {
+            Timeline::Scroll { source, .. } | Timeline::View { source, .. } => Some(source),
+            Timeline::Document | Timeline::Inactive => None,
+        }
+    }
+
+    /// The progress of this timeline through the given animation attachment
+    /// range, where 0 is the start of the range and 1 is its end. Returns `None`
+    /// if this is not a scroll-driven timeline or if the timeline is inactive.
+    fn progress(
+        &self,
+        scroll_states: &ScrollStateMap,
+        range_start: &AnimationRange,
+        range_end: &AnimationRange,
+    ) -> Option<f64> {
+        let (source, axis) = match *self {
+            Timeline::Scroll { source, axis } | Timeline::View { source, axis, .. } => {
+                (source, axis)
+            },
+            Timeline::Document | Timeline::Inactive => return None,
+        };
+
+        let (offset, scroll_range, scrollport_size) =
+            scroll_states.scroll_containers.get(&source)?.along(axis);
+        let ranges = match *self {
+            Timeline::View {
+                subject, ref inset, ..
+            } => {
+                let subject = scroll_states.subjects.get(&subject)?;
+                let (subject_start, subject_size) = match axis {
+                    PhysicalScrollAxis::Horizontal => (subject.origin.x, subject.size.width),
+                    PhysicalScrollAxis::Vertical => (subject.origin.y, subject.size.height),
+                };
+                TimelineRanges::for_view(subject_start, subject_size, scrollport_size, inset)
+            },
+            _ => {
+                // A scroll progress timeline is inactive if its scroll container
+                // can't be scrolled along its axis.
+                if scroll_range <= 0. {
+                    return None;
+                }
+                TimelineRanges::for_scroll(scroll_range)
+            },
+        };
+
+        let start = ranges.offset(range_start, 0.);
+        let end = ranges.offset(range_end, 1.);
+        if end <= start {
+            return None;
+        }
+        Some(((offset - start) / (end - start)) as f64)
+    }
+}
+
+/// The scroll offsets at which the named timeline ranges of a timeline start
+/// and end.
+///
+/// https://drafts.csswg.org/scroll-animations-1/#view-timelines-ranges
+struct TimelineRanges {
+    cover: (f32, f32),
+    contain: (f32, f32),
+    entry_crossing: (f32, f32),
+    exit_crossing: (f32, f32),
+}
+
+impl TimelineRanges {
+    /// The ranges of a scroll progress timeline, where every named range is the
+    /// full range of the timeline.
+    fn for_scroll(scroll_range: f32) -> Self {
+        let full = (0., scroll_range);
+        TimelineRanges {
+            cover: full,
+            contain: full,
+            entry_crossing: full,
+            exit_crossing: full,
+        }
+    }
+
+    /// The ranges of a view progress timeline, given the position and size of
+    /// the subject along the axis of the timeline.
+    fn for_view(
+        subject_start: f32,
+        subject_size: f32,
+        scrollport_size: f32,
+        inset: &ViewTimelineInset,
+    ) -> Self {
+        // `auto` insets should use the scroll-padding of the scroll container,
+        // which we don't support, so they resolve to zero.
+        let resolve_inset = |inset: &LengthPercentageOrAuto| match *inset {
+            LengthPercentageOrAuto::LengthPercentage(ref lp) => {
+                lp.resolve(Length::new(scrollport_size)).px()
+            },
+            LengthPercentageOrAuto::Auto => 0.,
+        };
+        let start_inset = resolve_inset(&inset.start);
+        let end_inset = resolve_inset(&inset.end);
+
+        // The offsets at which the start edge of the subject reaches the end
+        // edge of the scrollport, at which its end edge reaches the end edge of
+        // the scrollport, and so on.
+        let start_reaches_end = subject_start - scrollport_size + end_inset;
+        let end_reaches_end = subject_start + subject_size - scrollport_size + end_inset;
+        let start_reaches_start = subject_start - start_inset;
+        let end_reaches_start = subject_start + subject_size - start_inset;
+
+        TimelineRanges {
+            cover: (start_reaches_end, end_reaches_start),
+            contain: (
+                end_reaches_end.min(start_reaches_start),
+                end_reaches_end.max(start_reaches_start),
+            ),
+            entry_crossing: (start_reaches_end, end_reaches_end),
+            exit_crossing: (start_reaches_start, end_reaches_start),
+        }
+    }
+
+    fn named_range(&self, name: TimelineRangeName) -> (f32, f32) {
+        match name {
+            TimelineRangeName::Cover => self.cover,
+            TimelineRangeName::Contain => self.contain,
+            TimelineRangeName::Entry => (self.cover.0, self.contain.0),
+            TimelineRangeName::Exit => (self.contain.1, self.cover.1),
+            TimelineRangeName::EntryCrossing => self.entry_crossing,
+            TimelineRangeName::ExitCrossing => self.exit_crossing,
+        }
+    }
+
+    /// The scroll offset of an `animation-range-start` or `animation-range-end`
+    /// value, where `default_progress` is the position of `normal` and of named
+    /// ranges without an offset.
+    fn offset(&self, value: &AnimationRange, default_progress: f32) -> f32 {
+        let ((start, end), offset) = match *value {
+            AnimationRange::Normal => (self.cover, None),
+            AnimationRange::LengthPercentage(ref lp) => (self.cover, Some(lp)),
+            AnimationRange::Named(name, ref lp) => (self.named_range(name), lp.as_ref()),
+        };
+        let size = end - start;
+        start +
+            offset.map_or(size * default_progress, |lp| {
+                lp.resolve(Length::new(size)).px()
+            })
+    }
+}
+
+/// The `animation-range-start` and `animation-range-end` of the animation at
+/// the given index.
+#[cfg(feature = "servo")]
+fn animation_range(style: &style_structs::UI, index: usize) -> (AnimationRange, AnimationRange) {
+    (
+        style.animation_range_start_mod(index),
+        style.animation_range_end_mod(index),
+    )
+}
+
+/// As above, but a stub for Gecko, which doesn't support `animation-range`.
+#[cfg(feature = "gecko")]
+fn animation_range(_: &style_structs::UI, _: usize) -> (AnimationRange, AnimationRange) {
+    (AnimationRange::normal(), AnimationRange::normal())
+}
+
+/// Whether the transition at the given index has `transition-behavior:
+/// allow-discrete`.
+#[cfg(feature = "servo")]
+fn transition_allows_discrete(style: &style_structs::UI, index: usize) -> bool {
+    style.transition_behavior_mod(index) == TransitionBehavior::AllowDiscrete
+}
+
+/// As above, but a stub for Gecko, which doesn't support `transition-behavior`.
+#[cfg(feature = "gecko")]
+fn transition_allows_discrete(_: &style_structs::UI, _: usize) -> bool {
+    false
+}
+
+/// Whether `display` is transitioned with `transition-behavior: allow-discrete`
+/// in the given style.
+fn transitions_display_discretely(style: &ComputedValues) -> bool {
+    style
+        .transition_properties()
+        .find(|transition| transition.longhand_id == LonghandId::Display)
+        .map_or(false, |transition| {
+            transition_allows_discrete(style.get_ui(), transition.index)
+        })
+}
+
+/// Calls `f` with each styled ancestor of `element` and its style, from the
+/// parent to the root, and returns the first result that isn't `None`.
+fn find_in_ancestors<E, R>(
+    element: E,
+    mut f: impl FnMut(E, &ComputedValues) -> Option<R>,
+) -> Option<R>
+where
+    E: TElement,
+{
+    let mut current = element.traversal_parent();
+    while let Some(ancestor) = current {
+        if let Some(data) = ancestor.borrow_data() {
+            if let Some(style) = data.styles.get_primary() {
+                if let Some(result) = f(ancestor, style) {
+                    return Some(result);
+                }
+            }
+        }
+        current = ancestor.traversal_parent();
+    }
+    None
+}
+
+/// The root element, whose scroll state is the one of the document viewport,
+/// and its writing mode.
+fn root_scroll_container<E>(element: E, style: &ComputedValues) -> (OpaqueNode, WritingMode)
+where
+    E: TElement,
+{
+    let mut root = (element.as_node().opaque(), style.writing_mode);
+    find_in_ancestors(element, |ancestor, style| {
+        root = (ancestor.as_node().opaque(), style.writing_mode);
+        None::<()>
+    });
+    root
+}
+
+/// The nearest ancestor scroll container of `element` and its writing mode,
+/// falling back to the document viewport.
+fn nearest_scroll_container<E>(element: E, style: &ComputedValues) -> (OpaqueNode, WritingMode)
+where
+    E: TElement,
+{
+    let mut root = None;
+    let nearest = find_in_ancestors(element, |ancestor, style| {
+        let container = (ancestor.as_node().opaque(), style.writing_mode);
+        root = Some(container);
+        let box_style = style.get_box();
+        if box_style.clone_overflow_x().is_scrollable() ||
+            box_style.clone_overflow_y().is_scrollable()
+        {
+            return Some(container);
+        }
+        None
+    });
+    nearest
+        .or(root)
+        .unwrap_or_else(|| (element.as_node().opaque(), style.writing_mode))
 }
 
 /// A CSS Animation
@@ -398,6 +1197,21 @@ pub struct Animation {
     /// The current animation direction. This can only be `normal` or `reverse`.
     pub current_direction: AnimationDirection,
 
+    /// The timeline that drives this animation.
+    pub timeline: Timeline,
+
+    /// The `animation-range-start` property of this animation, which only
+    /// applies to scroll-driven animations.
+    pub range_start: AnimationRange,
+
+    /// The `animation-range-end` property of this animation, which only
+    /// applies to scroll-driven animations.
+    pub range_end: AnimationRange,
+
+    /// The scroll state that scroll-driven animations sample their progress from.
+    #[ignore_malloc_size_of = "Arc is hard"]
+    scroll_states: Arc<RwLock<ScrollStateMap>>,
+
     /// The original cascade style, needed to compute the generated keyframes of
     /// the animation.
     #[ignore_malloc_size_of = "ComputedValues"]
@@ -420,13 +1234,37 @@ impl Animation {
             None => return true,
         };
 
-        new_ui.animation_duration_mod(index).seconds() == 0.
+        new_ui.animation_duration_mod(index).seconds() == 0. &&
+            new_ui.animation_timeline_mod(index).is_auto()
+    }
+
+    /// Whether this animation is driven by a scroll or view progress timeline,
+    /// rather than by the document timeline.
+    pub fn is_scroll_driven(&self) -> bool {
+        self.timeline != Timeline::Document
+    }
+
+    /// Whether or not this animation needs to be ticked by the animation clock.
+    /// Once running, scroll-driven animations only change when their timeline
+    /// is scrolled.
+    fn needs_to_be_ticked(&self) -> bool {
+        match self.state {
+            AnimationState::Pending => true,
+            AnimationState::Running => !self.is_scroll_driven(),
+            _ => false,
+        }
     }
 
     /// Given the current time, advances this animation to the next iteration,
     /// updates times, and then toggles the direction if appropriate. Otherwise
     /// does nothing. Returns true if this animation has iterated.
     pub fn iterate_if_necessary(&mut self, time: f64) -> bool {
+        // Scroll-driven animations derive their current iteration from the
+        // progress of their timeline instead.
+        if self.is_scroll_driven() {
+            return false;
+        }
+
         if !self.iteration_over(time) {
             return false;
         }
@@ -505,6 +1343,12 @@ impl Animation {
     /// not take into account canceling i.e. when an animation or transition is
     /// canceled due to changes in the style.
     pub fn has_ended(&self, time: f64) -> bool {
+        // Scroll-driven animations can always be scrolled back into their
+        // active interval, so they never finish.
+        if self.is_scroll_driven() {
+            return false;
+        }
+
         if !self.on_last_iteration() {
             return false;
         }
@@ -591,119 +1435,277 @@ impl Animation {
         }
     }
 
-    /// Fill in an `AnimationValueMap` with values calculated from this animation at
-    /// the given time value.
-    fn get_property_declaration_at_time(&self, now: f64, map: &mut AnimationValueMap) {
-        debug_assert!(!self.computed_steps.is_empty());
+    /// Whether or not this animation applies its values before it starts.
+    fn fills_backwards(&self) -> bool {
+        self.fill_mode == AnimationFillMode::Backwards || self.fill_mode == AnimationFillMode::Both
+    }
 
-        let total_progress = match self.state {
-            AnimationState::Running | AnimationState::Pending | AnimationState::Finished => {
-                (now - self.started_at) / self.duration
-            },
-            AnimationState::Paused(progress) => progress,
-            AnimationState::Canceled => return,
-        };
+    /// Whether or not this animation applies its values after it ends.
+    fn fills_forwards(&self) -> bool {
+        self.fill_mode == AnimationFillMode::Forwards || self.fill_mode == AnimationFillMode::Both
+    }
 
-        if total_progress < 0. &&
-            self.fill_mode != AnimationFillMode::Backwards &&
-            self.fill_mode != AnimationFillMode::Both
-        {
-            return;
+    /// The progress through the current iteration of this scroll-driven
+    /// animation and the direction of that iteration, or `None` if the
+    /// animation has no effect at the current scroll position.
+    ///
+    /// Unlike time-based animations, this is computed from the timeline progress
+    /// alone, since the timeline can move backwards.
+    fn scroll_driven_iteration_progress(&self) -> Option<(f64, AnimationDirection)> {
+        let scroll_states = self.scroll_states.read();
+        let progress = self
+            .timeline
+            .progress(&scroll_states, &self.range_start, &self.range_end)?;
+        if progress < 0. && !self.fills_backwards() {
+            return None;
         }
-        if self.has_ended(now) &&
-            self.fill_mode != AnimationFillMode::Forwards &&
-            self.fill_mode != AnimationFillMode::Both
-        {
-            return;
+        if progress > 1. && !self.fills_forwards() {
+            return None;
         }
-        let total_progress = total_progress
-            .min(self.current_iteration_end_progress())
-            .max(0.0);
 
-        // Get the indices of the previous (from) keyframe and the next (to) keyframe.
-        let next_keyframe_index;
-        let prev_keyframe_index;
-        let num_steps = self.computed_steps.len();
-        match self.current_direction {
-            AnimationDirection::Normal => {
-                next_keyframe_index = self
-                    .computed_steps
-                    .iter()
-                    .position(|step| total_progress as f32 <= step.start_percentage);
-                prev_keyframe_index = next_keyframe_index
-                    .and_then(|pos| if pos != 0 { Some(pos - 1) } else { None })
-                    .unwrap_or(0);
+        let (iteration_progress, iteration) = match self.iteration_state {
+            KeyframesIterationState::Finite(_, iterations) => {
+                let overall_progress = progress.min(1.).max(0.) * iterations;
+                // The end of the active interval is the end of the last
+                // iteration, not the start of a new one.
+                let mut iteration = overall_progress.floor();
+                if iteration >= iterations && iteration > 0. {
+                    iteration -= 1.;
+                }
+                (overall_progress - iteration, iteration)
             },
-            AnimationDirection::Reverse => {
-                next_keyframe_index = self
-                    .computed_steps
-                    .iter()
-                    .rev()
-                    .position(|step| total_progress as f32 <= 1. - step.start_percentage)
-                    .map(|pos| num_steps - pos - 1);
-                prev_keyframe_index = next_keyframe_index
-                    .and_then(|pos| {
-                        if pos != num_steps - 1 {
-                            Some(pos + 1)
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(num_steps - 1)
+            // Dividing the timeline between infinitely many iterations makes
+            // them zero-length, so the animation is either before its first
+            // iteration or after the end of its last one.
+            //
+            // https://drafts.csswg.org/web-animations-2/#intrinsic-iteration-duration
+            KeyframesIterationState::Infinite(_) => {
+                if progress < 0. {
+                    (0., 0.)
+                } else if self.fills_forwards() {
+                    (1., f64::INFINITY)
+                } else {
+                    return None;
+                }
             },
-            _ => unreachable!(),
-        }
-
-        debug!(
-            "Animation::get_property_declaration_at_time: keyframe from {:?} to {:?}",
-            prev_keyframe_index, next_keyframe_index
-        );
+        };
 
-        let prev_keyframe = &self.computed_steps[prev_keyframe_index];
-        let next_keyframe = match next_keyframe_index {
-            Some(index) => &self.computed_steps[index],
-            None => return,
+        let odd_iteration = iteration % 2. == 1.;
+        let direction = match self.direction {
+            AnimationDirection::Normal => AnimationDirection::Normal,
+            AnimationDirection::Reverse => AnimationDirection::Reverse,
+            // The last of infinitely many iterations goes forwards.
+            _ if iteration.is_infinite() => AnimationDirection::Normal,
+            AnimationDirection::Alternate if odd_iteration => AnimationDirection::Reverse,
+            AnimationDirection::Alternate => AnimationDirection::Normal,
+            AnimationDirection::AlternateReverse if odd_iteration => AnimationDirection::Normal,
+            AnimationDirection::AlternateReverse => AnimationDirection::Reverse,
         };
 
-        // If we only need to take into account one keyframe, then exit early
-        // in order to avoid doing more work.
-        let mut add_declarations_to_map = |keyframe: &ComputedKeyframe| {
-            for value in keyframe.values.iter() {
-                map.insert(value.id(), value.clone());
-            }
+        Some((iteration_progress, direction))
+    }
+
+    /// The progress through the current iteration of this time-based animation
+    /// at the given time, or `None` if the animation has no effect at that time.
+    fn iteration_progress_at_time(&self, now: f64) -> Option<f64> {
+        let total_progress = match self.state {
+            AnimationState::Running | AnimationState::Pending | AnimationState::Finished => {
+                (now - self.started_at) / self.duration
+            },
+            AnimationState::Paused(progress) => progress,
+            AnimationState::Canceled => return None,
         };
-        if total_progress <= 0.0 {
-            add_declarations_to_map(&prev_keyframe);
-            return;
+
+        if total_progress < 0. && !self.fills_backwards() {
+            return None;
+        }
+        if self.has_ended(now) && !self.fills_forwards() {
+            return None;
         }
-        if total_progress >= 1.0 {
-            add_declarations_to_map(&next_keyframe);
+        Some(
+            total_progress
+                .min(self.current_iteration_end_progress())
+                .max(0.0),
+        )
+    }
+
+    /// Fill in an `AnimationValueMap` and a `CustomPropertyValueMap` with values
+    /// calculated from this animation at the given time value.
+    fn get_property_declaration_at_time(
+        &self,
+        now: f64,
+        map: &mut AnimationValueMap,
+        custom_values: &mut CustomPropertyValueMap,
+    ) {
+        debug_assert!(!self.computed_steps.is_empty());
+
+        if self.state == AnimationState::Canceled {
             return;
         }
 
-        let percentage_between_keyframes =
-            (next_keyframe.start_percentage - prev_keyframe.start_percentage).abs() as f64;
-        let duration_between_keyframes = percentage_between_keyframes * self.duration;
-        let direction_aware_prev_keyframe_start_percentage = match self.current_direction {
-            AnimationDirection::Normal => prev_keyframe.start_percentage as f64,
-            AnimationDirection::Reverse => 1. - prev_keyframe.start_percentage as f64,
-            _ => unreachable!(),
+        let progress = if self.is_scroll_driven() {
+            self.scroll_driven_iteration_progress()
+        } else {
+            self.iteration_progress_at_time(now)
+                .map(|progress| (progress, self.current_direction))
         };
-        let progress_between_keyframes = (total_progress -
-            direction_aware_prev_keyframe_start_percentage) /
-            percentage_between_keyframes;
-
-        for (from, to) in prev_keyframe.values.iter().zip(next_keyframe.values.iter()) {
-            let animation = PropertyAnimation {
-                from: from.clone(),
-                to: to.clone(),
-                timing_function: prev_keyframe.timing_function.clone(),
-                duration: duration_between_keyframes as f64,
-            };
+        let (total_progress, current_direction) = match progress {
+            Some(progress) => progress,
+            None => return,
+        };
+
+        // Scroll-driven animations don't have a duration, but the precision of the
+        // timing function output still depends on one.
+        let duration = if self.is_scroll_driven() {
+            1.
+        } else {
+            self.duration
+        };
+        sample_keyframes(
+            &self.computed_steps,
+            total_progress,
+            current_direction,
+            duration,
+            &self.cascade_style,
+            map,
+            custom_values,
+        );
+    }
+}
 
-            if let Ok(value) = animation.calculate_value(progress_between_keyframes) {
+/// Fill in an `AnimationValueMap` and a `CustomPropertyValueMap` with the
+/// values of the given keyframes at the given progress through the current
+/// iteration, which runs in the given direction and lasts for `duration`
+/// seconds.
+fn sample_keyframes(
+    steps: &[ComputedKeyframe],
+    total_progress: f64,
+    current_direction: AnimationDirection,
+    duration: f64,
+    cascade_style: &ComputedValues,
+    map: &mut AnimationValueMap,
+    custom_values: &mut CustomPropertyValueMap,
+) {
+    // Get the indices of the previous (from) keyframe and the next (to) keyframe.
+    let next_keyframe_index;
+    let prev_keyframe_index;
+    let num_steps = steps.len();
+    match current_direction {
+        AnimationDirection::Normal => {
+            next_keyframe_index = steps
+                .iter()
+                .position(|step| total_progress as f32 <= step.start_percentage);
+            prev_keyframe_index = next_keyframe_index
+                .and_then(|pos| if pos != 0 { Some(pos - 1) } else { None })
+                .unwrap_or(0);
+        },
+        AnimationDirection::Reverse => {
+            next_keyframe_index = steps
+                .iter()
+                .rev()
+                .position(|step| total_progress as f32 <= 1. - step.start_percentage)
+                .map(|pos| num_steps - pos - 1);
+            prev_keyframe_index = next_keyframe_index
+                .and_then(|pos| {
+                    if pos != num_steps - 1 {
+                        Some(pos + 1)
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(num_steps - 1)
+        },
+        _ => unreachable!(),
+    }
+
+    debug!(
+        "sample_keyframes: keyframe from {:?} to {:?}",
+        prev_keyframe_index, next_keyframe_index
+    );
+
+    let prev_keyframe = &steps[prev_keyframe_index];
+    let next_keyframe = match next_keyframe_index {
+        Some(index) => &steps[index],
+        None => return,
+    };
+
+    // If we only need to take into account one keyframe, then exit early
+    // in order to avoid doing more work.
+    let add_declarations_to_map =
+        |keyframe: &ComputedKeyframe,
+         map: &mut AnimationValueMap,
+         custom_values: &mut CustomPropertyValueMap| {
+            for index in 0..keyframe.values.len() {
+                let value = keyframe.composited_value(index, map, cascade_style);
                 map.insert(value.id(), value);
             }
+            for (name, value) in keyframe.custom_values.iter() {
+                if let Ok(value) = value.to_variable_value() {
+                    custom_values.insert(name.clone(), value);
+                }
+            }
+        };
+    if total_progress <= 0.0 {
+        add_declarations_to_map(&prev_keyframe, map, custom_values);
+        return;
+    }
+    if total_progress >= 1.0 {
+        add_declarations_to_map(&next_keyframe, map, custom_values);
+        return;
+    }
+
+    let percentage_between_keyframes =
+        (next_keyframe.start_percentage - prev_keyframe.start_percentage).abs() as f64;
+    let duration_between_keyframes = percentage_between_keyframes * duration;
+    let direction_aware_prev_keyframe_start_percentage = match current_direction {
+        AnimationDirection::Normal => prev_keyframe.start_percentage as f64,
+        AnimationDirection::Reverse => 1. - prev_keyframe.start_percentage as f64,
+        _ => unreachable!(),
+    };
+    let progress_between_keyframes = (total_progress -
+        direction_aware_prev_keyframe_start_percentage) /
+        percentage_between_keyframes;
+
+    for index in 0..prev_keyframe.values.len() {
+        let from = prev_keyframe.composited_value(index, map, cascade_style);
+        let to = next_keyframe.composited_value(index, map, cascade_style);
+        let animation = PropertyAnimation {
+            from,
+            to,
+            timing_function: prev_keyframe.timing_function.clone(),
+            duration: duration_between_keyframes as f64,
+        };
+
+        if let Ok(value) = animation.calculate_value(progress_between_keyframes) {
+            map.insert(value.id(), value);
+        }
+    }
+
+    for (name, from) in prev_keyframe.custom_values.iter() {
+        let to = match next_keyframe
+            .custom_values
+            .iter()
+            .find(|(next_name, _)| next_name == name)
+        {
+            Some((_, to)) => to,
+            None => continue,
+        };
+        let animation = CustomPropertyAnimation {
+            name: name.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            timing_function: prev_keyframe.timing_function.clone(),
+            duration: duration_between_keyframes,
+        };
+        // Values that can't be interpolated flip at the midpoint, like other
+        // discretely animated values.
+        let value = match animation.calculate_value(progress_between_keyframes) {
+            Ok(value) => value,
+            Err(()) if progress_between_keyframes < 0.5 => animation.from,
+            Err(()) => animation.to,
+        };
+        if let Ok(value) = value.to_variable_value() {
+            custom_values.insert(name.clone(), value);
         }
     }
 }
@@ -719,6 +1721,9 @@ impl fmt::Debug for Animation {
             .field("state", &self.state)
             .field("direction", &self.direction)
             .field("current_direction", &self.current_direction)
+            .field("timeline", &self.timeline)
+            .field("range_start", &self.range_start)
+            .field("range_end", &self.range_end)
             .field("cascade_style", &())
             .finish()
     }
@@ -844,6 +1849,607 @@ impl Transition {
     }
 }
 
+/// A CSS Transition of a registered custom property.
+///
+/// Unlike transitions of longhands, these don't take into account reversing of
+/// a running transition, and the script thread isn't notified about them.
+#[derive(Clone, Debug, MallocSizeOf)]
+pub struct CustomPropertyTransition {
+    /// The start time of this transition, which is the current value of the animation
+    /// timeline when this transition was created plus any animation delay.
+    pub start_time: f64,
+
+    /// The delay used for this transition.
+    pub delay: f64,
+
+    /// The internal `CustomPropertyAnimation` for this transition.
+    pub property_animation: CustomPropertyAnimation,
+
+    /// The state of this transition.
+    pub state: AnimationState,
+}
+
+impl CustomPropertyTransition {
+    /// Whether or not this transition has ended at the provided time.
+    pub fn has_ended(&self, time: f64) -> bool {
+        time >= self.start_time + (self.property_animation.duration)
+    }
+
+    /// Update the given transition at a given point of progress, returning
+    /// the value as a token stream.
+    pub fn calculate_value(&self, time: f64) -> Option<Arc<VariableValue>> {
+        let progress = (time - self.start_time) / (self.property_animation.duration);
+        if progress < 0.0 {
+            return None;
+        }
+
+        self.property_animation
+            .calculate_value(progress.min(1.0))
+            .ok()?
+            .to_variable_value()
+            .ok()
+    }
+}
+
+/// The identifier of an animation created with
+/// `DocumentAnimationSet::add_script_animation`.
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq)]
+pub struct ScriptAnimationId(pub u64);
+
+/// A keyframe of an animation created by the embedder.
+#[derive(Clone, Debug)]
+pub struct ScriptKeyframe {
+    /// The offset of this keyframe, between 0 and 1. Keyframes without an
+    /// offset are spaced evenly between the ones around them.
+    pub offset: Option<f64>,
+
+    /// The timing function used from this keyframe to the next one.
+    pub easing: TimingFunction,
+
+    /// The composite operation of this keyframe, or `None` to use the one of
+    /// the animation.
+    pub composite: Option<AnimationComposition>,
+
+    /// The values of this keyframe.
+    pub values: Vec<AnimationValue>,
+}
+
+/// The timing properties of the effect of an animation created by the embedder.
+/// Times are in seconds.
+///
+/// <https://drafts.csswg.org/web-animations-1/#the-effecttiming-dictionaries>
+#[derive(Clone, Debug, MallocSizeOf)]
+pub struct EffectTiming {
+    /// The delay before the start of the active interval.
+    pub delay: f64,
+
+    /// The delay after the end of the active interval.
+    pub end_delay: f64,
+
+    /// How the effect applies outside of its active interval.
+    pub fill: AnimationFillMode,
+
+    /// The iteration at which the effect starts, which may be fractional.
+    pub iteration_start: f64,
+
+    /// The number of iterations, which may be infinite.
+    pub iterations: f64,
+
+    /// The duration of a single iteration.
+    pub duration: f64,
+
+    /// The direction in which the iterations run.
+    pub direction: AnimationDirection,
+
+    /// The timing function applied to the progress of each iteration.
+    pub easing: TimingFunction,
+}
+
+impl Default for EffectTiming {
+    fn default() -> Self {
+        EffectTiming {
+            delay: 0.,
+            end_delay: 0.,
+            fill: AnimationFillMode::None,
+            iteration_start: 0.,
+            iterations: 1.,
+            duration: 0.,
+            direction: AnimationDirection::Normal,
+            easing: TimingFunction::linear(),
+        }
+    }
+}
+
+impl EffectTiming {
+    /// The duration of the active interval of the effect.
+    pub fn active_duration(&self) -> f64 {
+        if self.duration == 0. || self.iterations == 0. {
+            return 0.;
+        }
+        self.duration * self.iterations
+    }
+
+    /// The end time of the effect, including its delays.
+    pub fn end_time(&self) -> f64 {
+        (self.delay + self.active_duration() + self.end_delay).max(0.)
+    }
+
+    fn fills_backwards(&self) -> bool {
+        matches!(
+            self.fill,
+            AnimationFillMode::Backwards | AnimationFillMode::Both
+        )
+    }
+
+    fn fills_forwards(&self) -> bool {
+        matches!(
+            self.fill,
+            AnimationFillMode::Forwards | AnimationFillMode::Both
+        )
+    }
+}
+
+/// The phase of an animation effect at a given time.
+///
+/// <https://drafts.csswg.org/web-animations-1/#animation-effect-phases-and-states>
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EffectPhase {
+    Before,
+    Active,
+    After,
+}
+
+/// The play state of an animation created by the embedder.
+///
+/// <https://drafts.csswg.org/web-animations-1/#play-states>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq)]
+pub enum ScriptAnimationPlayState {
+    /// The animation has no current time.
+    Idle,
+    /// The animation is playing.
+    Running,
+    /// The animation is paused.
+    Paused,
+    /// The animation has reached the end of its effect, or its start when
+    /// playing backwards.
+    Finished,
+}
+
+/// The type of a `ScriptAnimationEvent`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScriptAnimationEventType {
+    /// The animation entered the finished play state.
+    Finish,
+    /// The animation was canceled.
+    Cancel,
+}
+
+/// An event about an animation created by the embedder, as returned by
+/// `DocumentAnimationSet::update_script_animations`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptAnimationEvent {
+    /// The key of the set holding the animation.
+    pub key: AnimationSetKey,
+
+    /// The identifier of the animation.
+    pub id: ScriptAnimationId,
+
+    /// The type of this event.
+    pub event_type: ScriptAnimationEventType,
+
+    /// The current time of the animation when this event was generated.
+    pub current_time: Option<f64>,
+}
+
+/// An animation created by the embedder rather than by style, which follows the
+/// timing model of Web Animations. Each property is interpolated between the
+/// keyframes which specify it.
+///
+/// <https://drafts.csswg.org/web-animations-1/#animations>
+#[derive(Clone, MallocSizeOf)]
+pub struct ScriptAnimation {
+    /// The identifier of this animation.
+    pub id: ScriptAnimationId,
+
+    /// The keyframes of the effect of this animation, one list per animated
+    /// property, each with a single value.
+    property_keyframes: Vec<Vec<ComputedKeyframe>>,
+
+    /// The timing properties of the effect of this animation.
+    pub timing: EffectTiming,
+
+    /// The time of the document timeline at which the current time of this
+    /// animation was zero, if it is playing.
+    start_time: Option<f64>,
+
+    /// The current time of this animation while it is paused, finished or
+    /// waiting to be played.
+    hold_time: Option<f64>,
+
+    /// The rate at which the current time advances relative to the timeline.
+    playback_rate: f64,
+
+    /// The current time the last time the finished state was updated.
+    previous_current_time: Option<f64>,
+
+    /// The style the animation was created for, whose values are used for the
+    /// properties that keyframes don't specify.
+    #[ignore_malloc_size_of = "ComputedValues"]
+    cascade_style: Arc<ComputedValues>,
+
+    /// Whether the finish event for the current finished state has been returned
+    /// by `DocumentAnimationSet::update_script_animations`.
+    finish_notified: bool,
+
+    /// Whether this animation was canceled and the cancel event hasn't been
+    /// returned by `DocumentAnimationSet::update_script_animations` yet.
+    cancel_pending: bool,
+}
+
+impl ScriptAnimation {
+    fn new(
+        id: ScriptAnimationId,
+        property_keyframes: Vec<Vec<ComputedKeyframe>>,
+        timing: EffectTiming,
+        cascade_style: Arc<ComputedValues>,
+    ) -> Self {
+        ScriptAnimation {
+            id,
+            property_keyframes,
+            timing,
+            start_time: None,
+            hold_time: None,
+            playback_rate: 1.,
+            previous_current_time: None,
+            cascade_style,
+            finish_notified: false,
+            cancel_pending: false,
+        }
+    }
+
+    /// The current time of this animation given the current time of the
+    /// document timeline, or `None` if the animation is idle.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#the-current-time-of-an-animation>
+    pub fn current_time(&self, now: f64) -> Option<f64> {
+        self.hold_time
+            .or_else(|| Some((now - self.start_time?) * self.playback_rate))
+    }
+
+    /// The playback rate of this animation.
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate
+    }
+
+    /// The play state of this animation.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#play-states>
+    pub fn play_state(&self, now: f64) -> ScriptAnimationPlayState {
+        let current_time = match self.current_time(now) {
+            Some(current_time) => current_time,
+            None => return ScriptAnimationPlayState::Idle,
+        };
+        if self.start_time.is_none() {
+            return ScriptAnimationPlayState::Paused;
+        }
+        if (self.playback_rate > 0. && current_time >= self.timing.end_time()) ||
+            (self.playback_rate < 0. && current_time <= 0.)
+        {
+            return ScriptAnimationPlayState::Finished;
+        }
+        ScriptAnimationPlayState::Running
+    }
+
+    fn is_idle(&self) -> bool {
+        self.start_time.is_none() && self.hold_time.is_none()
+    }
+
+    /// Play this animation, rewinding it if it is idle or finished. Returns an
+    /// error if it would need to be rewound to the end of an infinite effect.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#playing-an-animation-section>
+    pub fn play(&mut self, now: f64) -> Result<(), ()> {
+        let current_time = self.current_time(now);
+        let end_time = self.timing.end_time();
+        if self.playback_rate > 0. &&
+            current_time.map_or(true, |time| time < 0. || time >= end_time)
+        {
+            self.hold_time = Some(0.);
+        } else if self.playback_rate < 0. &&
+            current_time.map_or(true, |time| time <= 0. || time > end_time)
+        {
+            if end_time.is_infinite() {
+                return Err(());
+            }
+            self.hold_time = Some(end_time);
+        } else if self.playback_rate == 0. && current_time.is_none() {
+            self.hold_time = Some(0.);
+        }
+
+        if let Some(hold_time) = self.hold_time {
+            if self.playback_rate == 0. {
+                self.start_time = Some(now);
+            } else {
+                self.start_time = Some(now - hold_time / self.playback_rate);
+                self.hold_time = None;
+            }
+        }
+        self.update_finished_state(now, false);
+        Ok(())
+    }
+
+    /// Pause this animation. Returns an error if it is idle, playing backwards
+    /// and its effect is infinite.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#pausing-an-animation-section>
+    pub fn pause(&mut self, now: f64) -> Result<(), ()> {
+        if self.play_state(now) == ScriptAnimationPlayState::Paused {
+            return Ok(());
+        }
+        let hold_time = match self.current_time(now) {
+            Some(current_time) => current_time,
+            None if self.playback_rate >= 0. => 0.,
+            None => {
+                let end_time = self.timing.end_time();
+                if end_time.is_infinite() {
+                    return Err(());
+                }
+                end_time
+            },
+        };
+        self.hold_time = Some(hold_time);
+        self.start_time = None;
+        self.update_finished_state(now, false);
+        Ok(())
+    }
+
+    fn silently_set_current_time(&mut self, now: f64, time: f64) {
+        if self.hold_time.is_some() || self.start_time.is_none() || self.playback_rate == 0. {
+            self.hold_time = Some(time);
+        } else {
+            self.start_time = Some(now - time / self.playback_rate);
+        }
+        self.previous_current_time = None;
+    }
+
+    /// Seek this animation to the given current time.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#setting-the-current-time-of-an-animation>
+    pub fn set_current_time(&mut self, now: f64, time: f64) {
+        self.silently_set_current_time(now, time);
+        self.update_finished_state(now, true);
+    }
+
+    /// Change the playback rate of this animation, preserving its current time.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#setting-the-playback-rate-of-an-animation>
+    pub fn set_playback_rate(&mut self, now: f64, playback_rate: f64) {
+        let previous_time = self.current_time(now);
+        self.playback_rate = playback_rate;
+        if let Some(previous_time) = previous_time {
+            self.set_current_time(now, previous_time);
+        }
+    }
+
+    /// Reverse the playback direction of this animation and play it.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#reversing-an-animation-section>
+    pub fn reverse(&mut self, now: f64) -> Result<(), ()> {
+        let original_playback_rate = self.playback_rate;
+        self.set_playback_rate(now, -original_playback_rate);
+        let result = self.play(now);
+        if result.is_err() {
+            self.set_playback_rate(now, original_playback_rate);
+        }
+        result
+    }
+
+    /// Seek this animation to the end of its effect, or to its start when it
+    /// plays backwards. Returns an error if the playback rate is zero or the end
+    /// would be infinite.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#finishing-an-animation-section>
+    pub fn finish(&mut self, now: f64) -> Result<(), ()> {
+        let end_time = self.timing.end_time();
+        if self.playback_rate == 0. || (self.playback_rate > 0. && end_time.is_infinite()) {
+            return Err(());
+        }
+        let limit = if self.playback_rate > 0. {
+            end_time
+        } else {
+            0.
+        };
+        self.silently_set_current_time(now, limit);
+        if self.start_time.is_none() {
+            self.start_time = Some(now - limit / self.playback_rate);
+        }
+        self.update_finished_state(now, true);
+        Ok(())
+    }
+
+    /// Cancel this animation, which makes it idle and removes its effect.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#canceling-an-animation-section>
+    pub fn cancel(&mut self) {
+        if self.is_idle() {
+            return;
+        }
+        self.cancel_pending = true;
+        self.start_time = None;
+        self.hold_time = None;
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#updating-the-finished-state>
+    fn update_finished_state(&mut self, now: f64, did_seek: bool) {
+        let unconstrained_current_time = if did_seek {
+            self.current_time(now)
+        } else {
+            self.start_time
+                .map(|start_time| (now - start_time) * self.playback_rate)
+        };
+
+        if let (Some(current_time), Some(_)) = (unconstrained_current_time, self.start_time) {
+            let end_time = self.timing.end_time();
+            if self.playback_rate > 0. && current_time >= end_time {
+                self.hold_time = Some(match self.previous_current_time {
+                    _ if did_seek => current_time,
+                    Some(previous_time) => previous_time.max(end_time),
+                    None => end_time,
+                });
+            } else if self.playback_rate < 0. && current_time <= 0. {
+                self.hold_time = Some(match self.previous_current_time {
+                    _ if did_seek => current_time,
+                    Some(previous_time) => previous_time.min(0.),
+                    None => 0.,
+                });
+            } else if self.playback_rate != 0. && did_seek {
+                if let Some(hold_time) = self.hold_time.take() {
+                    self.start_time = Some(now - hold_time / self.playback_rate);
+                }
+            }
+        }
+
+        self.previous_current_time = self.current_time(now);
+    }
+
+    fn needs_to_be_ticked(&self) -> bool {
+        self.start_time.is_some() && self.hold_time.is_none() && self.playback_rate != 0.
+    }
+
+    /// Fill in an `AnimationValueMap` and a `CustomPropertyValueMap` with values
+    /// calculated from this animation at the given time value.
+    ///
+    /// <https://drafts.csswg.org/web-animations-1/#core-animation-effect-calculations>
+    fn get_property_declaration_at_time(
+        &self,
+        now: f64,
+        map: &mut AnimationValueMap,
+        custom_values: &mut CustomPropertyValueMap,
+    ) {
+        let local_time = match self.current_time(now) {
+            Some(local_time) => local_time,
+            None => return,
+        };
+
+        let timing = &self.timing;
+        let active_duration = timing.active_duration();
+        let end_time = timing.end_time();
+        let before_active_boundary = timing.delay.min(end_time).max(0.);
+        let active_after_boundary = (timing.delay + active_duration).min(end_time).max(0.);
+        let phase = if local_time < before_active_boundary ||
+            (self.playback_rate < 0. && local_time == before_active_boundary)
+        {
+            EffectPhase::Before
+        } else if local_time > active_after_boundary ||
+            (self.playback_rate >= 0. && local_time == active_after_boundary)
+        {
+            EffectPhase::After
+        } else {
+            EffectPhase::Active
+        };
+
+        let active_time = match phase {
+            EffectPhase::Before if timing.fills_backwards() => (local_time - timing.delay).max(0.),
+            EffectPhase::Active => local_time - timing.delay,
+            EffectPhase::After if timing.fills_forwards() => {
+                (local_time - timing.delay).min(active_duration).max(0.)
+            },
+            _ => return,
+        };
+
+        let overall_progress = if timing.duration == 0. {
+            match phase {
+                EffectPhase::Before => timing.iteration_start,
+                _ => timing.iteration_start + timing.iterations,
+            }
+        } else {
+            active_time / timing.duration + timing.iteration_start
+        };
+
+        let mut simple_iteration_progress = if overall_progress.is_infinite() {
+            timing.iteration_start % 1.
+        } else {
+            overall_progress % 1.
+        };
+        if simple_iteration_progress == 0. &&
+            phase != EffectPhase::Before &&
+            active_time == active_duration &&
+            timing.iterations != 0.
+        {
+            simple_iteration_progress = 1.;
+        }
+
+        let current_iteration = if phase == EffectPhase::After && timing.iterations.is_infinite() {
+            f64::INFINITY
+        } else if simple_iteration_progress == 1. {
+            overall_progress.floor() - 1.
+        } else {
+            overall_progress.floor()
+        };
+
+        let forwards = match timing.direction {
+            AnimationDirection::Normal => true,
+            AnimationDirection::Reverse => false,
+            AnimationDirection::Alternate | AnimationDirection::AlternateReverse => {
+                let mut iteration = current_iteration;
+                if timing.direction == AnimationDirection::AlternateReverse {
+                    iteration += 1.;
+                }
+                iteration.is_infinite() || iteration % 2. == 0.
+            },
+        };
+        let directed_progress = if forwards {
+            simple_iteration_progress
+        } else {
+            1. - simple_iteration_progress
+        };
+
+        let before_flag = if (phase == EffectPhase::Before && forwards) ||
+            (phase == EffectPhase::After && !forwards)
+        {
+            BeforeFlag::Set
+        } else {
+            BeforeFlag::Unset
+        };
+
+        // The precision of the timing function output depends on the duration,
+        // which may be zero.
+        let duration = if timing.duration > 0. && timing.duration.is_finite() {
+            timing.duration
+        } else {
+            1.
+        };
+        let epsilon = 1. / (200. * duration);
+        let progress = timing
+            .easing
+            .calculate_output(directed_progress, before_flag, epsilon);
+        for steps in self.property_keyframes.iter() {
+            sample_keyframes(
+                steps,
+                progress.min(1.).max(0.),
+                AnimationDirection::Normal,
+                duration,
+                &self.cascade_style,
+                map,
+                custom_values,
+            );
+        }
+    }
+}
+
+impl fmt::Debug for ScriptAnimation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScriptAnimation")
+            .field("id", &self.id)
+            .field("timing", &self.timing)
+            .field("start_time", &self.start_time)
+            .field("hold_time", &self.hold_time)
+            .field("playback_rate", &self.playback_rate)
+            .field("cascade_style", &())
+            .finish()
+    }
+}
+
 /// Holds the animation state for a particular element.
 #[derive(Debug, Default, MallocSizeOf)]
 pub struct ElementAnimationSet {
@@ -853,6 +2459,12 @@ pub struct ElementAnimationSet {
     /// The transitions for this element.
     pub transitions: Vec<Transition>,
 
+    /// The transitions of registered custom properties for this element.
+    pub custom_property_transitions: Vec<CustomPropertyTransition>,
+
+    /// The animations created by the embedder for this element.
+    pub script_animations: Vec<ScriptAnimation>,
+
     /// Whether or not this ElementAnimationSet has had animations or transitions
     /// which have been added, removed, or had their state changed.
     pub dirty: bool,
@@ -866,6 +2478,12 @@ impl ElementAnimationSet {
         for animation in self.animations.iter_mut() {
             animation.state = AnimationState::Canceled;
         }
+        for animation in self.script_animations.iter_mut() {
+            if !animation.is_idle() {
+                self.dirty = true;
+                animation.cancel();
+            }
+        }
         self.cancel_active_transitions();
     }
 
@@ -876,6 +2494,12 @@ impl ElementAnimationSet {
                 transition.state = AnimationState::Canceled;
             }
         }
+        for transition in self.custom_property_transitions.iter_mut() {
+            if transition.state != AnimationState::Finished {
+                self.dirty = true;
+                transition.state = AnimationState::Canceled;
+            }
+        }
     }
 
     /// Apply all active animations.
@@ -886,10 +2510,12 @@ impl ElementAnimationSet {
     ) {
         let now = context.current_time_for_animations;
         let mutable_style = Arc::make_mut(style);
-        if let Some(map) = self.get_value_map_for_active_animations(now) {
+        let mut custom_values = CustomPropertyValueMap::default();
+        if let Some((map, animated_custom_values)) = self.get_values_for_active_animations(now) {
             for value in map.values() {
                 value.set_in_style_for_servo(mutable_style);
             }
+            custom_values = animated_custom_values;
         }
 
         if let Some(map) = self.get_value_map_for_active_transitions(now) {
@@ -897,6 +2523,10 @@ impl ElementAnimationSet {
                 value.set_in_style_for_servo(mutable_style);
             }
         }
+
+        // Transitions override animations, like in the cascade.
+        custom_values.extend(self.get_custom_property_values_for_active_transitions(now));
+        mutable_style.set_custom_properties(custom_values);
     }
 
     /// Clear all canceled animations and transitions from this `ElementAnimationSet`.
@@ -905,12 +2535,17 @@ impl ElementAnimationSet {
             .retain(|animation| animation.state != AnimationState::Canceled);
         self.transitions
             .retain(|animation| animation.state != AnimationState::Canceled);
+        self.custom_property_transitions
+            .retain(|animation| animation.state != AnimationState::Canceled);
     }
 
     /// Whether this `ElementAnimationSet` is empty, which means it doesn't
     /// hold any animations in any state.
     pub fn is_empty(&self) -> bool {
-        self.animations.is_empty() && self.transitions.is_empty()
+        self.animations.is_empty() &&
+            self.transitions.is_empty() &&
+            self.custom_property_transitions.is_empty() &&
+            self.script_animations.is_empty()
     }
 
     /// Whether or not this state needs animation ticks for its transitions
@@ -918,10 +2553,16 @@ impl ElementAnimationSet {
     pub fn needs_animation_ticks(&self) -> bool {
         self.animations
             .iter()
-            .any(|animation| animation.state.needs_to_be_ticked()) ||
+            .any(|animation| animation.needs_to_be_ticked()) ||
             self.transitions
                 .iter()
-                .any(|transition| transition.state.needs_to_be_ticked())
+                .any(|transition| transition.state.needs_to_be_ticked()) ||
+            self.custom_property_transitions
+                .iter()
+                .any(|transition| transition.state.needs_to_be_ticked()) ||
+            self.script_animations
+                .iter()
+                .any(|animation| animation.needs_to_be_ticked())
     }
 
     /// The number of running animations and transitions for this `ElementAnimationSet`.
@@ -931,6 +2572,10 @@ impl ElementAnimationSet {
             .filter(|animation| animation.state.needs_to_be_ticked())
             .count() +
             self.transitions
+                .iter()
+                .filter(|transition| transition.state.needs_to_be_ticked())
+                .count() +
+            self.custom_property_transitions
                 .iter()
                 .filter(|transition| transition.state.needs_to_be_ticked())
                 .count()
@@ -940,14 +2585,20 @@ impl ElementAnimationSet {
     pub fn has_active_animation(&self) -> bool {
         self.animations
             .iter()
-            .any(|animation| animation.state != AnimationState::Canceled)
+            .any(|animation| animation.state != AnimationState::Canceled) ||
+            self.script_animations
+                .iter()
+                .any(|animation| !animation.is_idle())
     }
 
     /// If this `ElementAnimationSet` has any any active transitions.
     pub fn has_active_transition(&self) -> bool {
         self.transitions
             .iter()
-            .any(|transition| transition.state != AnimationState::Canceled)
+            .any(|transition| transition.state != AnimationState::Canceled) ||
+            self.custom_property_transitions
+                .iter()
+                .any(|transition| transition.state != AnimationState::Canceled)
     }
 
     /// Update our animations given a new style, canceling or starting new animations
@@ -979,6 +2630,8 @@ impl ElementAnimationSet {
         old_style: Option<&Arc<ComputedValues>>,
         after_change_style: &Arc<ComputedValues>,
     ) {
+        self.update_custom_property_transition_states(context.current_time_for_animations);
+
         // If this is the first style, we don't trigger any transitions and we assume
         // there were no previously triggered transitions.
         let mut before_change_style = match old_style {
@@ -986,8 +2639,12 @@ impl ElementAnimationSet {
             None => return,
         };
 
-        // If the style of this element is display:none, then cancel all active transitions.
-        if after_change_style.get_box().clone_display().is_none() {
+        // If the style of this element is display:none, then cancel all active
+        // transitions, unless `display` itself transitions, which keeps the
+        // element rendered until the end of the transition.
+        if after_change_style.get_box().clone_display().is_none() &&
+            !transitions_display_discretely(after_change_style)
+        {
             self.cancel_active_transitions();
             return;
         }
@@ -1007,6 +2664,12 @@ impl ElementAnimationSet {
             after_change_style,
             self,
         );
+        let transitioning_custom_properties = start_custom_property_transitions_if_applicable(
+            context,
+            &before_change_style,
+            after_change_style,
+            self,
+        );
 
         // Cancel any non-finished transitions that have properties which no longer transition.
         for transition in self.transitions.iter_mut() {
@@ -1019,6 +2682,16 @@ impl ElementAnimationSet {
             transition.state = AnimationState::Canceled;
             self.dirty = true;
         }
+        for transition in self.custom_property_transitions.iter_mut() {
+            if transition.state == AnimationState::Finished {
+                continue;
+            }
+            if transitioning_custom_properties.contains(&transition.property_animation.name) {
+                continue;
+            }
+            transition.state = AnimationState::Canceled;
+            self.dirty = true;
+        }
     }
 
     fn start_transition_if_applicable(
@@ -1033,6 +2706,7 @@ impl ElementAnimationSet {
         let timing_function = style.transition_timing_function_mod(index);
         let duration = style.transition_duration_mod(index);
         let delay = style.transition_delay_mod(index).seconds() as f64;
+        let allow_discrete = transition_allows_discrete(style, index);
         let now = context.current_time_for_animations;
 
         // Only start a new transition if the style actually changes between
@@ -1041,6 +2715,7 @@ impl ElementAnimationSet {
             longhand_id,
             timing_function,
             duration,
+            allow_discrete,
             old_style,
             new_style,
         ) {
@@ -1089,6 +2764,118 @@ impl ElementAnimationSet {
         self.dirty = true;
     }
 
+    /// Advances the state of the transitions of registered custom properties.
+    /// Unlike other transitions, these aren't tracked by the script thread, so
+    /// they're removed as soon as they end.
+    fn update_custom_property_transition_states(&mut self, now: f64) {
+        let previous_len = self.custom_property_transitions.len();
+        self.custom_property_transitions
+            .retain(|transition| !transition.has_ended(now));
+        if self.custom_property_transitions.len() != previous_len {
+            self.dirty = true;
+        }
+        for transition in self.custom_property_transitions.iter_mut() {
+            if transition.state == AnimationState::Pending && now >= transition.start_time {
+                transition.state = AnimationState::Running;
+            }
+        }
+    }
+
+    fn start_custom_property_transition_if_applicable(
+        &mut self,
+        context: &SharedStyleContext,
+        name: &CustomPropertyName,
+        registration: &PropertyRegistration,
+        index: usize,
+        old_style: &ComputedValues,
+        new_style: &Arc<ComputedValues>,
+    ) {
+        let style = new_style.get_ui();
+        let timing_function = style.transition_timing_function_mod(index);
+        let duration = style.transition_duration_mod(index);
+        let delay = style.transition_delay_mod(index).seconds() as f64;
+        let now = context.current_time_for_animations;
+
+        let property_animation = match CustomPropertyAnimation::from_custom_property(
+            name,
+            registration,
+            context,
+            timing_function,
+            duration,
+            old_style,
+            new_style,
+        ) {
+            Some(property_animation) => property_animation,
+            None => return,
+        };
+
+        // Don't trigger a new transition if the end state for that transition
+        // is the same as that of a transition that's running or completed.
+        if self
+            .custom_property_transitions
+            .iter()
+            .filter(|transition| transition.state != AnimationState::Canceled)
+            .any(|transition| {
+                transition.property_animation.name == *name &&
+                    transition.property_animation.to == property_animation.to
+            })
+        {
+            return;
+        }
+
+        // We always cancel any running transitions for the same property.
+        for transition in self.custom_property_transitions.iter_mut() {
+            if transition.state == AnimationState::Running &&
+                transition.property_animation.name == *name
+            {
+                transition.state = AnimationState::Canceled;
+            }
+        }
+
+        self.custom_property_transitions
+            .push(CustomPropertyTransition {
+                start_time: now + delay,
+                delay,
+                property_animation,
+                state: AnimationState::Pending,
+            });
+        self.dirty = true;
+    }
+
+    /// Returns the values of the registered custom properties with active
+    /// transitions, at the given time value.
+    pub fn get_custom_property_values_for_active_transitions(
+        &self,
+        now: f64,
+    ) -> Vec<(CustomPropertyName, Arc<VariableValue>)> {
+        self.custom_property_transitions
+            .iter()
+            .filter(|transition| transition.state != AnimationState::Canceled)
+            .filter_map(|transition| {
+                let value = transition.calculate_value(now)?;
+                Some((transition.property_animation.name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Returns the declaration block for this `ElementAnimationSet`'s active
+    /// transitions at the given time value, including those of registered
+    /// custom properties.
+    fn get_transition_declaration_block(&self, now: f64) -> Option<PropertyDeclarationBlock> {
+        let map = self.get_value_map_for_active_transitions(now)?;
+        let mut block = PropertyDeclarationBlock::from_animation_value_map(&map);
+        for (name, value) in self.get_custom_property_values_for_active_transitions(now) {
+            block.push(
+                PropertyDeclaration::Custom(CustomDeclaration {
+                    name,
+                    value: CustomDeclarationValue::Value(value),
+                }),
+                Importance::Normal,
+            );
+        }
+        Some(block)
+    }
+
     /// Generate a `AnimationValueMap` for this `ElementAnimationSet`'s
     /// active transitions at the given time value.
     pub fn get_value_map_for_active_transitions(&self, now: f64) -> Option<AnimationValueMap> {
@@ -1115,16 +2902,48 @@ impl ElementAnimationSet {
     /// Generate a `AnimationValueMap` for this `ElementAnimationSet`'s
     /// active animations at the given time value.
     pub fn get_value_map_for_active_animations(&self, now: f64) -> Option<AnimationValueMap> {
+        self.get_values_for_active_animations(now).map(|(map, _)| map)
+    }
+
+    /// Generate the values of the properties and registered custom properties
+    /// that this `ElementAnimationSet`'s active animations animate, at the
+    /// given time value.
+    fn get_values_for_active_animations(
+        &self,
+        now: f64,
+    ) -> Option<(AnimationValueMap, CustomPropertyValueMap)> {
         if !self.has_active_animation() {
             return None;
         }
 
         let mut map = Default::default();
+        let mut custom_values = Default::default();
         for animation in &self.animations {
-            animation.get_property_declaration_at_time(now, &mut map);
+            animation.get_property_declaration_at_time(now, &mut map, &mut custom_values);
+        }
+        for animation in &self.script_animations {
+            animation.get_property_declaration_at_time(now, &mut map, &mut custom_values);
         }
 
-        Some(map)
+        Some((map, custom_values))
+    }
+
+    /// Returns the declaration block for this `ElementAnimationSet`'s active
+    /// animations at the given time value, including those of registered
+    /// custom properties.
+    fn get_animation_declaration_block(&self, now: f64) -> Option<PropertyDeclarationBlock> {
+        let (map, custom_values) = self.get_values_for_active_animations(now)?;
+        let mut block = PropertyDeclarationBlock::from_animation_value_map(&map);
+        for (name, value) in custom_values {
+            block.push(
+                PropertyDeclaration::Custom(CustomDeclaration {
+                    name,
+                    value: CustomDeclarationValue::Value(value),
+                }),
+                Importance::Normal,
+            );
+        }
+        Some(block)
     }
 }
 
@@ -1170,6 +2989,15 @@ pub struct DocumentAnimationSet {
     /// The `ElementAnimationSet`s that this set contains.
     #[ignore_malloc_size_of = "Arc is hard"]
     pub sets: Arc<RwLock<FxHashMap<AnimationSetKey, ElementAnimationSet>>>,
+
+    /// The scroll state reported by the embedder, which scroll-driven animations
+    /// sample their progress from.
+    #[ignore_malloc_size_of = "Arc is hard"]
+    pub scroll_states: Arc<RwLock<ScrollStateMap>>,
+
+    /// The identifier of the next animation created by the embedder.
+    #[ignore_malloc_size_of = "Arc is hard"]
+    next_script_animation_id: Arc<AtomicU64>,
 }
 
 impl DocumentAnimationSet {
@@ -1200,11 +3028,8 @@ impl DocumentAnimationSet {
         self.sets
             .read()
             .get(key)
-            .and_then(|set| set.get_value_map_for_active_animations(time))
-            .map(|map| {
-                let block = PropertyDeclarationBlock::from_animation_value_map(&map);
-                Arc::new(shared_lock.wrap(block))
-            })
+            .and_then(|set| set.get_animation_declaration_block(time))
+            .map(|block| Arc::new(shared_lock.wrap(block)))
     }
 
     /// Return a locked PropertyDeclarationBlock with transition values for the given
@@ -1218,11 +3043,8 @@ impl DocumentAnimationSet {
         self.sets
             .read()
             .get(key)
-            .and_then(|set| set.get_value_map_for_active_transitions(time))
-            .map(|map| {
-                let block = PropertyDeclarationBlock::from_animation_value_map(&map);
-                Arc::new(shared_lock.wrap(block))
-            })
+            .and_then(|set| set.get_transition_declaration_block(time))
+            .map(|block| Arc::new(shared_lock.wrap(block)))
     }
 
     /// Get all the animation declarations for the given key, returning an empty
@@ -1239,14 +3061,12 @@ impl DocumentAnimationSet {
             None => return Default::default(),
         };
 
-        let animations = set.get_value_map_for_active_animations(time).map(|map| {
-            let block = PropertyDeclarationBlock::from_animation_value_map(&map);
-            Arc::new(shared_lock.wrap(block))
-        });
-        let transitions = set.get_value_map_for_active_transitions(time).map(|map| {
-            let block = PropertyDeclarationBlock::from_animation_value_map(&map);
-            Arc::new(shared_lock.wrap(block))
-        });
+        let animations = set
+            .get_animation_declaration_block(time)
+            .map(|block| Arc::new(shared_lock.wrap(block)));
+        let transitions = set
+            .get_transition_declaration_block(time)
+            .map(|block| Arc::new(shared_lock.wrap(block)));
         AnimationDeclarations {
             animations,
             transitions,
@@ -1259,6 +3079,169 @@ impl DocumentAnimationSet {
             set.cancel_all_animations();
         }
     }
+
+    /// Create an idle animation of the element at `key` with the given keyframes
+    /// and timing, which can then be controlled with `with_script_animation`.
+    ///
+    /// Keyframes without a composite operation use `composite`, and the values
+    /// they don't specify are taken from `base_style`, which is typically the
+    /// current style of the element. Returns an error if the keyframe offsets
+    /// are invalid.
+    pub fn add_script_animation(
+        &self,
+        key: AnimationSetKey,
+        keyframes: &[ScriptKeyframe],
+        timing: EffectTiming,
+        composite: AnimationComposition,
+        base_style: &Arc<ComputedValues>,
+    ) -> Result<ScriptAnimationId, ()> {
+        let property_keyframes =
+            ComputedKeyframe::generate_for_script_keyframes(keyframes, composite, base_style)?;
+        let id = ScriptAnimationId(
+            self.next_script_animation_id
+                .fetch_add(1, Ordering::Relaxed),
+        );
+
+        let mut sets = self.sets.write();
+        let set = sets.entry(key).or_default();
+        set.script_animations.push(ScriptAnimation::new(
+            id,
+            property_keyframes,
+            timing,
+            base_style.clone(),
+        ));
+        set.dirty = true;
+        Ok(id)
+    }
+
+    /// Run `f` with the animation created by `add_script_animation` with the
+    /// given identifier, if it still exists. Its element needs to be restyled
+    /// afterwards.
+    pub fn with_script_animation<R>(
+        &self,
+        id: ScriptAnimationId,
+        f: impl FnOnce(&mut ScriptAnimation) -> R,
+    ) -> Option<R> {
+        for set in self.sets.write().values_mut() {
+            if let Some(animation) = set
+                .script_animations
+                .iter_mut()
+                .find(|animation| animation.id == id)
+            {
+                set.dirty = true;
+                return Some(f(animation));
+            }
+        }
+        None
+    }
+
+    /// Remove the animation created by `add_script_animation` with the given
+    /// identifier, without generating a cancel event.
+    pub fn remove_script_animation(&self, id: ScriptAnimationId) {
+        for set in self.sets.write().values_mut() {
+            let count = set.script_animations.len();
+            set.script_animations.retain(|animation| animation.id != id);
+            if set.script_animations.len() != count {
+                set.dirty = true;
+                return;
+            }
+        }
+    }
+
+    /// Update the finished state of the animations created by the embedder at
+    /// the given time, returning the events of the animations which finished or
+    /// were canceled since the last call.
+    pub fn update_script_animations(&self, now: f64) -> Vec<ScriptAnimationEvent> {
+        let mut events = vec![];
+        for (key, set) in self.sets.write().iter_mut() {
+            for animation in set.script_animations.iter_mut() {
+                if animation.cancel_pending {
+                    animation.cancel_pending = false;
+                    events.push(ScriptAnimationEvent {
+                        key: key.clone(),
+                        id: animation.id,
+                        event_type: ScriptAnimationEventType::Cancel,
+                        current_time: None,
+                    });
+                }
+
+                animation.update_finished_state(now, false);
+                let finished = animation.play_state(now) == ScriptAnimationPlayState::Finished;
+                if finished && !animation.finish_notified {
+                    events.push(ScriptAnimationEvent {
+                        key: key.clone(),
+                        id: animation.id,
+                        event_type: ScriptAnimationEventType::Finish,
+                        current_time: animation.current_time(now),
+                    });
+                }
+                animation.finish_notified = finished;
+            }
+        }
+        events
+    }
+
+    /// Update the scroll state of the scroll container `node`. The scroll state
+    /// of the document viewport should be reported for the root element.
+    ///
+    /// The animations returned by `keys_with_animations_driven_by` need to be
+    /// restyled afterwards.
+    pub fn set_scroll_state(&self, node: OpaqueNode, state: ScrollState) {
+        self.scroll_states
+            .write()
+            .scroll_containers
+            .insert(node, state);
+    }
+
+    /// Update the border box of `node`, which is the subject of a view progress
+    /// timeline, relative to the scroll origin of its nearest scroll container.
+    pub fn set_view_timeline_subject_rect(&self, node: OpaqueNode, rect: Rect<f32, CSSPixel>) {
+        self.scroll_states.write().subjects.insert(node, rect);
+    }
+
+    /// Forget the scroll state and the view progress timeline subject geometry
+    /// of `node`, typically because it has been removed from the DOM or has
+    /// stopped being a scroll container.
+    pub fn clear_scroll_state(&self, node: OpaqueNode) {
+        let mut scroll_states = self.scroll_states.write();
+        scroll_states.scroll_containers.remove(&node);
+        scroll_states.subjects.remove(&node);
+    }
+
+    /// The keys of the sets with active animations driven by the scroll position
+    /// of the scroll container `node`.
+    pub fn keys_with_animations_driven_by(&self, node: OpaqueNode) -> Vec<AnimationSetKey> {
+        self.sets
+            .read()
+            .iter()
+            .filter(|(_, set)| {
+                set.animations.iter().any(|animation| {
+                    animation.state != AnimationState::Canceled &&
+                        animation.timeline.source() == Some(node)
+                })
+            })
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// The subjects of the active view progress timelines, whose geometry needs
+    /// to be reported with `set_view_timeline_subject_rect`.
+    pub fn view_timeline_subjects(&self) -> Vec<OpaqueNode> {
+        let mut subjects = vec![];
+        for set in self.sets.read().values() {
+            for animation in set.animations.iter() {
+                if animation.state == AnimationState::Canceled {
+                    continue;
+                }
+                if let Timeline::View { subject, .. } = animation.timeline {
+                    if !subjects.contains(&subject) {
+                        subjects.push(subject);
+                    }
+                }
+            }
+        }
+        subjects
+    }
 }
 
 /// Kick off any new transitions for this node and return all of the properties that are
@@ -1289,6 +3272,43 @@ pub fn start_transitions_if_applicable(
     properties_that_transition
 }
 
+/// Kick off any new transitions of registered custom properties for this node
+/// and return the names of the custom properties that are transitioning.
+pub fn start_custom_property_transitions_if_applicable(
+    context: &SharedStyleContext,
+    old_style: &ComputedValues,
+    new_style: &Arc<ComputedValues>,
+    animation_state: &mut ElementAnimationSet,
+) -> Vec<CustomPropertyName> {
+    let mut properties_that_transition = vec![];
+    let style = new_style.get_ui();
+    for index in 0..style.transition_property_count() {
+        let name = match style.transition_property_at(index) {
+            TransitionProperty::Custom(name) => name,
+            _ => continue,
+        };
+        if properties_that_transition.contains(&name) {
+            continue;
+        }
+        let registration = match context.stylist.get_custom_property_registration(&name) {
+            Some(registration) => registration,
+            None => continue,
+        };
+
+        animation_state.start_custom_property_transition_if_applicable(
+            context,
+            &name,
+            registration,
+            index,
+            old_style,
+            new_style,
+        );
+        properties_that_transition.push(name);
+    }
+
+    properties_that_transition
+}
+
 /// Triggers animations for a given node looking at the animation property
 /// values.
 pub fn maybe_start_animations<E>(
@@ -1301,15 +3321,16 @@ pub fn maybe_start_animations<E>(
     E: TElement,
 {
     let style = new_style.get_ui();
-    for (i, name) in style.animation_name_iter().enumerate() {
+    'animations: for (i, name) in style.animation_name_iter().enumerate() {
         let name = match name.as_atom() {
             Some(atom) => atom,
             None => continue,
         };
 
         debug!("maybe_start_animations: name={}", name);
+        let timeline = Timeline::resolve(element, new_style, &style.animation_timeline_mod(i));
         let duration = style.animation_duration_mod(i).seconds() as f64;
-        if duration == 0. {
+        if duration == 0. && timeline == Timeline::Document {
             continue;
         }
 
@@ -1353,18 +3374,24 @@ pub fn maybe_start_animations<E>(
 
         let now = context.current_time_for_animations;
         let started_at = now + delay as f64;
-        let mut starting_progress = (now - started_at) / duration;
+        let mut starting_progress = if duration == 0. {
+            0.
+        } else {
+            (now - started_at) / duration
+        };
         let state = match style.animation_play_state_mod(i) {
             AnimationPlayState::Paused => AnimationState::Paused(starting_progress),
             AnimationPlayState::Running => AnimationState::Pending,
         };
 
+        let (range_start, range_end) = animation_range(style, i);
         let computed_steps = ComputedKeyframe::generate_for_keyframes(
             element,
             &keyframe_animation,
             context,
             new_style,
             style.animation_timing_function_mod(i),
+            style.animation_composition_mod(i),
             resolver,
         );
 
@@ -1380,13 +3407,21 @@ pub fn maybe_start_animations<E>(
             state,
             direction: animation_direction,
             current_direction: initial_direction,
+            timeline,
+            range_start,
+            range_end,
+            scroll_states: context.animations.scroll_states.clone(),
             cascade_style: new_style.clone(),
             is_new: true,
         };
 
         // If we started with a negative delay, make sure we iterate the animation if
-        // the delay moves us past the first iteration.
-        while starting_progress > 1. && !new_animation.on_last_iteration() {
+        // the delay moves us past the first iteration. Scroll-driven animations
+        // compute their iteration from their timeline instead.
+        while !new_animation.is_scroll_driven() &&
+            starting_progress > 1. &&
+            !new_animation.on_last_iteration()
+        {
             new_animation.iterate();
             starting_progress -= 1.;
         }
@@ -1402,10 +3437,22 @@ pub fn maybe_start_animations<E>(
             if new_animation.name == existing_animation.name {
                 existing_animation
                     .update_from_other(&new_animation, context.current_time_for_animations);
-                return;
+                continue 'animations;
             }
         }
 
         animation_state.animations.push(new_animation);
     }
+
+    // Animations are composited in the order of `animation-name`, so that later
+    // animations apply on top of earlier ones.
+    //
+    // https://drafts.csswg.org/css-animations-2/#animation-composite-order
+    let position = |animation: &Animation| {
+        style
+            .animation_name_iter()
+            .position(|name| name.as_atom() == Some(&animation.name))
+            .unwrap_or(usize::max_value())
+    };
+    animation_state.animations.sort_by_key(position);
 }
@@ -127,6 +127,44 @@ impl CascadePriority {
     }
 }
 
+/// The proximity of the scoping root of a scoped style rule to the element it
+/// applies to, in number of generations.
+///
+/// Declarations of rules whose scoping root is nearer win, so a nearer root
+/// compares greater. Declarations of rules that are not scoped have infinite
+/// proximity, which compares less than any other proximity.
+///
+/// https://drafts.csswg.org/css-cascade-6/#cascade-proximity
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq)]
+pub struct ScopeProximity(u16);
+
+impl PartialOrd for ScopeProximity {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScopeProximity {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+impl ScopeProximity {
+    /// Creates a proximity for a scoping root the given number of
+    /// generations above the element (zero for the element itself).
+    pub fn new(generations: usize) -> Self {
+        Self(std::cmp::min(generations, std::u16::MAX as usize - 1) as u16)
+    }
+
+    /// The proximity of declarations that are not scoped.
+    pub const fn infinity() -> Self {
+        Self(std::u16::MAX)
+    }
+}
+
 /// A property declaration together with its precedence among rules of equal
 /// specificity so that we can sort them.
 ///
@@ -144,6 +182,8 @@ pub struct ApplicableDeclarationBlock {
     pub specificity: u32,
     /// The cascade priority of the rule.
     pub cascade_priority: CascadePriority,
+    /// The proximity of the scoping root of the rule, if it's scoped.
+    pub scope_proximity: ScopeProximity,
 }
 
 impl ApplicableDeclarationBlock {
@@ -160,6 +200,7 @@ impl ApplicableDeclarationBlock {
             source_order: 0,
             specificity: 0,
             cascade_priority: CascadePriority::new(level, layer_order),
+            scope_proximity: ScopeProximity::infinity(),
         }
     }
 
@@ -171,12 +212,14 @@ impl ApplicableDeclarationBlock {
         level: CascadeLevel,
         specificity: u32,
         layer_order: LayerOrder,
+        scope_proximity: ScopeProximity,
     ) -> Self {
         ApplicableDeclarationBlock {
             source,
             source_order: source_order & SOURCE_ORDER_MASK,
             specificity,
             cascade_priority: CascadePriority::new(level, layer_order),
+            scope_proximity,
         }
     }
 
@@ -0,0 +1,378 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Tracing of the cascade, to explain where the computed value of a property
+//! on a given element comes from.
+//!
+//! The cascade itself only keeps the rule node of an element, which is enough
+//! to compute its style but not to explain it. A `CascadeTrace` lists every
+//! declaration of a property that took part in the cascade, along with where
+//! it comes from and whether it won. See `Stylist::trace_cascade`.
+
+use crate::applicable_declarations::CascadePriority;
+use crate::custom_properties::{Name, VariableValue};
+use crate::media_queries::Device;
+use crate::properties::{CSSWideKeyword, PropertyDeclarationId, PropertyFlags};
+use crate::properties::{ComputedValues, CustomDeclarationValue, PropertyDeclaration};
+use crate::rule_tree::{CascadeLevel, StyleSource};
+use crate::selector_parser::PseudoElement;
+use crate::shared_lock::{Locked, SharedRwLockReadGuard, StylesheetGuards};
+use crate::stylesheets::layer_rule::LayerName;
+use crate::stylesheets::{AllRules, CssRule, Origin, RulesIterator, StyleRule};
+use crate::stylesheets::{StylesheetContents, StylesheetInDocument, UrlExtraData};
+use cssparser::{Parser, ParserInput, SourceLocation};
+use style_traits::ToCss;
+
+/// Where a declaration block that took part in the cascade comes from.
+#[derive(Clone, Debug, Default)]
+pub struct RuleProvenance {
+    /// The URL of the stylesheet the rule is in, if it is a style rule from
+    /// one of the stylist's stylesheets.
+    pub stylesheet: Option<UrlExtraData>,
+    /// The location of the rule in its stylesheet, if it is a style rule.
+    pub source_location: Option<SourceLocation>,
+    /// The serialized selectors of the rule, if it is a style rule.
+    pub selectors: Option<String>,
+    /// The name of the cascade layer of the rule, or `None` if it is not in
+    /// a named layer.
+    pub layer: Option<LayerName>,
+    /// The specificity with which the rule matched the element.
+    pub specificity: u32,
+}
+
+/// A declaration of the traced property that took part in the cascade.
+#[derive(Clone, Debug)]
+pub struct CascadeCandidate {
+    /// The declaration itself, as specified.
+    pub declaration: PropertyDeclaration,
+    /// The cascade level of the declaration.
+    pub level: CascadeLevel,
+    /// The cascade origin of the declaration.
+    pub origin: Origin,
+    /// Whether the declaration is `!important`.
+    pub important: bool,
+    /// Where the declaration comes from.
+    pub provenance: RuleProvenance,
+    /// Whether the declaration lost the cascade, either to a declaration with
+    /// higher precedence or to a `revert` or `revert-layer` keyword. A
+    /// `revert` or `revert-layer` declaration is itself always overridden.
+    pub overridden: bool,
+    /// The custom properties that `var()` functions in the declaration
+    /// reference, if any.
+    pub references: Vec<VariableReference>,
+}
+
+/// A custom property referenced from a `var()` function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableReference {
+    /// The name of the custom property, without the leading `--`.
+    pub name: Name,
+    /// The value that the declaration of the custom property that applies to
+    /// the element specifies, or `None` if the element has no such
+    /// declaration and the value was inherited.
+    pub specified_value: Option<String>,
+    /// The computed value of the custom property on the element, or `None`
+    /// if it is the guaranteed-invalid value.
+    pub computed_value: Option<String>,
+    /// The custom properties that the specified value references in turn.
+    pub references: Vec<VariableReference>,
+}
+
+/// The result of tracing the cascade of a property on an element.
+#[derive(Clone, Debug)]
+pub struct CascadeTrace {
+    /// The computed value of the property on the element.
+    pub computed_value: String,
+    /// Every declaration of the property that took part in the cascade, in
+    /// cascade order, that is, from the lowest to the highest precedence.
+    pub candidates: Vec<CascadeCandidate>,
+}
+
+impl CascadeTrace {
+    /// Returns the declaration that determined the computed value, if any.
+    ///
+    /// If there's none, the value was inherited or is the initial value.
+    pub fn applied(&self) -> Option<&CascadeCandidate> {
+        self.candidates.iter().find(|c| !c.overridden)
+    }
+}
+
+/// Walks the rule node of a style in the same way as the cascade does.
+pub(crate) struct CascadeTracer<'a> {
+    style: &'a ComputedValues,
+    guards: &'a StylesheetGuards<'a>,
+    restriction: Option<PropertyFlags>,
+}
+
+impl<'a> CascadeTracer<'a> {
+    /// Creates a tracer for `style`, the style of an element or of its
+    /// `pseudo` pseudo-element.
+    pub fn new(
+        style: &'a ComputedValues,
+        pseudo: Option<&PseudoElement>,
+        guards: &'a StylesheetGuards<'a>,
+    ) -> Self {
+        Self {
+            style,
+            guards,
+            restriction: pseudo.and_then(|p| p.property_restriction()),
+        }
+    }
+
+    /// Traces the cascade of `property`, using `provenance` to find out where
+    /// each declaration block comes from.
+    pub fn trace<F>(&self, property: PropertyDeclarationId, mut provenance: F) -> CascadeTrace
+    where
+        F: FnMut(&StyleSource, CascadePriority) -> RuleProvenance,
+    {
+        let mut visited = vec![];
+        let mut candidates = self
+            .declarations(property)
+            .into_iter()
+            .map(|(source, priority, declaration, applied)| {
+                let level = priority.cascade_level();
+                CascadeCandidate {
+                    declaration: declaration.clone(),
+                    level,
+                    origin: level.origin(),
+                    important: level.is_important(),
+                    provenance: provenance(source, priority),
+                    overridden: !applied,
+                    references: self.references(declaration, &mut visited),
+                }
+            })
+            .collect::<Vec<_>>();
+        candidates.reverse();
+
+        let property = match property {
+            PropertyDeclarationId::Longhand(id) => {
+                PropertyDeclarationId::Longhand(id.to_physical(self.style.writing_mode))
+            },
+            PropertyDeclarationId::Custom(..) => property,
+        };
+        CascadeTrace {
+            computed_value: self.style.computed_value_to_string(property),
+            candidates,
+        }
+    }
+
+    /// Returns the declarations of `property` in the rule node, from the
+    /// highest to the lowest precedence, along with whether each of them is
+    /// the one that applies.
+    fn declarations(
+        &self,
+        property: PropertyDeclarationId,
+    ) -> Vec<(
+        &'a StyleSource,
+        CascadePriority,
+        &'a PropertyDeclaration,
+        bool,
+    )> {
+        let writing_mode = self.style.writing_mode;
+        let property = match property {
+            PropertyDeclarationId::Longhand(id) => {
+                PropertyDeclarationId::Longhand(id.to_physical(writing_mode))
+            },
+            PropertyDeclarationId::Custom(..) => property,
+        };
+
+        let mut declarations = vec![];
+        let mut found_applied = false;
+        let mut reverted: Option<(CascadePriority, bool)> = None;
+        for node in self.style.rules().self_and_ancestors() {
+            let source = match node.style_source() {
+                Some(source) => source,
+                None => continue,
+            };
+            let priority = node.cascade_priority();
+            let level = priority.cascade_level();
+            let block = source.read(level.guard(self.guards));
+            for (declaration, importance) in block.declaration_importance_iter().rev() {
+                if importance != level.importance() {
+                    continue;
+                }
+                let matches = match (declaration.id(), property) {
+                    (PropertyDeclarationId::Longhand(id), PropertyDeclarationId::Longhand(p)) => {
+                        if let Some(restriction) = self.restriction {
+                            if !id.flags().contains(restriction) &&
+                                level.origin() != Origin::UserAgent
+                            {
+                                continue;
+                            }
+                        }
+                        id.to_physical(writing_mode) == p
+                    },
+                    (PropertyDeclarationId::Custom(name), PropertyDeclarationId::Custom(p)) => {
+                        name == p
+                    },
+                    _ => false,
+                };
+                if !matches {
+                    continue;
+                }
+
+                let mut applied = !found_applied &&
+                    reverted.map_or(true, |(reverted_priority, origin_revert)| {
+                        reverted_priority.allows_when_reverted(&priority, origin_revert)
+                    });
+                if applied {
+                    match css_wide_keyword(declaration) {
+                        Some(CSSWideKeyword::Revert) => {
+                            reverted = Some((priority, true));
+                            applied = false;
+                        },
+                        Some(CSSWideKeyword::RevertLayer) => {
+                            reverted = Some((priority, false));
+                            applied = false;
+                        },
+                        _ => found_applied = true,
+                    }
+                }
+                declarations.push((source, priority, declaration, applied));
+            }
+        }
+        declarations
+    }
+
+    /// Returns the custom properties that `declaration` references, and
+    /// recursively, the ones that their applicable declarations reference.
+    ///
+    /// `visited` holds the custom properties that are being expanded, in
+    /// order not to loop forever on reference cycles.
+    fn references(
+        &self,
+        declaration: &PropertyDeclaration,
+        visited: &mut Vec<Name>,
+    ) -> Vec<VariableReference> {
+        let names = match *declaration {
+            PropertyDeclaration::WithVariables(ref declaration) => {
+                let mut input = ParserInput::new(declaration.css());
+                match VariableValue::parse(&mut Parser::new(&mut input)) {
+                    Ok(value) => value.references().to_vec(),
+                    Err(..) => return vec![],
+                }
+            },
+            PropertyDeclaration::Custom(ref declaration) => match declaration.value {
+                CustomDeclarationValue::Value(ref value) => value.references().to_vec(),
+                CustomDeclarationValue::CSSWideKeyword(..) => return vec![],
+            },
+            _ => return vec![],
+        };
+
+        names
+            .into_iter()
+            .map(|name| {
+                let computed_value = self
+                    .style
+                    .custom_properties()
+                    .and_then(|map| map.get(&name))
+                    .map(|value| value.to_css_string());
+                if visited.contains(&name) {
+                    return VariableReference {
+                        name,
+                        specified_value: None,
+                        computed_value,
+                        references: vec![],
+                    };
+                }
+
+                let applied = self
+                    .declarations(PropertyDeclarationId::Custom(&name))
+                    .into_iter()
+                    .find(|&(_, _, _, applied)| applied)
+                    .map(|(_, _, declaration, _)| declaration);
+                visited.push(name.clone());
+                let references = applied.map_or(vec![], |d| self.references(d, visited));
+                visited.pop();
+                VariableReference {
+                    name,
+                    specified_value: applied.and_then(custom_value_to_string),
+                    computed_value,
+                    references,
+                }
+            })
+            .collect()
+    }
+}
+
+fn custom_value_to_string(declaration: &PropertyDeclaration) -> Option<String> {
+    match *declaration {
+        PropertyDeclaration::Custom(ref declaration) => Some(match declaration.value {
+            CustomDeclarationValue::Value(ref value) => value.to_css_string(),
+            CustomDeclarationValue::CSSWideKeyword(keyword) => keyword.to_css_string(),
+        }),
+        _ => None,
+    }
+}
+
+fn css_wide_keyword(declaration: &PropertyDeclaration) -> Option<CSSWideKeyword> {
+    match *declaration {
+        PropertyDeclaration::Custom(ref declaration) => match declaration.value {
+            CustomDeclarationValue::CSSWideKeyword(keyword) => Some(keyword),
+            CustomDeclarationValue::Value(..) => None,
+        },
+        _ => declaration.get_css_wide_keyword(),
+    }
+}
+
+/// Returns the URL of the stylesheet that contains `rule`, looking into
+/// `contents` and the stylesheets it imports.
+pub(crate) fn find_style_rule(
+    contents: &StylesheetContents,
+    rule: &Locked<StyleRule>,
+    device: &Device,
+    guard: &SharedRwLockReadGuard,
+) -> Option<UrlExtraData> {
+    if contains_style_rule(contents.rules(guard), rule, device, contents, guard) {
+        return Some(contents.url_data.read().clone());
+    }
+    for css_rule in contents.rules(guard).iter() {
+        if let CssRule::Import(ref lock) = *css_rule {
+            let import_rule = lock.read_with(guard);
+            #[cfg(feature = "servo")]
+            let imported = Some(&import_rule.stylesheet.0);
+            #[cfg(feature = "gecko")]
+            let imported = import_rule.stylesheet.as_sheet();
+            if let Some(imported) = imported {
+                let url = find_style_rule(imported.contents(), rule, device, guard);
+                if url.is_some() {
+                    return url;
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns whether `rule` is in `rules` or in the rules nested in them,
+/// without looking into imported stylesheets.
+fn contains_style_rule(
+    rules: &[CssRule],
+    rule: &Locked<StyleRule>,
+    device: &Device,
+    contents: &StylesheetContents,
+    guard: &SharedRwLockReadGuard,
+) -> bool {
+    rules.iter().any(|css_rule| {
+        if let CssRule::Style(ref style_rule) = *css_rule {
+            if std::ptr::eq(&**style_rule, rule) {
+                return true;
+            }
+        }
+        if let CssRule::Import(..) = *css_rule {
+            return false;
+        }
+        let mut effective = true;
+        let children = RulesIterator::<AllRules>::children(
+            css_rule,
+            device,
+            contents.quirks_mode,
+            guard,
+            &mut effective,
+        );
+        children.map_or(false, |children| {
+            contains_style_rule(children.as_slice(), rule, device, contents, guard)
+        })
+    })
+}
@@ -7,6 +7,7 @@
 /// cbindgen:ignore
 pub mod convert;
 pub mod mix;
+pub mod relative;
 
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ToCss};
@@ -112,6 +113,26 @@ impl ColorSpace {
         matches!(self, Self::Hsl | Self::Hwb | Self::Lch | Self::Oklch)
     }
 
+    /// Returns whether this is a `<predefined-rgb>` or an `<xyz-space>`,
+    /// that is, a color space for the `color()` function.
+    #[inline]
+    pub fn is_predefined(&self) -> bool {
+        !matches!(
+            self,
+            Self::Hsl | Self::Hwb | Self::Lab | Self::Lch | Self::Oklab | Self::Oklch
+        )
+    }
+
+    /// Returns whether this color space has a gamut, that is, whether it's an
+    /// RGB color space, in which colors out of [0, 1] can't be displayed.
+    #[inline]
+    pub fn is_bounded(&self) -> bool {
+        !matches!(
+            self,
+            Self::Lab | Self::Lch | Self::Oklab | Self::Oklch | Self::XyzD50 | Self::XyzD65
+        )
+    }
+
     /// Returns an index of the hue component in the color space, otherwise
     /// `None`.
     #[inline]
@@ -257,6 +278,120 @@ impl AbsoluteColor {
 
         Self::new(color_space, result, self.alpha)
     }
+
+    /// Returns whether the color is within the gamut of its color space.
+    pub fn in_gamut(&self) -> bool {
+        // Allow for the rounding errors of the conversions, so that colors
+        // that went through other color spaces don't need mapping.
+        const EPSILON: f32 = 0.00001;
+
+        match self.color_space {
+            ColorSpace::Hsl | ColorSpace::Hwb => self.to_color_space(ColorSpace::Srgb).in_gamut(),
+            color_space if !color_space.is_bounded() => true,
+            _ => {
+                let ColorComponents(c1, c2, c3) = self.components;
+                [c1, c2, c3]
+                    .iter()
+                    .all(|c| *c >= -EPSILON && *c <= 1. + EPSILON)
+            },
+        }
+    }
+
+    /// Clamps the components of the color to the gamut of its color space.
+    fn clip(&self) -> Self {
+        match self.color_space {
+            ColorSpace::Hsl | ColorSpace::Hwb => self
+                .to_color_space(ColorSpace::Srgb)
+                .clip()
+                .to_color_space(self.color_space),
+            color_space if !color_space.is_bounded() => self.clone(),
+            color_space => Self::new(
+                color_space,
+                self.components.map(|c| c.max(0.).min(1.)),
+                self.alpha,
+            ),
+        }
+    }
+
+    /// Returns the deltaEOK between this color and `other`, that is, their
+    /// euclidean distance in the Oklab color space.
+    ///
+    /// https://drafts.csswg.org/css-color-4/#color-difference-OK
+    fn delta_eok(&self, other: &Self) -> f32 {
+        let ColorComponents(l1, a1, b1) = self.to_color_space(ColorSpace::Oklab).components;
+        let ColorComponents(l2, a2, b2) = other.to_color_space(ColorSpace::Oklab).components;
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// Convert this color to the specified color space, and map it into the
+    /// gamut of that color space if needed, by reducing its chroma in the
+    /// OKLCh color space until clipping it isn't noticeable.
+    ///
+    /// Unlike `to_color_space`, this keeps the lightness and hue of colors
+    /// that are out of the gamut, so it should be used whenever a color is
+    /// going to be displayed.
+    ///
+    /// https://drafts.csswg.org/css-color-4/#binsearch
+    pub fn to_color_space_mapped(&self, color_space: ColorSpace) -> Self {
+        // The just noticeable difference between two colors.
+        const JND: f32 = 0.02;
+        // The precision of the binary search.
+        const EPSILON: f32 = 0.0001;
+
+        let result = self.to_color_space(color_space);
+        if result.in_gamut() {
+            return result;
+        }
+
+        let mut current = self.to_color_space(ColorSpace::Oklch);
+        if current.components.0 >= 1. {
+            let white = ColorComponents(1., 0., 0.);
+            return Self::new(ColorSpace::Oklab, white, self.alpha).to_color_space(color_space);
+        }
+        if current.components.0 <= 0. {
+            let black = ColorComponents(0., 0., 0.);
+            return Self::new(ColorSpace::Oklab, black, self.alpha).to_color_space(color_space);
+        }
+
+        let mut clipped = result.clip();
+        if clipped.delta_eok(&current) < JND {
+            return clipped;
+        }
+
+        let mut min = 0.;
+        let mut max = current.components.1;
+        let mut min_in_gamut = true;
+        while max - min > EPSILON {
+            let chroma = (min + max) / 2.;
+            current.components.1 = chroma;
+
+            let converted = current.to_color_space(color_space);
+            if min_in_gamut && converted.in_gamut() {
+                min = chroma;
+                continue;
+            }
+
+            clipped = converted.clip();
+            let delta_e = clipped.delta_eok(&current);
+            if delta_e >= JND {
+                max = chroma;
+            } else if JND - delta_e < EPSILON {
+                break;
+            } else {
+                min_in_gamut = false;
+                min = chroma;
+            }
+        }
+        clipped
+    }
+
+    /// Map this color into the gamut of its own color space, see
+    /// `to_color_space_mapped`.
+    pub fn map_into_gamut(&self) -> Self {
+        let mut result = self.to_color_space_mapped(self.color_space);
+        result.flags = self.flags;
+        result
+    }
 }
 
 impl From<cssparser::PredefinedColorSpace> for ColorSpace {
@@ -0,0 +1,394 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Relative color syntax, that is, color functions whose channels are derived
+//! from the channels of an origin color, e.g. `rgb(from red r g calc(b + 20))`.
+//!
+//! https://drafts.csswg.org/css-color-5/#relative-colors
+
+use super::{AbsoluteColor, ColorComponents, ColorSpace, SerializationFlags};
+use crate::parser::ParserContext;
+use crate::values::specified::calc::{CalcNode, Leaf};
+use crate::values::specified::Angle;
+use cssparser::{Parser, Token};
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// A keyword referring to a channel of the origin color of a relative color.
+///
+/// https://drafts.csswg.org/css-color-5/#relative-color-function
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, Parse, PartialEq, ToCss, ToShmem)]
+#[allow(missing_docs)]
+#[repr(u8)]
+pub enum ChannelKeyword {
+    Alpha,
+    A,
+    B,
+    C,
+    G,
+    H,
+    L,
+    R,
+    S,
+    W,
+    X,
+    Y,
+    Z,
+}
+
+/// How the value of a channel of a color function is interpreted.
+#[derive(Clone, Copy, Debug)]
+enum ChannelKind {
+    /// A `<number>` or a `<percentage>`, with the number 100% resolves to.
+    NumberOrPercentage(f32),
+    /// A hue, that is, a `<number>` or an `<angle>`, in degrees.
+    Hue,
+}
+
+/// The type of the leaves of a channel expression, which need to match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LeafType {
+    Number,
+    Percentage,
+    Angle,
+}
+
+/// The value of a channel of a relative color.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub enum ChannelValue {
+    /// The `none` keyword.
+    None,
+    /// A channel keyword, `<number>`, `<percentage>` or `<angle>`.
+    Leaf(Leaf),
+    /// A math function, which may reference channel keywords.
+    Calc(Box<CalcNode>),
+}
+
+impl ChannelValue {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if input.try_parse(|i| i.expect_ident_matching("none")).is_ok() {
+            return Ok(ChannelValue::None);
+        }
+
+        let location = input.current_source_location();
+        let leaf = match *input.next()? {
+            Token::Number { value, .. } => Leaf::Number(value),
+            Token::Percentage { unit_value, .. } => Leaf::Percentage(unit_value),
+            Token::Dimension {
+                value, ref unit, ..
+            } => match Angle::parse_dimension(value, unit, /* from_calc = */ false) {
+                Ok(angle) => Leaf::Angle(angle),
+                Err(()) => {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+                },
+            },
+            Token::Ident(ref ident) => match ChannelKeyword::from_ident(ident) {
+                Ok(keyword) => Leaf::ColorChannel(keyword, 1.),
+                Err(()) => {
+                    return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+                },
+            },
+            Token::Function(ref name) => {
+                let function = CalcNode::math_function(name, location)?;
+                let node = CalcNode::parse_color_channel(context, input, function)?;
+                return Ok(ChannelValue::Calc(Box::new(node)));
+            },
+            ref t => return Err(location.new_unexpected_token_error(t.clone())),
+        };
+        Ok(ChannelValue::Leaf(leaf))
+    }
+
+    /// Resolves this channel to a number in the reference range of the
+    /// channel, given the values the channel keywords resolve to.
+    fn resolve(
+        &self,
+        kind: ChannelKind,
+        keyword_value: &impl Fn(ChannelKeyword) -> Option<f32>,
+    ) -> Result<f32, ()> {
+        let leaf;
+        let node = match *self {
+            // Missing components are treated as zero.
+            ChannelValue::None => return Ok(0.),
+            ChannelValue::Leaf(ref l) => {
+                leaf = CalcNode::Leaf(l.clone());
+                &leaf
+            },
+            ChannelValue::Calc(ref node) => &**node,
+        };
+
+        // Channel keywords resolve to numbers, and we can't mix types, so all
+        // the leaves need to be of the same type.
+        let mut leaf_type = None;
+        node.resolve(|leaf| {
+            let (value, ty) = match (leaf, kind) {
+                (&Leaf::Number(n), _) => (n, LeafType::Number),
+                (&Leaf::ColorChannel(keyword, factor), _) => {
+                    (keyword_value(keyword).ok_or(())? * factor, LeafType::Number)
+                },
+                (&Leaf::Percentage(p), ChannelKind::NumberOrPercentage(hundred)) => {
+                    (p * hundred, LeafType::Percentage)
+                },
+                (&Leaf::Angle(ref angle), ChannelKind::Hue) => {
+                    (angle.degrees(), LeafType::Angle)
+                },
+                _ => return Err(()),
+            };
+            if *leaf_type.get_or_insert(ty) != ty {
+                return Err(());
+            }
+            Ok(value)
+        })
+    }
+}
+
+impl ToCss for ChannelValue {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            ChannelValue::None => dest.write_str("none"),
+            ChannelValue::Leaf(ref leaf) => leaf.to_css(dest),
+            ChannelValue::Calc(ref node) => node.to_css(dest),
+        }
+    }
+}
+
+/// The color space and channels of a relative color. The origin color is
+/// stored separately, see `GenericRelativeColor`.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct RelativeChannels {
+    /// The color space of the color function.
+    pub color_space: ColorSpace,
+    /// Whether an sRGB color uses the `color()` notation rather than `rgb()`.
+    pub flags: SerializationFlags,
+    /// The first channel.
+    pub c1: ChannelValue,
+    /// The second channel.
+    pub c2: ChannelValue,
+    /// The third channel.
+    pub c3: ChannelValue,
+    /// The alpha channel, which is the alpha of the origin color if omitted.
+    pub alpha: Option<ChannelValue>,
+}
+
+impl RelativeChannels {
+    /// Returns the color space of the color function with the given name, if
+    /// it supports relative colors, or `None` for the `color()` function,
+    /// whose color space is specified after the origin color.
+    pub fn color_space_for_function(name: &str) -> Result<Option<ColorSpace>, ()> {
+        Ok(Some(match_ignore_ascii_case! { name,
+            "rgb" | "rgba" => ColorSpace::Srgb,
+            "hsl" | "hsla" => ColorSpace::Hsl,
+            "hwb" => ColorSpace::Hwb,
+            "lab" => ColorSpace::Lab,
+            "lch" => ColorSpace::Lch,
+            "oklab" => ColorSpace::Oklab,
+            "oklch" => ColorSpace::Oklch,
+            "color" => return Ok(None),
+            _ => return Err(()),
+        }))
+    }
+
+    /// Parses the channels of a relative color, which follow the origin
+    /// color, given the color space of the color function, or `None` for the
+    /// `color()` function.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        color_space: Option<ColorSpace>,
+    ) -> Result<Self, ParseError<'i>> {
+        let (color_space, flags) = match color_space {
+            Some(color_space) => (color_space, SerializationFlags::empty()),
+            None => {
+                let location = input.current_source_location();
+                let color_space = ColorSpace::parse(input)?;
+                if !color_space.is_predefined() {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                (color_space, SerializationFlags::AS_COLOR_FUNCTION)
+            },
+        };
+
+        let c1 = ChannelValue::parse(context, input)?;
+        let c2 = ChannelValue::parse(context, input)?;
+        let c3 = ChannelValue::parse(context, input)?;
+        let alpha = if input.try_parse(|i| i.expect_delim('/')).is_ok() {
+            Some(ChannelValue::parse(context, input)?)
+        } else {
+            None
+        };
+
+        let channels = Self {
+            color_space,
+            flags,
+            c1,
+            c2,
+            c3,
+            alpha,
+        };
+
+        // Type-check the channels, which can't fail once the origin color is
+        // known.
+        let any_origin = AbsoluteColor::new(color_space, ColorComponents(0., 0., 0.), 0.);
+        if channels.try_resolve(&any_origin).is_err() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+
+        Ok(channels)
+    }
+
+    #[inline]
+    fn is_legacy_rgb(&self) -> bool {
+        self.color_space == ColorSpace::Srgb &&
+            !self.flags.contains(SerializationFlags::AS_COLOR_FUNCTION)
+    }
+
+    /// The keywords referring to the three channels of the origin color.
+    fn channel_keywords(&self) -> [ChannelKeyword; 3] {
+        use self::ChannelKeyword::*;
+
+        match self.color_space {
+            ColorSpace::Hsl => [H, S, L],
+            ColorSpace::Hwb => [H, W, B],
+            ColorSpace::Lab | ColorSpace::Oklab => [L, A, B],
+            ColorSpace::Lch | ColorSpace::Oklch => [L, C, H],
+            ColorSpace::XyzD50 | ColorSpace::XyzD65 => [X, Y, Z],
+            ColorSpace::Srgb |
+            ColorSpace::SrgbLinear |
+            ColorSpace::DisplayP3 |
+            ColorSpace::A98Rgb |
+            ColorSpace::ProphotoRgb |
+            ColorSpace::Rec2020 => [R, G, B],
+        }
+    }
+
+    /// How the values of the three channels are interpreted.
+    ///
+    /// https://drafts.csswg.org/css-color-4/#color-syntax
+    fn channel_kinds(&self) -> [ChannelKind; 3] {
+        use self::ChannelKind::*;
+
+        match self.color_space {
+            ColorSpace::Srgb if self.is_legacy_rgb() => [NumberOrPercentage(255.); 3],
+            ColorSpace::Hsl | ColorSpace::Hwb => {
+                [Hue, NumberOrPercentage(100.), NumberOrPercentage(100.)]
+            },
+            ColorSpace::Lab => [
+                NumberOrPercentage(100.),
+                NumberOrPercentage(125.),
+                NumberOrPercentage(125.),
+            ],
+            ColorSpace::Lch => [NumberOrPercentage(100.), NumberOrPercentage(150.), Hue],
+            ColorSpace::Oklab => [
+                NumberOrPercentage(1.),
+                NumberOrPercentage(0.4),
+                NumberOrPercentage(0.4),
+            ],
+            ColorSpace::Oklch => [NumberOrPercentage(1.), NumberOrPercentage(0.4), Hue],
+            _ => [NumberOrPercentage(1.); 3],
+        }
+    }
+
+    /// The scale between the numbers in the syntax of the color function and
+    /// the components of an `AbsoluteColor` in the same color space.
+    fn component_scale(&self) -> (f32, f32, f32) {
+        match self.color_space {
+            ColorSpace::Srgb if self.is_legacy_rgb() => (255., 255., 255.),
+            ColorSpace::Hsl | ColorSpace::Hwb => (1., 100., 100.),
+            _ => (1., 1., 1.),
+        }
+    }
+
+    fn try_resolve(&self, origin: &AbsoluteColor) -> Result<AbsoluteColor, ()> {
+        let origin = origin.to_color_space(self.color_space);
+        let scale = self.component_scale();
+        // Missing components of the origin color, like the hue of achromatic
+        // colors, resolve to zero.
+        let zero_if_missing = |c: f32| if c.is_nan() { 0. } else { c };
+        let origin_channels = [
+            zero_if_missing(origin.components.0 * scale.0),
+            zero_if_missing(origin.components.1 * scale.1),
+            zero_if_missing(origin.components.2 * scale.2),
+        ];
+        let origin_alpha = zero_if_missing(origin.alpha);
+
+        let keywords = self.channel_keywords();
+        let keyword_value = |keyword: ChannelKeyword| {
+            if keyword == ChannelKeyword::Alpha {
+                return Some(origin_alpha);
+            }
+            keywords
+                .iter()
+                .position(|k| *k == keyword)
+                .map(|i| origin_channels[i])
+        };
+
+        let kinds = self.channel_kinds();
+        let c1 = self.c1.resolve(kinds[0], &keyword_value)?;
+        let c2 = self.c2.resolve(kinds[1], &keyword_value)?;
+        let c3 = self.c3.resolve(kinds[2], &keyword_value)?;
+        let alpha = match self.alpha {
+            Some(ref alpha) => alpha
+                .resolve(ChannelKind::NumberOrPercentage(1.), &keyword_value)?
+                .max(0.)
+                .min(1.),
+            None => origin_alpha,
+        };
+
+        let components = ColorComponents(c1 / scale.0, c2 / scale.1, c3 / scale.2);
+        let mut color = AbsoluteColor::new(self.color_space, components, alpha);
+        color.flags = self.flags;
+        Ok(color)
+    }
+
+    /// Resolves the channels given the origin color.
+    pub fn resolve(&self, origin: &AbsoluteColor) -> AbsoluteColor {
+        self.try_resolve(origin).unwrap_or_else(|()| {
+            debug_assert!(false, "Channels should've been type-checked at parse time");
+            origin.clone()
+        })
+    }
+
+    /// The name of the color function.
+    pub fn function_name(&self) -> &'static str {
+        match self.color_space {
+            ColorSpace::Srgb if self.is_legacy_rgb() => "rgb",
+            ColorSpace::Hsl => "hsl",
+            ColorSpace::Hwb => "hwb",
+            ColorSpace::Lab => "lab",
+            ColorSpace::Lch => "lch",
+            ColorSpace::Oklab => "oklab",
+            ColorSpace::Oklch => "oklch",
+            _ => "color",
+        }
+    }
+}
+
+impl ToCss for RelativeChannels {
+    /// Serializes the channels, preceded by the color space for the `color()`
+    /// function.
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        if self.function_name() == "color" {
+            self.color_space.to_css(dest)?;
+            dest.write_char(' ')?;
+        }
+        self.c1.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.c2.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.c3.to_css(dest)?;
+        if let Some(ref alpha) = self.alpha {
+            dest.write_str(" / ")?;
+            alpha.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
@@ -20,7 +20,7 @@ use crate::rule_cache::RuleCache;
 use crate::rule_tree::StrongRuleNode;
 use crate::selector_parser::{SnapshotMap, EAGER_PSEUDO_COUNT};
 use crate::shared_lock::StylesheetGuards;
-use crate::sharing::StyleSharingCache;
+use crate::sharing::{StyleSharingCache, StyleSharingStatistics};
 use crate::stylist::Stylist;
 use crate::thread_state::{self, ThreadState};
 use crate::traversal::DomTraversal;
@@ -30,7 +30,7 @@ use euclid::default::Size2D;
 use euclid::Scale;
 #[cfg(feature = "servo")]
 use fxhash::FxHashMap;
-use selectors::NthIndexCache;
+use selectors::matching::SelectorCaches;
 #[cfg(feature = "gecko")]
 use servo_arc::Arc;
 #[cfg(feature = "servo")]
@@ -56,6 +56,9 @@ pub struct StyleSystemOptions {
     /// The minimum number of elements that must be traversed to trigger a dump
     /// of style statistics.
     pub style_statistics_threshold: usize,
+    /// Whether we should gather detailed statistics about the style sharing
+    /// cache, which are included in the style statistics.
+    pub style_sharing_statistics: bool,
 }
 
 #[cfg(feature = "gecko")]
@@ -92,6 +95,12 @@ pub static DEFAULT_DISABLE_STYLE_SHARING_CACHE: std::sync::atomic::AtomicBool =
 pub static DEFAULT_DUMP_STYLE_STATISTICS: std::sync::atomic::AtomicBool =
     std::sync::atomic::AtomicBool::new(false);
 
+/// A global variable holding the state of
+/// `StyleSystemOptions::default().style_sharing_statistics`.
+#[cfg(feature = "servo")]
+pub static DEFAULT_STYLE_SHARING_STATISTICS: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
 impl Default for StyleSystemOptions {
     #[cfg(feature = "servo")]
     fn default() -> Self {
@@ -102,6 +111,7 @@ impl Default for StyleSystemOptions {
                 .load(Ordering::Relaxed),
             dump_style_statistics: DEFAULT_DUMP_STYLE_STATISTICS.load(Ordering::Relaxed),
             style_statistics_threshold: DEFAULT_STATISTICS_THRESHOLD,
+            style_sharing_statistics: DEFAULT_STYLE_SHARING_STATISTICS.load(Ordering::Relaxed),
         }
     }
 
@@ -112,6 +122,7 @@ impl Default for StyleSystemOptions {
             dump_style_statistics: get_env_bool("DUMP_STYLE_STATISTICS"),
             style_statistics_threshold: get_env_usize("STYLE_STATISTICS_THRESHOLD")
                 .unwrap_or(DEFAULT_STATISTICS_THRESHOLD),
+            style_sharing_statistics: get_env_bool("STYLE_SHARING_STATISTICS"),
         }
     }
 }
@@ -275,7 +286,7 @@ impl ElementCascadeInputs {
 /// Statistics gathered during the traversal. We gather statistics on each
 /// thread and then combine them after the threads join via the Add
 /// implementation below.
-#[derive(AddAssign, Clone, Default)]
+#[derive(AddAssign, Clone, Default, Serialize)]
 pub struct PerThreadTraversalStatistics {
     /// The total number of elements traversed.
     pub elements_traversed: u32,
@@ -288,11 +299,16 @@ pub struct PerThreadTraversalStatistics {
     /// The number of styles reused via rule node comparison from the
     /// StyleSharingCache.
     pub styles_reused: u32,
+    /// Detailed statistics about the StyleSharingCache, only gathered when
+    /// `StyleSystemOptions::style_sharing_statistics` is set.
+    pub sharing: StyleSharingStatistics,
 }
 
 /// Statistics gathered during the traversal plus some information from
 /// other sources including stylist.
-#[derive(Default)]
+///
+/// These can be serialized, e.g. to JSON, for tooling.
+#[derive(Default, Serialize)]
 pub struct TraversalStatistics {
     /// Aggregated statistics gathered during the traversal.
     pub aggregated: PerThreadTraversalStatistics,
@@ -349,6 +365,21 @@ impl fmt::Display for TraversalStatistics {
         )?;
         writeln!(f, "[PERF],styles_shared,{}", self.aggregated.styles_shared)?;
         writeln!(f, "[PERF],styles_reused,{}", self.aggregated.styles_reused)?;
+        let sharing = &self.aggregated.sharing;
+        if sharing.lookups != 0 {
+            writeln!(f, "[PERF],sharing_lookups,{}", sharing.lookups)?;
+            writeln!(f, "[PERF],sharing_skipped,{}", sharing.skipped)?;
+            writeln!(f, "[PERF],sharing_hits,{}", sharing.hits)?;
+            writeln!(f, "[PERF],sharing_misses,{}", sharing.misses())?;
+            writeln!(
+                f,
+                "[PERF],sharing_candidates_tested,{}",
+                sharing.candidates_tested
+            )?;
+            for (reason, count) in sharing.rejections.iter() {
+                writeln!(f, "[PERF],sharing_rejected_by_{:?},{}", reason, count)?;
+            }
+        }
         writeln!(f, "[PERF],selectors,{}", self.selectors)?;
         writeln!(
             f,
@@ -647,8 +678,8 @@ pub struct ThreadLocalStyleContext<E: TElement> {
     /// A checker used to ensure that parallel.rs does not recurse indefinitely
     /// even on arbitrarily deep trees.  See Gecko bug 1376883.
     pub stack_limit_checker: StackLimitChecker,
-    /// A cache for nth-index-like selectors.
-    pub nth_index_cache: NthIndexCache,
+    /// Caches for expensive selectors, like nth-index-like ones and :has().
+    pub selector_caches: SelectorCaches,
 }
 
 impl<E: TElement> ThreadLocalStyleContext<E> {
@@ -663,7 +694,7 @@ impl<E: TElement> ThreadLocalStyleContext<E> {
             stack_limit_checker: StackLimitChecker::new(
                 (STYLE_THREAD_STACK_SIZE_KB - STACK_SAFETY_MARGIN_KB) * 1024,
             ),
-            nth_index_cache: NthIndexCache::default(),
+            selector_caches: SelectorCaches::default(),
         }
     }
 }
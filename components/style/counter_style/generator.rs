@@ -0,0 +1,911 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Generation of counter representations from counter styles.
+//!
+//! <https://drafts.csswg.org/css-counter-styles/#generate-a-counter>
+
+use super::{CounterBound, CounterStyleRuleData, Symbol, Symbols, System};
+use crate::selector_map::PrecomputedHashMap;
+use crate::values::generics::{CounterStyle, SymbolsType};
+use crate::Atom;
+use smallvec::SmallVec;
+use std::cmp;
+
+/// The maximum number of times a symbol is repeated when generating a
+/// representation, either by the algorithm itself or by padding.
+///
+/// Symbolic and additive systems, as well as `pad`, can otherwise make us
+/// build arbitrarily large strings.
+const MAX_REPETITIONS: usize = 1000;
+
+/// The algorithm a counter style uses to turn a counter value into its
+/// initial representation.
+#[derive(Clone, Debug, MallocSizeOf)]
+enum Algorithm {
+    Cyclic(Box<[String]>),
+    Numeric(Box<[String]>),
+    Alphabetic(Box<[String]>),
+    Symbolic(Box<[String]>),
+    Additive(Box<[(i32, String)]>),
+    Fixed {
+        first_symbol_value: i32,
+        symbols: Box<[String]>,
+    },
+    /// <https://drafts.csswg.org/css-counter-styles/#limited-chinese>
+    Cjk(#[ignore_malloc_size_of = "Static"] &'static CjkInfo),
+    /// <https://drafts.csswg.org/css-counter-styles/#ethiopic-numeric-counter-style>
+    EthiopicNumeric,
+}
+
+impl Algorithm {
+    /// Returns the algorithm defined by the `system`, `symbols` and
+    /// `additive-symbols` descriptors of the rule, or None if the rule uses
+    /// the `extends` system, whose algorithm is the one of the extended style.
+    fn from_descriptors(rule: &CounterStyleRuleData) -> Option<Self> {
+        let symbols = || rule.symbols().map_or_else(Default::default, symbol_strings);
+        Some(match *rule.resolved_system() {
+            System::Cyclic => Algorithm::Cyclic(symbols()),
+            System::Numeric => Algorithm::Numeric(symbols()),
+            System::Alphabetic => Algorithm::Alphabetic(symbols()),
+            System::Symbolic => Algorithm::Symbolic(symbols()),
+            System::Extends(..) => return None,
+            System::Fixed { first_symbol_value } => Algorithm::Fixed {
+                first_symbol_value: first_symbol_value.map_or(1, |v| v.value()),
+                symbols: symbols(),
+            },
+            System::Additive => Algorithm::Additive(rule.additive_symbols().map_or_else(
+                Default::default,
+                |tuples| {
+                    tuples
+                        .0
+                        .iter()
+                        .map(|tuple| (tuple.weight.value(), symbol_string(&tuple.symbol)))
+                        .collect()
+                },
+            )),
+        })
+    }
+
+    /// <https://drafts.csswg.org/css-counter-styles/#counter-style-range>
+    fn auto_range(&self) -> (i32, i32) {
+        match *self {
+            Algorithm::Alphabetic(..) | Algorithm::Symbolic(..) | Algorithm::EthiopicNumeric => {
+                (1, i32::max_value())
+            },
+            Algorithm::Additive(..) => (0, i32::max_value()),
+            Algorithm::Cyclic(..) |
+            Algorithm::Numeric(..) |
+            Algorithm::Fixed { .. } |
+            Algorithm::Cjk(..) => (i32::min_value(), i32::max_value()),
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-counter-styles/#counter-style-negative>
+    fn uses_negative_sign(&self) -> bool {
+        match *self {
+            Algorithm::Numeric(..) |
+            Algorithm::Alphabetic(..) |
+            Algorithm::Symbolic(..) |
+            Algorithm::Additive(..) |
+            Algorithm::Cjk(..) => true,
+            Algorithm::Cyclic(..) | Algorithm::Fixed { .. } | Algorithm::EthiopicNumeric => false,
+        }
+    }
+
+    /// Returns the initial representation of the value, or None if the
+    /// algorithm can't represent it.
+    ///
+    /// Algorithms that use a negative sign only get non-negative values.
+    fn generate(&self, value: i64) -> Option<String> {
+        match *self {
+            Algorithm::Cyclic(ref symbols) => {
+                if symbols.is_empty() {
+                    return None;
+                }
+                let index = (value - 1).rem_euclid(symbols.len() as i64);
+                Some(symbols[index as usize].clone())
+            },
+            Algorithm::Fixed {
+                first_symbol_value,
+                ref symbols,
+            } => {
+                let index = value - first_symbol_value as i64;
+                if index < 0 || index >= symbols.len() as i64 {
+                    return None;
+                }
+                Some(symbols[index as usize].clone())
+            },
+            Algorithm::Symbolic(ref symbols) => {
+                if value < 1 || symbols.is_empty() {
+                    return None;
+                }
+                let len = symbols.len() as i64;
+                let repetitions = ((value - 1) / len + 1) as usize;
+                if repetitions > MAX_REPETITIONS {
+                    return None;
+                }
+                Some(symbols[((value - 1) % len) as usize].repeat(repetitions))
+            },
+            Algorithm::Alphabetic(ref symbols) => {
+                if value < 1 || symbols.len() < 2 {
+                    return None;
+                }
+                let len = symbols.len() as i64;
+                let mut value = value;
+                let mut reversed = SmallVec::<[&str; 8]>::new();
+                while value != 0 {
+                    value -= 1;
+                    reversed.push(&symbols[(value % len) as usize]);
+                    value /= len;
+                }
+                Some(reversed.iter().rev().map(|s| *s).collect())
+            },
+            Algorithm::Numeric(ref symbols) => {
+                if symbols.len() < 2 {
+                    return None;
+                }
+                let len = symbols.len() as i64;
+                let mut value = value;
+                let mut reversed = SmallVec::<[&str; 12]>::new();
+                loop {
+                    reversed.push(&symbols[(value % len) as usize]);
+                    value /= len;
+                    if value == 0 {
+                        break;
+                    }
+                }
+                Some(reversed.iter().rev().map(|s| *s).collect())
+            },
+            Algorithm::Additive(ref tuples) => additive(tuples, value),
+            Algorithm::Cjk(info) => Some(cjk(info, value)),
+            Algorithm::EthiopicNumeric => {
+                if value < 1 {
+                    return None;
+                }
+                Some(ethiopic_numeric(value))
+            },
+        }
+    }
+}
+
+/// <https://drafts.csswg.org/css-counter-styles/#additive-system>
+fn additive(tuples: &[(i32, String)], mut value: i64) -> Option<String> {
+    if value == 0 {
+        return tuples
+            .iter()
+            .find(|&&(weight, _)| weight == 0)
+            .map(|&(_, ref symbol)| symbol.clone());
+    }
+    let mut result = String::new();
+    let mut repetitions = 0;
+    for &(weight, ref symbol) in tuples.iter() {
+        let weight = weight as i64;
+        if weight == 0 || weight > value {
+            continue;
+        }
+        let count = (value / weight) as usize;
+        repetitions += count;
+        if repetitions > MAX_REPETITIONS {
+            return None;
+        }
+        result.push_str(&symbol.repeat(count));
+        value -= weight * count as i64;
+        if value == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum CjkLanguage {
+    Chinese,
+    Japanese,
+    Korean,
+}
+
+/// The symbols and rules of one of the CJK longhand counter styles.
+#[derive(Debug)]
+struct CjkInfo {
+    language: CjkLanguage,
+    informal: bool,
+    /// The ten digits, from zero to nine.
+    digits: &'static str,
+    /// The markers for tens, hundreds and thousands.
+    digit_markers: [&'static str; 3],
+    /// The markers for 10^4, 10^8 and 10^12.
+    group_markers: [&'static str; 3],
+}
+
+static JAPANESE_INFORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Japanese,
+    informal: true,
+    digits: "〇一二三四五六七八九",
+    digit_markers: ["十", "百", "千"],
+    group_markers: ["万", "億", "兆"],
+};
+
+static JAPANESE_FORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Japanese,
+    informal: false,
+    digits: "零壱弐参四伍六七八九",
+    digit_markers: ["拾", "百", "阡"],
+    group_markers: ["萬", "億", "兆"],
+};
+
+static KOREAN_HANGUL_FORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Korean,
+    informal: false,
+    digits: "영일이삼사오육칠팔구",
+    digit_markers: ["십", "백", "천"],
+    group_markers: ["만", "억", "조"],
+};
+
+static KOREAN_HANJA_INFORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Korean,
+    informal: true,
+    digits: "零一二三四五六七八九",
+    digit_markers: ["十", "百", "千"],
+    group_markers: ["萬", "億", "兆"],
+};
+
+static KOREAN_HANJA_FORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Korean,
+    informal: false,
+    digits: "零壹貳參四五六七八九",
+    digit_markers: ["拾", "百", "仟"],
+    group_markers: ["萬", "億", "兆"],
+};
+
+static SIMP_CHINESE_INFORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Chinese,
+    informal: true,
+    digits: "零一二三四五六七八九",
+    digit_markers: ["十", "百", "千"],
+    group_markers: ["万", "亿", "万亿"],
+};
+
+static SIMP_CHINESE_FORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Chinese,
+    informal: false,
+    digits: "零壹贰叁肆伍陆柒捌玖",
+    digit_markers: ["拾", "佰", "仟"],
+    group_markers: ["万", "亿", "万亿"],
+};
+
+static TRAD_CHINESE_INFORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Chinese,
+    informal: true,
+    digits: "零一二三四五六七八九",
+    digit_markers: ["十", "百", "千"],
+    group_markers: ["萬", "億", "兆"],
+};
+
+static TRAD_CHINESE_FORMAL: CjkInfo = CjkInfo {
+    language: CjkLanguage::Chinese,
+    informal: false,
+    digits: "零壹貳參肆伍陸柒捌玖",
+    digit_markers: ["拾", "佰", "仟"],
+    group_markers: ["萬", "億", "兆"],
+};
+
+/// <https://drafts.csswg.org/css-counter-styles/#limited-chinese>
+fn cjk(info: &CjkInfo, value: i64) -> String {
+    let digit = |d: u8| info.digits.chars().nth(d as usize).unwrap();
+    if value == 0 {
+        return digit(0).to_string();
+    }
+
+    // Decimal digits, least significant first.
+    let mut digits = SmallVec::<[u8; 20]>::new();
+    let mut remaining = value;
+    while remaining != 0 {
+        digits.push((remaining % 10) as u8);
+        remaining /= 10;
+    }
+    let group_value = |group: usize| {
+        digits
+            .iter()
+            .skip(group * 4)
+            .take(4)
+            .rev()
+            .fold(0, |acc, &d| acc * 10 + d as u32)
+    };
+
+    let mut result = String::new();
+    let mut pending_zero = false;
+    for position in (0..digits.len()).rev() {
+        let d = digits[position];
+        let unit = position % 4;
+        let group = position / 4;
+        if d == 0 {
+            // Chinese collapses runs of non-trailing zeros into a single
+            // zero digit, Japanese and Korean drop them altogether.
+            pending_zero = true;
+        } else {
+            if pending_zero && info.language == CjkLanguage::Chinese {
+                result.push(digit(0));
+            }
+            pending_zero = false;
+            let drop_one = d == 1 &&
+                unit > 0 &&
+                info.informal &&
+                match info.language {
+                    CjkLanguage::Chinese => unit == 1 && group_value(group) < 20,
+                    CjkLanguage::Japanese | CjkLanguage::Korean => true,
+                };
+            if !drop_one {
+                result.push(digit(d));
+            }
+            if unit > 0 {
+                result.push_str(info.digit_markers[unit - 1]);
+            }
+        }
+        if unit == 0 && group > 0 && group_value(group) != 0 {
+            result.push_str(info.group_markers[group - 1]);
+            if info.language == CjkLanguage::Korean {
+                result.push(' ');
+            }
+        }
+    }
+    if info.language == CjkLanguage::Korean {
+        result.truncate(result.trim_end().len());
+    }
+    result
+}
+
+/// <https://drafts.csswg.org/css-counter-styles/#ethiopic-numeric-counter-style>
+fn ethiopic_numeric(value: i64) -> String {
+    const UNITS: [char; 9] = [
+        '\u{1369}', '\u{136A}', '\u{136B}', '\u{136C}', '\u{136D}', '\u{136E}', '\u{136F}',
+        '\u{1370}', '\u{1371}',
+    ];
+    const TENS: [char; 9] = [
+        '\u{1372}', '\u{1373}', '\u{1374}', '\u{1375}', '\u{1376}', '\u{1377}', '\u{1378}',
+        '\u{1379}', '\u{137A}',
+    ];
+    const HUNDRED: char = '\u{137B}';
+    const TEN_THOUSAND: char = '\u{137C}';
+
+    if value == 1 {
+        return UNITS[0].to_string();
+    }
+
+    // Groups of two decimal digits, least significant first.
+    let mut groups = SmallVec::<[u8; 10]>::new();
+    let mut remaining = value;
+    while remaining != 0 {
+        groups.push((remaining % 100) as u8);
+        remaining /= 100;
+    }
+
+    let mut result = String::new();
+    for (index, &group) in groups.iter().enumerate().rev() {
+        let most_significant = index == groups.len() - 1;
+        let odd = index % 2 == 1;
+        let removed = group == 0 || (group == 1 && (most_significant || odd));
+        if !removed {
+            let (tens, units) = (group / 10, group % 10);
+            if tens != 0 {
+                result.push(TENS[tens as usize - 1]);
+            }
+            if units != 0 {
+                result.push(UNITS[units as usize - 1]);
+            }
+        }
+        if odd {
+            if group != 0 {
+                result.push(HUNDRED);
+            }
+        } else if index != 0 {
+            result.push(TEN_THOUSAND);
+        }
+    }
+    result
+}
+
+fn symbol_string(symbol: &Symbol) -> String {
+    match *symbol {
+        Symbol::String(ref s) => String::from(&**s),
+        Symbol::Ident(ref ident) => ident.0.to_string(),
+    }
+}
+
+fn symbol_strings(symbols: &Symbols) -> Box<[String]> {
+    symbols.0.iter().map(symbol_string).collect()
+}
+
+fn chars(symbols: &str) -> Box<[String]> {
+    symbols.chars().map(String::from).collect()
+}
+
+/// A counter style with all its descriptors resolved, including the ones it
+/// inherits through the `extends` system.
+#[derive(Clone, Debug, MallocSizeOf)]
+pub struct ResolvedCounterStyle {
+    algorithm: Algorithm,
+    negative: (String, String),
+    prefix: String,
+    suffix: String,
+    /// Inclusive ranges, empty means `auto`.
+    range: Box<[(i32, i32)]>,
+    pad: (usize, String),
+    fallback: Atom,
+}
+
+impl ResolvedCounterStyle {
+    /// A style with the given algorithm and the initial value of every other
+    /// descriptor.
+    fn new(algorithm: Algorithm) -> Self {
+        ResolvedCounterStyle {
+            algorithm,
+            negative: ("-".into(), String::new()),
+            prefix: String::new(),
+            suffix: ". ".into(),
+            range: Box::new([]),
+            pad: (0, String::new()),
+            fallback: atom!("decimal"),
+        }
+    }
+
+    fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    fn with_negative(mut self, negative: &str) -> Self {
+        self.negative = (negative.into(), String::new());
+        self
+    }
+
+    fn with_range(mut self, start: i32, end: i32) -> Self {
+        self.range = Box::new([(start, end)]);
+        self
+    }
+
+    fn with_fallback(mut self, fallback: &str) -> Self {
+        self.fallback = Atom::from(fallback);
+        self
+    }
+
+    /// Resolves an @counter-style rule. `extended` is the style the rule
+    /// extends, if it uses the `extends` system, from which it takes its
+    /// algorithm and the descriptors it doesn't specify.
+    fn from_rule(rule: &CounterStyleRuleData, extended: Option<&Self>) -> Self {
+        let mut style = match Algorithm::from_descriptors(rule) {
+            Some(algorithm) => Self::new(algorithm),
+            None => extended.unwrap_or_else(|| decimal()).clone(),
+        };
+        if let Some(negative) = rule.negative() {
+            style.negative = (
+                symbol_string(&negative.0),
+                negative.1.as_ref().map_or_else(String::new, symbol_string),
+            );
+        }
+        if let Some(prefix) = rule.prefix() {
+            style.prefix = symbol_string(prefix);
+        }
+        if let Some(suffix) = rule.suffix() {
+            style.suffix = symbol_string(suffix);
+        }
+        if let Some(range) = rule.range() {
+            style.range = range
+                .0
+                .iter()
+                .map(|range| {
+                    let start = match range.start {
+                        CounterBound::Integer(i) => i.value(),
+                        CounterBound::Infinite => i32::min_value(),
+                    };
+                    let end = match range.end {
+                        CounterBound::Integer(i) => i.value(),
+                        CounterBound::Infinite => i32::max_value(),
+                    };
+                    (start, end)
+                })
+                .collect();
+        }
+        if let Some(pad) = rule.pad() {
+            style.pad = (pad.0.value().max(0) as usize, symbol_string(&pad.1));
+        }
+        if let Some(fallback) = rule.fallback() {
+            style.fallback = fallback.0 .0.clone();
+        }
+        style
+    }
+
+    /// The anonymous counter style defined by a `symbols()` function.
+    ///
+    /// <https://drafts.csswg.org/css-counter-styles/#symbols-function>
+    fn anonymous(symbols_type: &SymbolsType, symbols: &Symbols) -> Self {
+        let symbols = symbol_strings(symbols);
+        let algorithm = match *symbols_type {
+            SymbolsType::Cyclic => Algorithm::Cyclic(symbols),
+            SymbolsType::Numeric => Algorithm::Numeric(symbols),
+            SymbolsType::Alphabetic => Algorithm::Alphabetic(symbols),
+            SymbolsType::Symbolic => Algorithm::Symbolic(symbols),
+            SymbolsType::Fixed => Algorithm::Fixed {
+                first_symbol_value: 1,
+                symbols,
+            },
+        };
+        Self::new(algorithm).with_suffix(" ")
+    }
+
+    /// The symbol prepended to the representation in list markers.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The symbol appended to the representation in list markers.
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    /// The name of the style used for values this style can't represent.
+    pub fn fallback(&self) -> &Atom {
+        &self.fallback
+    }
+
+    /// Whether the value is in the range of this counter style.
+    pub fn is_in_range(&self, value: i32) -> bool {
+        if self.range.is_empty() {
+            let (start, end) = self.algorithm.auto_range();
+            return start <= value && value <= end;
+        }
+        self.range
+            .iter()
+            .any(|&(start, end)| start <= value && value <= end)
+    }
+
+    /// Generates the representation of the value in this style, without
+    /// following the fallback chain, nor adding the prefix and suffix.
+    ///
+    /// Returns None if the value is out of range or can't be represented.
+    pub fn generate_without_fallback(&self, value: i32) -> Option<String> {
+        if !self.is_in_range(value) {
+            return None;
+        }
+        let negative = value < 0 && self.algorithm.uses_negative_sign();
+        let value = value as i64;
+        let representation = self
+            .algorithm
+            .generate(if negative { -value } else { value })?;
+
+        let mut length = representation.chars().count();
+        if negative {
+            length += self.negative.0.chars().count() + self.negative.1.chars().count();
+        }
+        let padding = cmp::min(self.pad.0, MAX_REPETITIONS).saturating_sub(length);
+
+        let mut result = String::new();
+        if negative {
+            result.push_str(&self.negative.0);
+        }
+        result.push_str(&self.pad.1.repeat(padding));
+        result.push_str(&representation);
+        if negative {
+            result.push_str(&self.negative.1);
+        }
+        Some(result)
+    }
+}
+
+lazy_static! {
+    /// The predefined counter styles.
+    ///
+    /// <https://drafts.csswg.org/css-counter-styles/#predefined-counters>
+    static ref PREDEFINED: PrecomputedHashMap<Atom, ResolvedCounterStyle> = predefined_styles();
+}
+
+fn predefined_styles() -> PrecomputedHashMap<Atom, ResolvedCounterStyle> {
+    let numeric = |digits: &str| ResolvedCounterStyle::new(Algorithm::Numeric(chars(digits)));
+    let alphabetic =
+        |letters: &str| ResolvedCounterStyle::new(Algorithm::Alphabetic(chars(letters)));
+    let cyclic =
+        |symbol: &str| ResolvedCounterStyle::new(Algorithm::Cyclic(chars(symbol))).with_suffix(" ");
+    let additive_style = |tuples: &[(i32, &str)]| {
+        ResolvedCounterStyle::new(Algorithm::Additive(
+            tuples
+                .iter()
+                .map(|&(weight, symbol)| (weight, symbol.into()))
+                .collect(),
+        ))
+    };
+    // Armenian letters are in numeric order: nine units, nine tens, nine
+    // hundreds and nine thousands.
+    let armenian = |letters: &str| {
+        let mut tuples: Vec<_> = letters
+            .chars()
+            .enumerate()
+            .map(|(i, c)| ((i as i32 % 9 + 1) * 10i32.pow(i as u32 / 9), c.to_string()))
+            .collect();
+        tuples.reverse();
+        ResolvedCounterStyle::new(Algorithm::Additive(tuples.into_boxed_slice()))
+            .with_range(1, 9999)
+    };
+    let roman = |symbols: [&str; 13]| {
+        let weights = [1000, 900, 500, 400, 100, 90, 50, 40, 10, 9, 5, 4, 1];
+        let tuples: SmallVec<[(i32, &str); 13]> = weights
+            .iter()
+            .cloned()
+            .zip(symbols.iter().cloned())
+            .collect();
+        additive_style(&tuples).with_range(1, 3999)
+    };
+    let cjk_style = |info: &'static CjkInfo| {
+        let style = ResolvedCounterStyle::new(Algorithm::Cjk(info))
+            .with_range(-9999, 9999)
+            .with_fallback("cjk-decimal");
+        match info.language {
+            CjkLanguage::Japanese => style.with_suffix("、").with_negative("マイナス"),
+            CjkLanguage::Korean => style.with_suffix(", ").with_negative("마이너스 "),
+            CjkLanguage::Chinese => style.with_suffix("、"),
+        }
+    };
+    let simp_chinese = |info| cjk_style(info).with_negative("负");
+    let trad_chinese = |info| cjk_style(info).with_negative("負");
+
+    let mut decimal_leading_zero = numeric("0123456789");
+    decimal_leading_zero.pad = (2, "0".into());
+
+    let styles = vec![
+        ("decimal", numeric("0123456789")),
+        ("decimal-leading-zero", decimal_leading_zero),
+        ("arabic-indic", numeric("٠١٢٣٤٥٦٧٨٩")),
+        ("armenian", armenian("ԱԲԳԴԵԶԷԸԹԺԻԼԽԾԿՀՁՂՃՄՅՆՇՈՉՊՋՌՍՎՏՐՑՒՓՔ")),
+        ("upper-armenian", armenian("ԱԲԳԴԵԶԷԸԹԺԻԼԽԾԿՀՁՂՃՄՅՆՇՈՉՊՋՌՍՎՏՐՑՒՓՔ")),
+        ("lower-armenian", armenian("աբգդեզէըթժիլխծկհձղճմյնշոչպջռսվտրցւփք")),
+        ("bengali", numeric("০১২৩৪৫৬৭৮৯")),
+        ("cambodian", numeric("០១២៣៤៥៦៧៨៩")),
+        ("khmer", numeric("០១២៣៤៥៦៧៨៩")),
+        ("cjk-decimal", numeric("〇一二三四五六七八九").with_suffix("、")),
+        ("devanagari", numeric("०१२३४५६७८९")),
+        (
+            "georgian",
+            additive_style(&[
+                (10000, "ჵ"), (9000, "ჰ"), (8000, "ჯ"), (7000, "ჴ"), (6000, "ხ"),
+                (5000, "ჭ"), (4000, "წ"), (3000, "ძ"), (2000, "ც"), (1000, "ჩ"),
+                (900, "შ"), (800, "ყ"), (700, "ღ"), (600, "ქ"), (500, "ფ"),
+                (400, "ჳ"), (300, "ტ"), (200, "ს"), (100, "რ"), (90, "ჟ"),
+                (80, "პ"), (70, "ო"), (60, "ჲ"), (50, "ნ"), (40, "მ"),
+                (30, "ლ"), (20, "კ"), (10, "ი"), (9, "თ"), (8, "ჱ"),
+                (7, "ზ"), (6, "ვ"), (5, "ე"), (4, "დ"), (3, "გ"),
+                (2, "ბ"), (1, "ა"),
+            ])
+            .with_range(1, 19999),
+        ),
+        ("gujarati", numeric("૦૧૨૩૪૫૬૭૮૯")),
+        ("gurmukhi", numeric("੦੧੨੩੪੫੬੭੮੯")),
+        (
+            "hebrew",
+            additive_style(&[
+                (10000, "י׳"), (9000, "ט׳"), (8000, "ח׳"), (7000, "ז׳"), (6000, "ו׳"),
+                (5000, "ה׳"), (4000, "ד׳"), (3000, "ג׳"), (2000, "ב׳"), (1000, "א׳"),
+                (400, "ת"), (300, "ש"), (200, "ר"), (100, "ק"), (90, "צ"),
+                (80, "פ"), (70, "ע"), (60, "ס"), (50, "נ"), (40, "מ"),
+                (30, "ל"), (20, "כ"), (19, "יט"), (18, "יח"), (17, "יז"),
+                (16, "טז"), (15, "טו"), (10, "י"), (9, "ט"), (8, "ח"),
+                (7, "ז"), (6, "ו"), (5, "ה"), (4, "ד"), (3, "ג"),
+                (2, "ב"), (1, "א"),
+            ])
+            .with_range(1, 10999),
+        ),
+        ("kannada", numeric("೦೧೨೩೪೫೬೭೮೯")),
+        ("lao", numeric("໐໑໒໓໔໕໖໗໘໙")),
+        ("malayalam", numeric("൦൧൨൩൪൫൬൭൮൯")),
+        ("mongolian", numeric("᠐᠑᠒᠓᠔᠕᠖᠗᠘᠙")),
+        ("myanmar", numeric("၀၁၂၃၄၅၆၇၈၉")),
+        ("oriya", numeric("୦୧୨୩୪୫୬୭୮୯")),
+        ("persian", numeric("۰۱۲۳۴۵۶۷۸۹")),
+        (
+            "lower-roman",
+            roman(["m", "cm", "d", "cd", "c", "xc", "l", "xl", "x", "ix", "v", "iv", "i"]),
+        ),
+        (
+            "upper-roman",
+            roman(["M", "CM", "D", "CD", "C", "XC", "L", "XL", "X", "IX", "V", "IV", "I"]),
+        ),
+        ("tamil", numeric("௦௧௨௩௪௫௬௭௮௯")),
+        ("telugu", numeric("౦౧౨౩౪౫౬౭౮౯")),
+        ("thai", numeric("๐๑๒๓๔๕๖๗๘๙")),
+        ("tibetan", numeric("༠༡༢༣༤༥༦༧༨༩")),
+        ("lower-alpha", alphabetic("abcdefghijklmnopqrstuvwxyz")),
+        ("lower-latin", alphabetic("abcdefghijklmnopqrstuvwxyz")),
+        ("upper-alpha", alphabetic("ABCDEFGHIJKLMNOPQRSTUVWXYZ")),
+        ("upper-latin", alphabetic("ABCDEFGHIJKLMNOPQRSTUVWXYZ")),
+        ("cjk-earthly-branch", alphabetic("子丑寅卯辰巳午未申酉戌亥").with_suffix("、")),
+        ("cjk-heavenly-stem", alphabetic("甲乙丙丁戊己庚辛壬癸").with_suffix("、")),
+        ("lower-greek", alphabetic("αβγδεζηθικλμνξοπρστυφχψω")),
+        (
+            "hiragana",
+            alphabetic("あいうえおかきくけこさしすせそたちつてとなにぬねのはひふへほまみむめもやゆよらりるれろわゐゑをん")
+                .with_suffix("、"),
+        ),
+        (
+            "hiragana-iroha",
+            alphabetic("いろはにほへとちりぬるをわかよたれそつねならむうゐのおくやまけふこえてあさきゆめみしゑひもせす")
+                .with_suffix("、"),
+        ),
+        (
+            "katakana",
+            alphabetic("アイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワヰヱヲン")
+                .with_suffix("、"),
+        ),
+        (
+            "katakana-iroha",
+            alphabetic("イロハニホヘトチリヌルヲワカヨタレソツネナラムウヰノオクヤマケフコエテアサキユメミシヱヒモセス")
+                .with_suffix("、"),
+        ),
+        ("disc", cyclic("\u{2022}")),
+        ("circle", cyclic("\u{25E6}")),
+        ("square", cyclic("\u{25AA}")),
+        ("disclosure-open", cyclic("\u{25BE}")),
+        ("disclosure-closed", cyclic("\u{25B8}")),
+        ("japanese-informal", cjk_style(&JAPANESE_INFORMAL)),
+        ("japanese-formal", cjk_style(&JAPANESE_FORMAL)),
+        ("korean-hangul-formal", cjk_style(&KOREAN_HANGUL_FORMAL)),
+        ("korean-hanja-informal", cjk_style(&KOREAN_HANJA_INFORMAL)),
+        ("korean-hanja-formal", cjk_style(&KOREAN_HANJA_FORMAL)),
+        ("simp-chinese-informal", simp_chinese(&SIMP_CHINESE_INFORMAL)),
+        ("simp-chinese-formal", simp_chinese(&SIMP_CHINESE_FORMAL)),
+        ("trad-chinese-informal", trad_chinese(&TRAD_CHINESE_INFORMAL)),
+        ("trad-chinese-formal", trad_chinese(&TRAD_CHINESE_FORMAL)),
+        ("cjk-ideographic", trad_chinese(&TRAD_CHINESE_INFORMAL)),
+        (
+            "ethiopic-numeric",
+            ResolvedCounterStyle::new(Algorithm::EthiopicNumeric).with_suffix("/ "),
+        ),
+    ];
+    styles
+        .into_iter()
+        .map(|(name, style)| (Atom::from(name), style))
+        .collect()
+}
+
+fn decimal() -> &'static ResolvedCounterStyle {
+    &PREDEFINED[&atom!("decimal")]
+}
+
+/// Returns whether the rule with the given name is part of a cycle of
+/// `extends` systems.
+fn is_in_extends_cycle(
+    name: &Atom,
+    rules: &PrecomputedHashMap<Atom, &CounterStyleRuleData>,
+) -> bool {
+    let mut seen = SmallVec::<[&Atom; 4]>::new();
+    let mut current = name;
+    loop {
+        let next = match rules.get(current).map(|rule| rule.resolved_system()) {
+            Some(&System::Extends(ref next)) => &next.0,
+            _ => return false,
+        };
+        if next == name {
+            return true;
+        }
+        if seen.contains(&next) {
+            return false;
+        }
+        seen.push(next);
+        current = next;
+    }
+}
+
+/// The set of counter styles defined in a document: the predefined ones,
+/// plus the ones defined by @counter-style rules.
+#[derive(Clone, Debug, Default, MallocSizeOf)]
+pub struct CounterStyleRegistry {
+    styles: PrecomputedHashMap<Atom, ResolvedCounterStyle>,
+}
+
+impl CounterStyleRegistry {
+    /// Creates a registry containing only the predefined counter styles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry from the given @counter-style rules. If several
+    /// rules have the same name, the last one wins.
+    pub fn from_rules<'a, I>(rules: I) -> Self
+    where
+        I: IntoIterator<Item = &'a CounterStyleRuleData>,
+    {
+        let mut by_name = PrecomputedHashMap::default();
+        for rule in rules {
+            by_name.insert(rule.name().0.clone(), rule);
+        }
+        let mut registry = Self::default();
+        for name in by_name.keys() {
+            registry.resolve(name, &by_name);
+        }
+        registry
+    }
+
+    fn resolve(&mut self, name: &Atom, rules: &PrecomputedHashMap<Atom, &CounterStyleRuleData>) {
+        if self.styles.contains_key(name) {
+            return;
+        }
+        let rule = rules[name];
+        let style = match *rule.resolved_system() {
+            System::Extends(ref extended) => {
+                let extended = &extended.0;
+                // Styles extending an undefined style, or taking part in a
+                // cycle, are treated as if they extended decimal.
+                if is_in_extends_cycle(name, rules) {
+                    ResolvedCounterStyle::from_rule(rule, Some(decimal()))
+                } else if rules.contains_key(extended) {
+                    self.resolve(extended, rules);
+                    ResolvedCounterStyle::from_rule(rule, Some(&self.styles[extended]))
+                } else {
+                    let extended = PREDEFINED.get(extended).unwrap_or_else(decimal);
+                    ResolvedCounterStyle::from_rule(rule, Some(extended))
+                }
+            },
+            _ => ResolvedCounterStyle::from_rule(rule, None),
+        };
+        self.styles.insert(name.clone(), style);
+    }
+
+    /// Returns whether a @counter-style rule defines the given name.
+    pub fn defines(&self, name: &Atom) -> bool {
+        self.styles.contains_key(name)
+    }
+
+    /// Looks up a counter style by name.
+    pub fn get(&self, name: &Atom) -> Option<&ResolvedCounterStyle> {
+        self.styles.get(name).or_else(|| PREDEFINED.get(name))
+    }
+
+    /// Generates the representation of the value in the given style,
+    /// following the fallback chain as needed.
+    ///
+    /// <https://drafts.csswg.org/css-counter-styles/#counter-style-fallback>
+    fn generate(&self, style: &ResolvedCounterStyle, value: i32) -> String {
+        let mut current = style;
+        let mut visited = SmallVec::<[&Atom; 4]>::new();
+        loop {
+            if let Some(representation) = current.generate_without_fallback(value) {
+                return representation;
+            }
+            let fallback = current.fallback();
+            if visited.contains(&fallback) {
+                break;
+            }
+            visited.push(fallback);
+            current = self.get(fallback).unwrap_or_else(|| decimal());
+        }
+        decimal()
+            .generate_without_fallback(value)
+            .expect("decimal can represent every value")
+    }
+
+    fn with_style<R>(&self, style: &CounterStyle, f: impl FnOnce(&ResolvedCounterStyle) -> R) -> R {
+        match *style {
+            CounterStyle::Name(ref name) => f(self.get(&name.0).unwrap_or_else(|| decimal())),
+            CounterStyle::Symbols(ref symbols_type, ref symbols) => {
+                f(&ResolvedCounterStyle::anonymous(symbols_type, symbols))
+            },
+        }
+    }
+
+    /// Generates the counter representation of the value, as used by the
+    /// `counter()` and `counters()` functions.
+    ///
+    /// Undefined counter style names are treated as `decimal`.
+    pub fn counter_representation(&self, style: &CounterStyle, value: i32) -> String {
+        self.with_style(style, |style| self.generate(style, value))
+    }
+
+    /// Generates the text of a list item marker, that is, the counter
+    /// representation wrapped in the prefix and suffix of the style.
+    pub fn marker_representation(&self, style: &CounterStyle, value: i32) -> String {
+        self.with_style(style, |style| {
+            let mut result = style.prefix().to_owned();
+            result.push_str(&self.generate(style, value));
+            result.push_str(style.suffix());
+            result
+        })
+    }
+}
@@ -15,6 +15,7 @@ use crate::values::CustomIdent;
 use crate::Atom;
 use cssparser::{AtRuleParser, DeclarationListParser, DeclarationParser};
 use cssparser::{CowRcStr, Parser, SourceLocation, Token};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use selectors::parser::SelectorParseErrorKind;
 use std::fmt::{self, Write};
 use std::mem;
@@ -22,6 +23,10 @@ use std::num::Wrapping;
 use style_traits::{Comma, CssWriter, OneOrMoreSeparated, ParseError};
 use style_traits::{StyleParseErrorKind, ToCss};
 
+mod generator;
+
+pub use self::generator::{CounterStyleRegistry, ResolvedCounterStyle};
+
 /// Parse a counter style name reference.
 ///
 /// This allows the reserved counter style names "decimal" and "disc".
@@ -207,6 +212,11 @@ macro_rules! counter_style_descriptors {
                     true
                 }
             )+
+
+            /// Measure heap usage.
+            pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+                self.name.size_of(ops) $( + self.$ident.size_of(ops) )+
+            }
         }
 
         impl<'a, 'b, 'i> DeclarationParser<'i> for CounterStyleRuleParser<'a, 'b> {
@@ -340,7 +350,7 @@ impl CounterStyleRuleData {
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-system>
-#[derive(Clone, Debug, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToShmem)]
 pub enum System {
     /// 'cyclic'
     Cyclic,
@@ -452,7 +462,7 @@ impl Symbol {
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-negative>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub struct Negative(pub Symbol, pub Option<Symbol>);
 
 impl Parse for Negative {
@@ -468,7 +478,7 @@ impl Parse for Negative {
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-range>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub struct CounterRange {
     /// The start of the range.
     pub start: CounterBound,
@@ -479,12 +489,12 @@ pub struct CounterRange {
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-range>
 ///
 /// Empty represents 'auto'
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 #[css(comma)]
 pub struct CounterRanges(#[css(iterable, if_empty = "auto")] pub crate::OwnedSlice<CounterRange>);
 
 /// A bound found in `CounterRanges`.
-#[derive(Clone, Copy, Debug, ToCss, ToShmem)]
+#[derive(Clone, Copy, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub enum CounterBound {
     /// An integer bound.
     Integer(Integer),
@@ -531,7 +541,7 @@ fn parse_bound<'i, 't>(
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-pad>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub struct Pad(pub Integer, pub Symbol);
 
 impl Parse for Pad {
@@ -547,7 +557,7 @@ impl Parse for Pad {
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-fallback>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub struct Fallback(pub CustomIdent);
 
 impl Parse for Fallback {
@@ -583,7 +593,7 @@ impl Parse for Symbols {
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#descdef-counter-style-additive-symbols>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 #[css(comma)]
 pub struct AdditiveSymbols(#[css(iterable)] pub crate::OwnedSlice<AdditiveTuple>);
 
@@ -605,7 +615,7 @@ impl Parse for AdditiveSymbols {
 }
 
 /// <integer> && <symbol>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub struct AdditiveTuple {
     /// <integer>
     pub weight: Integer,
@@ -630,7 +640,7 @@ impl Parse for AdditiveTuple {
 }
 
 /// <https://drafts.csswg.org/css-counter-styles/#counter-style-speak-as>
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub enum SpeakAs {
     /// auto
     Auto,
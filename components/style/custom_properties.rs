@@ -9,7 +9,10 @@
 use crate::applicable_declarations::CascadePriority;
 use crate::media_queries::Device;
 use crate::properties::{CSSWideKeyword, CustomDeclaration, CustomDeclarationValue};
+use crate::properties_and_values::registry::PropertyRegistration;
 use crate::selector_map::{PrecomputedHashMap, PrecomputedHashSet, PrecomputedHasher};
+use crate::stylist::Stylist;
+use crate::values::computed;
 use crate::Atom;
 use cssparser::{
     CowRcStr, Delimiter, Parser, ParserInput, SourcePosition, Token, TokenSerializationType,
@@ -328,6 +331,48 @@ impl VariableValue {
         }))
     }
 
+    /// Whether this value has references to other custom properties or to
+    /// environment variables, which need to be substituted.
+    pub fn has_references(&self) -> bool {
+        !self.references.is_empty() || self.references_environment
+    }
+
+    /// The names of the custom properties referenced by var() functions in
+    /// this value.
+    pub fn references(&self) -> &[Name] {
+        &self.references
+    }
+
+    /// Substitutes the `var()` and `env()` references of `value`, if any,
+    /// using the given computed custom properties.
+    ///
+    /// Returns `Err(())` if the value is invalid at computed-value time.
+    pub fn substitute_references(
+        value: &Arc<Self>,
+        custom_properties: Option<&Arc<CustomPropertiesMap>>,
+        device: &Device,
+    ) -> Result<Arc<Self>, ()> {
+        if !value.has_references() {
+            return Ok(value.clone());
+        }
+        let css = substitute(&value.css, value.first_token_type, custom_properties, device)
+            .map_err(|_| ())?;
+        let mut input = ParserInput::new(&css);
+        let mut input = Parser::new(&mut input);
+        Self::parse(&mut input).map_err(|_| ())
+    }
+
+    /// Whether this value consists of the same tokens as `other`, ignoring
+    /// comments and leading and trailing whitespace, and treating any run of
+    /// whitespace as a single space.
+    pub fn same_tokens_as(&self, other: &Self) -> bool {
+        let mut input = ParserInput::new(&self.css);
+        let mut input = Parser::new(&mut input);
+        let mut other_input = ParserInput::new(&other.css);
+        let mut other_input = Parser::new(&mut other_input);
+        same_tokens(&mut input, &mut other_input)
+    }
+
     /// Create VariableValue from an int.
     fn integer(number: i32) -> Self {
         Self::from_token(Token::Number {
@@ -380,6 +425,51 @@ impl VariableValue {
     }
 }
 
+/// Returns the next token of the input, with whitespace normalized as
+/// described in `VariableValue::same_tokens_as`.
+fn next_normalized_token<'i, 't>(input: &mut Parser<'i, 't>) -> Option<Token<'i>> {
+    let token = input.next_including_whitespace().ok()?.clone();
+    if let Token::WhiteSpace(..) = token {
+        // Comments between whitespace make the tokenizer split the run.
+        input.skip_whitespace();
+        if input.is_exhausted() {
+            return None;
+        }
+        return Some(Token::WhiteSpace(" "));
+    }
+    Some(token)
+}
+
+/// Whether the rest of the two inputs consist of the same tokens, see
+/// `VariableValue::same_tokens_as`.
+fn same_tokens(input: &mut Parser, other: &mut Parser) -> bool {
+    input.skip_whitespace();
+    other.skip_whitespace();
+    loop {
+        let token = next_normalized_token(input);
+        if token != next_normalized_token(other) {
+            return false;
+        }
+        match token {
+            None => return true,
+            Some(Token::Function(..)) |
+            Some(Token::ParenthesisBlock) |
+            Some(Token::SquareBracketBlock) |
+            Some(Token::CurlyBracketBlock) => {
+                let same = input.parse_nested_block(|input| {
+                    let same = other
+                        .parse_nested_block(|other| Ok::<_, ParseError>(same_tokens(input, other)));
+                    Ok::<_, ParseError>(same.unwrap_or(false))
+                });
+                if !same.unwrap_or(false) {
+                    return false;
+                }
+            },
+            Some(..) => {},
+        }
+    }
+}
+
 /// Parse the value of a non-custom property that contains `var()` references.
 pub fn parse_non_custom_with_var<'i, 't>(
     input: &mut Parser<'i, 't>,
@@ -611,25 +701,34 @@ fn parse_env_function<'i, 't>(
 
 /// A struct that takes care of encapsulating the cascade process for custom
 /// properties.
-pub struct CustomPropertiesBuilder<'a> {
+pub struct CustomPropertiesBuilder<'a, 'b: 'a> {
     seen: PrecomputedHashSet<&'a Name>,
     may_have_cycles: bool,
     custom_properties: Option<CustomPropertiesMap>,
     inherited: Option<&'a Arc<CustomPropertiesMap>>,
     reverted: PrecomputedHashMap<&'a Name, (CascadePriority, bool)>,
-    device: &'a Device,
+    stylist: &'a Stylist,
+    computed_context: &'a computed::Context<'b>,
 }
 
-impl<'a> CustomPropertiesBuilder<'a> {
+impl<'a, 'b: 'a> CustomPropertiesBuilder<'a, 'b> {
     /// Create a new builder, inheriting from a given custom properties map.
-    pub fn new(inherited: Option<&'a Arc<CustomPropertiesMap>>, device: &'a Device) -> Self {
+    ///
+    /// The values of registered custom properties are computed with
+    /// `computed_context`, the context of the element being styled.
+    pub fn new(
+        inherited: Option<&'a Arc<CustomPropertiesMap>>,
+        stylist: &'a Stylist,
+        computed_context: &'a computed::Context<'b>,
+    ) -> Self {
         Self {
             seen: PrecomputedHashSet::default(),
             reverted: Default::default(),
             may_have_cycles: false,
             custom_properties: None,
             inherited,
-            device,
+            stylist,
+            computed_context,
         }
     }
 
@@ -662,6 +761,7 @@ impl<'a> CustomPropertiesBuilder<'a> {
             });
         }
 
+        let registration = self.stylist.get_custom_property_registration(name);
         let map = self.custom_properties.as_mut().unwrap();
         match *value {
             CustomDeclarationValue::Value(ref unparsed_value) => {
@@ -672,17 +772,47 @@ impl<'a> CustomPropertiesBuilder<'a> {
                 // environment variable here, perform substitution here instead
                 // of forcing a full traversal in `substitute_all` afterwards.
                 let value = if !has_references && unparsed_value.references_environment {
-                    let result = substitute_references_in_value(unparsed_value, &map, &self.device);
+                    let result =
+                        substitute_references_in_value(unparsed_value, &map, self.stylist.device());
                     match result {
                         Ok(new_value) => new_value,
                         Err(..) => {
-                            map.remove(name);
+                            handle_invalid_at_computed_value_time(
+                                name,
+                                map,
+                                self.inherited,
+                                registration,
+                            );
                             return;
                         },
                     }
                 } else {
                     (*unparsed_value).clone()
                 };
+
+                // Values of typed registered properties without references
+                // can be computed right away, otherwise it's done after
+                // substitution.
+                let value = match registration {
+                    Some(registration) if registration.is_typed() && !has_references => {
+                        match registration
+                            .compute(&value, self.computed_context)
+                            .and_then(|computed| computed.to_variable_value())
+                        {
+                            Ok(value) => value,
+                            Err(()) => {
+                                handle_invalid_at_computed_value_time(
+                                    name,
+                                    map,
+                                    self.inherited,
+                                    Some(registration),
+                                );
+                                return;
+                            },
+                        }
+                    },
+                    _ => value,
+                };
                 map.insert(name.clone(), value);
             },
             CustomDeclarationValue::CSSWideKeyword(keyword) => match keyword {
@@ -692,18 +822,31 @@ impl<'a> CustomPropertiesBuilder<'a> {
                     self.reverted.insert(name, (priority, origin_revert));
                 },
                 CSSWideKeyword::Initial => {
-                    map.remove(name);
+                    let initial_value = registration.and_then(|r| r.initial_value.as_ref());
+                    insert_or_remove(map, name, initial_value);
+                },
+                // Only non-inherited registered properties get here, see
+                // value_may_affect_style.
+                CSSWideKeyword::Inherit => {
+                    let registration = registration.unwrap();
+                    let inherited = self.inherited.and_then(|m| m.get(name));
+                    insert_or_remove(map, name, inherited.or(registration.initial_value.as_ref()));
+                },
+                CSSWideKeyword::Unset => {
+                    let registration = registration.unwrap();
+                    insert_or_remove(map, name, registration.initial_value.as_ref());
                 },
-                // handled in value_may_affect_style
-                CSSWideKeyword::Unset | CSSWideKeyword::Inherit => unreachable!(),
             },
         }
     }
 
     fn value_may_affect_style(&self, name: &Name, value: &CustomDeclarationValue) -> bool {
+        let registration = self.stylist.get_custom_property_registration(name);
         match *value {
             CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Unset) |
-            CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Inherit) => {
+            CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Inherit)
+                if registration.map_or(true, |r| r.inherits) =>
+            {
                 // Custom properties are inherited by default. So
                 // explicit 'inherit' or 'unset' means we can just use
                 // any existing value in the inherited CustomPropertiesMap.
@@ -719,7 +862,9 @@ impl<'a> CustomPropertiesBuilder<'a> {
             .or_else(|| self.inherited.and_then(|m| m.get(name)));
 
         match (existing_value, value) {
-            (None, &CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Initial)) => {
+            (None, &CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Initial))
+                if registration.map_or(true, |r| r.initial_value.is_none()) =>
+            {
                 // The initial value of a custom property is the same as it
                 // not existing in the map.
                 return false;
@@ -753,6 +898,40 @@ impl<'a> CustomPropertiesBuilder<'a> {
         true
     }
 
+    /// Gives registered properties that weren't specified their initial
+    /// value, if they're not inherited or there's nothing to inherit.
+    ///
+    /// Inherited properties only need this at the root, since the initial
+    /// values are inherited from there, and non-inherited ones only if the
+    /// parent had another value, so the map is only copied if one of those
+    /// changes.
+    fn apply_initial_values_of_registered_properties(&mut self) {
+        for (name, registration) in self.stylist.custom_property_registrations() {
+            if registration.inherits && self.inherited.is_some() {
+                continue;
+            }
+            if self.seen.contains(&name) {
+                continue;
+            }
+            let existing_value = match self.custom_properties {
+                Some(ref map) => map.get(name),
+                None => self.inherited.and_then(|m| m.get(name)),
+            };
+            let initial_value = registration.initial_value.as_ref();
+            if existing_value == initial_value {
+                continue;
+            }
+            let inherited = self.inherited;
+            let map = self.custom_properties.get_or_insert_with(|| match inherited {
+                Some(inherited) => (**inherited).clone(),
+                None => CustomPropertiesMap::default(),
+            });
+            insert_or_remove(map, name, initial_value);
+            // Make sure we don't reuse the inherited map.
+            self.seen.insert(name);
+        }
+    }
+
     /// Returns the final map of applicable custom properties.
     ///
     /// If there was any specified property, we've created a new map and now we
@@ -760,13 +939,21 @@ impl<'a> CustomPropertiesBuilder<'a> {
     ///
     /// Otherwise, just use the inherited custom properties map.
     pub fn build(mut self) -> Option<Arc<CustomPropertiesMap>> {
+        self.apply_initial_values_of_registered_properties();
+
         let mut map = match self.custom_properties.take() {
             Some(m) => m,
             None => return self.inherited.cloned(),
         };
 
         if self.may_have_cycles {
-            substitute_all(&mut map, &self.seen, self.device);
+            substitute_all(
+                &mut map,
+                &self.seen,
+                self.inherited,
+                self.stylist,
+                self.computed_context,
+            );
         }
 
         // Some pages apply a lot of redundant custom properties, see e.g.
@@ -782,6 +969,52 @@ impl<'a> CustomPropertiesBuilder<'a> {
     }
 }
 
+/// Sets the value of the given custom property, or removes it from the map if
+/// there's no value.
+fn insert_or_remove(
+    map: &mut CustomPropertiesMap,
+    name: &Name,
+    value: Option<&Arc<VariableValue>>,
+) {
+    match value {
+        Some(value) => {
+            map.insert(name.clone(), value.clone());
+        },
+        None => {
+            map.remove(name);
+        },
+    }
+}
+
+/// Handles a custom property whose value is invalid at computed-value time,
+/// either because substitution failed, it's part of a cycle, or it doesn't
+/// match the syntax of its registration.
+///
+/// Unregistered properties get the guaranteed-invalid value. Registered ones
+/// behave as `unset`.
+///
+/// <https://drafts.csswg.org/css-variables/#invalid-at-computed-value-time>
+fn handle_invalid_at_computed_value_time(
+    name: &Name,
+    map: &mut CustomPropertiesMap,
+    inherited: Option<&Arc<CustomPropertiesMap>>,
+    registration: Option<&PropertyRegistration>,
+) {
+    let registration = match registration {
+        Some(registration) => registration,
+        None => {
+            map.remove(name);
+            return;
+        },
+    };
+    let inherited_value = if registration.inherits {
+        inherited.and_then(|m| m.get(name))
+    } else {
+        None
+    };
+    insert_or_remove(map, name, inherited_value.or(registration.initial_value.as_ref()));
+}
+
 /// Resolve all custom properties to either substituted, invalid, or unset
 /// (meaning we should use the inherited value).
 ///
@@ -789,7 +1022,9 @@ impl<'a> CustomPropertiesBuilder<'a> {
 fn substitute_all(
     custom_properties_map: &mut CustomPropertiesMap,
     seen: &PrecomputedHashSet<&Name>,
-    device: &Device,
+    inherited: Option<&Arc<CustomPropertiesMap>>,
+    stylist: &Stylist,
+    computed_context: &computed::Context,
 ) {
     // The cycle dependencies removal in this function is a variant
     // of Tarjan's algorithm. It is mostly based on the pseudo-code
@@ -813,8 +1048,7 @@ fn substitute_all(
     }
     /// Context struct for traversing the variable graph, so that we can
     /// avoid referencing all the fields multiple times.
-    #[derive(Debug)]
-    struct Context<'a> {
+    struct Context<'a, 'b: 'a> {
         /// Number of variables visited. This is used as the order index
         /// when we visit a new unresolved variable.
         count: usize,
@@ -826,8 +1060,14 @@ fn substitute_all(
         /// all unfinished strong connected components.
         stack: SmallVec<[usize; 5]>,
         map: &'a mut CustomPropertiesMap,
-        /// To resolve the environment to substitute `env()` variables.
-        device: &'a Device,
+        /// The inherited custom properties, for registered properties that
+        /// are invalid at computed-value time.
+        inherited: Option<&'a Arc<CustomPropertiesMap>>,
+        /// To resolve the environment to substitute `env()` variables, and to
+        /// look up registered properties.
+        stylist: &'a Stylist,
+        /// To compute the values of registered properties.
+        computed_context: &'a computed::Context<'b>,
     }
 
     /// This function combines the traversal for cycle removal and value
@@ -848,7 +1088,7 @@ fn substitute_all(
     ///   doesn't have reference at all in specified value, or it has
     ///   been completely resolved.
     /// * There is no such variable at all.
-    fn traverse<'a>(name: &Name, context: &mut Context<'a>) -> Option<usize> {
+    fn traverse<'a, 'b>(name: &Name, context: &mut Context<'a, 'b>) -> Option<usize> {
         // Some shortcut checks.
         let (name, value) = {
             let value = context.map.get(name)?;
@@ -948,28 +1188,38 @@ fn substitute_all(
                 break;
             }
             // Anything here is in a loop which can traverse to the
-            // variable we are handling, so remove it from the map, it's invalid
-            // at computed-value time.
-            context.map.remove(&var_name);
+            // variable we are handling, so it's invalid at computed-value
+            // time.
+            handle_invalid(&var_name, context);
             in_loop = true;
         }
         if in_loop {
             // This variable is in loop. Resolve to invalid.
-            context.map.remove(&name);
+            handle_invalid(&name, context);
             return None;
         }
 
         // Now we have shown that this variable is not in a loop, and all of its
         // dependencies should have been resolved. We can start substitution
         // now.
-        let result = substitute_references_in_value(&value, &context.map, &context.device);
+        let device = context.stylist.device();
+        let registration = context.stylist.get_custom_property_registration(&name);
+        let result = substitute_references_in_value(&value, &context.map, device)
+            .map_err(|_| ())
+            .and_then(|substituted| match registration {
+                Some(registration) if registration.is_typed() => registration
+                    .compute(&substituted, context.computed_context)
+                    .and_then(|computed| computed.to_variable_value()),
+                _ => Ok(substituted),
+            });
         match result {
             Ok(computed_value) => {
                 context.map.insert(name, computed_value);
             },
             Err(..) => {
-                // This is invalid, reset it to the guaranteed-invalid value.
-                context.map.remove(&name);
+                // This is invalid, reset it to the guaranteed-invalid value,
+                // or the unset value for registered properties.
+                handle_invalid(&name, context);
             },
         }
 
@@ -977,6 +1227,11 @@ fn substitute_all(
         None
     }
 
+    fn handle_invalid(name: &Name, context: &mut Context) {
+        let registration = context.stylist.get_custom_property_registration(name);
+        handle_invalid_at_computed_value_time(name, context.map, context.inherited, registration);
+    }
+
     // Note that `seen` doesn't contain names inherited from our parent, but
     // those can't have variable references (since we inherit the computed
     // variables) so we don't want to spend cycles traversing them anyway.
@@ -987,7 +1242,9 @@ fn substitute_all(
             stack: SmallVec::new(),
             var_info: SmallVec::new(),
             map: custom_properties_map,
-            device,
+            inherited,
+            stylist,
+            computed_context,
         };
         traverse(name, &mut context);
     }
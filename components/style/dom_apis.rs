@@ -12,7 +12,9 @@ use crate::invalidation::element::invalidator::{DescendantInvalidationLists, Inv
 use crate::invalidation::element::invalidator::{InvalidationProcessor, InvalidationVector};
 use crate::values::AtomIdent;
 use selectors::attr::CaseSensitivity;
-use selectors::matching::{self, MatchingContext, MatchingMode, NeedsSelectorFlags};
+use selectors::matching::{
+    self, MatchingContext, MatchingForInvalidation, MatchingMode, NeedsSelectorFlags,
+};
 use selectors::parser::{Combinator, Component, LocalName, SelectorImpl};
 use selectors::{Element, SelectorList};
 use smallvec::SmallVec;
@@ -26,14 +28,15 @@ pub fn element_matches<E>(
 where
     E: Element,
 {
-    let mut nth_index_cache = Default::default();
+    let mut selector_caches = Default::default();
 
     let mut context = MatchingContext::new(
         MatchingMode::Normal,
         None,
-        &mut nth_index_cache,
+        &mut selector_caches,
         quirks_mode,
         NeedsSelectorFlags::No,
+        MatchingForInvalidation::No,
     );
     context.scope_element = Some(element.opaque());
     context.current_host = element.containing_shadow_host().map(|e| e.opaque());
@@ -49,14 +52,15 @@ pub fn element_closest<E>(
 where
     E: Element,
 {
-    let mut nth_index_cache = Default::default();
+    let mut selector_caches = Default::default();
 
     let mut context = MatchingContext::new(
         MatchingMode::Normal,
         None,
-        &mut nth_index_cache,
+        &mut selector_caches,
         quirks_mode,
         NeedsSelectorFlags::No,
+        MatchingForInvalidation::No,
     );
     context.scope_element = Some(element.opaque());
     context.current_host = element.containing_shadow_host().map(|e| e.opaque());
@@ -623,15 +627,16 @@ pub fn query_selector<E, Q>(
 {
     use crate::invalidation::element::invalidator::TreeStyleInvalidator;
 
-    let mut nth_index_cache = Default::default();
+    let mut selector_caches = Default::default();
     let quirks_mode = root.owner_doc().quirks_mode();
 
     let mut matching_context = MatchingContext::new(
         MatchingMode::Normal,
         None,
-        &mut nth_index_cache,
+        &mut selector_caches,
         quirks_mode,
         NeedsSelectorFlags::No,
+        MatchingForInvalidation::No,
     );
     let root_element = root.as_element();
     matching_context.scope_element = root_element.map(|e| e.opaque());
@@ -8,10 +8,16 @@
 
 use crate::selector_parser::SelectorImpl;
 use crate::stylesheets::UrlExtraData;
+#[cfg(feature = "servo")]
+use cssparser::ToCss;
 use cssparser::{BasicParseErrorKind, ParseErrorKind, SourceLocation, Token};
+use selectors::parser::SelectorParseErrorKind;
 use selectors::SelectorList;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use std::fmt;
-use style_traits::ParseError;
+#[cfg(feature = "servo")]
+use std::sync::Mutex;
+use style_traits::{ParseError, StyleParseErrorKind, ValueParseErrorKind};
 
 /// Errors that can be encountered while parsing CSS.
 #[derive(Debug)]
@@ -28,6 +34,8 @@ pub enum ContextualParseError<'a> {
     UnsupportedFontFeatureValuesDescriptor(&'a str, ParseError<'a>),
     /// A font palette values descriptor was not recognized.
     UnsupportedFontPaletteValuesDescriptor(&'a str, ParseError<'a>),
+    /// A property descriptor was not recognized.
+    UnsupportedPropertyDescriptor(&'a str, ParseError<'a>),
     /// A keyframe rule was not valid.
     InvalidKeyframeRule(&'a str, ParseError<'a>),
     /// A font feature values rule was not valid.
@@ -159,6 +167,14 @@ impl<'a> fmt::Display for ContextualParseError<'a> {
                 )?;
                 parse_error_to_str(err, f)
             },
+            ContextualParseError::UnsupportedPropertyDescriptor(decl, ref err) => {
+                write!(
+                    f,
+                    "Unsupported @property descriptor declaration: '{}', ",
+                    decl
+                )?;
+                parse_error_to_str(err, f)
+            },
             ContextualParseError::InvalidKeyframeRule(rule, ref err) => {
                 write!(f, "Invalid keyframe rule: '{}', ", rule)?;
                 parse_error_to_str(err, f)
@@ -229,6 +245,333 @@ impl<'a> fmt::Display for ContextualParseError<'a> {
     }
 }
 
+/// How serious a reported error is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The input was valid, but is likely not what the author intended.
+    Warning,
+    /// The input was invalid and has been dropped.
+    Error,
+}
+
+impl<'a> ContextualParseError<'a> {
+    /// Returns a stable identifier for this kind of error, suitable for
+    /// tooling to match on.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ContextualParseError::UnsupportedPropertyDeclaration(..) => {
+                "unsupported-property-declaration"
+            },
+            ContextualParseError::UnsupportedFontFaceDescriptor(..) => {
+                "unsupported-font-face-descriptor"
+            },
+            ContextualParseError::UnsupportedFontFeatureValuesDescriptor(..) => {
+                "unsupported-font-feature-values-descriptor"
+            },
+            ContextualParseError::UnsupportedFontPaletteValuesDescriptor(..) => {
+                "unsupported-font-palette-values-descriptor"
+            },
+            ContextualParseError::UnsupportedPropertyDescriptor(..) => {
+                "unsupported-property-descriptor"
+            },
+            ContextualParseError::InvalidKeyframeRule(..) => "invalid-keyframe-rule",
+            ContextualParseError::InvalidFontFeatureValuesRule(..) => {
+                "invalid-font-feature-values-rule"
+            },
+            ContextualParseError::UnsupportedKeyframePropertyDeclaration(..) => {
+                "unsupported-keyframe-property-declaration"
+            },
+            ContextualParseError::InvalidRule(..) => "invalid-rule",
+            ContextualParseError::UnsupportedRule(..) => "unsupported-rule",
+            ContextualParseError::UnsupportedViewportDescriptorDeclaration(..) => {
+                "unsupported-viewport-descriptor-declaration"
+            },
+            ContextualParseError::UnsupportedCounterStyleDescriptorDeclaration(..) => {
+                "unsupported-counter-style-descriptor-declaration"
+            },
+            ContextualParseError::InvalidCounterStyleWithoutSymbols(..) => {
+                "invalid-counter-style-without-symbols"
+            },
+            ContextualParseError::InvalidCounterStyleNotEnoughSymbols(..) => {
+                "invalid-counter-style-not-enough-symbols"
+            },
+            ContextualParseError::InvalidCounterStyleWithoutAdditiveSymbols => {
+                "invalid-counter-style-without-additive-symbols"
+            },
+            ContextualParseError::InvalidCounterStyleExtendsWithSymbols => {
+                "invalid-counter-style-extends-with-symbols"
+            },
+            ContextualParseError::InvalidCounterStyleExtendsWithAdditiveSymbols => {
+                "invalid-counter-style-extends-with-additive-symbols"
+            },
+            ContextualParseError::InvalidMediaRule(..) => "invalid-media-rule",
+            ContextualParseError::UnsupportedValue(..) => "unsupported-value",
+            ContextualParseError::NeverMatchingHostSelector(..) => "never-matching-host-selector",
+        }
+    }
+
+    /// Returns how serious this error is.
+    pub fn severity(&self) -> Severity {
+        match *self {
+            // The selector is valid, it just can't ever match.
+            ContextualParseError::NeverMatchingHostSelector(..) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Returns the underlying parse error, if any.
+    pub fn parse_error(&self) -> Option<&ParseError<'a>> {
+        match *self {
+            ContextualParseError::UnsupportedPropertyDeclaration(_, ref err, _) |
+            ContextualParseError::UnsupportedFontFaceDescriptor(_, ref err) |
+            ContextualParseError::UnsupportedFontFeatureValuesDescriptor(_, ref err) |
+            ContextualParseError::UnsupportedFontPaletteValuesDescriptor(_, ref err) |
+            ContextualParseError::UnsupportedPropertyDescriptor(_, ref err) |
+            ContextualParseError::InvalidKeyframeRule(_, ref err) |
+            ContextualParseError::InvalidFontFeatureValuesRule(_, ref err) |
+            ContextualParseError::UnsupportedKeyframePropertyDeclaration(_, ref err) |
+            ContextualParseError::InvalidRule(_, ref err) |
+            ContextualParseError::UnsupportedRule(_, ref err) |
+            ContextualParseError::UnsupportedViewportDescriptorDeclaration(_, ref err) |
+            ContextualParseError::UnsupportedCounterStyleDescriptorDeclaration(_, ref err) |
+            ContextualParseError::InvalidMediaRule(_, ref err) |
+            ContextualParseError::UnsupportedValue(_, ref err) => Some(err),
+            ContextualParseError::InvalidCounterStyleWithoutSymbols(..) |
+            ContextualParseError::InvalidCounterStyleNotEnoughSymbols(..) |
+            ContextualParseError::InvalidCounterStyleWithoutAdditiveSymbols |
+            ContextualParseError::InvalidCounterStyleExtendsWithSymbols |
+            ContextualParseError::InvalidCounterStyleExtendsWithAdditiveSymbols |
+            ContextualParseError::NeverMatchingHostSelector(..) => None,
+        }
+    }
+
+    /// Returns the offending source text: the declaration, rule, or value
+    /// that was dropped, or the selector or counter style system the error
+    /// is about.
+    pub fn source_text(&self) -> Option<&str> {
+        match *self {
+            ContextualParseError::UnsupportedPropertyDeclaration(text, ..) |
+            ContextualParseError::UnsupportedFontFaceDescriptor(text, _) |
+            ContextualParseError::UnsupportedFontFeatureValuesDescriptor(text, _) |
+            ContextualParseError::UnsupportedFontPaletteValuesDescriptor(text, _) |
+            ContextualParseError::UnsupportedPropertyDescriptor(text, _) |
+            ContextualParseError::InvalidKeyframeRule(text, _) |
+            ContextualParseError::InvalidFontFeatureValuesRule(text, _) |
+            ContextualParseError::UnsupportedKeyframePropertyDeclaration(text, _) |
+            ContextualParseError::InvalidRule(text, _) |
+            ContextualParseError::UnsupportedRule(text, _) |
+            ContextualParseError::UnsupportedViewportDescriptorDeclaration(text, _) |
+            ContextualParseError::UnsupportedCounterStyleDescriptorDeclaration(text, _) |
+            ContextualParseError::InvalidMediaRule(text, _) |
+            ContextualParseError::UnsupportedValue(text, _) => Some(text),
+            ContextualParseError::InvalidCounterStyleWithoutSymbols(ref text) |
+            ContextualParseError::InvalidCounterStyleNotEnoughSymbols(ref text) |
+            ContextualParseError::NeverMatchingHostSelector(ref text) => Some(text),
+            ContextualParseError::InvalidCounterStyleWithoutAdditiveSymbols |
+            ContextualParseError::InvalidCounterStyleExtendsWithSymbols |
+            ContextualParseError::InvalidCounterStyleExtendsWithAdditiveSymbols => None,
+        }
+    }
+
+    /// Returns the selectors of the style rule containing an unsupported
+    /// property declaration, if any.
+    pub fn selectors(&self) -> Option<&'a SelectorList<SelectorImpl>> {
+        match *self {
+            ContextualParseError::UnsupportedPropertyDeclaration(_, _, selectors) => selectors,
+            _ => None,
+        }
+    }
+
+    /// Returns a stable identifier for the kind of the underlying parse error,
+    /// if any.
+    pub fn reason(&self) -> Option<&'static str> {
+        self.parse_error().map(parse_error_code)
+    }
+}
+
+/// Returns a stable identifier for the kind of a parse error.
+pub fn parse_error_code(err: &ParseError) -> &'static str {
+    match err.kind {
+        ParseErrorKind::Basic(ref kind) => match *kind {
+            BasicParseErrorKind::UnexpectedToken(..) => "unexpected-token",
+            BasicParseErrorKind::EndOfInput => "end-of-input",
+            BasicParseErrorKind::AtRuleInvalid(..) => "at-rule-invalid",
+            BasicParseErrorKind::AtRuleBodyInvalid => "at-rule-body-invalid",
+            BasicParseErrorKind::QualifiedRuleInvalid => "qualified-rule-invalid",
+        },
+        ParseErrorKind::Custom(ref kind) => match *kind {
+            StyleParseErrorKind::BadUrlInDeclarationValueBlock(..) => {
+                "bad-url-in-declaration-value-block"
+            },
+            StyleParseErrorKind::BadStringInDeclarationValueBlock(..) => {
+                "bad-string-in-declaration-value-block"
+            },
+            StyleParseErrorKind::UnbalancedCloseParenthesisInDeclarationValueBlock => {
+                "unbalanced-close-parenthesis-in-declaration-value-block"
+            },
+            StyleParseErrorKind::UnbalancedCloseSquareBracketInDeclarationValueBlock => {
+                "unbalanced-close-square-bracket-in-declaration-value-block"
+            },
+            StyleParseErrorKind::UnbalancedCloseCurlyBracketInDeclarationValueBlock => {
+                "unbalanced-close-curly-bracket-in-declaration-value-block"
+            },
+            StyleParseErrorKind::PropertyDeclarationValueNotExhausted => {
+                "property-declaration-value-not-exhausted"
+            },
+            StyleParseErrorKind::UnexpectedDimension(..) => "unexpected-dimension",
+            StyleParseErrorKind::MediaQueryExpectedFeatureName(..) => {
+                "media-query-expected-feature-name"
+            },
+            StyleParseErrorKind::MediaQueryExpectedFeatureValue => {
+                "media-query-expected-feature-value"
+            },
+            StyleParseErrorKind::MediaQueryUnexpectedOperator => "media-query-unexpected-operator",
+            StyleParseErrorKind::RangedExpressionWithNoValue => "ranged-expression-with-no-value",
+            StyleParseErrorKind::UnexpectedFunction(..) => "unexpected-function",
+            StyleParseErrorKind::UnexpectedNamespaceRule => "unexpected-namespace-rule",
+            StyleParseErrorKind::UnexpectedImportRule => "unexpected-import-rule",
+            StyleParseErrorKind::DisallowedImportRule => "disallowed-import-rule",
+            StyleParseErrorKind::UnexpectedCharsetRule => "unexpected-charset-rule",
+            StyleParseErrorKind::UnsupportedAtRule(..) => "unsupported-at-rule",
+            StyleParseErrorKind::UnspecifiedError => "unspecified-error",
+            StyleParseErrorKind::UnexpectedTokenWithinNamespace(..) => {
+                "unexpected-token-within-namespace"
+            },
+            StyleParseErrorKind::ValueError(ref kind) => match *kind {
+                ValueParseErrorKind::InvalidColor(..) => "invalid-color",
+                ValueParseErrorKind::InvalidFilter(..) => "invalid-filter",
+            },
+            StyleParseErrorKind::SelectorError(ref kind) => selector_parse_error_code(kind),
+            StyleParseErrorKind::UnknownProperty(..) => "unknown-property",
+            StyleParseErrorKind::ExperimentalProperty => "experimental-property",
+            StyleParseErrorKind::InvalidColor(..) => "invalid-color",
+            StyleParseErrorKind::InvalidFilter(..) => "invalid-filter",
+            StyleParseErrorKind::OtherInvalidValue(..) => "invalid-value",
+            StyleParseErrorKind::AnimationPropertyInKeyframeBlock => {
+                "animation-property-in-keyframe-block"
+            },
+            StyleParseErrorKind::NotAllowedInPageRule => "not-allowed-in-page-rule",
+        },
+    }
+}
+
+fn selector_parse_error_code(kind: &SelectorParseErrorKind) -> &'static str {
+    match *kind {
+        SelectorParseErrorKind::NoQualifiedNameInAttributeSelector(..) => {
+            "selector-no-qualified-name-in-attribute-selector"
+        },
+        SelectorParseErrorKind::EmptySelector => "selector-empty",
+        SelectorParseErrorKind::DanglingCombinator => "selector-dangling-combinator",
+        SelectorParseErrorKind::NonCompoundSelector => "selector-non-compound",
+        SelectorParseErrorKind::NonPseudoElementAfterSlotted => {
+            "selector-non-pseudo-element-after-slotted"
+        },
+        SelectorParseErrorKind::InvalidPseudoElementAfterSlotted => {
+            "selector-invalid-pseudo-element-after-slotted"
+        },
+        SelectorParseErrorKind::InvalidPseudoElementInsideWhere => {
+            "selector-invalid-pseudo-element-inside-where"
+        },
+        SelectorParseErrorKind::InvalidState => "selector-invalid-state",
+        SelectorParseErrorKind::UnexpectedTokenInAttributeSelector(..) => {
+            "selector-unexpected-token-in-attribute-selector"
+        },
+        SelectorParseErrorKind::PseudoElementExpectedColon(..) => {
+            "selector-pseudo-element-expected-colon"
+        },
+        SelectorParseErrorKind::PseudoElementExpectedIdent(..) => {
+            "selector-pseudo-element-expected-ident"
+        },
+        SelectorParseErrorKind::NoIdentForPseudo(..) => "selector-no-ident-for-pseudo",
+        SelectorParseErrorKind::UnsupportedPseudoClassOrElement(..) => {
+            "selector-unsupported-pseudo-class-or-element"
+        },
+        SelectorParseErrorKind::UnexpectedIdent(..) => "selector-unexpected-ident",
+        SelectorParseErrorKind::ExpectedNamespace(..) => "selector-expected-namespace",
+        SelectorParseErrorKind::ExpectedBarInAttr(..) => "selector-expected-bar-in-attribute",
+        SelectorParseErrorKind::BadValueInAttr(..) => "selector-bad-value-in-attribute",
+        SelectorParseErrorKind::InvalidQualNameInAttr(..) => {
+            "selector-invalid-qualified-name-in-attribute"
+        },
+        SelectorParseErrorKind::ExplicitNamespaceUnexpectedToken(..) => {
+            "selector-explicit-namespace-unexpected-token"
+        },
+        SelectorParseErrorKind::ClassNeedsIdent(..) => "selector-class-needs-ident",
+    }
+}
+
+/// A range of source text. Lines and columns are counted the same way as in
+/// `SourceLocation`, i.e. columns are one-based and in UTF-16 code units.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SourceRange {
+    /// Where the range starts.
+    pub start: SourceLocation,
+    /// Where the range ends (exclusive).
+    pub end: SourceLocation,
+}
+
+impl SourceRange {
+    /// Returns an empty range at `location`.
+    pub fn at(location: SourceLocation) -> Self {
+        SourceRange {
+            start: location,
+            end: location,
+        }
+    }
+
+    /// Returns the range that `text` spans within `source`, where `first_line`
+    /// is the line number of the start of `source`.
+    ///
+    /// Returns `None` if `text` is not a slice of `source`.
+    pub fn of_slice(source: &str, text: &str, first_line: u32) -> Option<Self> {
+        let source_start = source.as_ptr() as usize;
+        let text_start = text.as_ptr() as usize;
+        if text_start < source_start || text_start + text.len() > source_start + source.len() {
+            return None;
+        }
+        let origin = SourceLocation {
+            line: first_line,
+            column: 1,
+        };
+        let start = advance(origin, &source[..text_start - source_start]);
+        Some(SourceRange {
+            start,
+            end: advance(start, text),
+        })
+    }
+}
+
+/// Returns the location right after `text`, if it starts at `location`.
+fn advance(mut location: SourceLocation, text: &str) -> SourceLocation {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {},
+            '\n' | '\r' | '\x0C' => {
+                location.line += 1;
+                location.column = 1;
+            },
+            _ => location.column += c.len_utf16() as u32,
+        }
+    }
+    location
+}
+
+impl Serialize for SourceRange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut range = serializer.serialize_struct("SourceRange", 4)?;
+        range.serialize_field("start_line", &self.start.line)?;
+        range.serialize_field("start_column", &self.start.column)?;
+        range.serialize_field("end_line", &self.end.line)?;
+        range.serialize_field("end_column", &self.end.column)?;
+        range.end()
+    }
+}
+
 /// A generic trait for an error reporter.
 pub trait ParseErrorReporter {
     /// Called when the style engine detects an error.
@@ -271,3 +614,121 @@ impl ParseErrorReporter for RustLogReporter {
         }
     }
 }
+
+/// A structured form of a reported error, meant to be consumed by tooling.
+///
+/// Serializing it (for example to JSON) gives a stable, machine-readable
+/// representation of the error.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug, Serialize)]
+pub struct Diagnostic {
+    /// A stable identifier for the kind of error, see
+    /// `ContextualParseError::code`.
+    pub code: &'static str,
+    /// A stable identifier for the underlying parse error, see
+    /// `parse_error_code`.
+    pub reason: Option<&'static str>,
+    /// How serious the error is.
+    pub severity: Severity,
+    /// The URL of the style sheet.
+    pub url: String,
+    /// The line the error was reported at.
+    pub line: u32,
+    /// The column the error was reported at.
+    pub column: u32,
+    /// The source range of the offending text, if known. Otherwise this is
+    /// an empty range at the reported line and column.
+    pub range: SourceRange,
+    /// The offending declaration, rule, value or selector text.
+    pub source: Option<String>,
+    /// The selectors of the style rule containing an unsupported property
+    /// declaration.
+    pub selectors: Option<String>,
+    /// A human-readable description of the error.
+    pub message: String,
+}
+
+#[cfg(feature = "servo")]
+impl Diagnostic {
+    /// Creates a diagnostic for the given error, which spans `range`.
+    pub fn new(
+        url: &UrlExtraData,
+        location: SourceLocation,
+        range: SourceRange,
+        error: &ContextualParseError,
+    ) -> Self {
+        Diagnostic {
+            code: error.code(),
+            reason: error.reason(),
+            severity: error.severity(),
+            url: url.as_str().to_owned(),
+            line: location.line,
+            column: location.column,
+            range,
+            source: error.source_text().map(|s| s.to_owned()),
+            selectors: error.selectors().map(|s| s.to_css_string()),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// An error reporter that keeps every error as a `Diagnostic`, so they can be
+/// inspected after parsing, e.g. after `Stylesheet::from_str`.
+///
+/// If it is given the style sheet source, the diagnostics include the exact
+/// range of the offending text.
+#[cfg(feature = "servo")]
+#[derive(Debug, Default)]
+pub struct CollectingReporter<'a> {
+    source: Option<(&'a str, u32)>,
+    diagnostics: Mutex<Vec<Diagnostic>>,
+}
+
+#[cfg(feature = "servo")]
+impl CollectingReporter<'static> {
+    /// Creates a reporter with no diagnostics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "servo")]
+impl<'a> CollectingReporter<'a> {
+    /// Creates a reporter for errors in `source`, which is parsed with the
+    /// given line number offset.
+    pub fn with_source(source: &'a str, line_number_offset: u32) -> Self {
+        CollectingReporter {
+            source: Some((source, line_number_offset)),
+            diagnostics: Mutex::new(vec![]),
+        }
+    }
+
+    /// Returns the diagnostics reported so far, leaving the reporter empty.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.diagnostics.lock().unwrap())
+    }
+
+    /// Returns all the reported diagnostics, in the order they were reported.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics.into_inner().unwrap()
+    }
+}
+
+#[cfg(feature = "servo")]
+impl<'a> ParseErrorReporter for CollectingReporter<'a> {
+    fn report_error(
+        &self,
+        url: &UrlExtraData,
+        location: SourceLocation,
+        error: ContextualParseError,
+    ) {
+        let range = self
+            .source
+            .and_then(|(source, first_line)| {
+                SourceRange::of_slice(source, error.source_text()?, first_line)
+            })
+            .unwrap_or_else(|| SourceRange::at(location));
+        let diagnostic = Diagnostic::new(url, location, range, &error);
+        self.diagnostics.lock().unwrap().push(diagnostic);
+    }
+}
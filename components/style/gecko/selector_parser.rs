@@ -12,6 +12,7 @@ use crate::properties::ComputedValues;
 use crate::selector_parser::{Direction, HorizontalDirection, SelectorParser};
 use crate::str::starts_with_ignore_ascii_case;
 use crate::string_cache::{Atom, Namespace, WeakAtom, WeakNamespace};
+use crate::stylist::ScopeRootsCache;
 use crate::values::{AtomIdent, AtomString};
 use cssparser::{BasicParseError, BasicParseErrorKind, Parser};
 use cssparser::{CowRcStr, SourceLocation, ToCss, Token};
@@ -246,6 +247,10 @@ pub struct ExtraMatchingData<'a> {
     /// The style of the originating element in order to evaluate @container
     /// size queries affecting pseudo-elements.
     pub originating_element_style: Option<&'a ComputedValues>,
+
+    /// The scoping roots of the elements being matched against rules inside
+    /// scope rules.
+    pub scope_roots: ScopeRootsCache,
 }
 
 impl ::selectors::SelectorImpl for SelectorImpl {
@@ -338,6 +343,11 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
         true
     }
 
+    #[inline]
+    fn parse_parent_selector(&self) -> bool {
+        static_prefs::pref!("layout.css.nesting.enabled")
+    }
+
     #[inline]
     fn is_is_alias(&self, function: &str) -> bool {
         function.eq_ignore_ascii_case("-moz-any")
@@ -12,6 +12,7 @@ use crate::values::computed::{Context, ToComputedValue};
 use cssparser::Parser;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use nsstring::nsCString;
+use serde::{Deserialize, Serialize};
 use servo_arc::Arc;
 use std::collections::HashMap;
 use std::fmt::{self, Write};
@@ -359,6 +360,38 @@ impl ToCss for ComputedUrl {
     }
 }
 
+// The extra data of a URL can't be sent to another process, so computed URLs
+// are serialized in their resolved form, which doesn't need it.
+impl Serialize for ComputedUrl {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut spec = nsCString::new();
+        unsafe {
+            bindings::Gecko_GetComputedURLSpec(self, &mut spec);
+        }
+        let spec = unsafe { spec.as_str_unchecked() };
+        (spec, self.0 .0.cors_mode).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ComputedUrl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (spec, cors_mode) = <(String, CorsMode)>::deserialize(deserializer)?;
+        Ok(ComputedUrl(CssUrl(Arc::new(CssUrlData {
+            serialization: spec.into(),
+            // The URL is already resolved, so any base works.
+            extra_data: UrlExtraData::for_user_agent_sheet(0),
+            cors_mode,
+            load_data: LoadDataSource::Owned(LoadData::default()),
+        }))))
+    }
+}
+
 /// The computed value of a CSS image `url()`.
 #[derive(Clone, Debug, Eq, MallocSizeOf, PartialEq)]
 #[repr(transparent)]
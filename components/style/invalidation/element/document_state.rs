@@ -10,11 +10,13 @@ use crate::invalidation::element::invalidator::{DescendantInvalidationLists, Inv
 use crate::invalidation::element::invalidator::{Invalidation, InvalidationProcessor};
 use crate::invalidation::element::state_and_attributes;
 use crate::stylist::CascadeData;
+#[cfg(feature = "servo")]
+use crate::stylist::ScopeRootsCache;
 use dom::DocumentState;
 use selectors::matching::{
-    MatchingContext, MatchingMode, NeedsSelectorFlags, QuirksMode, VisitedHandlingMode,
+    MatchingContext, MatchingForInvalidation, MatchingMode, NeedsSelectorFlags, QuirksMode,
+    SelectorCaches, VisitedHandlingMode,
 };
-use selectors::NthIndexCache;
 
 /// A struct holding the members necessary to invalidate document state
 /// selectors.
@@ -22,6 +24,12 @@ use selectors::NthIndexCache;
 pub struct InvalidationMatchingData {
     /// The document state that has changed, which makes it always match.
     pub document_state: DocumentState,
+
+    /// The scoping roots of the elements being matched against rules inside
+    /// scope rules. This is the extra matching data of Servo, while Gecko
+    /// keeps these along with it in its `ExtraMatchingData`.
+    #[cfg(feature = "servo")]
+    pub scope_roots: ScopeRootsCache,
 }
 
 impl Default for InvalidationMatchingData {
@@ -29,6 +37,8 @@ impl Default for InvalidationMatchingData {
     fn default() -> Self {
         Self {
             document_state: DocumentState::empty(),
+            #[cfg(feature = "servo")]
+            scope_roots: ScopeRootsCache::default(),
         }
     }
 }
@@ -47,16 +57,17 @@ impl<'a, E: TElement, I> DocumentStateInvalidationProcessor<'a, E, I> {
     pub fn new(
         rules: I,
         document_states_changed: DocumentState,
-        nth_index_cache: &'a mut NthIndexCache,
+        selector_caches: &'a mut SelectorCaches,
         quirks_mode: QuirksMode,
     ) -> Self {
         let mut matching_context = MatchingContext::<'a, E::Impl>::new_for_visited(
             MatchingMode::Normal,
             None,
-            nth_index_cache,
+            selector_caches,
             VisitedHandlingMode::AllLinksVisitedAndUnvisited,
             quirks_mode,
             NeedsSelectorFlags::No,
+            MatchingForInvalidation::Yes,
         );
 
         matching_context.extra_data.invalidation_data.document_state = document_states_changed;
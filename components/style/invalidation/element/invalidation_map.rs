@@ -13,7 +13,7 @@ use crate::AllocErr;
 use crate::{Atom, LocalName, Namespace, ShrinkIfNeeded};
 use dom::{DocumentState, ElementState};
 use selectors::attr::NamespaceConstraint;
-use selectors::parser::{Combinator, Component};
+use selectors::parser::{Combinator, Component, RelativeSelector};
 use selectors::parser::{Selector, SelectorIter};
 use selectors::visitor::SelectorVisitor;
 use smallvec::SmallVec;
@@ -64,6 +64,9 @@ pub struct Dependency {
     ///    * One dependency from .bar pointing to C (parent: None)
     ///    * One dependency from .foo pointing to A (parent: None)
     ///
+    /// Dependencies on relative selectors (the arguments of `:has()`) always
+    /// have a parent, pointing to the compound of the outer selector that
+    /// contains the `:has()`.
     pub parent: Option<Box<Dependency>>,
 }
 
@@ -199,6 +202,12 @@ pub struct InvalidationMap {
     /// A map of other attribute affecting selectors.
     pub other_attribute_affecting_selectors:
         PrecomputedHashMap<LocalName, SmallVec<[Dependency; 1]>>,
+    /// A list of dependencies on the rightmost compound of every relative
+    /// selector, which may change its matching when elements are inserted
+    /// into or removed from the tree.
+    ///
+    /// This is only used in relative selector invalidation maps.
+    pub relative_selectors: Vec<Dependency>,
 }
 
 impl InvalidationMap {
@@ -210,6 +219,7 @@ impl InvalidationMap {
             state_affecting_selectors: SelectorMap::new(),
             document_state_selectors: Vec::new(),
             other_attribute_affecting_selectors: PrecomputedHashMap::default(),
+            relative_selectors: Vec::new(),
         }
     }
 
@@ -217,6 +227,7 @@ impl InvalidationMap {
     pub fn len(&self) -> usize {
         self.state_affecting_selectors.len() +
             self.document_state_selectors.len() +
+            self.relative_selectors.len() +
             self.other_attribute_affecting_selectors
                 .iter()
                 .fold(0, |accum, (_, ref v)| accum + v.len()) +
@@ -235,6 +246,7 @@ impl InvalidationMap {
         self.state_affecting_selectors.clear();
         self.document_state_selectors.clear();
         self.other_attribute_affecting_selectors.clear();
+        self.relative_selectors.clear();
     }
 
     /// Shrink the capacity of hash maps if needed.
@@ -247,10 +259,15 @@ impl InvalidationMap {
 
     /// Adds a selector to this `InvalidationMap`.  Returns Err(..) to
     /// signify OOM.
+    ///
+    /// Dependencies on the relative selectors inside `:has()` are added to
+    /// `relative_selector_invalidation_map` instead, since changes to them
+    /// affect elements up the tree rather than down.
     pub fn note_selector(
         &mut self,
         selector: &Selector<SelectorImpl>,
         quirks_mode: QuirksMode,
+        relative_selector_invalidation_map: &mut InvalidationMap,
     ) -> Result<(), AllocErr> {
         debug!("InvalidationMap::note_selector({:?})", selector);
 
@@ -264,6 +281,7 @@ impl InvalidationMap {
                 document_state: &mut document_state,
                 selector,
                 parent_selectors: &mut parent_stack,
+                relative_selector_invalidation_map: Some(relative_selector_invalidation_map),
                 quirks_mode,
                 compound_state: PerCompoundState::new(0),
                 alloc_error: &mut alloc_error,
@@ -326,6 +344,11 @@ struct SelectorDependencyCollector<'a> {
     /// lists.
     parent_selectors: &'a mut SmallVec<[(Selector<SelectorImpl>, usize); 5]>,
 
+    /// The map where dependencies on relative selectors go, or `None` if
+    /// we're already collecting dependencies for a relative selector (since
+    /// `:has()` can't be nested).
+    relative_selector_invalidation_map: Option<&'a mut InvalidationMap>,
+
     /// The quirks mode of the document where we're inserting dependencies.
     quirks_mode: QuirksMode,
 
@@ -399,14 +422,27 @@ impl<'a> SelectorDependencyCollector<'a> {
         true
     }
 
+    /// Adds a dependency on the rightmost compound of the relative selector
+    /// we're visiting, which insertions and removals in the tree may affect.
+    fn add_relative_selector_dependency(&mut self) -> bool {
+        debug_assert!(self.selector.is_relative());
+        let dependency = self.dependency();
+        if let Err(err) = self.map.relative_selectors.try_reserve(1) {
+            *self.alloc_error = Some(err.into());
+            return false;
+        }
+        self.map.relative_selectors.push(dependency);
+        true
+    }
+
     fn dependency(&self) -> Dependency {
         let mut parent = None;
 
         // TODO(emilio): Maybe we should refcount the parent dependencies, or
         // cache them or something.
         for &(ref selector, ref selector_offset) in self.parent_selectors.iter() {
-            debug_assert_ne!(
-                self.compound_state.offset, 0,
+            debug_assert!(
+                self.compound_state.offset != 0 || self.selector.is_relative(),
                 "Shouldn't bother creating nested dependencies for the rightmost compound",
             );
             let new_parent = Dependency {
@@ -459,6 +495,10 @@ impl<'a> SelectorVisitor for SelectorDependencyCollector<'a> {
                 document_state: &mut *self.document_state,
                 selector,
                 parent_selectors: &mut *self.parent_selectors,
+                relative_selector_invalidation_map: self
+                    .relative_selector_invalidation_map
+                    .as_mut()
+                    .map(|map| &mut **map),
                 quirks_mode: self.quirks_mode,
                 compound_state: PerCompoundState::new(index),
                 alloc_error: &mut *self.alloc_error,
@@ -471,6 +511,39 @@ impl<'a> SelectorVisitor for SelectorDependencyCollector<'a> {
         true
     }
 
+    fn visit_relative_selector_list(&mut self, list: &[RelativeSelector<SelectorImpl>]) -> bool {
+        let map = match self.relative_selector_invalidation_map {
+            Some(ref mut map) => &mut **map,
+            None => {
+                debug_assert!(false, ":has() shouldn't be nested");
+                return true;
+            },
+        };
+
+        // Unlike with :is() and friends, we can't cheat and visit the
+        // rightmost compound of the relative selector with the outer visitor,
+        // since it doesn't match the same element as the outer compound.
+        self.parent_selectors
+            .push((self.selector.clone(), self.compound_state.offset));
+        for relative_selector in list {
+            let mut nested = SelectorDependencyCollector {
+                map: &mut *map,
+                document_state: &mut *self.document_state,
+                selector: &relative_selector.selector,
+                parent_selectors: &mut *self.parent_selectors,
+                relative_selector_invalidation_map: None,
+                quirks_mode: self.quirks_mode,
+                compound_state: PerCompoundState::new(0),
+                alloc_error: &mut *self.alloc_error,
+            };
+            if !nested.add_relative_selector_dependency() || !nested.visit_whole_selector() {
+                return false;
+            }
+        }
+        self.parent_selectors.pop();
+        true
+    }
+
     fn visit_simple_selector(&mut self, s: &Component<SelectorImpl>) -> bool {
         use crate::selector_parser::NonTSPseudoClass;
 
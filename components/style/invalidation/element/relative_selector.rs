@@ -0,0 +1,699 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Invalidation of the style of the anchors of relative selectors (the
+//! elements `:has()` applies to), due to state or attribute changes in the
+//! elements the relative selectors match, or to elements being inserted into
+//! or removed from the tree.
+//!
+//! Unlike other invalidations, these go up the tree (to ancestors) and
+//! backwards (to previous siblings), so they can't be processed during the
+//! traversal, and need to happen before it, for every element with a snapshot.
+
+use crate::context::SharedStyleContext;
+use crate::dom::{TElement, TNode};
+use crate::invalidation::element::element_wrapper::{ElementSnapshot, ElementWrapper};
+use crate::invalidation::element::invalidation_map::{
+    Dependency, DependencyInvalidationKind, InvalidationMap,
+};
+use crate::invalidation::element::restyle_hints::RestyleHint;
+use crate::invalidation::element::state_and_attributes::check_dependency;
+use crate::selector_parser::{SelectorImpl, Snapshot};
+use crate::stylesheets::origin::OriginSet;
+use crate::stylist::Stylist;
+use crate::{Atom, WeakAtom};
+use dom::ElementState;
+use selectors::attr::CaseSensitivity;
+use selectors::matching::{
+    matches_compound_selector_from, CompoundSelectorMatchingResult, MatchingContext,
+    MatchingForInvalidation, MatchingMode, NeedsSelectorFlags, SelectorCaches, VisitedHandlingMode,
+};
+use selectors::parser::{Combinator, Component, Selector};
+use selectors::{Element, OpaqueElement};
+use smallvec::SmallVec;
+
+/// An invalidator for the anchors of the relative selectors that may match
+/// a given element.
+pub struct RelativeSelectorInvalidator<'a, 'b: 'a, E: TElement> {
+    /// The element that has changed.
+    pub element: E,
+    /// The shared style context, to look up snapshots and invalidation maps.
+    pub shared_context: &'a SharedStyleContext<'b>,
+}
+
+/// The changes of an element with regard to its snapshot.
+struct Changes<'a> {
+    snapshot: &'a Snapshot,
+    state_changes: ElementState,
+    removed_id: Option<&'a WeakAtom>,
+    added_id: Option<&'a WeakAtom>,
+    classes_removed: SmallVec<[Atom; 8]>,
+    classes_added: SmallVec<[Atom; 8]>,
+}
+
+impl<'a, 'b: 'a, E: TElement + 'b> RelativeSelectorInvalidator<'a, 'b, E> {
+    /// Invalidates the anchors of the relative selectors whose matching may
+    /// have changed due to the changes in this element, calling
+    /// `on_invalidated` with each anchor (or sibling of it) that needs to be
+    /// restyled, and the restyle hint it needs.
+    ///
+    /// This needs to be called before the element's snapshot is handled.
+    pub fn invalidate_relative_selectors_for_this<F>(self, mut on_invalidated: F)
+    where
+        F: FnMut(E, RestyleHint),
+    {
+        let element = self.element;
+        if !element.has_snapshot() ||
+            element.handled_snapshot() ||
+            element.implemented_pseudo_element().is_some()
+        {
+            return;
+        }
+
+        let wrapper = ElementWrapper::new(element, &*self.shared_context.snapshot_map);
+        let snapshot = match wrapper.snapshot() {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+        let state_changes = wrapper.state_changes();
+        if !snapshot.has_attrs() && state_changes.is_empty() {
+            return;
+        }
+
+        let mut classes_removed = SmallVec::<[Atom; 8]>::new();
+        let mut classes_added = SmallVec::<[Atom; 8]>::new();
+        if snapshot.class_changed() {
+            snapshot.each_class(|c| {
+                if !element.has_class(c, CaseSensitivity::CaseSensitive) {
+                    classes_removed.push(c.0.clone())
+                }
+            });
+            element.each_class(|c| {
+                if !snapshot.has_class(c, CaseSensitivity::CaseSensitive) {
+                    classes_added.push(c.0.clone())
+                }
+            });
+        }
+
+        let mut removed_id = None;
+        let mut added_id = None;
+        if snapshot.id_changed() {
+            let old_id = snapshot.id_attr();
+            let current_id = element.id();
+            if old_id != current_id {
+                removed_id = old_id;
+                added_id = current_id;
+            }
+        }
+
+        let changes = Changes {
+            snapshot,
+            state_changes,
+            removed_id,
+            added_id,
+            classes_removed,
+            classes_added,
+        };
+
+        let mut selector_caches = SelectorCaches::default();
+        let mut matching_context = MatchingContext::new_for_visited(
+            MatchingMode::Normal,
+            None,
+            &mut selector_caches,
+            VisitedHandlingMode::AllLinksVisitedAndUnvisited,
+            self.shared_context.quirks_mode(),
+            NeedsSelectorFlags::No,
+            MatchingForInvalidation::Yes,
+        );
+
+        for (map, host) in relative_selector_maps(element, self.shared_context.stylist) {
+            matching_context.current_host = host;
+            let mut dependencies = SmallVec::<[&Dependency; 8]>::new();
+            collect_dependencies(element, &changes, map, &matching_context, &mut dependencies);
+            for dependency in dependencies {
+                if !check_dependency(dependency, &element, &wrapper, &mut matching_context) {
+                    continue;
+                }
+                debug!(
+                    "RelativeSelectorInvalidator: {:?} changed, dependency: {:?}",
+                    element, dependency
+                );
+                invalidate_anchors(
+                    element,
+                    dependency,
+                    &mut matching_context,
+                    &mut on_invalidated,
+                );
+            }
+        }
+    }
+}
+
+/// An invalidator for the anchors of the relative selectors whose matching may
+/// change due to an element (and its subtree) being inserted into or removed
+/// from the tree.
+pub struct RelativeSelectorDomMutationInvalidator<'a, E: TElement> {
+    /// The element being inserted or removed.
+    pub element: E,
+    /// The stylist, to look up the invalidation maps.
+    pub stylist: &'a Stylist,
+}
+
+impl<'a, E: TElement + 'a> RelativeSelectorDomMutationInvalidator<'a, E> {
+    /// Invalidates the anchors of the relative selectors whose matching may
+    /// have changed due to this element being inserted or removed, calling
+    /// `on_invalidated` like `invalidate_relative_selectors_for_this` does.
+    ///
+    /// This needs to be called once the element has been inserted, or before
+    /// it's removed, so that its ancestors and siblings can be reached.
+    pub fn invalidate_relative_selectors_for_dom_mutation<F>(self, mut on_invalidated: F)
+    where
+        F: FnMut(E, RestyleHint),
+    {
+        let element = self.element;
+        if element.implemented_pseudo_element().is_some() {
+            return;
+        }
+
+        // The mutated subtree is either styled from scratch, or not at all.
+        let mut on_invalidated = |anchor: E, hint| {
+            if !is_inclusive_descendant_of(anchor, element) {
+                on_invalidated(anchor, hint)
+            }
+        };
+
+        let mut later_siblings = SmallVec::<[E; 8]>::new();
+        let mut sibling = element.next_sibling_element();
+        while let Some(s) = sibling {
+            later_siblings.push(s);
+            sibling = s.next_sibling_element();
+        }
+
+        let mut selector_caches = SelectorCaches::default();
+        let mut matching_context = MatchingContext::new_for_visited(
+            MatchingMode::Normal,
+            None,
+            &mut selector_caches,
+            VisitedHandlingMode::AllLinksVisitedAndUnvisited,
+            self.stylist.quirks_mode(),
+            NeedsSelectorFlags::No,
+            MatchingForInvalidation::Yes,
+        );
+
+        for (map, host) in relative_selector_maps(element, self.stylist) {
+            matching_context.current_host = host;
+            for dependency in &map.relative_selectors {
+                debug!(
+                    "RelativeSelectorInvalidator: {:?} inserted or removed, dependency: {:?}",
+                    element, dependency
+                );
+                invalidate_anchors_for_subtree(
+                    element,
+                    dependency,
+                    None,
+                    &mut matching_context,
+                    &mut on_invalidated,
+                );
+                if !has_sibling_combinator(dependency) {
+                    continue;
+                }
+                // The elements after the mutated one have different previous
+                // siblings with and without it, so their relative selectors
+                // may reach different anchors.
+                for sibling in &later_siblings {
+                    invalidate_anchors_for_subtree(
+                        *sibling,
+                        dependency,
+                        Some(element),
+                        &mut matching_context,
+                        &mut on_invalidated,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Returns the relative selector invalidation maps that apply to `element`,
+/// along with the shadow host whose rules they come from, if any.
+fn relative_selector_maps<'a, E: TElement>(
+    element: E,
+    stylist: &'a Stylist,
+) -> SmallVec<[(&'a InvalidationMap, Option<OpaqueElement>); 6]>
+where
+    E: 'a,
+{
+    let mut shadow_rule_datas = SmallVec::<[_; 3]>::new();
+    let matches_document_author_rules =
+        element.each_applicable_non_document_style_rule_data(|data, host| {
+            shadow_rule_datas.push((data, host.opaque()))
+        });
+    let document_origins = if !matches_document_author_rules {
+        OriginSet::ORIGIN_USER_AGENT | OriginSet::ORIGIN_USER
+    } else {
+        OriginSet::all()
+    };
+
+    let mut maps = SmallVec::<[_; 6]>::new();
+    for (cascade_data, origin) in stylist.iter_origins() {
+        if document_origins.contains(origin.into()) {
+            maps.push((cascade_data.relative_selector_invalidation_map(), None));
+        }
+    }
+    for (data, host) in shadow_rule_datas {
+        maps.push((data.relative_selector_invalidation_map(), Some(host)));
+    }
+    maps
+}
+
+/// Notes that `element` needs to be restyled with the given hint, marking its
+/// ancestors as having dirty descendants so that the traversal reaches it.
+///
+/// Elements that haven't been styled yet are skipped, since the traversal will
+/// style them anyway.
+#[cfg(feature = "servo")]
+fn note_dirty<E: TElement>(element: E, hint: RestyleHint) {
+    {
+        let mut data = match element.mutate_data() {
+            Some(data) => data,
+            None => return,
+        };
+        data.hint.insert(hint);
+    }
+
+    let mut current = element.traversal_parent();
+    while let Some(parent) = current {
+        if parent.has_dirty_descendants() {
+            break;
+        }
+        unsafe { parent.set_dirty_descendants() };
+        current = parent.traversal_parent();
+    }
+}
+
+/// Invalidates the anchors of the relative selectors affected by the changes
+/// in the dirty part of the tree rooted at `root`, that is, by the elements
+/// with a snapshot that hasn't been handled yet, and by the elements that have
+/// been inserted since the last restyle.
+///
+/// Gecko does this element by element from `Servo_ProcessInvalidations`
+/// instead, so this is only needed for Servo, which handles snapshots lazily
+/// during the traversal. Removed elements can't be found here, so they need to
+/// go through `invalidate_relative_selectors_for_removal`.
+#[cfg(feature = "servo")]
+pub fn invalidate_relative_selectors_in_dirty_subtree<E: TElement>(
+    root: E,
+    shared_context: &SharedStyleContext,
+) {
+    // Nothing could have matched before the initial style.
+    if !root.has_data() {
+        return;
+    }
+
+    // Find the mutated elements before invalidating anything, since that may
+    // mark more of the tree as dirty.
+    let mut changed = SmallVec::<[E; 8]>::new();
+    let mut inserted = SmallVec::<[E; 8]>::new();
+    collect_mutated_elements(root, &mut changed, &mut inserted);
+
+    for element in changed {
+        RelativeSelectorInvalidator {
+            element,
+            shared_context,
+        }
+        .invalidate_relative_selectors_for_this(note_dirty);
+    }
+
+    for element in inserted {
+        RelativeSelectorDomMutationInvalidator {
+            element,
+            stylist: shared_context.stylist,
+        }
+        .invalidate_relative_selectors_for_dom_mutation(note_dirty);
+    }
+}
+
+/// Invalidates the anchors of the relative selectors whose matching may change
+/// due to `element` being removed from the tree.
+///
+/// This needs to be called before the element is removed.
+#[cfg(feature = "servo")]
+pub fn invalidate_relative_selectors_for_removal<E: TElement>(element: E, stylist: &Stylist) {
+    RelativeSelectorDomMutationInvalidator { element, stylist }
+        .invalidate_relative_selectors_for_dom_mutation(note_dirty);
+}
+
+/// Collects the elements with an unhandled snapshot, and the roots of the
+/// subtrees inserted since the last restyle, in the dirty part of the tree
+/// rooted at `element`.
+#[cfg(feature = "servo")]
+fn collect_mutated_elements<E: TElement>(
+    element: E,
+    changed: &mut SmallVec<[E; 8]>,
+    inserted: &mut SmallVec<[E; 8]>,
+) {
+    if element.has_snapshot() && !element.handled_snapshot() {
+        changed.push(element);
+    }
+
+    if !element.has_dirty_descendants() {
+        return;
+    }
+
+    for child in element.traversal_children() {
+        let child = match child.as_element() {
+            Some(child) => child,
+            None => continue,
+        };
+        if !child.has_data() {
+            inserted.push(child);
+            continue;
+        }
+        collect_mutated_elements(child, changed, inserted);
+    }
+}
+
+/// Invalidates the anchors of the relative selector of the given dependency
+/// for every element in the subtree rooted at `element` that matches its
+/// rightmost compound.
+///
+/// If `skipped` is given, only the anchors that the element reaches either
+/// with or without that sibling in the tree are invalidated.
+fn invalidate_anchors_for_subtree<E, F>(
+    element: E,
+    dependency: &Dependency,
+    skipped: Option<E>,
+    matching_context: &mut MatchingContext<E::Impl>,
+    on_invalidated: &mut F,
+) where
+    E: TElement,
+    F: FnMut(E, RestyleHint),
+{
+    if matches_compound(
+        &dependency.selector,
+        dependency.selector_offset,
+        matching_context,
+        &element,
+    ) {
+        match skipped {
+            None => invalidate_anchors(element, dependency, matching_context, on_invalidated),
+            Some(skipped) => {
+                let with = anchors(element, dependency, None, matching_context);
+                let without = anchors(element, dependency, Some(skipped), matching_context);
+                if let (Some((with, outer)), Some((without, _))) = (with, without) {
+                    let only_with = with.iter().filter(|a| !without.contains(*a));
+                    let only_without = without.iter().filter(|a| !with.contains(*a));
+                    for anchor in only_with.chain(only_without) {
+                        invalidate_anchor(*anchor, outer, on_invalidated);
+                    }
+                }
+            },
+        }
+    }
+
+    for child in element.as_node().dom_children() {
+        if let Some(child) = child.as_element() {
+            invalidate_anchors_for_subtree(
+                child,
+                dependency,
+                skipped,
+                matching_context,
+                on_invalidated,
+            );
+        }
+    }
+}
+
+/// Whether `element` matches the compound selector of `selector` that starts
+/// at `offset`, in matching order.
+fn matches_compound<E: TElement>(
+    selector: &Selector<SelectorImpl>,
+    offset: usize,
+    matching_context: &mut MatchingContext<E::Impl>,
+    element: &E,
+) -> bool {
+    let components = selector.iter_raw_match_order().as_slice();
+    let end = components[offset..]
+        .iter()
+        .position(|component| component.is_combinator())
+        .map_or(components.len(), |i| offset + i);
+    !matches!(
+        matches_compound_selector_from(selector, components.len() - end, matching_context, element),
+        CompoundSelectorMatchingResult::NotMatched
+    )
+}
+
+/// Whether `element` is `ancestor` or one of its descendants.
+fn is_inclusive_descendant_of<E: TElement>(element: E, ancestor: E) -> bool {
+    let mut current = Some(element);
+    while let Some(e) = current {
+        if e == ancestor {
+            return true;
+        }
+        current = e.parent_element();
+    }
+    false
+}
+
+/// Whether the relative selector of the given dependency has any sibling
+/// combinator.
+fn has_sibling_combinator(dependency: &Dependency) -> bool {
+    dependency.selector.iter_raw_match_order().any(|component| {
+        matches!(
+            *component,
+            Component::Combinator(Combinator::NextSibling) |
+                Component::Combinator(Combinator::LaterSibling)
+        )
+    })
+}
+
+/// Collects the dependencies in the given relative selector invalidation map
+/// that may be affected by the changes in the element.
+fn collect_dependencies<'selectors, E: TElement>(
+    element: E,
+    changes: &Changes,
+    map: &'selectors InvalidationMap,
+    matching_context: &MatchingContext<E::Impl>,
+    dependencies: &mut SmallVec<[&'selectors Dependency; 8]>,
+) {
+    let quirks_mode = matching_context.quirks_mode();
+    for id in changes.removed_id.iter().chain(changes.added_id.iter()) {
+        if let Some(deps) = map.id_to_selector.get(id, quirks_mode) {
+            dependencies.extend(deps.iter());
+        }
+    }
+
+    for class in changes
+        .classes_added
+        .iter()
+        .chain(changes.classes_removed.iter())
+    {
+        if let Some(deps) = map.class_to_selector.get(class, quirks_mode) {
+            dependencies.extend(deps.iter());
+        }
+    }
+
+    changes.snapshot.each_attr_changed(|attribute| {
+        if let Some(deps) = map.other_attribute_affecting_selectors.get(attribute) {
+            dependencies.extend(deps.iter());
+        }
+    });
+
+    if changes.state_changes.is_empty() {
+        return;
+    }
+    map.state_affecting_selectors.lookup_with_additional(
+        element,
+        quirks_mode,
+        changes.removed_id,
+        &changes.classes_removed,
+        changes.state_changes,
+        |dependency| {
+            if dependency.state.intersects(changes.state_changes) {
+                dependencies.push(&dependency.dep);
+            }
+            true
+        },
+    );
+}
+
+/// Pushes `element` to `elements` if it's not there yet.
+fn push_unique<E: TElement>(elements: &mut SmallVec<[E; 8]>, element: E) {
+    if !elements.contains(&element) {
+        elements.push(element);
+    }
+}
+
+/// Returns the elements that may be the anchor of the relative selector of the
+/// given dependency, given that `element` matches the compound selector the
+/// dependency points to, as if `skipped` wasn't in the tree.
+///
+/// The compound selectors between the element and the anchor are matched
+/// against the current state of the elements in between.
+fn anchor_candidates<E: TElement>(
+    element: E,
+    dependency: &Dependency,
+    skipped: Option<E>,
+    matching_context: &mut MatchingContext<E::Impl>,
+) -> SmallVec<[E; 8]> {
+    let prev_sibling = |e: E| {
+        let sibling = e.prev_sibling_element();
+        if sibling.is_some() && sibling == skipped {
+            return sibling.and_then(|s| s.prev_sibling_element());
+        }
+        sibling
+    };
+
+    let mut candidates = SmallVec::<[E; 8]>::new();
+    candidates.push(element);
+    let components = dependency.selector.iter_raw_match_order().as_slice();
+    for (i, component) in components
+        .iter()
+        .enumerate()
+        .skip(dependency.selector_offset)
+    {
+        let combinator = match *component {
+            Component::Combinator(combinator) => combinator,
+            _ => continue,
+        };
+        let mut next = SmallVec::<[E; 8]>::new();
+        for candidate in candidates {
+            match combinator {
+                Combinator::Child => {
+                    if let Some(parent) = candidate.parent_element() {
+                        push_unique(&mut next, parent);
+                    }
+                },
+                Combinator::Descendant => {
+                    let mut current = candidate.parent_element();
+                    while let Some(ancestor) = current {
+                        push_unique(&mut next, ancestor);
+                        current = ancestor.parent_element();
+                    }
+                },
+                Combinator::NextSibling => {
+                    if let Some(sibling) = prev_sibling(candidate) {
+                        push_unique(&mut next, sibling);
+                    }
+                },
+                Combinator::LaterSibling => {
+                    let mut current = prev_sibling(candidate);
+                    while let Some(sibling) = current {
+                        push_unique(&mut next, sibling);
+                        current = prev_sibling(sibling);
+                    }
+                },
+                Combinator::PseudoElement | Combinator::Part | Combinator::SlotAssignment => {
+                    debug_assert!(false, "Pseudo-elements aren't allowed in :has()");
+                },
+            }
+        }
+        next.retain(|e| matches_compound(&dependency.selector, i + 1, matching_context, e));
+        candidates = next;
+    }
+    candidates
+}
+
+/// Returns the elements that may be the anchor of the relative selector of the
+/// given dependency, like `anchor_candidates` does, along with the dependency
+/// of the outer selector that contains the relative selector.
+fn anchors<'d, E: TElement>(
+    element: E,
+    dependency: &'d Dependency,
+    skipped: Option<E>,
+    matching_context: &mut MatchingContext<E::Impl>,
+) -> Option<(SmallVec<[E; 8]>, &'d Dependency)> {
+    let mut candidates = anchor_candidates(element, dependency, skipped, matching_context);
+
+    // If the change was in a non-rightmost compound of a selector list nested
+    // inside the relative selector (like `.a` in `:has(:is(.a .b))`), find the
+    // elements the compound containing the list may match first, and go on
+    // from there.
+    let mut dependency = dependency;
+    while !dependency.selector.is_relative() {
+        dependency = match dependency.parent {
+            Some(ref parent) => parent,
+            None => {
+                debug_assert!(false, "Nested dependency without a relative parent?");
+                return None;
+            },
+        };
+        let mut next = SmallVec::<[E; 8]>::new();
+        for candidate in candidates {
+            for c in anchor_candidates(candidate, dependency, skipped, matching_context) {
+                push_unique(&mut next, c);
+            }
+        }
+        candidates = next;
+    }
+
+    Some((candidates, outer_dependency(dependency)?))
+}
+
+/// Returns the dependency of the outer selector that contains the `:has()`
+/// the given relative selector dependency belongs to.
+fn outer_dependency(dependency: &Dependency) -> Option<&Dependency> {
+    let mut outer: &Dependency = match dependency.parent {
+        Some(ref parent) => parent,
+        None => {
+            debug_assert!(false, "Relative selector dependency without a parent?");
+            return None;
+        },
+    };
+
+    // A :has() in the rightmost compound of a nested selector list applies to
+    // the same element as the compound containing the list.
+    while outer.invalidation_kind() == DependencyInvalidationKind::Element {
+        outer = match outer.parent {
+            Some(ref parent) => parent,
+            None => break,
+        };
+    }
+    Some(outer)
+}
+
+/// Invalidates the anchors of the relative selector of the given dependency,
+/// according to the dependency of the outer selector.
+fn invalidate_anchors<E, F>(
+    element: E,
+    dependency: &Dependency,
+    matching_context: &mut MatchingContext<E::Impl>,
+    on_invalidated: &mut F,
+) where
+    E: TElement,
+    F: FnMut(E, RestyleHint),
+{
+    let (candidates, outer) = match anchors(element, dependency, None, matching_context) {
+        Some(anchors) => anchors,
+        None => return,
+    };
+    for anchor in candidates {
+        invalidate_anchor(anchor, outer, on_invalidated);
+    }
+}
+
+/// Invalidates an anchor of a relative selector (or the elements its style
+/// affects), according to the dependency of the outer selector.
+fn invalidate_anchor<E, F>(anchor: E, outer: &Dependency, on_invalidated: &mut F)
+where
+    E: TElement,
+    F: FnMut(E, RestyleHint),
+{
+    match outer.invalidation_kind() {
+        DependencyInvalidationKind::Element => on_invalidated(anchor, RestyleHint::RESTYLE_SELF),
+        DependencyInvalidationKind::ElementAndDescendants |
+        DependencyInvalidationKind::Descendants |
+        DependencyInvalidationKind::SlottedElements |
+        DependencyInvalidationKind::Parts => {
+            on_invalidated(anchor, RestyleHint::restyle_subtree())
+        },
+        DependencyInvalidationKind::Siblings => {
+            let mut sibling = anchor.next_sibling_element();
+            while let Some(s) = sibling {
+                on_invalidated(s, RestyleHint::restyle_subtree());
+                sibling = s.next_sibling_element();
+            }
+        },
+    }
+}
@@ -548,15 +548,22 @@ impl StylesheetInvalidationSet {
                 // It's not clear what handling changes for this correctly would
                 // look like.
             },
+            StartingStyle(..) => {
+                // The rules inside only apply to elements being styled for the
+                // first time, so they can't affect the style of existing
+                // elements.
+            },
             CounterStyle(..) |
             Page(..) |
             Viewport(..) |
             FontFeatureValues(..) |
             FontPaletteValues(..) |
+            Property(..) |
             LayerStatement(..) |
             FontFace(..) |
             Keyframes(..) |
             Container(..) |
+            Scope(..) |
             Style(..) => {
                 if is_generic_change {
                     // TODO(emilio): We need to do this for selector / keyframe
@@ -567,7 +574,28 @@ impl StylesheetInvalidationSet {
                     return self.invalidate_fully();
                 }
 
-                self.collect_invalidations_for_rule(rule, guard, device, quirks_mode)
+                self.collect_invalidations_for_rule(rule, guard, device, quirks_mode);
+                if self.fully_invalid {
+                    return;
+                }
+
+                // Nested rules inside a style rule aren't visited otherwise.
+                if let Style(ref lock) = *rule {
+                    if lock.read_with(guard).rules.is_some() {
+                        let rules = EffectiveRulesIterator::effective_children(
+                            device,
+                            quirks_mode,
+                            guard,
+                            rule,
+                        );
+                        for rule in rules {
+                            self.collect_invalidations_for_rule(rule, guard, device, quirks_mode);
+                            if self.fully_invalid {
+                                break;
+                            }
+                        }
+                    }
+                }
             },
             Document(..) | Import(..) | Media(..) | Supports(..) | LayerBlock(..) => {
                 if !is_generic_change &&
@@ -611,7 +639,7 @@ impl StylesheetInvalidationSet {
                 }
             },
             Document(..) | Namespace(..) | Import(..) | Media(..) | Supports(..) |
-            Container(..) | LayerStatement(..) | LayerBlock(..) => {
+            Container(..) | LayerStatement(..) | LayerBlock(..) | StartingStyle(..) => {
                 // Do nothing, relevant nested rules are visited as part of the
                 // iteration.
             },
@@ -633,8 +661,14 @@ impl StylesheetInvalidationSet {
                     // existing elements.
                 }
             },
+            Scope(..) => {
+                // The selectors of the rules inside depend on the scoping
+                // roots and limits, which we don't track.
+                debug!(" > Found @scope rule, marking the whole subtree invalid.");
+                self.fully_invalid = true;
+            },
             CounterStyle(..) | Page(..) | Viewport(..) | FontFeatureValues(..) |
-            FontPaletteValues(..) => {
+            FontPaletteValues(..) | Property(..) => {
                 debug!(
                     " > Found unsupported rule, marking the whole subtree \
                      invalid."
@@ -82,6 +82,7 @@ pub mod attr;
 pub mod author_styles;
 pub mod bezier;
 pub mod bloom;
+pub mod cascade_trace;
 pub mod color;
 #[path = "properties/computed_value_flags.rs"]
 pub mod computed_value_flags;
@@ -106,9 +107,11 @@ pub mod invalidation;
 pub mod logical_geometry;
 pub mod matching;
 pub mod media_queries;
+pub mod memory_report;
 pub mod parallel;
 pub mod parser;
 pub mod piecewise_linear;
+pub mod properties_and_values;
 #[macro_use]
 pub mod queries;
 pub mod rule_cache;
@@ -253,6 +253,41 @@ trait PrivateMatchMethods: TElement {
         Some(style.0)
     }
 
+    /// Computes the starting style of the element, if it didn't have a style
+    /// before or was `display: none`, and it might start transitions.
+    #[cfg(feature = "servo")]
+    fn starting_style(
+        &self,
+        context: &mut StyleContext<Self>,
+        old_style: Option<&ComputedValues>,
+        new_style: &ComputedValues,
+    ) -> Option<Arc<ComputedValues>> {
+        if old_style.map_or(false, |style| !style.clone_display().is_none()) {
+            return None;
+        }
+
+        if new_style.clone_display().is_none() || !new_style.get_ui().specifies_transitions() {
+            return None;
+        }
+
+        let any_starting_style_rules = context
+            .shared
+            .stylist
+            .any_applicable_rule_data(*self, |data| data.any_starting_style_rules());
+        if !any_starting_style_rules {
+            return None;
+        }
+
+        StyleResolverForElement::new(
+            *self,
+            context,
+            RuleInclusion::All,
+            PseudoElementResolution::IfApplicable,
+        )
+        .resolve_starting_style()
+        .map(|style| style.0)
+    }
+
     fn needs_animations_update(
         &self,
         context: &mut StyleContext<Self>,
@@ -657,9 +692,17 @@ trait PrivateMatchMethods: TElement {
             pseudo_element,
         );
 
+        // Elements that didn't have a style before, or were `display: none`,
+        // transition from their starting style, if any.
+        let starting_style = match pseudo_element {
+            Some(..) => None,
+            None => self.starting_style(context, old_values.as_deref(), new_values),
+        };
+        let before_change_style = starting_style.as_ref().or(old_values.as_ref());
+
         let might_need_transitions_update = self.might_need_transitions_update(
             context,
-            old_values.as_deref(),
+            before_change_style.map(|style| &**style),
             new_values,
             pseudo_element,
         );
@@ -700,7 +743,7 @@ trait PrivateMatchMethods: TElement {
         animation_set.update_transitions_for_new_style(
             might_need_transitions_update,
             &shared_context,
-            old_values.as_ref(),
+            before_change_style,
             after_change_style.as_ref().unwrap_or(new_values),
         );
 
@@ -985,6 +1028,17 @@ pub trait MatchMethods: TElement {
             restyle_requirement = ChildRestyleRequirement::MustMatchDescendants;
         }
 
+        if !old_primary_style.custom_properties_equal(new_primary_style) &&
+            context
+                .shared
+                .stylist
+                .any_applicable_rule_data(*self, |data| data.any_style_container_query())
+        {
+            // We may be the query container of style() queries, which need to
+            // be re-evaluated on all our descendants.
+            restyle_requirement = ChildRestyleRequirement::MustMatchDescendants;
+        }
+
         restyle_requirement = cmp::max(
             restyle_requirement,
             self.accumulate_damage_for(
@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Hierarchical reports of the heap memory used by the style system.
+//!
+//! Unlike the flat Gecko `ServoStyleSetSizes`, a `MemoryReport` keeps the
+//! structure of what was measured (origins, stylesheets, rule types, selector
+//! maps...), so that embedders can find out which stylesheets, or which of
+//! the style system data structures, are responsible for most of the memory
+//! usage.
+
+use std::fmt;
+
+/// A node of a memory report.
+///
+/// The total size of a node is the size measured for the node itself, plus
+/// the total size of all of its children.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MemoryReport {
+    /// The name of this node.
+    pub name: String,
+    /// The heap memory, in bytes, that was measured for this node itself,
+    /// excluding its children.
+    pub size: usize,
+    /// The nodes this node's memory is broken down into.
+    pub children: Vec<MemoryReport>,
+}
+
+impl MemoryReport {
+    /// Creates an empty report with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self::with_size(name, 0)
+    }
+
+    /// Creates a report with the given name and size, and no children.
+    pub fn with_size(name: impl Into<String>, size: usize) -> Self {
+        Self {
+            name: name.into(),
+            size,
+            children: vec![],
+        }
+    }
+
+    /// Returns the total size of this node, including all of its children.
+    pub fn total(&self) -> usize {
+        self.size + self.children.iter().map(|c| c.total()).sum::<usize>()
+    }
+
+    /// Returns the first child of this node with the given name, if any.
+    pub fn child(&self, name: &str) -> Option<&MemoryReport> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// Returns the first child of this node with the given name, creating an
+    /// empty one if there is none.
+    pub fn child_mut(&mut self, name: &str) -> &mut MemoryReport {
+        match self.children.iter().position(|c| c.name == name) {
+            Some(index) => &mut self.children[index],
+            None => {
+                self.children.push(MemoryReport::new(name));
+                self.children.last_mut().unwrap()
+            },
+        }
+    }
+
+    /// Returns the descendant of this node at the given path of names, if
+    /// any.
+    pub fn find(&self, path: &[&str]) -> Option<&MemoryReport> {
+        path.iter()
+            .try_fold(self, |report, name| report.child(name))
+    }
+
+    /// Adds `size` bytes to the child of this node with the given name,
+    /// creating it if needed.
+    pub fn add(&mut self, name: &str, size: usize) {
+        self.child_mut(name).size += size;
+    }
+
+    /// Appends a child to this node, even if there's another child with the
+    /// same name already.
+    pub fn add_child(&mut self, child: MemoryReport) {
+        self.children.push(child);
+    }
+
+    fn fmt_with_indent(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{} {}",
+            "",
+            self.total(),
+            self.name,
+            indent = indent
+        )?;
+        let mut children = self.children.iter().collect::<Vec<_>>();
+        children.sort_by(|a, b| b.total().cmp(&a.total()));
+        for child in children {
+            child.fmt_with_indent(f, indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the report as an indented tree of total sizes, with the largest
+/// children of each node first.
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with_indent(f, 0)
+    }
+}
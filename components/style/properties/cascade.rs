@@ -11,7 +11,6 @@ use crate::dom::TElement;
 use crate::font_metrics::FontMetricsOrientation;
 use crate::values::specified::length::FontBaseSize;
 use crate::logical_geometry::WritingMode;
-use crate::media_queries::Device;
 use crate::properties::{
     CSSWideKeyword, ComputedValueFlags, ComputedValues, DeclarationImportanceIterator, Importance,
     LonghandId, LonghandIdSet, PropertyDeclaration, PropertyDeclarationId, PropertyFlags,
@@ -24,6 +23,7 @@ use crate::shared_lock::StylesheetGuards;
 use crate::style_adjuster::StyleAdjuster;
 use crate::stylesheets::{Origin, layer_rule::LayerOrder};
 use crate::stylesheets::container_rule::ContainerSizeQuery;
+use crate::stylist::Stylist;
 use crate::values::{computed, specified};
 use fxhash::FxHashMap;
 use servo_arc::Arc;
@@ -41,7 +41,8 @@ enum CanHaveLogicalProperties {
 ///
 /// The arguments are:
 ///
-///   * `device`: Used to get the initial viewport and other external state.
+///   * `stylist`: Used to get the device, with the initial viewport and other
+///   external state, and the custom property registrations.
 ///
 ///   * `rule_node`: The rule node in the tree that represent the CSS rules that
 ///   matched.
@@ -52,7 +53,7 @@ enum CanHaveLogicalProperties {
 ///   * `flags`: Various flags.
 ///
 pub fn cascade<E>(
-    device: &Device,
+    stylist: &Stylist,
     pseudo: Option<&PseudoElement>,
     rule_node: &StrongRuleNode,
     guards: &StylesheetGuards,
@@ -71,7 +72,7 @@ where
     E: TElement,
 {
     cascade_rules(
-        device,
+        stylist,
         pseudo,
         rule_node,
         guards,
@@ -172,7 +173,7 @@ impl<'a> Iterator for DeclarationIterator<'a> {
 }
 
 fn cascade_rules<E>(
-    device: &Device,
+    stylist: &Stylist,
     pseudo: Option<&PseudoElement>,
     rule_node: &StrongRuleNode,
     guards: &StylesheetGuards,
@@ -195,7 +196,7 @@ where
         parent_style_ignoring_first_line.is_some()
     );
     apply_declarations(
-        device,
+        stylist,
         pseudo,
         rule_node,
         guards,
@@ -232,7 +233,7 @@ pub enum CascadeMode<'a> {
 /// NOTE: This function expects the declaration with more priority to appear
 /// first.
 pub fn apply_declarations<'a, E, I>(
-    device: &Device,
+    stylist: &Stylist,
     pseudo: Option<&PseudoElement>,
     rules: &StrongRuleNode,
     guards: &StylesheetGuards,
@@ -271,25 +272,9 @@ where
             parent_style.unwrap().is_first_line_style()
     );
 
+    let device = stylist.device();
     let inherited_style = parent_style.unwrap_or(device.default_computed_values());
 
-    let mut declarations = SmallVec::<[(&_, CascadePriority); 32]>::new();
-    let mut referenced_properties = LonghandIdSet::default();
-    let custom_properties = {
-        let mut builder = CustomPropertiesBuilder::new(inherited_style.custom_properties(), device);
-
-        for (declaration, priority) in iter {
-            declarations.push((declaration, priority));
-            if let PropertyDeclaration::Custom(ref declaration) = *declaration {
-                builder.cascade(declaration, priority);
-            } else {
-                referenced_properties.insert(declaration.id().as_longhand().unwrap());
-            }
-        }
-
-        builder.build()
-    };
-
     let is_root_element = pseudo.is_none() && element.map_or(false, |e| e.is_root());
     let container_size_query =
         ContainerSizeQuery::for_option_element(element, originating_element_style);
@@ -304,7 +289,7 @@ where
             parent_style_ignoring_first_line,
             pseudo,
             Some(rules.clone()),
-            custom_properties,
+            /* custom_properties = */ None,
             is_root_element,
         ),
         quirks_mode,
@@ -312,6 +297,27 @@ where
         container_size_query,
     );
 
+    let mut declarations = SmallVec::<[(&_, CascadePriority); 32]>::new();
+    let mut referenced_properties = LonghandIdSet::default();
+    context.builder.custom_properties = {
+        // Registered custom properties are computed before the font of the
+        // element is, so their font-relative units resolve against the
+        // inherited font.
+        let mut builder =
+            CustomPropertiesBuilder::new(inherited_style.custom_properties(), stylist, &context);
+
+        for (declaration, priority) in iter {
+            declarations.push((declaration, priority));
+            if let PropertyDeclaration::Custom(ref declaration) = *declaration {
+                builder.cascade(declaration, priority);
+            } else {
+                referenced_properties.insert(declaration.id().as_longhand().unwrap());
+            }
+        }
+
+        builder.build()
+    };
+
     context.style().add_flags(cascade_input_flags);
 
     let using_cached_reset_properties;
@@ -773,7 +773,12 @@ def _remove_common_first_line_and_first_letter_properties(props, engine):
     props.remove("overflow-wrap")
     props.remove("text-align")
     props.remove("text-justify")
-    props.remove("white-space")
+    if engine == "gecko":
+        props.remove("white-space")
+    else:
+        props.remove("white-space-collapse")
+        props.remove("text-wrap-mode")
+        props.remove("white-space-trim")
     props.remove("word-break")
     props.remove("text-indent")
 
@@ -872,6 +877,7 @@ class PropertyRestrictions:
         props = PropertyRestrictions.first_line(data)
         props.add("opacity")
         props.add("white-space")
+        props.update(PropertyRestrictions.shorthand(data, "white-space"))
         props.add("text-overflow")
         props.add("text-align")
         props.add("text-justify")
@@ -892,6 +898,7 @@ class PropertyRestrictions:
                 "line-height",
                 "-moz-osx-font-smoothing",
             ]
+            + PropertyRestrictions.shorthand(data, "white-space")
             + PropertyRestrictions.spec(data, "css-fonts")
             + PropertyRestrictions.spec(data, "css-animations")
             + PropertyRestrictions.spec(data, "css-transitions")
@@ -916,6 +923,7 @@ class PropertyRestrictions:
                 # https://drafts.fxtf.org/compositing/#background-blend-mode
                 "background-blend-mode",
             ]
+            + PropertyRestrictions.shorthand(data, "white-space")
             + PropertyRestrictions.shorthand(data, "text-decoration")
             + PropertyRestrictions.shorthand(data, "background")
             + PropertyRestrictions.shorthand(data, "outline")
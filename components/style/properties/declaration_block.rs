@@ -13,14 +13,18 @@ use crate::custom_properties::CustomPropertiesBuilder;
 use crate::error_reporting::{ContextualParseError, ParseErrorReporter};
 use crate::parser::ParserContext;
 use crate::properties::animated_properties::{AnimationValue, AnimationValueMap};
+use crate::rule_cache::RuleCacheConditions;
 use crate::rule_tree::CascadeLevel;
 use crate::selector_parser::SelectorImpl;
 use crate::shared_lock::Locked;
 use crate::str::{CssString, CssStringWriter};
+use crate::stylesheets::container_rule::ContainerSizeQuery;
 use crate::stylesheets::{CssRuleType, Origin, UrlExtraData, layer_rule::LayerOrder};
+use crate::stylist::Stylist;
 use crate::values::computed::Context;
 use cssparser::{parse_important, CowRcStr, DeclarationListParser, ParserInput};
 use cssparser::{AtRuleParser, DeclarationParser, Delimiter, ParseErrorKind, Parser};
+use cssparser::{ParserState, QualifiedRuleParser, Token};
 use itertools::Itertools;
 use selectors::SelectorList;
 use smallbitvec::{self, SmallBitVec};
@@ -94,8 +98,7 @@ impl Importance {
 }
 
 /// Overridden declarations are skipped.
-#[cfg_attr(feature = "gecko", derive(MallocSizeOf))]
-#[derive(Clone, ToShmem)]
+#[derive(Clone, MallocSizeOf, ToShmem)]
 pub struct PropertyDeclarationBlock {
     /// The group of declarations, along with their importance.
     ///
@@ -796,7 +799,7 @@ impl PropertyDeclarationBlock {
         dest: &mut CssStringWriter,
         computed_values: Option<&ComputedValues>,
         custom_properties_block: Option<&PropertyDeclarationBlock>,
-        device: &Device,
+        stylist: &Stylist,
     ) -> fmt::Result {
         if let Ok(shorthand) = property.as_shorthand() {
             return self.shorthand_to_css(shorthand, dest);
@@ -815,7 +818,14 @@ impl PropertyDeclarationBlock {
             if let Some(block) = custom_properties_block {
                 // FIXME(emilio): This is not super-efficient here, and all this
                 // feels like a hack anyway...
-                block.cascade_custom_properties(cv.custom_properties(), device)
+                let mut conditions = RuleCacheConditions::default();
+                let context = Context::new(
+                    StyleBuilder::for_inheritance(stylist.device(), Some(cv), None),
+                    QuirksMode::NoQuirks,
+                    &mut conditions,
+                    ContainerSizeQuery::none(),
+                );
+                block.cascade_custom_properties_with_context(&context, stylist)
             } else {
                 cv.custom_properties().cloned()
             }
@@ -839,7 +849,7 @@ impl PropertyDeclarationBlock {
                         computed_values.writing_mode,
                         custom_properties.as_ref(),
                         QuirksMode::NoQuirks,
-                        device,
+                        stylist.device(),
                         &mut Default::default()
                     )
                     .to_css(dest)
@@ -885,22 +895,10 @@ impl PropertyDeclarationBlock {
     pub fn cascade_custom_properties_with_context(
         &self,
         context: &Context,
+        stylist: &Stylist,
     ) -> Option<Arc<crate::custom_properties::CustomPropertiesMap>> {
-        self.cascade_custom_properties(
-            context.style().custom_properties(),
-            context.device(),
-        )
-    }
-
-    /// Returns a custom properties map which is the result of cascading custom
-    /// properties in this declaration block along with the given custom
-    /// properties.
-    fn cascade_custom_properties(
-        &self,
-        inherited_custom_properties: Option<&Arc<crate::custom_properties::CustomPropertiesMap>>,
-        device: &Device,
-    ) -> Option<Arc<crate::custom_properties::CustomPropertiesMap>> {
-        let mut builder = CustomPropertiesBuilder::new(inherited_custom_properties, device);
+        let inherited_custom_properties = context.style().custom_properties();
+        let mut builder = CustomPropertiesBuilder::new(inherited_custom_properties, stylist, context);
 
         for declaration in self.normal_declaration_iter() {
             if let PropertyDeclaration::Custom(ref declaration) = *declaration {
@@ -1363,7 +1361,16 @@ impl<'a, 'b, 'i> DeclarationParser<'i> for PropertyDeclarationParser<'a, 'b> {
     }
 }
 
-type SmallParseErrorVec<'i> = SmallVec<[(ParseError<'i>, &'i str, Option<PropertyId>); 2]>;
+/// An error found while parsing the contents of a declaration block. These
+/// are reported once the whole block is parsed, see `report_css_errors`.
+enum DeclarationBlockError<'i> {
+    /// An invalid declaration, with the property it was for, if known.
+    Declaration(ParseError<'i>, &'i str, Option<PropertyId>),
+    /// An invalid nested rule.
+    Rule(ParseError<'i>, &'i str),
+}
+
+type SmallParseErrorVec<'i> = SmallVec<[DeclarationBlockError<'i>; 2]>;
 
 fn alias_of_known_property(name: &str) -> Option<PropertyId> {
     let mut prefixed = String::with_capacity(name.len() + 5);
@@ -1440,8 +1447,17 @@ fn report_css_errors(
     selectors: Option<&SelectorList<SelectorImpl>>,
     errors: &mut SmallParseErrorVec,
 ) {
-    for (error, slice, property) in errors.drain(..) {
-        report_one_css_error(context, Some(block), selectors, error, slice, property)
+    for error in errors.drain(..) {
+        match error {
+            DeclarationBlockError::Declaration(error, slice, property) => {
+                report_one_css_error(context, Some(block), selectors, error, slice, property)
+            },
+            DeclarationBlockError::Rule(error, slice) => {
+                let location = error.location;
+                let error = ContextualParseError::InvalidRule(slice, error);
+                context.log_css_error(location, error);
+            },
+        }
     }
 }
 
@@ -1475,7 +1491,7 @@ pub fn parse_property_declaration_list(
 
                 if context.error_reporting_enabled() {
                     let property = iter.parser.last_parsed_property_id.take();
-                    errors.push((error, slice, property));
+                    errors.push(DeclarationBlockError::Declaration(error, slice, property));
                 }
             },
         }
@@ -1487,3 +1503,161 @@ pub fn parse_property_declaration_list(
 
     block
 }
+
+/// Parse the contents of a block that can contain both property declarations
+/// and nested rules, like a style rule when CSS Nesting is enabled, returning
+/// the declaration block and the list of nested rules.
+///
+/// Anything that fails to parse as a declaration is re-parsed as a nested
+/// rule using `rule_parser`, per
+/// https://drafts.csswg.org/css-syntax/#consume-block-contents
+pub fn parse_property_declaration_list_with_rules<'i, 't, P, R>(
+    context: &ParserContext,
+    input: &mut Parser<'i, 't>,
+    selectors: Option<&SelectorList<SelectorImpl>>,
+    rule_parser: &mut P,
+) -> (PropertyDeclarationBlock, Vec<R>)
+where
+    P: QualifiedRuleParser<'i, QualifiedRule = R, Error = StyleParseErrorKind<'i>> +
+        AtRuleParser<'i, AtRule = R, Error = StyleParseErrorKind<'i>>,
+{
+    let mut declarations = SourcePropertyDeclaration::new();
+    let mut block = PropertyDeclarationBlock::new();
+    let mut rules = Vec::new();
+    let mut parser = PropertyDeclarationParser {
+        context,
+        last_parsed_property_id: None,
+        declarations: &mut declarations,
+    };
+    let mut errors = SmallParseErrorVec::new();
+
+    loop {
+        let start = input.state();
+        let name = match input.next_including_whitespace_and_comments() {
+            Ok(&Token::WhiteSpace(_)) | Ok(&Token::Comment(_)) | Ok(&Token::Semicolon) => continue,
+            Ok(&Token::Ident(ref name)) => name.clone(),
+            Ok(&Token::AtKeyword(ref name)) => {
+                let name = name.clone();
+                match parse_nested_at_rule(&start, name, input, rule_parser) {
+                    Ok(rule) => rules.push(rule),
+                    Err(error) => {
+                        if context.error_reporting_enabled() {
+                            let slice = input.slice_from(start.position());
+                            errors.push(DeclarationBlockError::Rule(error, slice));
+                        }
+                    },
+                }
+                continue;
+            },
+            Ok(_) => {
+                input.reset(&start);
+                match parse_nested_qualified_rule(input, rule_parser) {
+                    Ok(rule) => rules.push(rule),
+                    Err(error) => {
+                        if context.error_reporting_enabled() {
+                            let slice = input.slice_from(start.position());
+                            errors.push(DeclarationBlockError::Rule(error, slice));
+                        }
+                    },
+                }
+                continue;
+            },
+            Err(..) => break,
+        };
+
+        let result = input.parse_until_after(Delimiter::Semicolon, |input| {
+            input.expect_colon()?;
+            parser.parse_value(name, input)
+        });
+
+        match result {
+            Ok(importance) => {
+                block.extend(parser.declarations.drain(), importance);
+                // We've successfully parsed a declaration, so forget about
+                // `last_parsed_property_id`. It'd be wrong to associate any
+                // following error with this property.
+                parser.last_parsed_property_id = None;
+            },
+            Err(error) => {
+                parser.declarations.clear();
+
+                // Something like `div:hover { .. }` starts like a declaration,
+                // but may still be a valid nested style rule.
+                let end = input.state();
+                input.reset(&start);
+                if let Ok(rule) = parse_nested_qualified_rule(input, rule_parser) {
+                    rules.push(rule);
+                    parser.last_parsed_property_id = None;
+                    continue;
+                }
+                input.reset(&end);
+
+                if context.error_reporting_enabled() {
+                    let property = parser.last_parsed_property_id.take();
+                    let slice = input.slice_from(start.position());
+                    errors.push(DeclarationBlockError::Declaration(error, slice, property));
+                }
+            },
+        }
+    }
+
+    if !errors.is_empty() {
+        report_css_errors(context, &block, selectors, &mut errors)
+    }
+
+    (block, rules)
+}
+
+/// Parses a qualified rule nested in a declaration block. Unlike top-level
+/// qualified rules, the prelude of nested rules ends at a semicolon.
+fn parse_nested_qualified_rule<'i, 't, P, R>(
+    input: &mut Parser<'i, 't>,
+    parser: &mut P,
+) -> Result<R, ParseError<'i>>
+where
+    P: QualifiedRuleParser<'i, QualifiedRule = R, Error = StyleParseErrorKind<'i>>,
+{
+    let start = input.state();
+    let delimiters = Delimiter::Semicolon | Delimiter::CurlyBracketBlock;
+    let prelude = input.parse_until_before(delimiters, |input| parser.parse_prelude(input));
+    match *input.next()? {
+        Token::CurlyBracketBlock => {},
+        ref t => {
+            let t = t.clone();
+            return Err(input.new_unexpected_token_error(t));
+        },
+    }
+    let prelude = prelude?;
+    input.parse_nested_block(|input| parser.parse_block(prelude, &start, input))
+}
+
+/// Parses an at-rule nested in a declaration block, whose at-keyword token
+/// has already been consumed.
+fn parse_nested_at_rule<'i, 't, P, R>(
+    start: &ParserState,
+    name: CowRcStr<'i>,
+    input: &mut Parser<'i, 't>,
+    parser: &mut P,
+) -> Result<R, ParseError<'i>>
+where
+    P: AtRuleParser<'i, AtRule = R, Error = StyleParseErrorKind<'i>>,
+{
+    let delimiters = Delimiter::Semicolon | Delimiter::CurlyBracketBlock;
+    let prelude = input.parse_until_before(delimiters, |input| parser.parse_prelude(name, input));
+    let prelude = match prelude {
+        Ok(prelude) => prelude,
+        Err(error) => {
+            // Skip the rest of the rule, including its block if any.
+            let _ = input.next();
+            return Err(error);
+        },
+    };
+    match input.next() {
+        Ok(&Token::CurlyBracketBlock) => {
+            input.parse_nested_block(|input| parser.parse_block(prelude, start, input))
+        },
+        _ => parser
+            .rule_without_block(prelude, start)
+            .map_err(|()| input.new_custom_error(StyleParseErrorKind::UnspecifiedError)),
+    }
+}
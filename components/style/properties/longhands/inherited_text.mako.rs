@@ -148,51 +148,60 @@ ${helpers.predefined_type(
     servo_restyle_damage="rebuild_and_reflow",
 )}
 
-<%helpers:single_keyword
-    name="white-space"
-    values="normal pre nowrap pre-wrap pre-line"
-    engines="gecko servo-2013 servo-2020",
-    extra_gecko_values="break-spaces -moz-pre-space"
-    gecko_enum_prefix="StyleWhiteSpace"
-    needs_conversion="True"
-    animation_value_type="discrete"
-    spec="https://drafts.csswg.org/css-text/#propdef-white-space"
-    servo_restyle_damage="rebuild_and_reflow"
->
-    % if engine in ["servo-2013", "servo-2020"]:
-    impl SpecifiedValue {
-        pub fn allow_wrap(&self) -> bool {
-            match *self {
-                SpecifiedValue::Nowrap |
-                SpecifiedValue::Pre => false,
-                SpecifiedValue::Normal |
-                SpecifiedValue::PreWrap |
-                SpecifiedValue::PreLine => true,
-            }
-        }
-
-        pub fn preserve_newlines(&self) -> bool {
-            match *self {
-                SpecifiedValue::Normal |
-                SpecifiedValue::Nowrap => false,
-                SpecifiedValue::Pre |
-                SpecifiedValue::PreWrap |
-                SpecifiedValue::PreLine => true,
-            }
-        }
-
-        pub fn preserve_spaces(&self) -> bool {
-            match *self {
-                SpecifiedValue::Normal |
-                SpecifiedValue::Nowrap |
-                SpecifiedValue::PreLine => false,
-                SpecifiedValue::Pre |
-                SpecifiedValue::PreWrap => true,
-            }
-        }
-    }
-    % endif
-</%helpers:single_keyword>
+// Gecko still uses the legacy single white-space longhand, see the
+// white-space shorthand for the CSS Text Module Level 4 split.
+${helpers.single_keyword(
+    "white-space",
+    "normal pre nowrap pre-wrap pre-line",
+    engines="gecko",
+    extra_gecko_values="break-spaces -moz-pre-space",
+    gecko_enum_prefix="StyleWhiteSpace",
+    needs_conversion="True",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-text/#propdef-white-space",
+)}
+
+// CSS Text Module Level 4
+
+${helpers.predefined_type(
+    "white-space-collapse",
+    "WhiteSpaceCollapse",
+    "computed::WhiteSpaceCollapse::Collapse",
+    engines="servo-2013 servo-2020",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-text-4/#propdef-white-space-collapse",
+    servo_restyle_damage="rebuild_and_reflow",
+)}
+
+${helpers.predefined_type(
+    "text-wrap-mode",
+    "TextWrapMode",
+    "computed::TextWrapMode::Wrap",
+    engines="servo-2013 servo-2020",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-text-4/#propdef-text-wrap-mode",
+    servo_restyle_damage="rebuild_and_reflow",
+)}
+
+${helpers.predefined_type(
+    "text-wrap-style",
+    "TextWrapStyle",
+    "computed::TextWrapStyle::Auto",
+    engines="servo-2013 servo-2020",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-text-4/#propdef-text-wrap-style",
+    servo_restyle_damage="reflow",
+)}
+
+${helpers.predefined_type(
+    "text-spacing-trim",
+    "TextSpacingTrim",
+    "computed::TextSpacingTrim::Normal",
+    engines="servo-2013 servo-2020",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-text-4/#text-spacing-trim-property",
+    servo_restyle_damage="rebuild_and_reflow",
+)}
 
 ${helpers.predefined_type(
     "text-shadow",
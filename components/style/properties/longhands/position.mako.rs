@@ -372,9 +372,11 @@ ${helpers.predefined_type(
             "grid-%s-%s" % (kind, range),
             "GridLine",
             "Default::default()",
-            engines="gecko",
+            engines="gecko servo-2020",
+            servo_2020_pref="layout.grid.enabled",
             animation_value_type="discrete",
             spec="https://drafts.csswg.org/css-grid/#propdef-grid-%s-%s" % (kind, range),
+            servo_restyle_damage="reflow",
         )}
     % endfor
 
@@ -382,18 +384,22 @@ ${helpers.predefined_type(
         "grid-auto-%ss" % kind,
         "ImplicitGridTracks",
         "Default::default()",
-        engines="gecko",
+        engines="gecko servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         animation_value_type="discrete",
         spec="https://drafts.csswg.org/css-grid/#propdef-grid-auto-%ss" % kind,
+        servo_restyle_damage="reflow",
     )}
 
     ${helpers.predefined_type(
         "grid-template-%ss" % kind,
         "GridTemplateComponent",
         "specified::GenericGridTemplateComponent::None",
-        engines="gecko",
+        engines="gecko servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         spec="https://drafts.csswg.org/css-grid/#propdef-grid-template-%ss" % kind,
         animation_value_type="ComputedValue",
+        servo_restyle_damage="reflow",
     )}
 
 % endfor
@@ -412,27 +418,32 @@ ${helpers.predefined_type(
     "grid-auto-flow",
     "GridAutoFlow",
     "computed::GridAutoFlow::ROW",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-grid/#propdef-grid-auto-flow",
+    servo_restyle_damage="reflow",
 )}
 
 ${helpers.predefined_type(
     "grid-template-areas",
     "GridTemplateAreas",
     "computed::GridTemplateAreas::none()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-grid/#propdef-grid-template-areas",
+    servo_restyle_damage="reflow",
 )}
 
 ${helpers.predefined_type(
     "column-gap",
     "length::NonNegativeLengthPercentageOrNormal",
     "computed::length::NonNegativeLengthPercentageOrNormal::normal()",
-    engines="gecko servo-2013",
-    aliases="grid-column-gap" if engine == "gecko" else "",
+    engines="gecko servo-2013 servo-2020",
+    aliases="grid-column-gap" if engine in ["gecko", "servo-2020"] else "",
     servo_2013_pref="layout.columns.enabled",
+    servo_2020_pref="layout.grid.enabled",
     spec="https://drafts.csswg.org/css-align-3/#propdef-column-gap",
     animation_value_type="NonNegativeLengthPercentageOrNormal",
     servo_restyle_damage="reflow",
@@ -443,7 +454,8 @@ ${helpers.predefined_type(
     "row-gap",
     "length::NonNegativeLengthPercentageOrNormal",
     "computed::length::NonNegativeLengthPercentageOrNormal::normal()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     aliases="grid-row-gap",
     spec="https://drafts.csswg.org/css-align-3/#propdef-row-gap",
     animation_value_type="NonNegativeLengthPercentageOrNormal",
@@ -79,3 +79,13 @@ ${helpers.predefined_type(
    animation_value_type="ComputedValue",
    spec="https://drafts.csswg.org/css-text-decor-4/#text-decoration-width-property"
 )}
+
+${helpers.predefined_type(
+    "white-space-trim",
+    "WhiteSpaceTrim",
+    "computed::WhiteSpaceTrim::NONE",
+    engines="servo-2013 servo-2020",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-text-4/#propdef-white-space-trim",
+    servo_restyle_damage="rebuild_and_reflow",
+)}
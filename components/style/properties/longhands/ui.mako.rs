@@ -186,6 +186,16 @@ ${helpers.predefined_type(
     spec="https://drafts.csswg.org/css-transitions/#propdef-transition-delay",
 )}
 
+${helpers.single_keyword(
+    "transition-behavior",
+    "normal allow-discrete",
+    engines="servo-2013 servo-2020",
+    vector=True,
+    need_index=True,
+    animation_value_type="none",
+    spec="https://drafts.csswg.org/css-transitions-2/#transition-behavior-property",
+)}
+
 <% animation_extra_prefixes = "moz:layout.css.prefixes.animations webkit" %>
 
 ${helpers.predefined_type(
@@ -291,7 +301,7 @@ ${helpers.single_keyword(
 ${helpers.single_keyword(
     "animation-composition",
     "replace add accumulate",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     need_index=True,
     animation_value_type="none",
     vector=True,
@@ -319,7 +329,7 @@ ${helpers.predefined_type(
     "animation-timeline",
     "AnimationTimeline",
     "computed::AnimationTimeline::auto()",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     initial_specified_value="specified::AnimationTimeline::auto()",
     vector=True,
     need_index=True,
@@ -335,7 +345,7 @@ ${helpers.predefined_type(
     "computed::ScrollTimelineName::none()",
     vector=True,
     need_index=True,
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     animation_value_type="none",
     gecko_pref="layout.css.scroll-driven-animations.enabled",
     spec="https://drafts.csswg.org/scroll-animations-1/#scroll-timeline-name",
@@ -348,7 +358,7 @@ ${helpers.predefined_type(
     "computed::ScrollAxis::default()",
     vector=True,
     need_index=True,
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     animation_value_type="none",
     gecko_pref="layout.css.scroll-driven-animations.enabled",
     spec="https://drafts.csswg.org/scroll-animations-1/#scroll-timeline-axis",
@@ -361,7 +371,7 @@ ${helpers.predefined_type(
     "computed::ScrollTimelineName::none()",
     vector=True,
     need_index=True,
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     animation_value_type="none",
     gecko_pref="layout.css.scroll-driven-animations.enabled",
     spec="https://drafts.csswg.org/scroll-animations-1/#view-timeline-name",
@@ -374,7 +384,7 @@ ${helpers.predefined_type(
     "computed::ScrollAxis::default()",
     vector=True,
     need_index=True,
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     animation_value_type="none",
     gecko_pref="layout.css.scroll-driven-animations.enabled",
     spec="https://drafts.csswg.org/scroll-animations-1/#view-timeline-axis",
@@ -387,9 +397,24 @@ ${helpers.predefined_type(
     "computed::ViewTimelineInset::zero()",
     vector=True,
     need_index=True,
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     animation_value_type="none",
     gecko_pref="layout.css.scroll-driven-animations.enabled",
     spec="https://drafts.csswg.org/scroll-animations-1/#view-timeline-axis",
     rule_types_allowed=DEFAULT_RULES_EXCEPT_KEYFRAME,
 )}
+
+% for edge in ["start", "end"]:
+${helpers.predefined_type(
+    "animation-range-" + edge,
+    "AnimationRange",
+    "computed::AnimationRange::normal()",
+    engines="servo-2013 servo-2020",
+    initial_specified_value="specified::AnimationRange::normal()",
+    vector=True,
+    need_index=True,
+    animation_value_type="none",
+    spec="https://drafts.csswg.org/scroll-animations-1/#animation-range-" + edge,
+    rule_types_allowed=DEFAULT_RULES_EXCEPT_KEYFRAME,
+)}
+% endfor
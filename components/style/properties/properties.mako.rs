@@ -27,7 +27,7 @@ use crate::context::QuirksMode;
 #[cfg(feature = "servo")] use crate::logical_geometry::LogicalMargin;
 #[cfg(feature = "servo")] use crate::computed_values;
 use crate::logical_geometry::WritingMode;
-use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use malloc_size_of::{MallocConditionalSizeOf, MallocSizeOf, MallocSizeOfOps};
 use crate::computed_value_flags::*;
 use fxhash::FxHashMap;
 use crate::media_queries::Device;
@@ -1676,13 +1676,14 @@ impl ShorthandId {
 }
 
 /// An unparsed property value that contains `var()` functions.
-#[derive(Debug, Eq, PartialEq, ToShmem)]
+#[derive(Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
 pub struct UnparsedValue {
     /// The css serialization for this value.
     css: String,
     /// The first token type for this serialization.
     first_token_type: TokenSerializationType,
     /// The url data for resolving url values.
+    #[ignore_malloc_size_of = "Arc"]
     url_data: UrlExtraData,
     /// The shorthand this came from.
     from_shorthand: Option<ShorthandId>,
@@ -2220,8 +2221,7 @@ impl PropertyId {
 }
 
 /// A declaration using a CSS-wide keyword.
-#[cfg_attr(feature = "gecko", derive(MallocSizeOf))]
-#[derive(Clone, PartialEq, ToCss, ToShmem)]
+#[derive(Clone, MallocSizeOf, PartialEq, ToCss, ToShmem)]
 pub struct WideKeywordDeclaration {
     #[css(skip)]
     id: LonghandId,
@@ -2230,15 +2230,31 @@ pub struct WideKeywordDeclaration {
 }
 
 /// An unparsed declaration that contains `var()` functions.
-#[cfg_attr(feature = "gecko", derive(MallocSizeOf))]
 #[derive(Clone, PartialEq, ToCss, ToShmem)]
 pub struct VariableDeclaration {
     #[css(skip)]
     id: LonghandId,
-    #[cfg_attr(feature = "gecko", ignore_malloc_size_of = "XXX: how to handle this?")]
     value: Arc<UnparsedValue>,
 }
 
+impl MallocSizeOf for VariableDeclaration {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        // The value is shared between all the longhands of a shorthand, so
+        // only the first of them measures it.
+        self.value.conditional_size_of(ops)
+    }
+}
+
+impl VariableDeclaration {
+    /// The CSS text of the declared value, before `var()` substitution.
+    ///
+    /// For declarations coming from a shorthand, this is the value of the
+    /// whole shorthand.
+    pub fn css(&self) -> &str {
+        &self.value.css
+    }
+}
+
 /// A custom property declaration value is either an unparsed value or a CSS
 /// wide-keyword.
 #[derive(Clone, PartialEq, ToCss, ToShmem)]
@@ -2249,15 +2265,22 @@ pub enum CustomDeclarationValue {
     CSSWideKeyword(CSSWideKeyword),
 }
 
+impl MallocSizeOf for CustomDeclarationValue {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        match *self {
+            CustomDeclarationValue::Value(ref value) => value.conditional_size_of(ops),
+            CustomDeclarationValue::CSSWideKeyword(..) => 0,
+        }
+    }
+}
+
 /// A custom property declaration with the property name and the declared value.
-#[cfg_attr(feature = "gecko", derive(MallocSizeOf))]
-#[derive(Clone, PartialEq, ToCss, ToShmem)]
+#[derive(Clone, MallocSizeOf, PartialEq, ToCss, ToShmem)]
 pub struct CustomDeclaration {
     /// The name of the custom property.
     #[css(skip)]
     pub name: crate::custom_properties::Name,
     /// The value of the custom property.
-    #[cfg_attr(feature = "gecko", ignore_malloc_size_of = "XXX: how to handle this?")]
     pub value: CustomDeclarationValue,
 }
 
@@ -2954,16 +2977,20 @@ pub mod style_structs {
             }
 
             /// Returns true if animation properties are equal between styles, but without
-            /// considering keyframe data and animation-timeline.
+            /// considering keyframe data.
             #[cfg(feature = "servo")]
             pub fn animations_equals(&self, other: &Self) -> bool {
                 self.animation_name_iter().eq(other.animation_name_iter()) &&
+                self.animation_composition_iter().eq(other.animation_composition_iter()) &&
                 self.animation_delay_iter().eq(other.animation_delay_iter()) &&
                 self.animation_direction_iter().eq(other.animation_direction_iter()) &&
                 self.animation_duration_iter().eq(other.animation_duration_iter()) &&
                 self.animation_fill_mode_iter().eq(other.animation_fill_mode_iter()) &&
                 self.animation_iteration_count_iter().eq(other.animation_iteration_count_iter()) &&
                 self.animation_play_state_iter().eq(other.animation_play_state_iter()) &&
+                self.animation_range_end_iter().eq(other.animation_range_end_iter()) &&
+                self.animation_range_start_iter().eq(other.animation_range_start_iter()) &&
+                self.animation_timeline_iter().eq(other.animation_timeline_iter()) &&
                 self.animation_timing_function_iter().eq(other.animation_timing_function_iter())
             }
 
@@ -3335,6 +3362,30 @@ impl ComputedValuesInner {
         }
     % endfor
 
+    /// Sets the computed values of the given custom properties, which is used
+    /// to apply the animated values of registered custom properties to the
+    /// before-change style of transitions.
+    ///
+    /// Other styles get these values from the animation and transition rules
+    /// instead, so that they're substituted into other properties.
+    pub fn set_custom_properties<I>(&mut self, values: I)
+    where
+        I: IntoIterator<
+            Item = (crate::custom_properties::Name, Arc<crate::custom_properties::VariableValue>),
+        >,
+    {
+        let mut values = values.into_iter().peekable();
+        if values.peek().is_none() {
+            return;
+        }
+        let mut map = match self.custom_properties {
+            Some(ref map) => (**map).clone(),
+            None => Default::default(),
+        };
+        map.extend(values);
+        self.custom_properties = Some(Arc::new(map));
+    }
+
     /// Gets a reference to the rule node. Panic if no rule node exists.
     pub fn rules(&self) -> &StrongRuleNode {
         self.rules.as_ref().unwrap()
@@ -4043,6 +4094,13 @@ impl<'a> StyleBuilder<'a> {
     /// ::first-line case some of the inherited information needs to come from
     /// one ComputedValues instance and some from a different one.
 
+    /// The custom properties of our parent, which for container query
+    /// evaluation are the ones of the query container.
+    #[inline]
+    pub fn inherited_custom_properties(&self) -> Option<<&Arc<crate::custom_properties::CustomPropertiesMap>> {
+        self.inherited_style.custom_properties()
+    }
+
     /// Inherited writing-mode.
     pub fn inherited_writing_mode(&self) -> &WritingMode {
         &self.inherited_style.writing_mode
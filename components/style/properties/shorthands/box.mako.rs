@@ -147,11 +147,12 @@ ${helpers.two_properties_shorthand(
 
 <%helpers:shorthand name="offset"
                     engines="gecko"
-                    sub_properties="offset-path offset-distance offset-rotate offset-anchor"
+                    sub_properties="offset-path offset-distance offset-rotate offset-anchor
+                                    offset-position"
                     gecko_pref="layout.css.motion-path.enabled",
                     spec="https://drafts.fxtf.org/motion-1/#offset-shorthand">
     use crate::parser::Parse;
-    use crate::values::specified::motion::{OffsetPath, OffsetRotate};
+    use crate::values::specified::motion::{OffsetPath, OffsetPosition, OffsetRotate};
     use crate::values::specified::position::PositionOrAuto;
     use crate::values::specified::LengthPercentage;
     use crate::Zero;
@@ -160,27 +161,33 @@ ${helpers.two_properties_shorthand(
         context: &ParserContext,
         input: &mut Parser<'i, 't>,
     ) -> Result<Longhands, ParseError<'i>> {
-        // FIXME: Bug 1559232: Support offset-position.
-        // Per the spec, this must have offet-position and/or offset-path. However, we don't
-        // support offset-position, so offset-path is necessary now.
-        let offset_path = OffsetPath::parse(context, input)?;
+        // Per the spec, this must have offset-position and/or offset-path.
+        // OffsetPosition::parse() fails if offset-position is disabled.
+        let offset_position = input.try_parse(|i| OffsetPosition::parse(context, i)).ok();
+        let offset_path = input.try_parse(|i| OffsetPath::parse(context, i)).ok();
+        if offset_position.is_none() && offset_path.is_none() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
 
         let mut offset_distance = None;
         let mut offset_rotate = None;
-        loop {
-            if offset_distance.is_none() {
-                if let Ok(value) = input.try_parse(|i| LengthPercentage::parse(context, i)) {
-                    offset_distance = Some(value);
+        // offset-distance and offset-rotate are grouped with offset-path.
+        if offset_path.is_some() {
+            loop {
+                if offset_distance.is_none() {
+                    if let Ok(value) = input.try_parse(|i| LengthPercentage::parse(context, i)) {
+                        offset_distance = Some(value);
+                    }
                 }
-            }
 
-            if offset_rotate.is_none() {
-                if let Ok(value) = input.try_parse(|i| OffsetRotate::parse(context, i)) {
-                    offset_rotate = Some(value);
-                    continue;
+                if offset_rotate.is_none() {
+                    if let Ok(value) = input.try_parse(|i| OffsetRotate::parse(context, i)) {
+                        offset_rotate = Some(value);
+                        continue;
+                    }
                 }
+                break;
             }
-            break;
         }
 
         let offset_anchor = input.try_parse(|i| {
@@ -189,7 +196,8 @@ ${helpers.two_properties_shorthand(
         }).ok();
 
         Ok(expanded! {
-            offset_path: offset_path,
+            offset_position: offset_position.unwrap_or(OffsetPosition::normal()),
+            offset_path: offset_path.unwrap_or(OffsetPath::none()),
             offset_distance: offset_distance.unwrap_or(LengthPercentage::zero()),
             offset_rotate: offset_rotate.unwrap_or(OffsetRotate::auto()),
             offset_anchor: offset_anchor.unwrap_or(PositionOrAuto::auto()),
@@ -198,9 +206,23 @@ ${helpers.two_properties_shorthand(
 
     impl<'a> ToCss for LonghandsToSerialize<'a>  {
         fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
-            // FIXME: Bug 1559232: Support offset-position. We don't support offset-position,
-            // so always serialize offset-path now.
-            self.offset_path.to_css(dest)?;
+            let must_serialize_path = *self.offset_path != OffsetPath::None ||
+                !self.offset_distance.is_zero() ||
+                !self.offset_rotate.is_auto();
+            let position_is_default = self.offset_position.is_normal();
+
+            if !position_is_default {
+                self.offset_position.to_css(dest)?;
+            }
+
+            // Serialize offset-path if offset-position is normal, so we never
+            // produce an empty string.
+            if must_serialize_path || position_is_default {
+                if !position_is_default {
+                    dest.write_char(' ')?;
+                }
+                self.offset_path.to_css(dest)?;
+            }
 
             if !self.offset_distance.is_zero() {
                 dest.write_char(' ')?;
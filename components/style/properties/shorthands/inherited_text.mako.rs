@@ -89,3 +89,158 @@
         }
     }
 </%helpers:shorthand>
+
+// CSS Text Module Level 4
+// https://drafts.csswg.org/css-text-4/
+<%helpers:shorthand
+    name="white-space"
+    engines="servo-2013 servo-2020"
+    sub_properties="white-space-collapse text-wrap-mode white-space-trim"
+    spec="https://drafts.csswg.org/css-text-4/#white-space-property"
+>
+    use crate::properties::longhands::{text_wrap_mode, white_space_collapse, white_space_trim};
+    use crate::values::specified::{TextWrapMode, WhiteSpaceCollapse, WhiteSpaceTrim};
+    use style_traits::values::SequenceWriter;
+
+    fn parse_special_keyword<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Longhands, ParseError<'i>> {
+        let (collapse, mode) = try_match_ident_ignore_ascii_case! { input,
+            "normal" => (WhiteSpaceCollapse::Collapse, TextWrapMode::Wrap),
+            "pre" => (WhiteSpaceCollapse::Preserve, TextWrapMode::Nowrap),
+            "nowrap" => (WhiteSpaceCollapse::Collapse, TextWrapMode::Nowrap),
+            "pre-wrap" => (WhiteSpaceCollapse::Preserve, TextWrapMode::Wrap),
+            "pre-line" => (WhiteSpaceCollapse::PreserveBreaks, TextWrapMode::Wrap),
+        };
+        Ok(expanded! {
+            white_space_collapse: collapse,
+            text_wrap_mode: mode,
+            white_space_trim: WhiteSpaceTrim::NONE,
+        })
+    }
+
+    pub fn parse_value<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Longhands, ParseError<'i>> {
+        if let Ok(result) = input.try_parse(parse_special_keyword) {
+            return Ok(result);
+        }
+
+        let mut collapse = None;
+        let mut mode = None;
+        let mut trim = None;
+        loop {
+            if collapse.is_none() {
+                if let Ok(value) = input.try_parse(|input| white_space_collapse::parse(context, input)) {
+                    collapse = Some(value);
+                    continue
+                }
+            }
+            if mode.is_none() {
+                if let Ok(value) = input.try_parse(|input| text_wrap_mode::parse(context, input)) {
+                    mode = Some(value);
+                    continue
+                }
+            }
+            if trim.is_none() {
+                if let Ok(value) = input.try_parse(|input| white_space_trim::parse(context, input)) {
+                    trim = Some(value);
+                    continue
+                }
+            }
+            break
+        }
+
+        if collapse.is_none() && mode.is_none() && trim.is_none() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(expanded! {
+            white_space_collapse: unwrap_or_initial!(white_space_collapse, collapse),
+            text_wrap_mode: unwrap_or_initial!(text_wrap_mode, mode),
+            white_space_trim: unwrap_or_initial!(white_space_trim, trim),
+        })
+    }
+
+    impl<'a> ToCss for LonghandsToSerialize<'a> {
+        fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
+            if *self.white_space_trim == WhiteSpaceTrim::NONE {
+                let keyword = match (*self.white_space_collapse, *self.text_wrap_mode) {
+                    (WhiteSpaceCollapse::Collapse, TextWrapMode::Wrap) => Some("normal"),
+                    (WhiteSpaceCollapse::Preserve, TextWrapMode::Nowrap) => Some("pre"),
+                    (WhiteSpaceCollapse::Collapse, TextWrapMode::Nowrap) => Some("nowrap"),
+                    (WhiteSpaceCollapse::Preserve, TextWrapMode::Wrap) => Some("pre-wrap"),
+                    (WhiteSpaceCollapse::PreserveBreaks, TextWrapMode::Wrap) => Some("pre-line"),
+                    _ => None,
+                };
+                if let Some(keyword) = keyword {
+                    return dest.write_str(keyword);
+                }
+            }
+
+            let mut writer = SequenceWriter::new(dest, " ");
+            if *self.white_space_collapse != WhiteSpaceCollapse::Collapse {
+                writer.item(self.white_space_collapse)?;
+            }
+            if *self.text_wrap_mode != TextWrapMode::Wrap {
+                writer.item(self.text_wrap_mode)?;
+            }
+            if *self.white_space_trim != WhiteSpaceTrim::NONE {
+                writer.item(self.white_space_trim)?;
+            }
+            Ok(())
+        }
+    }
+</%helpers:shorthand>
+
+<%helpers:shorthand
+    name="text-wrap"
+    engines="servo-2013 servo-2020"
+    sub_properties="text-wrap-mode text-wrap-style"
+    spec="https://drafts.csswg.org/css-text-4/#text-wrap"
+>
+    use crate::properties::longhands::{text_wrap_mode, text_wrap_style};
+    use crate::values::specified::{TextWrapMode, TextWrapStyle};
+
+    pub fn parse_value<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Longhands, ParseError<'i>> {
+        let mut mode = None;
+        let mut style = None;
+        loop {
+            if mode.is_none() {
+                if let Ok(value) = input.try_parse(|input| text_wrap_mode::parse(context, input)) {
+                    mode = Some(value);
+                    continue
+                }
+            }
+            if style.is_none() {
+                if let Ok(value) = input.try_parse(|input| text_wrap_style::parse(context, input)) {
+                    style = Some(value);
+                    continue
+                }
+            }
+            break
+        }
+
+        if mode.is_none() && style.is_none() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(expanded! {
+            text_wrap_mode: unwrap_or_initial!(text_wrap_mode, mode),
+            text_wrap_style: unwrap_or_initial!(text_wrap_style, style),
+        })
+    }
+
+    impl<'a> ToCss for LonghandsToSerialize<'a> {
+        fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
+            if *self.text_wrap_style == TextWrapStyle::Auto {
+                return self.text_wrap_mode.to_css(dest);
+            }
+            if *self.text_wrap_mode != TextWrapMode::Wrap {
+                self.text_wrap_mode.to_css(dest)?;
+                dest.write_char(' ')?;
+            }
+            self.text_wrap_style.to_css(dest)
+        }
+    }
+</%helpers:shorthand>
@@ -22,10 +22,17 @@ macro_rules! try_parse_one {
                     extra_prefixes="moz:layout.css.prefixes.transitions webkit"
                     sub_properties="transition-property transition-duration
                                     transition-timing-function
-                                    transition-delay"
+                                    transition-delay
+                                    ${'' if engine == 'gecko' else 'transition-behavior'}"
                     spec="https://drafts.csswg.org/css-transitions/#propdef-transition">
+    <%
+        # Gecko doesn't support transition-behavior yet.
+        props = "duration timing_function delay".split()
+        if engine != "gecko":
+            props.append("behavior")
+    %>
     use crate::parser::Parse;
-    % for prop in "delay duration property timing_function".split():
+    % for prop in ["property"] + props:
     use crate::properties::longhands::transition_${prop};
     % endfor
     use crate::values::specified::TransitionProperty;
@@ -35,7 +42,7 @@ macro_rules! try_parse_one {
         input: &mut Parser<'i, 't>,
     ) -> Result<Longhands, ParseError<'i>> {
         struct SingleTransition {
-            % for prop in "duration timing_function delay".split():
+            % for prop in props:
             transition_${prop}: transition_${prop}::SingleSpecifiedValue,
             % endfor
             // Unlike other properties, transition-property uses an Option<> to
@@ -47,7 +54,7 @@ macro_rules! try_parse_one {
             context: &ParserContext,
             input: &mut Parser<'i, 't>,
         ) -> Result<SingleTransition,ParseError<'i>> {
-            % for prop in "property duration timing_function delay".split():
+            % for prop in ["property"] + props:
             let mut ${prop} = None;
             % endfor
 
@@ -58,6 +65,9 @@ macro_rules! try_parse_one {
                 try_parse_one!(context, input, duration, transition_duration);
                 try_parse_one!(context, input, timing_function, transition_timing_function);
                 try_parse_one!(context, input, delay, transition_delay);
+                % if engine != "gecko":
+                try_parse_one!(context, input, behavior, transition_behavior);
+                % endif
                 // Must check 'transition-property' after 'transition-timing-function' since
                 // 'transition-property' accepts any keyword.
                 if property.is_none() {
@@ -80,7 +90,7 @@ macro_rules! try_parse_one {
 
             if parsed != 0 {
                 Ok(SingleTransition {
-                    % for prop in "duration timing_function delay".split():
+                    % for prop in props:
                     transition_${prop}: ${prop}.unwrap_or_else(transition_${prop}::single_value
                                                                                  ::get_initial_specified_value),
                     % endfor
@@ -92,7 +102,7 @@ macro_rules! try_parse_one {
             }
         }
 
-        % for prop in "property duration timing_function delay".split():
+        % for prop in ["property"] + props:
         let mut ${prop}s = Vec::new();
         % endfor
 
@@ -108,13 +118,13 @@ macro_rules! try_parse_one {
                 return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
             }
 
-            % for prop in "duration timing_function delay".split():
+            % for prop in props:
             ${prop}s.push(result.transition_${prop});
             % endfor
         }
 
         Ok(expanded! {
-            % for prop in "property duration timing_function delay".split():
+            % for prop in ["property"] + props:
             transition_${prop}: transition_${prop}::SpecifiedValue(${prop}s.into()),
             % endfor
         })
@@ -128,13 +138,13 @@ macro_rules! try_parse_one {
             // * when all value lists have the same length, or
             // * when transition-property is none, and other value lists have exactly one item.
             if property_len == 0 {
-                % for name in "duration delay timing_function".split():
+                % for name in props:
                     if self.transition_${name}.0.len() != 1 {
                         return Ok(());
                     }
                 % endfor
             } else {
-                % for name in "duration delay timing_function".split():
+                % for name in props:
                     if self.transition_${name}.0.len() != property_len {
                         return Ok(());
                     }
@@ -157,6 +167,12 @@ macro_rules! try_parse_one {
                     dest.write_char(' ')?;
                     self.transition_${name}.0[i].to_css(dest)?;
                 % endfor
+                % if engine != "gecko":
+                    if self.transition_behavior.0[i] != transition_behavior::single_value::get_initial_value() {
+                        dest.write_char(' ')?;
+                        self.transition_behavior.0[i].to_css(dest)?;
+                    }
+                % endif
             }
             Ok(())
         }
@@ -310,7 +326,7 @@ macro_rules! try_parse_one {
 </%helpers:shorthand>
 
 <%helpers:shorthand
-    engines="gecko"
+    engines="gecko servo-2013 servo-2020"
     name="scroll-timeline"
     sub_properties="scroll-timeline-name scroll-timeline-axis"
     gecko_pref="layout.css.scroll-driven-animations.enabled",
@@ -369,7 +385,7 @@ macro_rules! try_parse_one {
 
 // Note: view-timeline shorthand doesn't take view-timeline-inset into account.
 <%helpers:shorthand
-    engines="gecko"
+    engines="gecko servo-2013 servo-2020"
     name="view-timeline"
     sub_properties="view-timeline-name view-timeline-axis"
     gecko_pref="layout.css.scroll-driven-animations.enabled",
@@ -425,3 +441,73 @@ macro_rules! try_parse_one {
         }
     }
 </%helpers:shorthand>
+
+<%helpers:shorthand
+    engines="servo-2013 servo-2020"
+    name="animation-range"
+    sub_properties="animation-range-start animation-range-end"
+    spec="https://drafts.csswg.org/scroll-animations-1/#animation-range"
+>
+    use crate::values::specified::AnimationRange;
+
+    /// The animation-range-end value implied by an animation-range-start value
+    /// when the shorthand omits it: the end of the same named range, or
+    /// `normal` otherwise.
+    fn implied_end(start: &AnimationRange) -> AnimationRange {
+        match *start {
+            AnimationRange::Named(name, None) => AnimationRange::Named(name, None),
+            _ => AnimationRange::Normal,
+        }
+    }
+
+    pub fn parse_value<'i>(
+        context: &ParserContext,
+        input: &mut Parser<'i, '_>,
+    ) -> Result<Longhands, ParseError<'i>> {
+        use crate::properties::longhands::{animation_range_end, animation_range_start};
+
+        let mut starts = Vec::with_capacity(1);
+        let mut ends = Vec::with_capacity(1);
+        input.parse_comma_separated(|input| {
+            let start = animation_range_start::single_value::parse(context, input)?;
+            let end = input
+                .try_parse(|i| animation_range_end::single_value::parse(context, i))
+                .unwrap_or_else(|_| implied_end(&start));
+
+            starts.push(start);
+            ends.push(end);
+
+            Ok(())
+        })?;
+
+        Ok(expanded! {
+            animation_range_start: animation_range_start::SpecifiedValue(starts.into()),
+            animation_range_end: animation_range_end::SpecifiedValue(ends.into()),
+        })
+    }
+
+    impl<'a> ToCss for LonghandsToSerialize<'a>  {
+        fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
+            let len = self.animation_range_start.0.len();
+            if len != self.animation_range_end.0.len() {
+                return Ok(());
+            }
+
+            for i in 0..len {
+                if i != 0 {
+                    dest.write_str(", ")?;
+                }
+
+                let start = &self.animation_range_start.0[i];
+                let end = &self.animation_range_end.0[i];
+                start.to_css(dest)?;
+
+                if *end != implied_end(start) {
+                    dest.write_char(' ')?;
+                    end.to_css(dest)?;
+                }
+            }
+            Ok(())
+        }
+    }
+</%helpers:shorthand>
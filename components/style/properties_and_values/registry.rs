@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Registered custom properties.
+
+use super::rule::PropertyRule;
+use super::syntax::Descriptor;
+use super::value::ComputedValue;
+use crate::custom_properties::VariableValue;
+use crate::media_queries::Device;
+use crate::stylesheets::UrlExtraData;
+use crate::values::computed::Context;
+use selectors::matching::QuirksMode;
+use servo_arc::Arc;
+
+/// A custom property registration, as collected from an `@property` rule.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#custom-property-registration>
+#[derive(Clone, Debug, MallocSizeOf)]
+pub struct PropertyRegistration {
+    /// The syntax of the property.
+    pub syntax: Descriptor,
+    /// Whether the property inherits.
+    pub inherits: bool,
+    /// The computed initial value of the property, serialized as a token
+    /// stream, or `None` for the guaranteed-invalid value.
+    #[ignore_malloc_size_of = "Arc"]
+    pub initial_value: Option<Arc<VariableValue>>,
+    /// The url data to resolve urls in values of the property.
+    #[ignore_malloc_size_of = "Arc"]
+    pub url_data: UrlExtraData,
+}
+
+impl PropertyRegistration {
+    /// Creates a registration from a valid `@property` rule, computing its
+    /// initial value.
+    pub fn new(rule: &PropertyRule, device: &Device, quirks_mode: QuirksMode) -> Self {
+        let initial_value = rule.initial_value.as_ref().map(|initial_value| {
+            if rule.syntax.is_universal() {
+                return initial_value.clone();
+            }
+            // The initial value was validated at parse time, and is
+            // computationally independent, so it doesn't depend on any
+            // element, and this can't fail.
+            Context::for_media_query_evaluation(device, quirks_mode, |context| {
+                ComputedValue::compute(initial_value, &rule.syntax, &rule.url_data, context)
+            })
+            .and_then(|computed| computed.to_variable_value())
+            .unwrap_or_else(|()| initial_value.clone())
+        });
+        Self {
+            syntax: rule.syntax.clone(),
+            inherits: rule.inherits,
+            initial_value,
+            url_data: rule.url_data.clone(),
+        }
+    }
+
+    /// Whether values of this property have a type, and thus need to be
+    /// computed and can be interpolated.
+    #[inline]
+    pub fn is_typed(&self) -> bool {
+        !self.syntax.is_universal()
+    }
+
+    /// Parses and computes the given value according to the syntax of this
+    /// registration, in the given context.
+    pub fn compute(&self, value: &VariableValue, context: &Context) -> Result<ComputedValue, ()> {
+        ComputedValue::compute(value, &self.syntax, &self.url_data, context)
+    }
+
+    /// Parses a computed value of this property, as stored in the custom
+    /// properties map, e.g. to interpolate it.
+    ///
+    /// Computed values don't depend on the style of any element, so no
+    /// element context is needed to parse them.
+    pub fn parse_computed(
+        &self,
+        value: &VariableValue,
+        device: &Device,
+        quirks_mode: QuirksMode,
+    ) -> Result<ComputedValue, ()> {
+        Context::for_media_query_evaluation(device, quirks_mode, |context| {
+            self.compute(value, context)
+        })
+    }
+}
@@ -0,0 +1,274 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The [`@property`] at-rule.
+//!
+//! [`@property`]: https://drafts.css-houdini.org/css-properties-values-api-1/#at-property-rule
+
+use super::syntax::Descriptor;
+use super::value::SpecifiedValue as SpecifiedRegisteredValue;
+use crate::custom_properties::{self, Name as CustomPropertyName, SpecifiedValue};
+use crate::error_reporting::ContextualParseError;
+use crate::parser::{Parse, ParserContext};
+use crate::shared_lock::{SharedRwLockReadGuard, ToCssWithGuard};
+use crate::str::CssStringWriter;
+use crate::stylesheets::UrlExtraData;
+use crate::values::serialize_atom_name;
+use crate::values::specified::NoCalcLength;
+use crate::Atom;
+use cssparser::{AtRuleParser, CowRcStr, DeclarationListParser, DeclarationParser, Parser};
+use cssparser::{ParserInput, QualifiedRuleParser, SourceLocation, Token};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalSizeOf};
+use servo_arc::Arc;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// The name of a custom property registered by an `@property` rule, without
+/// the leading `--`.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct PropertyRuleName(pub CustomPropertyName);
+
+impl Parse for PropertyRuleName {
+    fn parse<'i, 't>(
+        _context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        match custom_properties::parse_name(ident) {
+            Ok(name) => Ok(PropertyRuleName(Atom::from(name))),
+            Err(()) => Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+        }
+    }
+}
+
+impl ToCss for PropertyRuleName {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        dest.write_str("--")?;
+        serialize_atom_name(&self.0, dest)
+    }
+}
+
+/// A `@property` rule, which registers a custom property.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#at-property-rule>
+#[derive(Clone, Debug, ToShmem)]
+pub struct PropertyRule {
+    /// The name of the registered custom property.
+    pub name: PropertyRuleName,
+    /// The syntax of the custom property.
+    pub syntax: Descriptor,
+    /// Whether the custom property is inherited.
+    pub inherits: bool,
+    /// The initial value of the custom property, which is only allowed to be
+    /// missing if the syntax is universal.
+    pub initial_value: Option<Arc<SpecifiedValue>>,
+    /// The url data of the stylesheet the rule comes from, used to resolve
+    /// urls in values of the registered property.
+    pub url_data: UrlExtraData,
+    /// The line and column of the rule's source code.
+    pub source_location: SourceLocation,
+}
+
+impl PropertyRule {
+    /// Parses the block of a `@property` rule, returning an error if it
+    /// doesn't describe a valid registration.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api-1/#the-syntax-descriptor>
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        name: PropertyRuleName,
+        location: SourceLocation,
+    ) -> Result<Self, ParseError<'i>> {
+        let start = input.current_source_location();
+        let mut descriptors = PropertyDescriptors::default();
+        {
+            let parser = PropertyRuleParser {
+                descriptors: &mut descriptors,
+            };
+            let mut iter = DeclarationListParser::new(input, parser);
+            while let Some(declaration) = iter.next() {
+                if let Err((error, slice)) = declaration {
+                    let location = error.location;
+                    let error = ContextualParseError::UnsupportedPropertyDescriptor(slice, error);
+                    context.log_css_error(location, error);
+                }
+            }
+        }
+
+        // The syntax and inherits descriptors are required, and so is the
+        // initial value if the syntax isn't universal.
+        let invalid = || start.new_custom_error(StyleParseErrorKind::UnspecifiedError);
+        let (syntax, inherits) = match (descriptors.syntax, descriptors.inherits) {
+            (Some(syntax), Some(inherits)) => (syntax, inherits),
+            _ => return Err(invalid()),
+        };
+        if !syntax.is_universal() {
+            let initial_value = descriptors.initial_value.as_ref().ok_or_else(invalid)?;
+            if !is_valid_initial_value(context, &syntax, initial_value) {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self {
+            name,
+            syntax,
+            inherits,
+            initial_value: descriptors.initial_value,
+            url_data: context.url_data.clone(),
+            source_location: location,
+        })
+    }
+
+    /// Measure heap usage.
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let mut n = self.syntax.size_of(ops);
+        if let Some(ref initial_value) = self.initial_value {
+            n += initial_value.unconditional_size_of(ops);
+        }
+        n
+    }
+}
+
+impl ToCssWithGuard for PropertyRule {
+    /// <https://drafts.css-houdini.org/css-properties-values-api-1/#serialize-a-csspropertyrule>
+    fn to_css(&self, _guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        dest.write_str("@property ")?;
+        self.name.to_css(&mut CssWriter::new(dest))?;
+        dest.write_str(" { syntax: ")?;
+        self.syntax.to_css(&mut CssWriter::new(dest))?;
+        dest.write_str("; inherits: ")?;
+        dest.write_str(if self.inherits { "true" } else { "false" })?;
+        dest.write_str("; ")?;
+        if let Some(ref initial_value) = self.initial_value {
+            dest.write_str("initial-value: ")?;
+            initial_value.to_css(&mut CssWriter::new(dest))?;
+            dest.write_str("; ")?;
+        }
+        dest.write_char('}')
+    }
+}
+
+/// Whether `initial_value` parses according to `syntax`, and is
+/// computationally independent.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#initial-value-descriptor>
+fn is_valid_initial_value(
+    context: &ParserContext,
+    syntax: &Descriptor,
+    initial_value: &SpecifiedValue,
+) -> bool {
+    if initial_value.has_references() {
+        return false;
+    }
+    let css = initial_value.to_css_string();
+    let mut input = ParserInput::new(&css);
+    let mut input = Parser::new(&mut input);
+    if SpecifiedRegisteredValue::parse(context, &mut input, syntax).is_err() {
+        return false;
+    }
+    let mut input = ParserInput::new(&css);
+    is_computationally_independent(context, &mut Parser::new(&mut input))
+}
+
+/// Whether the given tokens don't contain any relative lengths, which would
+/// need an element to be resolved.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#computationally-independent>
+fn is_computationally_independent(context: &ParserContext, input: &mut Parser) -> bool {
+    loop {
+        let token = match input.next() {
+            Ok(token) => token.clone(),
+            Err(..) => return true,
+        };
+        match token {
+            Token::Dimension {
+                value, ref unit, ..
+            } => match NoCalcLength::parse_dimension(context, value, unit) {
+                Ok(NoCalcLength::Absolute(..)) | Err(()) => {},
+                Ok(..) => return false,
+            },
+            Token::Function(..) |
+            Token::ParenthesisBlock |
+            Token::SquareBracketBlock |
+            Token::CurlyBracketBlock => {
+                let independent = input.parse_nested_block(|input| {
+                    Ok::<_, ParseError>(is_computationally_independent(context, input))
+                });
+                if !matches!(independent, Ok(true)) {
+                    return false;
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+#[derive(Default)]
+struct PropertyDescriptors {
+    syntax: Option<Descriptor>,
+    inherits: Option<bool>,
+    initial_value: Option<Arc<SpecifiedValue>>,
+}
+
+/// Parser for the descriptors of a `@property` rule.
+struct PropertyRuleParser<'a> {
+    descriptors: &'a mut PropertyDescriptors,
+}
+
+/// Default methods reject all at rules.
+impl<'a, 'i> AtRuleParser<'i> for PropertyRuleParser<'a> {
+    type Prelude = ();
+    type AtRule = ();
+    type Error = StyleParseErrorKind<'i>;
+}
+
+impl<'a, 'i> QualifiedRuleParser<'i> for PropertyRuleParser<'a> {
+    type Prelude = ();
+    type QualifiedRule = ();
+    type Error = StyleParseErrorKind<'i>;
+}
+
+impl<'a, 'i> DeclarationParser<'i> for PropertyRuleParser<'a> {
+    type Declaration = ();
+    type Error = StyleParseErrorKind<'i>;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<(), ParseError<'i>> {
+        match_ignore_ascii_case! { &*name,
+            "syntax" => {
+                let location = input.current_source_location();
+                let syntax = input.expect_string()?;
+                let syntax = Descriptor::from_str(&syntax).map_err(|_| {
+                    location.new_custom_error(StyleParseErrorKind::UnspecifiedError)
+                })?;
+                self.descriptors.syntax = Some(syntax);
+            },
+            "inherits" => {
+                let location = input.current_source_location();
+                let ident = input.expect_ident()?;
+                let inherits = match_ignore_ascii_case! { &*ident,
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(location.new_unexpected_token_error(
+                        Token::Ident(ident.clone())
+                    )),
+                };
+                self.descriptors.inherits = Some(inherits);
+            },
+            "initial-value" => {
+                self.descriptors.initial_value = Some(SpecifiedValue::parse(input)?);
+            },
+            _ => return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError)),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Used for parsing and serializing component names from the syntax string.
+
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ToCss};
+
+/// Some data types can only accept a single value, while others can accept
+/// multiple values.
+///
+/// https://drafts.css-houdini.org/css-properties-values-api-1/#supported-names
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum DataType {
+    /// Any valid `<length>` value
+    Length,
+    /// `<number>` values
+    Number,
+    /// Any valid <percentage> value
+    Percentage,
+    /// Any valid `<length>` or `<percentage>` value, any valid `<calc()>`
+    /// expression combining `<length>` and `<percentage>` components.
+    LengthPercentage,
+    /// Any valid `<color>` value
+    Color,
+    /// Any valid `<image>` value
+    Image,
+    /// Any valid `<url>` value
+    Url,
+    /// Any valid `<integer>` value
+    Integer,
+    /// Any valid `<angle>` value
+    Angle,
+    /// Any valid `<time>` value
+    Time,
+    /// Any valid `<resolution>` value
+    Resolution,
+    /// Any valid `<transform-function>` value
+    TransformFunction,
+    /// A list of valid `<transform-function>` values. Note that
+    /// `<transform-list>` is a pre-multiplied data type name.
+    TransformList,
+    /// Any valid `<custom-ident>` value
+    CustomIdent,
+}
+
+impl DataType {
+    /// Converts a component name from a syntax string to the matching
+    /// `DataType`, if any.
+    pub fn from_str(ty: &str) -> Option<Self> {
+        Some(match ty.as_bytes() {
+            b"length" => DataType::Length,
+            b"number" => DataType::Number,
+            b"percentage" => DataType::Percentage,
+            b"length-percentage" => DataType::LengthPercentage,
+            b"color" => DataType::Color,
+            b"image" => DataType::Image,
+            b"url" => DataType::Url,
+            b"integer" => DataType::Integer,
+            b"angle" => DataType::Angle,
+            b"time" => DataType::Time,
+            b"resolution" => DataType::Resolution,
+            b"transform-function" => DataType::TransformFunction,
+            b"custom-ident" => DataType::CustomIdent,
+            b"transform-list" => DataType::TransformList,
+            _ => return None,
+        })
+    }
+
+    /// Returns the name of this data type, as it appears in a syntax string.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DataType::Length => "length",
+            DataType::Number => "number",
+            DataType::Percentage => "percentage",
+            DataType::LengthPercentage => "length-percentage",
+            DataType::Color => "color",
+            DataType::Image => "image",
+            DataType::Url => "url",
+            DataType::Integer => "integer",
+            DataType::Angle => "angle",
+            DataType::Time => "time",
+            DataType::Resolution => "resolution",
+            DataType::TransformFunction => "transform-function",
+            DataType::CustomIdent => "custom-ident",
+            DataType::TransformList => "transform-list",
+        }
+    }
+
+    /// Whether this data type is pre-multiplied, and thus can't be followed by
+    /// a multiplier.
+    #[inline]
+    pub fn is_pre_multiplied(&self) -> bool {
+        matches!(*self, DataType::TransformList)
+    }
+}
+
+impl ToCss for DataType {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        dest.write_char('<')?;
+        dest.write_str(self.as_str())?;
+        dest.write_char('>')
+    }
+}
@@ -0,0 +1,300 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Used for parsing and serializing the [`@property`] syntax string.
+//!
+//! <https://drafts.css-houdini.org/css-properties-values-api-1/#parsing-syntax>
+
+use crate::values::CustomIdent;
+use crate::Atom;
+use cssparser::serialize_string;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ToCss};
+
+mod data_type;
+
+pub use self::data_type::DataType;
+
+/// The string representing the universal syntax definition, which matches any
+/// token sequence.
+const UNIVERSAL_SYNTAX: &str = "*";
+
+/// The reason a syntax string failed to parse.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// The syntax string was empty or only contained whitespace.
+    EmptyInput,
+    /// The syntax string ended where a component was expected, e.g. after a
+    /// trailing `|`.
+    UnexpectedEOF,
+    /// A `|` was found where a component was expected.
+    UnexpectedPipe,
+    /// A data type name was not closed with `>`.
+    UnclosedDataTypeName,
+    /// A data type name wasn't one of the supported names.
+    UnknownDataTypeName,
+    /// A `<transform-list>` was followed by a multiplier.
+    UnexpectedMultiplier,
+    /// An identifier was a CSS-wide keyword or `default`.
+    InvalidCustomIdent,
+    /// An identifier was not a valid CSS identifier.
+    InvalidName,
+    /// Something other than a `|` was found after a component.
+    ExpectedPipeBetweenComponents,
+}
+
+/// A syntax descriptor, as specified by the `syntax` descriptor of the
+/// `@property` rule.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#the-syntax-descriptor>
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct Descriptor {
+    /// The parsed components. An empty list means this is the universal
+    /// syntax definition.
+    components: Box<[Component]>,
+    /// The specified string, which is used for serialization.
+    specified: Box<str>,
+}
+
+impl Descriptor {
+    /// Returns the universal syntax definition, `*`.
+    pub fn universal() -> Self {
+        Self {
+            components: Default::default(),
+            specified: UNIVERSAL_SYNTAX.into(),
+        }
+    }
+
+    /// Whether this is the universal syntax definition, which accepts any
+    /// token sequence and doesn't make the property typed.
+    #[inline]
+    pub fn is_universal(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// The components of this syntax definition, in specified order.
+    #[inline]
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Parses a syntax string.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api-1/#consume-syntax-definition>
+    pub fn from_str(css: &str) -> Result<Self, ParseError> {
+        // 1. Strip leading and trailing ASCII whitespace from string.
+        let input = css.trim_matches(is_ascii_whitespace);
+
+        // 2. If string's length is 0, return failure.
+        if input.is_empty() {
+            return Err(ParseError::EmptyInput);
+        }
+
+        // 3. If string's length is 1, and the only code point in string is
+        //    U+002A ASTERISK (*), return the universal syntax descriptor.
+        if input == UNIVERSAL_SYNTAX {
+            return Ok(Self {
+                components: Default::default(),
+                specified: css.into(),
+            });
+        }
+
+        // 4. Let stream be an input stream created from the code points of
+        //    string, preprocessed as specified in [css-syntax-3]. Let
+        //    descriptor be an initially empty list of syntax components.
+        let mut parser = SyntaxParser {
+            input,
+            position: 0,
+        };
+        let mut components = vec![];
+
+        // 5. Repeatedly consume the next input code point from stream.
+        loop {
+            parser.skip_whitespace();
+            components.push(parser.parse_component()?);
+            parser.skip_whitespace();
+            match parser.next_byte() {
+                None => break,
+                Some(b'|') => continue,
+                Some(_) => return Err(ParseError::ExpectedPipeBetweenComponents),
+            }
+        }
+
+        Ok(Self {
+            components: components.into_boxed_slice(),
+            specified: css.into(),
+        })
+    }
+}
+
+impl ToCss for Descriptor {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        serialize_string(&self.specified, dest)
+    }
+}
+
+/// A multiplier of a syntax component.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#multipliers>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToComputedValue, ToShmem)]
+pub enum Multiplier {
+    /// Indicates a space-separated list (`+`).
+    Space,
+    /// Indicates a comma-separated list (`#`).
+    Comma,
+}
+
+/// The name of a syntax component, either a data type or a keyword.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub enum ComponentName {
+    /// A data type name, like `<length>`.
+    DataType(DataType),
+    /// A literal identifier.
+    ///
+    /// <https://drafts.csswg.org/css-values-4/#custom-idents>
+    Ident(CustomIdent),
+}
+
+/// A single syntax component, with its optional multiplier.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api-1/#syntax-component>
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct Component {
+    name: ComponentName,
+    multiplier: Option<Multiplier>,
+}
+
+impl Component {
+    /// Returns the component's name.
+    #[inline]
+    pub fn name(&self) -> &ComponentName {
+        &self.name
+    }
+
+    /// Returns the component's multiplier, if one exists.
+    #[inline]
+    pub fn multiplier(&self) -> Option<Multiplier> {
+        self.multiplier
+    }
+
+    /// Returns whether this component accepts a list of values, either because
+    /// of its multiplier or because its data type is pre-multiplied.
+    #[inline]
+    pub fn is_list(&self) -> bool {
+        self.multiplier.is_some() ||
+            matches!(self.name, ComponentName::DataType(ref ty) if ty.is_pre_multiplied())
+    }
+}
+
+/// A minimal parser over the code points of a syntax string. We can't use the
+/// CSS tokenizer for this, since whitespace is significant between a component
+/// and its multiplier.
+struct SyntaxParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> SyntaxParser<'a> {
+    fn peek_byte(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).cloned()
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        let byte = self.peek_byte()?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek_byte().map_or(false, |b| is_ascii_whitespace(b as char)) {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes a name made of name code points, returning it.
+    fn consume_name(&mut self) -> &'a str {
+        let start = self.position;
+        while let Some(c) = self.input[self.position..].chars().next() {
+            if !is_name_code_point(c) {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        &self.input[start..self.position]
+    }
+
+    /// <https://drafts.css-houdini.org/css-properties-values-api-1/#consume-syntax-component>
+    fn parse_component(&mut self) -> Result<Component, ParseError> {
+        let name = match self.peek_byte() {
+            None => return Err(ParseError::UnexpectedEOF),
+            Some(b'|') => return Err(ParseError::UnexpectedPipe),
+            Some(b'<') => {
+                self.position += 1;
+                let name = self.consume_name();
+                if self.next_byte() != Some(b'>') {
+                    return Err(ParseError::UnclosedDataTypeName);
+                }
+                let ty = DataType::from_str(name).ok_or(ParseError::UnknownDataTypeName)?;
+                ComponentName::DataType(ty)
+            },
+            Some(_) => {
+                let name = self.consume_name();
+                if !is_valid_ident(name) {
+                    return Err(ParseError::InvalidName);
+                }
+                if !CustomIdent::is_valid(name, &[]) {
+                    return Err(ParseError::InvalidCustomIdent);
+                }
+                ComponentName::Ident(CustomIdent(Atom::from(name)))
+            },
+        };
+
+        let multiplier = match self.peek_byte() {
+            Some(b'+') => Some(Multiplier::Space),
+            Some(b'#') => Some(Multiplier::Comma),
+            _ => None,
+        };
+        if multiplier.is_some() {
+            if matches!(name, ComponentName::DataType(ref ty) if ty.is_pre_multiplied()) {
+                return Err(ParseError::UnexpectedMultiplier);
+            }
+            self.position += 1;
+        }
+
+        Ok(Component { name, multiplier })
+    }
+}
+
+fn is_ascii_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
+
+/// <https://drafts.csswg.org/css-syntax-3/#name-start-code-point>
+fn is_name_start_code_point(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+/// <https://drafts.csswg.org/css-syntax-3/#name-code-point>
+fn is_name_code_point(c: char) -> bool {
+    is_name_start_code_point(c) || c.is_ascii_digit() || c == '-'
+}
+
+/// Whether `name` would be tokenized as a single <ident-token>. Escapes are
+/// not supported.
+///
+/// <https://drafts.csswg.org/css-syntax-3/#would-start-an-identifier>
+fn is_valid_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some('-') => match chars.next() {
+            Some(c) => c == '-' || is_name_start_code_point(c),
+            None => false,
+        },
+        Some(c) => is_name_start_code_point(c),
+        None => false,
+    }
+}
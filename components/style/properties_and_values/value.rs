@@ -0,0 +1,330 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Parsing, computing and interpolating the values of registered custom
+//! properties.
+//!
+//! <https://drafts.css-houdini.org/css-properties-values-api-1/#calculation-of-computed-values>
+
+use super::syntax::{ComponentName, DataType, Descriptor, Multiplier};
+use crate::custom_properties::VariableValue;
+use crate::parser::{Parse, ParserContext};
+use crate::stylesheets::{Origin, UrlExtraData};
+use crate::values::animated::{Animate, Procedure};
+use crate::values::computed::{Context, ToComputedValue};
+use crate::values::specified::{self, url::SpecifiedUrl};
+use crate::values::CustomIdent;
+use crate::{Atom, OwnedSlice};
+use cssparser::{Parser, ParserInput};
+use servo_arc::Arc;
+use std::fmt::{self, Write};
+use style_traits::{
+    Comma, CssWriter, ParseError, ParsingMode, Separator, Space, StyleParseErrorKind, ToCss,
+};
+
+/// A single component of a registered custom property value.
+#[derive(Animate, Clone, Debug, PartialEq, ToComputedValue, ToCss)]
+#[animation(no_bound(Image, Url))]
+pub enum GenericValueComponent<
+    Length,
+    Number,
+    Percentage,
+    LengthPercentage,
+    Color,
+    Image,
+    Url,
+    Integer,
+    Angle,
+    Time,
+    Resolution,
+    TransformFunction,
+    TransformList,
+> {
+    /// A `<length>` value.
+    Length(Length),
+    /// A `<number>` value.
+    Number(Number),
+    /// A `<percentage>` value.
+    Percentage(Percentage),
+    /// A `<length-percentage>` value.
+    LengthPercentage(LengthPercentage),
+    /// A `<color>` value.
+    Color(Color),
+    /// An `<image>` value.
+    #[animation(error)]
+    Image(Image),
+    /// A `<url>` value.
+    #[animation(error)]
+    Url(Url),
+    /// An `<integer>` value.
+    Integer(Integer),
+    /// An `<angle>` value.
+    Angle(Angle),
+    /// A `<time>` value.
+    Time(Time),
+    /// A `<resolution>` value.
+    Resolution(Resolution),
+    /// A `<transform-function>` value.
+    TransformFunction(TransformFunction),
+    /// A `<custom-ident>` value, matching one of the literal identifiers of
+    /// the syntax.
+    #[animation(error)]
+    CustomIdent(CustomIdent),
+    /// A `<transform-list>` value.
+    TransformList(TransformList),
+}
+
+/// A list of components, for syntax components with a multiplier.
+#[derive(Clone, Debug, PartialEq, ToComputedValue)]
+pub struct ComponentList<Component> {
+    /// The multiplier of the syntax component, which determines the
+    /// separator.
+    pub multiplier: Multiplier,
+    /// The list of components.
+    pub components: OwnedSlice<Component>,
+}
+
+impl<Component: ToCss> ToCss for ComponentList<Component> {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        let separator = match self.multiplier {
+            Multiplier::Space => " ",
+            Multiplier::Comma => ", ",
+        };
+        let mut iter = self.components.iter();
+        if let Some(first) = iter.next() {
+            first.to_css(dest)?;
+        }
+        for component in iter {
+            dest.write_str(separator)?;
+            component.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Component: Animate> Animate for ComponentList<Component> {
+    fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()> {
+        // Lists only interpolate pairwise, so they need to have the same
+        // length.
+        //
+        // https://drafts.css-houdini.org/css-properties-values-api-1/#animation-behavior-of-custom-properties
+        if self.multiplier != other.multiplier ||
+            self.components.len() != other.components.len()
+        {
+            return Err(());
+        }
+        let components = self
+            .components
+            .iter()
+            .zip(other.components.iter())
+            .map(|(this, other)| this.animate(other, procedure))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            multiplier: self.multiplier,
+            components: components.into(),
+        })
+    }
+}
+
+/// A registered custom property value, either a single component or a list
+/// of them.
+#[derive(Animate, Clone, Debug, PartialEq, ToComputedValue, ToCss)]
+pub enum GenericValue<Component> {
+    /// A single component.
+    Component(Component),
+    /// A list of components.
+    List(ComponentList<Component>),
+}
+
+/// A specified value component.
+pub type ValueComponent = GenericValueComponent<
+    specified::Length,
+    specified::Number,
+    specified::Percentage,
+    specified::LengthPercentage,
+    specified::Color,
+    specified::Image,
+    SpecifiedUrl,
+    specified::Integer,
+    specified::Angle,
+    specified::Time,
+    specified::Resolution,
+    specified::transform::TransformOperation,
+    specified::Transform,
+>;
+
+/// A specified registered custom property value.
+pub type SpecifiedValue = GenericValue<ValueComponent>;
+
+/// A computed registered custom property value.
+pub type ComputedValue = <SpecifiedValue as ToComputedValue>::ComputedValue;
+
+impl ValueComponent {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        name: &ComponentName,
+    ) -> Result<Self, ParseError<'i>> {
+        let ty = match *name {
+            ComponentName::DataType(ty) => ty,
+            ComponentName::Ident(ref ident) => {
+                let location = input.current_source_location();
+                let found = input.expect_ident()?;
+                // Literal identifiers in the syntax match case-sensitively.
+                if ident.0 != Atom::from(&**found) {
+                    return Err(location.new_custom_error(
+                        StyleParseErrorKind::UnexpectedIdent(found.clone()),
+                    ));
+                }
+                return Ok(Self::CustomIdent(ident.clone()));
+            },
+        };
+        Ok(match ty {
+            DataType::Length => Self::Length(specified::Length::parse(context, input)?),
+            DataType::Number => Self::Number(specified::Number::parse(context, input)?),
+            DataType::Percentage => {
+                Self::Percentage(specified::Percentage::parse(context, input)?)
+            },
+            DataType::LengthPercentage => {
+                Self::LengthPercentage(specified::LengthPercentage::parse(context, input)?)
+            },
+            DataType::Color => Self::Color(specified::Color::parse(context, input)?),
+            DataType::Image => Self::Image(specified::Image::parse(context, input)?),
+            DataType::Url => Self::Url(SpecifiedUrl::parse(context, input)?),
+            DataType::Integer => Self::Integer(specified::Integer::parse(context, input)?),
+            DataType::Angle => Self::Angle(specified::Angle::parse(context, input)?),
+            DataType::Time => Self::Time(specified::Time::parse(context, input)?),
+            DataType::Resolution => {
+                Self::Resolution(specified::Resolution::parse(context, input)?)
+            },
+            DataType::TransformFunction => {
+                Self::TransformFunction(Self::parse_transform_function(context, input)?)
+            },
+            DataType::TransformList => {
+                let location = input.current_source_location();
+                let list = specified::Transform::parse(context, input)?;
+                // Unlike the `transform` property, `<transform-list>` doesn't
+                // accept `none`.
+                if list.0.is_empty() {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                Self::TransformList(list)
+            },
+            DataType::CustomIdent => {
+                let location = input.current_source_location();
+                let ident = input.expect_ident()?;
+                Self::CustomIdent(CustomIdent::from_ident(location, ident, &[])?)
+            },
+        })
+    }
+
+    /// Parses a single `<transform-function>` with the parser of the
+    /// `transform` property, by giving it only the tokens of the function.
+    fn parse_transform_function<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<specified::transform::TransformOperation, ParseError<'i>> {
+        let location = input.current_source_location();
+        let start = input.position();
+        input.expect_function()?;
+        input.parse_nested_block(|input| -> Result<(), ParseError<'i>> {
+            while input.next().is_ok() {}
+            Ok(())
+        })?;
+        let mut function = ParserInput::new(input.slice_from(start));
+        let list = specified::Transform::parse(context, &mut Parser::new(&mut function))?;
+        debug_assert!(list.0.len() <= 1);
+        list.0
+            .into_iter()
+            .next()
+            .ok_or_else(|| location.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+    }
+}
+
+impl SpecifiedValue {
+    /// Parses a value against the given syntax definition, trying each of its
+    /// components in order. The whole input must be consumed.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api-1/#parse-a-value-according-to-a-syntax-definition>
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        syntax: &Descriptor,
+    ) -> Result<Self, ParseError<'i>> {
+        debug_assert!(!syntax.is_universal());
+        for component in syntax.components() {
+            let result = input.try_parse(|input| {
+                input.parse_entirely(|input| {
+                    let multiplier = match component.multiplier() {
+                        Some(multiplier) => multiplier,
+                        None => {
+                            return Ok(Self::Component(ValueComponent::parse(
+                                context,
+                                input,
+                                component.name(),
+                            )?));
+                        },
+                    };
+                    let parse_one = |input: &mut Parser<'i, '_>| {
+                        ValueComponent::parse(context, input, component.name())
+                    };
+                    let components = match multiplier {
+                        Multiplier::Space => Space::parse(input, parse_one)?,
+                        Multiplier::Comma => Comma::parse(input, parse_one)?,
+                    };
+                    Ok(Self::List(ComponentList {
+                        multiplier,
+                        components: components.into(),
+                    }))
+                })
+            });
+            if result.is_ok() {
+                return result;
+            }
+        }
+        Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+    }
+}
+
+impl ComputedValue {
+    /// Parses a token stream against the given syntax definition, and computes
+    /// it with the given context.
+    ///
+    /// Relative urls are resolved against `url_data`.
+    pub fn compute(
+        value: &VariableValue,
+        syntax: &Descriptor,
+        url_data: &UrlExtraData,
+        context: &Context,
+    ) -> Result<Self, ()> {
+        let parser_context = ParserContext::new(
+            Origin::Author,
+            url_data,
+            None,
+            ParsingMode::DEFAULT,
+            context.quirks_mode,
+            None,
+            None,
+        );
+        let css = value.to_css_string();
+        let mut input = ParserInput::new(&css);
+        let mut input = Parser::new(&mut input);
+        let specified =
+            SpecifiedValue::parse(&parser_context, &mut input, syntax).map_err(|_| ())?;
+        Ok(specified.to_computed_value(context))
+    }
+
+    /// Serializes this value into a token stream, which is how computed values
+    /// of registered custom properties are stored in the custom properties
+    /// map.
+    pub fn to_variable_value(&self) -> Result<Arc<VariableValue>, ()> {
+        let css = self.to_css_string();
+        let mut input = ParserInput::new(&css);
+        let mut input = Parser::new(&mut input);
+        VariableValue::parse(&mut input).map_err(|_| ())
+    }
+}
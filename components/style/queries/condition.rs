@@ -7,6 +7,7 @@
 //! https://drafts.csswg.org/mediaqueries-4/#typedef-media-condition
 //! https://drafts.csswg.org/css-contain-3/#typedef-container-condition
 
+use super::feature_expression::StyleFeature;
 use super::{FeatureFlags, FeatureType, QueryFeatureExpression};
 use crate::values::computed;
 use crate::{error_reporting::ContextualParseError, parser::ParserContext};
@@ -121,6 +122,10 @@ impl std::ops::BitAndAssign for KleeneValue {
 pub enum QueryCondition {
     /// A simple feature expression, implicitly parenthesized.
     Feature(QueryFeatureExpression),
+    /// A style feature, only valid inside a `style()` query.
+    StyleFeature(StyleFeature),
+    /// A `style()` container query.
+    Style(Box<QueryCondition>),
     /// A negation of a condition.
     Not(Box<QueryCondition>),
     /// A set of joint operations.
@@ -140,6 +145,12 @@ impl ToCss for QueryCondition {
             // NOTE(emilio): QueryFeatureExpression already includes the
             // parenthesis.
             QueryCondition::Feature(ref f) => f.to_css(dest),
+            QueryCondition::StyleFeature(ref f) => f.to_css(dest),
+            QueryCondition::Style(ref c) => {
+                dest.write_str("style(")?;
+                c.to_css(dest)?;
+                dest.write_char(')')
+            },
             QueryCondition::Not(ref c) => {
                 dest.write_str("not ")?;
                 c.to_css(dest)
@@ -187,7 +198,9 @@ impl QueryCondition {
         visitor(self);
         match *self {
             Self::Feature(..) => {},
+            Self::StyleFeature(..) => {},
             Self::GeneralEnclosed(..) => {},
+            Self::Style(ref cond) => cond.visit(visitor),
             Self::Not(ref cond) => cond.visit(visitor),
             Self::Operation(ref conds, _op) => {
                 for cond in conds.iter() {
@@ -203,8 +216,10 @@ impl QueryCondition {
     pub fn cumulative_flags(&self) -> FeatureFlags {
         let mut result = FeatureFlags::empty();
         self.visit(&mut |condition| {
-            if let Self::Feature(ref f) = condition {
-                result.insert(f.feature_flags())
+            match *condition {
+                Self::Feature(ref f) => result.insert(f.feature_flags()),
+                Self::Style(..) => result.insert(FeatureFlags::CONTAINER_REQUIRES_STYLE),
+                _ => {},
             }
         });
         result
@@ -272,6 +287,10 @@ impl QueryCondition {
         input: &mut Parser<'i, '_>,
         feature_type: FeatureType,
     ) -> Result<Self, ParseError<'i>> {
+        if feature_type == FeatureType::Style {
+            return Self::parse_style_query_in_parenthesis_block(context, input);
+        }
+
         // Base case. Make sure to preserve this error as it's more generally
         // relevant.
         let feature_error = match input.try_parse(|input| {
@@ -286,6 +305,29 @@ impl QueryCondition {
         Err(feature_error)
     }
 
+    /// Parses a `<style-in-parens>`, or the contents of the `style()`
+    /// function, where we've already consumed the parenthesis.
+    ///
+    /// https://drafts.csswg.org/css-contain-3/#typedef-style-in-parens
+    fn parse_style_query_in_parenthesis_block<'i>(
+        context: &ParserContext,
+        input: &mut Parser<'i, '_>,
+    ) -> Result<Self, ParseError<'i>> {
+        let feature_error = match input.try_parse(|input| StyleFeature::parse(context, input)) {
+            Ok(feature) => return Ok(Self::StyleFeature(feature)),
+            Err(e) => e,
+        };
+        if let Ok(inner) = Self::parse(context, input, FeatureType::Style) {
+            return Ok(inner);
+        }
+        Err(feature_error)
+    }
+
+    fn style_queries_enabled(context: &ParserContext) -> bool {
+        context.chrome_rules_enabled() ||
+            static_prefs::pref!("layout.css.style-queries.enabled")
+    }
+
     /// Parse a condition in parentheses, or `<general-enclosed>`.
     ///
     /// https://drafts.csswg.org/mediaqueries/#typedef-media-in-parens
@@ -305,6 +347,11 @@ impl QueryCondition {
                     })
                 });
                 match nested {
+                    // `(--foo: bar)` inside a `style()` query, which
+                    // serializes with the parentheses.
+                    Ok(nested) if feature_type == FeatureType::Style => {
+                        return Ok(Self::InParens(Box::new(nested)))
+                    },
                     Ok(nested) => return Ok(nested),
                     Err(e) => {
                         // We're about to swallow the error in a `<general-enclosed>`
@@ -315,9 +362,21 @@ impl QueryCondition {
                     }
                 }
             },
-            Token::Function(..) => {
-                // TODO: handle `style()` queries, etc.
+            Token::Function(ref name)
+                if feature_type == FeatureType::Container &&
+                    name.eq_ignore_ascii_case("style") &&
+                    Self::style_queries_enabled(context) =>
+            {
+                let query = input.try_parse(|input| {
+                    input.parse_nested_block(|input| {
+                        Self::parse_style_query_in_parenthesis_block(context, input)
+                    })
+                });
+                if let Ok(query) = query {
+                    return Ok(Self::Style(Box::new(query)));
+                }
             },
+            Token::Function(..) => {},
             ref t => return Err(start_location.new_unexpected_token_error(t.clone())),
         }
         input.parse_nested_block(consume_any_value)?;
@@ -332,6 +391,8 @@ impl QueryCondition {
     pub fn matches(&self, context: &computed::Context) -> KleeneValue {
         match *self {
             QueryCondition::Feature(ref f) => f.matches(context),
+            QueryCondition::StyleFeature(ref f) => f.matches(context),
+            QueryCondition::Style(ref c) => c.matches(context),
             QueryCondition::GeneralEnclosed(_) => KleeneValue::Unknown,
             QueryCondition::InParens(ref c) => c.matches(context),
             QueryCondition::Not(ref c) => !c.matches(context),
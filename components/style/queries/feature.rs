@@ -118,9 +118,14 @@ bitflags! {
         const CONTAINER_REQUIRES_WIDTH_AXIS = 1 << 4;
         /// The feature requires containment in the physical height axis.
         const CONTAINER_REQUIRES_HEIGHT_AXIS = 1 << 5;
+        /// The feature queries the computed style of the container, that is,
+        /// it's part of a `style()` query.
+        const CONTAINER_REQUIRES_STYLE = 1 << 6;
     }
 }
 
+malloc_size_of_is_0!(FeatureFlags);
+
 impl FeatureFlags {
     /// Returns parsing requirement flags.
     pub fn parsing_requirements(self) -> Self {
@@ -7,6 +7,7 @@
 
 use super::feature::{Evaluator, QueryFeatureDescription};
 use super::feature::{FeatureFlags, KeywordDiscriminant};
+use crate::custom_properties::{self, VariableValue};
 use crate::parser::{Parse, ParserContext};
 use crate::queries::condition::KleeneValue;
 use crate::str::{starts_with_ignore_ascii_case, string_as_ascii_lowercase};
@@ -15,6 +16,7 @@ use crate::values::specified::{Integer, Length, Number, Resolution};
 use crate::values::CSSFloat;
 use crate::{Atom, Zero};
 use cssparser::{Parser, Token};
+use servo_arc::Arc;
 use std::cmp::{Ordering, PartialOrd};
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
@@ -26,6 +28,9 @@ pub enum FeatureType {
     Media,
     /// We're parsing a container feature.
     Container,
+    /// We're parsing the contents of a `style()` container query, whose
+    /// features are `StyleFeature`s rather than `QueryFeatureExpression`s.
+    Style,
 }
 
 impl FeatureType {
@@ -40,6 +45,7 @@ impl FeatureType {
         match *self {
             FeatureType::Media => &MEDIA_FEATURES,
             FeatureType::Container => &CONTAINER_FEATURES,
+            FeatureType::Style => &[],
         }
     }
 
@@ -738,3 +744,109 @@ impl QueryExpressionValue {
         })
     }
 }
+
+/// A style feature inside a `style()` container query, like `--theme: dark`
+/// or just `--theme`.
+///
+/// https://drafts.csswg.org/css-contain-3/#typedef-style-feature
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct StyleFeature {
+    /// The custom property name, without the leading `--`.
+    name: custom_properties::Name,
+    /// The value to compare against, if any.
+    #[ignore_malloc_size_of = "Arc"]
+    value: Option<Arc<custom_properties::SpecifiedValue>>,
+}
+
+impl ToCss for StyleFeature {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        dest.write_str("--")?;
+        crate::values::serialize_atom_name(&self.name, dest)?;
+        if let Some(ref value) = self.value {
+            dest.write_str(": ")?;
+            value.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+impl StyleFeature {
+    /// Parse a style feature, where we've already consumed the parenthesis
+    /// or the `style(` function token, if any.
+    ///
+    /// TODO: Support standard properties as well, see
+    /// https://drafts.csswg.org/css-contain-3/#style-container
+    pub fn parse<'i, 't>(
+        _: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        let name = match custom_properties::parse_name(ident) {
+            Ok(name) => custom_properties::Name::from(name),
+            Err(()) => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+        };
+        let value = if input.try_parse(|input| input.expect_colon()).is_ok() {
+            input.skip_whitespace();
+            Some(VariableValue::parse(input)?)
+        } else {
+            None
+        };
+        Ok(Self { name, value })
+    }
+
+    /// Returns whether the query container has the value of this feature.
+    ///
+    /// The query container is the parent style of the context, see
+    /// `Context::for_container_query_evaluation`.
+    pub fn matches(&self, context: &computed::Context) -> KleeneValue {
+        let stylist = match context.stylist {
+            Some(stylist) => stylist,
+            None => return KleeneValue::Unknown,
+        };
+        if context.container_info.is_none() {
+            // There's no eligible query container.
+            return KleeneValue::Unknown;
+        }
+
+        let custom_properties = context.builder.inherited_custom_properties();
+        let current_value = custom_properties.and_then(|map| map.get(&self.name));
+        let registration = stylist.get_custom_property_registration(&self.name);
+        let value = match self.value {
+            Some(ref value) => value,
+            None => {
+                // `style(--foo)` matches if the property doesn't have its
+                // initial value.
+                let initial_value = registration.and_then(|r| r.initial_value.as_ref());
+                return KleeneValue::from(current_value != initial_value);
+            },
+        };
+        let current_value = match current_value {
+            Some(v) => v,
+            None => return KleeneValue::False,
+        };
+
+        let device = context.device();
+        let value = match VariableValue::substitute_references(value, custom_properties, device) {
+            Ok(v) => v,
+            Err(()) => return KleeneValue::False,
+        };
+        // Values of registered properties are compared as computed values.
+        let value = match registration {
+            Some(registration) if registration.is_typed() => {
+                match registration
+                    .compute(&value, context)
+                    .and_then(|computed| computed.to_variable_value())
+                {
+                    Ok(value) => value,
+                    Err(()) => return KleeneValue::False,
+                }
+            },
+            _ => value,
+        };
+        KleeneValue::from(current_value.same_tokens_as(&value))
+    }
+}
@@ -12,6 +12,7 @@ use crate::selector_parser::PseudoElement;
 use crate::shared_lock::StylesheetGuards;
 use crate::values::computed::NonNegativeLength;
 use fxhash::FxHashMap;
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOfOps};
 use servo_arc::Arc;
 use smallvec::SmallVec;
 
@@ -82,6 +83,18 @@ impl RuleCache {
         }
     }
 
+    /// Measure heap usage.
+    ///
+    /// The cached computed values are not measured, since they're shared with
+    /// the elements they were computed for.
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let mut n = self.map.shallow_size_of(ops);
+        for entries in self.map.values() {
+            n += entries.shallow_size_of(ops);
+        }
+        n
+    }
+
     /// Walk the rule tree and return a rule node for using as the key
     /// for rule cache.
     ///
@@ -133,7 +133,8 @@ where
     /// (either the document tree, or a shadow tree).
     ///
     /// All rules in the same tree need to be matched together, and this
-    /// function takes care of sorting them by specificity and source order.
+    /// function takes care of sorting them by scope proximity, specificity and
+    /// source order.
     #[inline]
     fn in_tree(&mut self, host: Option<E>, f: impl FnOnce(&mut Self)) {
         debug_assert!(!self.in_sort_scope, "Nested sorting makes no sense");
@@ -144,7 +145,12 @@ where
         f(self);
         if start != self.rules.len() {
             self.rules[start..].sort_unstable_by_key(|block| {
-                (block.layer_order(), block.specificity, block.source_order())
+                (
+                    block.layer_order(),
+                    block.scope_proximity,
+                    block.specificity,
+                    block.source_order(),
+                )
             });
         }
         self.context.current_host = old_host;
@@ -5,17 +5,17 @@
 //! A data structure to efficiently index structs containing selectors by local
 //! name, ids and hash.
 
-use crate::applicable_declarations::ApplicableDeclarationList;
+use crate::applicable_declarations::{ApplicableDeclarationList, ScopeProximity};
 use crate::context::QuirksMode;
 use crate::dom::TElement;
 use crate::rule_tree::CascadeLevel;
 use crate::selector_parser::SelectorImpl;
-use crate::stylist::{CascadeData, ContainerConditionId, Rule, Stylist};
+use crate::stylist::{CascadeData, ContainerConditionId, Rule, ScopeConditionId, Stylist};
 use crate::AllocErr;
 use crate::{Atom, LocalName, Namespace, ShrinkIfNeeded, WeakAtom};
 use dom::ElementState;
 use precomputed_hash::PrecomputedHash;
-use selectors::matching::{matches_selector, MatchingContext};
+use selectors::matching::{matches_selector, IncludeStartingStyle, MatchingContext};
 use selectors::parser::{Combinator, Component, SelectorIter};
 use smallvec::SmallVec;
 use std::collections::hash_map;
@@ -341,16 +341,30 @@ impl SelectorMap<Rule> {
         E: TElement,
     {
         for rule in rules {
-            if !matches_selector(
-                &rule.selector,
-                0,
-                Some(&rule.hashes),
-                &element,
-                matching_context,
-            ) {
+            if rule.is_starting_style &&
+                matching_context.include_starting_style == IncludeStartingStyle::No
+            {
                 continue;
             }
 
+            let scope_proximity = if rule.scope_condition_id == ScopeConditionId::none() {
+                if !matches_selector(
+                    &rule.selector,
+                    0,
+                    Some(&rule.hashes),
+                    &element,
+                    matching_context,
+                ) {
+                    continue;
+                }
+                ScopeProximity::infinity()
+            } else {
+                match cascade_data.scoped_rule_matches(rule, element, matching_context) {
+                    Some(proximity) => proximity,
+                    None => continue,
+                }
+            };
+
             if rule.container_condition_id != ContainerConditionId::none() {
                 if !cascade_data.container_condition_matches(
                     rule.container_condition_id,
@@ -362,7 +376,15 @@ impl SelectorMap<Rule> {
                 }
             }
 
-            matching_rules.push(rule.to_applicable_declaration_block(cascade_level, cascade_data));
+            if rule.is_starting_style {
+                matching_context.has_starting_style = true;
+            }
+
+            matching_rules.push(rule.to_applicable_declaration_block(
+                cascade_level,
+                scope_proximity,
+                cascade_data,
+            ));
         }
     }
 }
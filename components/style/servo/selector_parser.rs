@@ -13,7 +13,7 @@ use crate::invalidation::element::element_wrapper::ElementSnapshot;
 use crate::properties::longhands::display::computed_value::T as Display;
 use crate::properties::{ComputedValues, PropertyFlags};
 use crate::selector_parser::AttrValue as SelectorAttrValue;
-use crate::selector_parser::{PseudoElementCascadeType, SelectorParser};
+use crate::selector_parser::{Direction, PseudoElementCascadeType, SelectorParser};
 use crate::values::{AtomIdent, AtomString};
 use crate::{Atom, CaseSensitivityExt, LocalName, Namespace, Prefix};
 use cssparser::{serialize_identifier, CowRcStr, Parser as CssParser, SourceLocation, ToCss};
@@ -25,7 +25,7 @@ use selectors::visitor::SelectorVisitor;
 use std::fmt;
 use std::mem;
 use std::ops::{Deref, DerefMut};
-use style_traits::{ParseError, StyleParseErrorKind};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss as ToCss_};
 
 /// A pseudo-element, both public and private.
 ///
@@ -40,16 +40,14 @@ pub enum PseudoElement {
     After = 0,
     Before,
     Selection,
-    // If/when :first-letter is added, update is_first_letter accordingly.
-
-    // If/when :first-line is added, update is_first_line accordingly.
-
-    // If/when ::first-letter, ::first-line, or ::placeholder are added, adjust
-    // our property_restriction implementation to do property filtering for
-    // them.  Also, make sure the UA sheet has the !important rules some of the
-    // APPLIES_TO_PLACEHOLDER properties expect!
 
     // Non-eager pseudos.
+    FirstLetter,
+    FirstLine,
+    // NB: UserAgentStylesheets::new adds the !important rules in
+    // PLACEHOLDER_USER_AGENT_RULES for ::placeholder to the UA sheets.
+    Marker,
+    Placeholder,
     DetailsSummary,
     DetailsContent,
     ServoText,
@@ -67,6 +65,17 @@ pub enum PseudoElement {
 /// The count of all pseudo-elements.
 pub const PSEUDO_COUNT: usize = PseudoElement::ServoInlineAbsolute as usize + 1;
 
+/// The rules the user-agent stylesheet needs for `::placeholder`.
+///
+/// The placeholder is laid out like the value of its input, so these pin the
+/// APPLIES_TO_PLACEHOLDER properties that would otherwise let authors change
+/// how its text wraps.
+pub const PLACEHOLDER_USER_AGENT_RULES: &str = "\
+input::placeholder, textarea::placeholder { opacity: 0.54; }
+input::placeholder { white-space: pre !important; overflow-wrap: normal !important; }
+textarea::placeholder { white-space: pre-wrap !important; }
+";
+
 impl ::selectors::parser::PseudoElement for PseudoElement {
     type Impl = SelectorImpl;
 }
@@ -81,6 +90,10 @@ impl ToCss for PseudoElement {
             After => "::after",
             Before => "::before",
             Selection => "::selection",
+            FirstLetter => "::first-letter",
+            FirstLine => "::first-line",
+            Marker => "::marker",
+            Placeholder => "::placeholder",
             DetailsSummary => "::-servo-details-summary",
             DetailsContent => "::-servo-details-content",
             ServoText => "::-servo-text",
@@ -143,7 +156,7 @@ impl PseudoElement {
     /// Whether this pseudo-element is the ::marker pseudo.
     #[inline]
     pub fn is_marker(&self) -> bool {
-        false
+        *self == PseudoElement::Marker
     }
 
     /// Whether this pseudo-element is the ::selection pseudo.
@@ -167,13 +180,19 @@ impl PseudoElement {
     /// Whether the current pseudo element is :first-letter
     #[inline]
     pub fn is_first_letter(&self) -> bool {
-        false
+        *self == PseudoElement::FirstLetter
     }
 
     /// Whether the current pseudo element is :first-line
     #[inline]
     pub fn is_first_line(&self) -> bool {
-        false
+        *self == PseudoElement::FirstLine
+    }
+
+    /// Whether this pseudo-element is the ::placeholder pseudo.
+    #[inline]
+    pub fn is_placeholder(&self) -> bool {
+        *self == PseudoElement::Placeholder
     }
 
     /// Whether this pseudo-element is the ::-moz-color-swatch pseudo.
@@ -223,6 +242,10 @@ impl PseudoElement {
             PseudoElement::After | PseudoElement::Before | PseudoElement::Selection => {
                 PseudoElementCascadeType::Eager
             },
+            PseudoElement::FirstLetter |
+            PseudoElement::FirstLine |
+            PseudoElement::Marker |
+            PseudoElement::Placeholder |
             PseudoElement::DetailsSummary => PseudoElementCascadeType::Lazy,
             PseudoElement::DetailsContent |
             PseudoElement::ServoText |
@@ -252,7 +275,13 @@ impl PseudoElement {
     /// Property flag that properties must have to apply to this pseudo-element.
     #[inline]
     pub fn property_restriction(&self) -> Option<PropertyFlags> {
-        None
+        Some(match *self {
+            PseudoElement::FirstLetter => PropertyFlags::APPLIES_TO_FIRST_LETTER,
+            PseudoElement::FirstLine => PropertyFlags::APPLIES_TO_FIRST_LINE,
+            PseudoElement::Marker => PropertyFlags::APPLIES_TO_MARKER,
+            PseudoElement::Placeholder => PropertyFlags::APPLIES_TO_PLACEHOLDER,
+            _ => return None,
+        })
     }
 
     /// Whether this pseudo-element should actually exist if it has
@@ -280,21 +309,37 @@ pub type Lang = Box<str>;
 pub enum NonTSPseudoClass {
     Active,
     AnyLink,
+    Autofill,
     Checked,
+    Default,
     Defined,
+    Dir(Direction),
     Disabled,
     Enabled,
     Focus,
+    FocusVisible,
+    FocusWithin,
     Fullscreen,
     Hover,
+    InRange,
     Indeterminate,
+    Invalid,
     Lang(Lang),
     Link,
+    Modal,
+    Open,
+    Optional,
+    OutOfRange,
+    Paused,
     PlaceholderShown,
+    Playing,
+    PopoverOpen,
     ReadWrite,
     ReadOnly,
+    Required,
     ServoNonZeroBorder,
     Target,
+    Valid,
     Visited,
 }
 
@@ -333,26 +378,46 @@ impl ToCss for NonTSPseudoClass {
             serialize_identifier(lang, dest)?;
             return dest.write_char(')');
         }
+        if let Dir(ref dir) = *self {
+            dest.write_str(":dir(")?;
+            dir.to_css(&mut CssWriter::new(dest))?;
+            return dest.write_char(')');
+        }
 
         dest.write_str(match *self {
             Active => ":active",
             AnyLink => ":any-link",
+            Autofill => ":autofill",
             Checked => ":checked",
+            Default => ":default",
             Defined => ":defined",
             Disabled => ":disabled",
             Enabled => ":enabled",
             Focus => ":focus",
+            FocusVisible => ":focus-visible",
+            FocusWithin => ":focus-within",
             Fullscreen => ":fullscreen",
             Hover => ":hover",
+            InRange => ":in-range",
             Indeterminate => ":indeterminate",
+            Invalid => ":invalid",
             Link => ":link",
+            Modal => ":modal",
+            Open => ":open",
+            Optional => ":optional",
+            OutOfRange => ":out-of-range",
+            Paused => ":paused",
             PlaceholderShown => ":placeholder-shown",
+            Playing => ":playing",
+            PopoverOpen => ":popover-open",
             ReadWrite => ":read-write",
             ReadOnly => ":read-only",
+            Required => ":required",
             ServoNonZeroBorder => ":-servo-nonzero-border",
             Target => ":target",
+            Valid => ":valid",
             Visited => ":visited",
-            Lang(_) => unreachable!(),
+            Lang(_) | Dir(_) => unreachable!(),
         })
     }
 }
@@ -375,6 +440,22 @@ impl NonTSPseudoClass {
             ReadOnly | ReadWrite => ElementState::IN_READWRITE_STATE,
             PlaceholderShown => ElementState::IN_PLACEHOLDER_SHOWN_STATE,
             Target => ElementState::IN_TARGET_STATE,
+            Autofill => ElementState::AUTOFILL,
+            Default => ElementState::DEFAULT,
+            Dir(ref dir) => dir.element_state(),
+            FocusVisible => ElementState::FOCUSRING,
+            FocusWithin => ElementState::FOCUS_WITHIN,
+            InRange => ElementState::INRANGE,
+            Invalid => ElementState::INVALID,
+            Modal => ElementState::MODAL,
+            Open => ElementState::OPEN,
+            Optional => ElementState::OPTIONAL_,
+            OutOfRange => ElementState::OUTOFRANGE,
+            Paused => ElementState::PAUSED,
+            Playing => ElementState::PLAYING,
+            PopoverOpen => ElementState::POPOVER_OPEN,
+            Required => ElementState::REQUIRED,
+            Valid => ElementState::VALID,
 
             AnyLink | Lang(_) | Link | Visited | ServoNonZeroBorder => ElementState::empty(),
         }
@@ -387,7 +468,9 @@ impl NonTSPseudoClass {
 
     /// Returns true if the given pseudoclass should trigger style sharing cache revalidation.
     pub fn needs_cache_revalidation(&self) -> bool {
-        self.state_flag().is_empty()
+        // :dir() depends on state only, but may have an empty state_flag for
+        // invalid arguments.
+        self.state_flag().is_empty() && !matches!(*self, NonTSPseudoClass::Dir(_))
     }
 }
 
@@ -417,6 +500,16 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
     type Impl = SelectorImpl;
     type Error = StyleParseErrorKind<'i>;
 
+    #[inline]
+    fn parse_parent_selector(&self) -> bool {
+        static_prefs::pref!("layout.css.nesting.enabled")
+    }
+
+    #[inline]
+    fn parse_has(&self) -> bool {
+        static_prefs::pref!("layout.css.has-selector.enabled")
+    }
+
     fn parse_non_ts_pseudo_class(
         &self,
         location: SourceLocation,
@@ -426,20 +519,35 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
         let pseudo_class = match_ignore_ascii_case! { &name,
             "active" => Active,
             "any-link" => AnyLink,
+            "autofill" | "-webkit-autofill" => Autofill,
             "checked" => Checked,
+            "default" => Default,
             "defined" => Defined,
             "disabled" => Disabled,
             "enabled" => Enabled,
             "focus" => Focus,
+            "focus-visible" => FocusVisible,
+            "focus-within" => FocusWithin,
             "fullscreen" => Fullscreen,
             "hover" => Hover,
+            "in-range" => InRange,
             "indeterminate" => Indeterminate,
+            "invalid" => Invalid,
             "-moz-inert" => MozInert,
             "link" => Link,
+            "modal" => Modal,
+            "open" => Open,
+            "optional" => Optional,
+            "out-of-range" => OutOfRange,
+            "paused" => Paused,
             "placeholder-shown" => PlaceholderShown,
+            "playing" => Playing,
+            "popover-open" => PopoverOpen,
             "read-write" => ReadWrite,
             "read-only" => ReadOnly,
+            "required" => Required,
             "target" => Target,
+            "valid" => Valid,
             "visited" => Visited,
             "-servo-nonzero-border" => {
                 if !self.in_user_agent_stylesheet() {
@@ -465,6 +573,9 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "lang" => {
                 Lang(parser.expect_ident_or_string()?.as_ref().into())
             },
+            "dir" => {
+                Dir(Direction::parse(parser)?)
+            },
             _ => return Err(parser.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone()))),
         };
 
@@ -481,6 +592,10 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "before" => Before,
             "after" => After,
             "selection" => Selection,
+            "first-letter" => FirstLetter,
+            "first-line" => FirstLine,
+            "marker" => Marker,
+            "placeholder" => Placeholder,
             "-servo-details-summary" => {
                 if !self.in_user_agent_stylesheet() {
                     return Err(location.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone())))
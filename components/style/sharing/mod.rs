@@ -75,14 +75,14 @@ use crate::stylist::Stylist;
 use crate::values::AtomIdent;
 use atomic_refcell::{AtomicRefCell, AtomicRefMut};
 use owning_ref::OwningHandle;
-use selectors::matching::{NeedsSelectorFlags, VisitedHandlingMode};
-use selectors::NthIndexCache;
+use selectors::matching::{NeedsSelectorFlags, SelectorCaches, VisitedHandlingMode};
 use servo_arc::Arc;
 use smallbitvec::SmallBitVec;
 use smallvec::SmallVec;
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
-use std::ops::Deref;
+use std::ops::{AddAssign, Deref};
 use std::ptr::NonNull;
 use uluru::LRUCache;
 
@@ -98,6 +98,124 @@ mod checks;
 /// tested.
 pub const SHARING_CACHE_SIZE: usize = 32;
 
+/// A check that prevented a style sharing candidate from sharing its style
+/// with the target element.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum SharingMissReason {
+    /// The parents have different styles, or don't allow sharing between
+    /// cousins.
+    Parent,
+    /// The local names are different.
+    LocalName,
+    /// The namespaces are different.
+    Namespace,
+    /// The element states are different.
+    State,
+    /// Only one of the elements is a link.
+    Link,
+    /// The elements are in different shadow trees.
+    ContainingShadow,
+    /// The elements are assigned to different slots.
+    AssignedSlot,
+    /// The target is a shadow host.
+    ShadowHost,
+    /// The target has running animations.
+    Animations,
+    /// Only one of the elements matches user and author rules.
+    UserAndAuthorRules,
+    /// The ids may match different rules.
+    Id,
+    /// The style attributes are different.
+    StyleAttribute,
+    /// The class lists are different.
+    Class,
+    /// The presentational hints are different.
+    PresHints,
+    /// The part lists are different.
+    Parts,
+    /// The elements match different revalidation selectors.
+    Revalidation,
+}
+
+/// Statistics about the style sharing cache, which are only gathered when
+/// `StyleSystemOptions::style_sharing_statistics` is set.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StyleSharingStatistics {
+    /// The number of elements looked up in the cache.
+    pub lookups: u32,
+    /// The number of lookups that didn't test any candidate, because the
+    /// element or the current state of the cache doesn't allow sharing.
+    pub skipped: u32,
+    /// The number of lookups that found a candidate to share style with.
+    pub hits: u32,
+    /// The number of candidates tested.
+    pub candidates_tested: u32,
+    /// The number of candidates rejected by each check.
+    pub rejections: BTreeMap<SharingMissReason, u32>,
+    /// The number of revalidation failures each revalidation selector was
+    /// responsible for, by serialization.
+    pub revalidation_failures: BTreeMap<String, u32>,
+}
+
+impl StyleSharingStatistics {
+    /// The number of lookups that didn't find a candidate to share style with.
+    pub fn misses(&self) -> u32 {
+        self.lookups - self.hits
+    }
+
+    /// The proportion of lookups that found a candidate to share style with.
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            return 0.;
+        }
+        self.hits as f64 / self.lookups as f64
+    }
+
+    fn record_rejection<E>(
+        &mut self,
+        reason: SharingMissReason,
+        target: &StyleSharingTarget<E>,
+        candidate: &StyleSharingCandidate<E>,
+        stylist: &Stylist,
+    ) where
+        E: TElement,
+    {
+        *self.rejections.entry(reason).or_insert(0) += 1;
+        if reason != SharingMissReason::Revalidation {
+            return;
+        }
+
+        let (for_element, for_candidate) = match (
+            target.validation_data.revalidation_match_results.as_ref(),
+            candidate.validation_data.revalidation_match_results.as_ref(),
+        ) {
+            (Some(for_element), Some(for_candidate)) => (for_element, for_candidate),
+            _ => return,
+        };
+        let indices: Vec<usize> = (0..for_element.len())
+            .filter(|i| for_element.get(*i) != for_candidate.get(*i))
+            .collect();
+        for selector in stylist.revalidation_selectors_at(target.element, &indices) {
+            *self.revalidation_failures.entry(selector).or_insert(0) += 1;
+        }
+    }
+}
+
+impl AddAssign for StyleSharingStatistics {
+    fn add_assign(&mut self, other: Self) {
+        self.lookups += other.lookups;
+        self.skipped += other.skipped;
+        self.hits += other.hits;
+        self.candidates_tested += other.candidates_tested;
+        for (reason, count) in other.rejections {
+            *self.rejections.entry(reason).or_insert(0) += count;
+        }
+        for (selector, count) in other.revalidation_failures {
+            *self.revalidation_failures.entry(selector).or_insert(0) += count;
+        }
+    }
+}
+
 /// Opaque pointer type to compare ComputedValues identities.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct OpaqueComputedValues(NonNull<()>);
@@ -227,7 +345,7 @@ impl ValidationData {
         element: E,
         stylist: &Stylist,
         bloom: &StyleBloom<E>,
-        nth_index_cache: &mut NthIndexCache,
+        selector_caches: &mut SelectorCaches,
         bloom_known_valid: bool,
         needs_selector_flags: NeedsSelectorFlags,
     ) -> &SmallBitVec
@@ -254,7 +372,7 @@ impl ValidationData {
             stylist.match_revalidation_selectors(
                 element,
                 bloom_to_use,
-                nth_index_cache,
+                selector_caches,
                 needs_selector_flags,
             )
         })
@@ -317,13 +435,13 @@ impl<E: TElement> StyleSharingCandidate<E> {
         &mut self,
         stylist: &Stylist,
         bloom: &StyleBloom<E>,
-        nth_index_cache: &mut NthIndexCache,
+        selector_caches: &mut SelectorCaches,
     ) -> &SmallBitVec {
         self.validation_data.revalidation_match_results(
             self.element,
             stylist,
             bloom,
-            nth_index_cache,
+            selector_caches,
             /* bloom_known_valid = */ false,
             // The candidate must already have the right bits already, if
             // needed.
@@ -383,7 +501,7 @@ impl<E: TElement> StyleSharingTarget<E> {
         &mut self,
         stylist: &Stylist,
         bloom: &StyleBloom<E>,
-        nth_index_cache: &mut NthIndexCache,
+        selector_caches: &mut SelectorCaches,
     ) -> &SmallBitVec {
         // It's important to set the selector flags. Otherwise, if we succeed in
         // sharing the style, we may not set the slow selector flags for the
@@ -404,7 +522,7 @@ impl<E: TElement> StyleSharingTarget<E> {
             self.element,
             stylist,
             bloom,
-            nth_index_cache,
+            selector_caches,
             /* bloom_known_valid = */ true,
             NeedsSelectorFlags::Yes,
         )
@@ -418,7 +536,15 @@ impl<E: TElement> StyleSharingTarget<E> {
         let cache = &mut context.thread_local.sharing_cache;
         let shared_context = &context.shared;
         let bloom_filter = &context.thread_local.bloom_filter;
-        let nth_index_cache = &mut context.thread_local.nth_index_cache;
+        let selector_caches = &mut context.thread_local.selector_caches;
+        let mut statistics = if shared_context.options.style_sharing_statistics {
+            Some(&mut context.thread_local.statistics.sharing)
+        } else {
+            None
+        };
+        if let Some(ref mut statistics) = statistics {
+            statistics.lookups += 1;
+        }
 
         if cache.dom_depth != bloom_filter.matching_depth() {
             debug!(
@@ -427,6 +553,9 @@ impl<E: TElement> StyleSharingTarget<E> {
                 bloom_filter.matching_depth(),
                 self.element
             );
+            if let Some(statistics) = statistics {
+                statistics.skipped += 1;
+            }
             return None;
         }
         debug_assert_eq!(
@@ -434,7 +563,17 @@ impl<E: TElement> StyleSharingTarget<E> {
             self.element.traversal_parent()
         );
 
-        cache.share_style_if_possible(shared_context, bloom_filter, nth_index_cache, self)
+        let styles = cache.share_style_if_possible(
+            shared_context,
+            bloom_filter,
+            selector_caches,
+            statistics.as_mut().map(|statistics| &mut **statistics),
+            self,
+        );
+        if let (Some(statistics), Some(_)) = (statistics, styles.as_ref()) {
+            statistics.hits += 1;
+        }
+        styles
     }
 
     /// Gets the validation data used to match against this target, if any.
@@ -655,39 +794,43 @@ impl<E: TElement> StyleSharingCache<E> {
         &mut self,
         shared_context: &SharedStyleContext,
         bloom_filter: &StyleBloom<E>,
-        nth_index_cache: &mut NthIndexCache,
+        selector_caches: &mut SelectorCaches,
+        mut statistics: Option<&mut StyleSharingStatistics>,
         target: &mut StyleSharingTarget<E>,
     ) -> Option<ResolvedElementStyles> {
-        if shared_context.options.disable_style_sharing_cache {
-            debug!(
-                "{:?} Cannot share style: style sharing cache disabled",
-                target.element
-            );
-            return None;
-        }
-
-        if target.inheritance_parent().is_none() {
-            debug!(
-                "{:?} Cannot share style: element has no parent",
-                target.element
-            );
-            return None;
-        }
-
-        if target.is_in_native_anonymous_subtree() {
-            debug!("{:?} Cannot share style: NAC", target.element);
+        let skip_reason = if shared_context.options.disable_style_sharing_cache {
+            Some("style sharing cache disabled")
+        } else if target.inheritance_parent().is_none() {
+            Some("element has no parent")
+        } else if target.is_in_native_anonymous_subtree() {
+            Some("NAC")
+        } else {
+            None
+        };
+        if let Some(reason) = skip_reason {
+            debug!("{:?} Cannot share style: {}", target.element, reason);
+            if let Some(statistics) = statistics {
+                statistics.skipped += 1;
+            }
             return None;
         }
 
         self.cache_mut().entries.lookup(|candidate| {
-            Self::test_candidate(
+            let result = Self::test_candidate(
                 target,
                 candidate,
                 &shared_context,
                 bloom_filter,
-                nth_index_cache,
+                selector_caches,
                 shared_context,
-            )
+            );
+            if let Some(ref mut statistics) = statistics {
+                statistics.candidates_tested += 1;
+                if let Err(reason) = result {
+                    statistics.record_rejection(reason, target, candidate, shared_context.stylist);
+                }
+            }
+            result.ok()
         })
     }
 
@@ -696,9 +839,9 @@ impl<E: TElement> StyleSharingCache<E> {
         candidate: &mut StyleSharingCandidate<E>,
         shared: &SharedStyleContext,
         bloom: &StyleBloom<E>,
-        nth_index_cache: &mut NthIndexCache,
+        selector_caches: &mut SelectorCaches,
         shared_context: &SharedStyleContext,
-    ) -> Option<ResolvedElementStyles> {
+    ) -> Result<ResolvedElementStyles, SharingMissReason> {
         debug_assert!(!target.is_in_native_anonymous_subtree());
 
         // Check that we have the same parent, or at least that the parents
@@ -707,36 +850,36 @@ impl<E: TElement> StyleSharingCache<E> {
         // shared style.
         if !checks::parents_allow_sharing(target, candidate) {
             trace!("Miss: Parent");
-            return None;
+            return Err(SharingMissReason::Parent);
         }
 
         if target.local_name() != candidate.element.local_name() {
             trace!("Miss: Local Name");
-            return None;
+            return Err(SharingMissReason::LocalName);
         }
 
         if target.namespace() != candidate.element.namespace() {
             trace!("Miss: Namespace");
-            return None;
+            return Err(SharingMissReason::Namespace);
         }
 
         // We do not ignore visited state here, because Gecko needs to store
         // extra bits on visited styles, so these contexts cannot be shared.
         if target.element.state() != candidate.state() {
             trace!("Miss: User and Author State");
-            return None;
+            return Err(SharingMissReason::State);
         }
 
         if target.is_link() != candidate.element.is_link() {
             trace!("Miss: Link");
-            return None;
+            return Err(SharingMissReason::Link);
         }
 
         // If two elements belong to different shadow trees, different rules may
         // apply to them, from the respective trees.
         if target.element.containing_shadow() != candidate.element.containing_shadow() {
             trace!("Miss: Different containing shadow roots");
-            return None;
+            return Err(SharingMissReason::ContainingShadow);
         }
 
         // If the elements are not assigned to the same slot they could match
@@ -749,55 +892,55 @@ impl<E: TElement> StyleSharingCache<E> {
             // TODO(emilio): We could have a look at whether the shadow roots
             // actually have slotted rules and such.
             trace!("Miss: Different assigned slots");
-            return None;
+            return Err(SharingMissReason::AssignedSlot);
         }
 
         if target.element.shadow_root().is_some() {
             trace!("Miss: Shadow host");
-            return None;
+            return Err(SharingMissReason::ShadowHost);
         }
 
         if target.element.has_animations(shared_context) {
             trace!("Miss: Has Animations");
-            return None;
+            return Err(SharingMissReason::Animations);
         }
 
         if target.matches_user_and_author_rules() !=
             candidate.element.matches_user_and_author_rules()
         {
             trace!("Miss: User and Author Rules");
-            return None;
+            return Err(SharingMissReason::UserAndAuthorRules);
         }
 
         // It's possible that there are no styles for either id.
         if checks::may_match_different_id_rules(shared, target.element, candidate.element) {
             trace!("Miss: ID Attr");
-            return None;
+            return Err(SharingMissReason::Id);
         }
 
         if !checks::have_same_style_attribute(target, candidate) {
             trace!("Miss: Style Attr");
-            return None;
+            return Err(SharingMissReason::StyleAttribute);
         }
 
         if !checks::have_same_class(target, candidate) {
             trace!("Miss: Class");
-            return None;
+            return Err(SharingMissReason::Class);
         }
 
         if !checks::have_same_presentational_hints(target, candidate) {
             trace!("Miss: Pres Hints");
-            return None;
+            return Err(SharingMissReason::PresHints);
         }
 
         if !checks::have_same_parts(target, candidate) {
             trace!("Miss: Shadow parts");
-            return None;
+            return Err(SharingMissReason::Parts);
         }
 
-        if !checks::revalidate(target, candidate, shared, bloom, nth_index_cache) {
+        if !checks::revalidate(target, candidate, shared, bloom, selector_caches) {
             trace!("Miss: Revalidation");
-            return None;
+            return Err(SharingMissReason::Revalidation);
         }
 
         debug_assert!(target.has_current_styles_for_traversal(
@@ -809,7 +952,7 @@ impl<E: TElement> StyleSharingCache<E> {
             "Sharing allowed between {:?} and {:?}",
             target.element, candidate.element
         );
-        Some(candidate.element.borrow_data().unwrap().share_styles())
+        Ok(candidate.element.borrow_data().unwrap().share_styles())
     }
 
     /// Attempts to find an element in the cache with the given primary rule
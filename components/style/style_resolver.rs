@@ -16,8 +16,8 @@ use crate::rule_tree::StrongRuleNode;
 use crate::selector_parser::{PseudoElement, SelectorImpl};
 use crate::stylist::RuleInclusion;
 use log::Level::Trace;
-use selectors::matching::{MatchingContext, NeedsSelectorFlags};
-use selectors::matching::{MatchingMode, VisitedHandlingMode};
+use selectors::matching::{IncludeStartingStyle, MatchingContext, MatchingForInvalidation};
+use selectors::matching::{MatchingMode, NeedsSelectorFlags, VisitedHandlingMode};
 use servo_arc::Arc;
 
 /// Whether pseudo-elements should be resolved or not.
@@ -46,6 +46,7 @@ where
 struct MatchingResults {
     rule_node: StrongRuleNode,
     flags: ComputedValueFlags,
+    has_starting_style: bool,
 }
 
 /// A style returned from the resolver machinery.
@@ -186,15 +187,20 @@ where
         parent_style: Option<&ComputedValues>,
         layout_parent_style: Option<&ComputedValues>,
     ) -> PrimaryStyle {
-        let primary_results = self.match_primary(VisitedHandlingMode::AllLinksUnvisited);
+        let primary_results = self.match_primary(
+            VisitedHandlingMode::AllLinksUnvisited,
+            IncludeStartingStyle::No,
+        );
 
         let inside_link = parent_style.map_or(false, |s| s.visited_style().is_some());
 
         let visited_rules = if self.context.shared.visited_styles_enabled &&
             (inside_link || self.element.is_link())
         {
-            let visited_matching_results =
-                self.match_primary(VisitedHandlingMode::RelevantLinkVisited);
+            let visited_matching_results = self.match_primary(
+                VisitedHandlingMode::RelevantLinkVisited,
+                IncludeStartingStyle::No,
+            );
             Some(visited_matching_results.rule_node)
         } else {
             None
@@ -288,6 +294,44 @@ where
         }
     }
 
+    /// Resolve the starting style of the element, that is, its style with the
+    /// rules inside `@starting-style` applied, which is used as the
+    /// before-change style of transitions when the element didn't have a style
+    /// before. Returns `None` if none of those rules applies to the element.
+    ///
+    /// <https://drafts.csswg.org/css-transitions-2/#defining-before-change-style>
+    pub fn resolve_starting_style(&mut self) -> Option<ResolvedStyle> {
+        let results = self.match_primary(
+            VisitedHandlingMode::AllLinksUnvisited,
+            IncludeStartingStyle::Yes,
+        );
+        if !results.has_starting_style {
+            return None;
+        }
+
+        let inside_link = with_default_parent_styles(self.element, |parent_style, _| {
+            parent_style.map_or(false, |s| s.visited_style().is_some())
+        });
+        let visited_rules = if self.context.shared.visited_styles_enabled &&
+            (inside_link || self.element.is_link())
+        {
+            let visited_matching_results = self.match_primary(
+                VisitedHandlingMode::RelevantLinkVisited,
+                IncludeStartingStyle::Yes,
+            );
+            Some(visited_matching_results.rule_node)
+        } else {
+            None
+        };
+
+        let inputs = CascadeInputs {
+            rules: Some(results.rule_node),
+            visited_rules,
+            flags: results.flags,
+        };
+        Some(self.cascade_style_and_visited_with_default_parents(inputs))
+    }
+
     /// Resolve an element's styles with the default inheritance parent/layout
     /// parents.
     pub fn resolve_style_with_default_parents(&mut self) -> ResolvedElementStyles {
@@ -420,7 +464,11 @@ where
         originating_element_style: &PrimaryStyle,
         layout_parent_style: Option<&ComputedValues>,
     ) -> Option<ResolvedStyle> {
-        let MatchingResults { rule_node, mut flags } = self.match_pseudo(
+        let MatchingResults {
+            rule_node,
+            mut flags,
+            ..
+        } = self.match_pseudo(
             &originating_element_style.style.0,
             pseudo,
             VisitedHandlingMode::AllLinksUnvisited,
@@ -450,7 +498,11 @@ where
         ))
     }
 
-    fn match_primary(&mut self, visited_handling: VisitedHandlingMode) -> MatchingResults {
+    fn match_primary(
+        &mut self,
+        visited_handling: VisitedHandlingMode,
+        include_starting_style: IncludeStartingStyle,
+    ) -> MatchingResults {
         debug!(
             "Match primary for {:?}, visited: {:?}",
             self.element, visited_handling
@@ -458,15 +510,17 @@ where
         let mut applicable_declarations = ApplicableDeclarationList::new();
 
         let bloom_filter = self.context.thread_local.bloom_filter.filter();
-        let nth_index_cache = &mut self.context.thread_local.nth_index_cache;
+        let selector_caches = &mut self.context.thread_local.selector_caches;
         let mut matching_context = MatchingContext::new_for_visited(
             MatchingMode::Normal,
             Some(bloom_filter),
-            nth_index_cache,
+            selector_caches,
             visited_handling,
             self.context.shared.quirks_mode(),
             NeedsSelectorFlags::Yes,
+            MatchingForInvalidation::No,
         );
+        matching_context.include_starting_style = include_starting_style;
 
         let stylist = &self.context.shared.stylist;
         let implemented_pseudo = self.element.implemented_pseudo_element();
@@ -502,6 +556,7 @@ where
         MatchingResults {
             rule_node,
             flags: matching_context.extra_data.cascade_input_flags,
+            has_starting_style: matching_context.has_starting_style,
         }
     }
 
@@ -533,15 +588,16 @@ where
         }
 
         let bloom_filter = self.context.thread_local.bloom_filter.filter();
-        let nth_index_cache = &mut self.context.thread_local.nth_index_cache;
+        let selector_caches = &mut self.context.thread_local.selector_caches;
 
         let mut matching_context = MatchingContext::<'_, E::Impl>::new_for_visited(
             MatchingMode::ForStatelessPseudoElement,
             Some(bloom_filter),
-            nth_index_cache,
+            selector_caches,
             visited_handling,
             self.context.shared.quirks_mode(),
             NeedsSelectorFlags::Yes,
+            MatchingForInvalidation::No,
         );
         matching_context.extra_data.originating_element_style =
             Some(originating_element_style);
@@ -570,6 +626,7 @@ where
         Some(MatchingResults {
             rule_node,
             flags: matching_context.extra_data.cascade_input_flags,
+            has_starting_style: matching_context.has_starting_style,
         })
     }
 }
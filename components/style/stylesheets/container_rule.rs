@@ -9,7 +9,6 @@
 use crate::computed_value_flags::ComputedValueFlags;
 use crate::dom::TElement;
 use crate::logical_geometry::{LogicalSize, WritingMode};
-use crate::media_queries::Device;
 use crate::parser::ParserContext;
 use crate::properties::ComputedValues;
 use crate::queries::condition::KleeneValue;
@@ -21,13 +20,15 @@ use crate::shared_lock::{
 };
 use crate::str::CssStringWriter;
 use crate::stylesheets::CssRules;
+use crate::stylist::Stylist;
 use crate::values::computed::{CSSPixelLength, ContainerType, Context, Ratio};
 use crate::values::specified::ContainerName;
 use app_units::Au;
 use cssparser::{Parser, SourceLocation};
 use euclid::default::Size2D;
-#[cfg(feature = "gecko")]
-use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use malloc_size_of::{
+    MallocSizeOfOps, MallocUnconditionalShallowSizeOf, MallocUnconditionalSizeOf,
+};
 use servo_arc::Arc;
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ParseError, ToCss};
@@ -55,10 +56,9 @@ impl ContainerRule {
     }
 
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
-        // Measurement of other fields may be added later.
-        self.rules.unconditional_shallow_size_of(ops) +
+        self.condition.unconditional_size_of(ops) +
+            self.rules.unconditional_shallow_size_of(ops) +
             self.rules.read_with(guard).size_of(guard, ops)
     }
 }
@@ -95,7 +95,7 @@ impl ToCssWithGuard for ContainerRule {
 }
 
 /// A container condition and filter, combined.
-#[derive(Debug, ToShmem, ToCss)]
+#[derive(Debug, MallocSizeOf, ToShmem, ToCss)]
 pub struct ContainerCondition {
     #[css(skip_if = "ContainerName::is_none")]
     name: ContainerName,
@@ -182,6 +182,12 @@ impl ContainerCondition {
         })
     }
 
+    /// Whether this condition contains a `style()` query, and thus depends on
+    /// the computed values of the container.
+    pub fn has_style_query(&self) -> bool {
+        self.flags.contains(FeatureFlags::CONTAINER_REQUIRES_STYLE)
+    }
+
     fn valid_container_info<E>(
         &self,
         potential_container: E,
@@ -252,7 +258,7 @@ impl ContainerCondition {
     /// Tries to match a container query condition for a given element.
     pub(crate) fn matches<E>(
         &self,
-        device: &Device,
+        stylist: &Stylist,
         element: E,
         originating_element_style: Option<&ComputedValues>,
         invalidation_flags: &mut ComputedValueFlags,
@@ -268,7 +274,7 @@ impl ContainerCondition {
         // Set up the lookup for the container in question, as the condition may be using container query lengths.
         let size_query_container_lookup = ContainerSizeQuery::for_option_element(container, None);
         Context::for_container_query_evaluation(
-            device,
+            stylist,
             info,
             size_query_container_lookup,
             |context| {
@@ -348,7 +354,7 @@ fn eval_orientation(context: &Context, value: Option<Orientation>) -> KleeneValu
 
 /// https://drafts.csswg.org/css-contain-3/#container-features
 ///
-/// TODO: Support style queries, perhaps.
+/// Style queries are handled separately, see `StyleFeature`.
 pub static CONTAINER_FEATURES: [QueryFeatureDescription; 6] = [
     feature!(
         atom!("width"),
@@ -14,8 +14,7 @@ use crate::str::CssStringWriter;
 use crate::stylesheets::CssRules;
 use crate::values::CssUrl;
 use cssparser::{Parser, SourceLocation};
-#[cfg(feature = "gecko")]
-use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use servo_arc::Arc;
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
@@ -33,10 +32,9 @@ pub struct DocumentRule {
 
 impl DocumentRule {
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
-        // Measurement of other fields may be added later.
-        self.rules.unconditional_shallow_size_of(ops) +
+        self.condition.size_of(ops) +
+            self.rules.unconditional_shallow_size_of(ops) +
             self.rules.read_with(guard).size_of(guard, ops)
     }
 }
@@ -72,7 +70,7 @@ impl DeepCloneWithLock for DocumentRule {
 }
 
 /// The kind of media document that the rule will match.
-#[derive(Clone, Copy, Debug, Parse, PartialEq, ToCss, ToShmem)]
+#[derive(Clone, Copy, Debug, MallocSizeOf, Parse, PartialEq, ToCss, ToShmem)]
 #[allow(missing_docs)]
 pub enum MediaDocumentKind {
     All,
@@ -82,7 +80,7 @@ pub enum MediaDocumentKind {
 }
 
 /// A matching function for a `@document` rule's condition.
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 pub enum DocumentMatchingFunction {
     /// Exact URL matching function. It evaluates to true whenever the
     /// URL of the document being styled is exactly the URL given.
@@ -245,7 +243,7 @@ impl DocumentMatchingFunction {
 /// The `@document` rule's condition is written as a comma-separated list of
 /// URL matching functions, and the condition evaluates to true whenever any
 /// one of those functions evaluates to true.
-#[derive(Clone, Debug, ToCss, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToCss, ToShmem)]
 #[css(comma)]
 pub struct DocumentCondition(#[css(iterable)] Vec<DocumentMatchingFunction>);
 
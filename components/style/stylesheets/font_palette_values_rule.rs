@@ -22,6 +22,7 @@ use crate::values::DashedIdent;
 use cssparser::{AtRuleParser, CowRcStr};
 use cssparser::{DeclarationParser, DeclarationListParser, Parser};
 use cssparser::{QualifiedRuleParser, SourceLocation};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
 use style_traits::{Comma, OneOrMoreSeparated};
@@ -110,6 +111,14 @@ impl FontPaletteValuesRule {
         }
     }
 
+    /// Measure heap usage.
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.name.size_of(ops) +
+            self.family_names.size_of(ops) +
+            self.base_palette.size_of(ops) +
+            self.override_colors.size_of(ops)
+    }
+
     /// Parses a `FontPaletteValuesRule`.
     pub fn parse(
         context: &ParserContext,
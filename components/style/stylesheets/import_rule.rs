@@ -14,6 +14,7 @@ use crate::stylesheets::layer_rule::LayerName;
 use crate::stylesheets::{CssRule, StylesheetInDocument};
 use crate::values::CssUrl;
 use cssparser::SourceLocation;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ToCss};
 use to_shmem::{self, SharedMemoryBuilder, ToShmem};
@@ -125,7 +126,7 @@ impl DeepCloneWithLock for ImportSheet {
 }
 
 /// The layer keyword or function in an import rule.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, MallocSizeOf)]
 pub struct ImportLayer {
     /// The layer name, or None for an anonymous layer.
     pub name: Option<LayerName>,
@@ -167,6 +168,17 @@ pub struct ImportRule {
     pub source_location: SourceLocation,
 }
 
+impl ImportRule {
+    /// Measure heap usage.
+    ///
+    /// The imported stylesheet isn't measured here, because it's measured as
+    /// a stylesheet of its own (on the C++ side in Gecko's case, in the child
+    /// list of the ServoStyleSheet).
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.url.size_of(ops) + self.layer.size_of(ops)
+    }
+}
+
 impl ToShmem for ImportRule {
     fn to_shmem(&self, _builder: &mut SharedMemoryBuilder) -> to_shmem::Result<Self> {
         Err(String::from(
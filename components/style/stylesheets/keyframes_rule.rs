@@ -4,6 +4,7 @@
 
 //! Keyframes: https://drafts.csswg.org/css-animations/#keyframes
 
+use crate::custom_properties::Name as CustomPropertyName;
 use crate::error_reporting::ContextualParseError;
 use crate::parser::ParserContext;
 use crate::properties::longhands::animation_composition::single_value::SpecifiedValue as SpecifiedComposition;
@@ -22,6 +23,7 @@ use cssparser::{
     parse_one_rule, DeclarationListParser, DeclarationParser, ParserState, SourceLocation, Token,
 };
 use cssparser::{AtRuleParser, CowRcStr, Parser, ParserInput, QualifiedRuleParser, RuleListParser};
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use servo_arc::Arc;
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ParseError, ParsingMode, StyleParseErrorKind, ToCss};
@@ -74,6 +76,16 @@ impl KeyframesRule {
         }
         None
     }
+
+    /// Measure heap usage.
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+        let mut n = self.keyframes.shallow_size_of(ops);
+        for keyframe in self.keyframes.iter() {
+            n += keyframe.unconditional_shallow_size_of(ops) +
+                keyframe.read_with(guard).size_of(guard, ops);
+        }
+        n
+    }
 }
 
 impl DeepCloneWithLock for KeyframesRule {
@@ -203,6 +215,13 @@ impl ToCssWithGuard for Keyframe {
 }
 
 impl Keyframe {
+    /// Measure heap usage.
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+        self.selector.0.shallow_size_of(ops) +
+            self.block.unconditional_shallow_size_of(ops) +
+            self.block.read_with(guard).size_of(ops)
+    }
+
     /// Parse a CSS keyframe.
     pub fn parse<'i>(
         css: &'i str,
@@ -407,16 +426,21 @@ pub struct KeyframesAnimation {
     pub steps: Vec<KeyframesStep>,
     /// The properties that change in this animation.
     pub properties_changed: LonghandIdSet,
+    /// The custom properties that change in this animation. Only the ones that
+    /// are registered with a syntax other than `*` are actually animated.
+    pub custom_properties_changed: Vec<CustomPropertyName>,
     /// Vendor prefix type the @keyframes has.
     pub vendor_prefix: Option<VendorPrefix>,
 }
 
-/// Get all the animated properties in a keyframes animation.
+/// Get all the animated properties and custom properties in a keyframes
+/// animation.
 fn get_animated_properties(
     keyframes: &[Arc<Locked<Keyframe>>],
     guard: &SharedRwLockReadGuard,
-) -> LonghandIdSet {
+) -> (LonghandIdSet, Vec<CustomPropertyName>) {
     let mut ret = LonghandIdSet::new();
+    let mut custom_properties = vec![];
     // NB: declarations are already deduplicated, so we don't have to check for
     // it here.
     for keyframe in keyframes {
@@ -431,7 +455,12 @@ fn get_animated_properties(
         for declaration in block.normal_declaration_iter() {
             let longhand_id = match declaration.id() {
                 PropertyDeclarationId::Longhand(id) => id,
-                _ => continue,
+                PropertyDeclarationId::Custom(name) => {
+                    if !custom_properties.contains(name) {
+                        custom_properties.push(name.clone());
+                    }
+                    continue;
+                },
             };
 
             if longhand_id == LonghandId::Display {
@@ -446,7 +475,7 @@ fn get_animated_properties(
         }
     }
 
-    ret
+    (ret, custom_properties)
 }
 
 impl KeyframesAnimation {
@@ -466,6 +495,7 @@ impl KeyframesAnimation {
         let mut result = KeyframesAnimation {
             steps: vec![],
             properties_changed: LonghandIdSet::new(),
+            custom_properties_changed: vec![],
             vendor_prefix,
         };
 
@@ -473,8 +503,11 @@ impl KeyframesAnimation {
             return result;
         }
 
-        result.properties_changed = get_animated_properties(keyframes, guard);
-        if result.properties_changed.is_empty() {
+        let (properties_changed, custom_properties_changed) =
+            get_animated_properties(keyframes, guard);
+        result.properties_changed = properties_changed;
+        result.custom_properties_changed = custom_properties_changed;
+        if result.properties_changed.is_empty() && result.custom_properties_changed.is_empty() {
             return result;
         }
 
@@ -14,6 +14,7 @@ use crate::values::AtomIdent;
 use super::CssRules;
 
 use cssparser::{Parser, SourceLocation, Token};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use servo_arc::Arc;
 use smallvec::SmallVec;
 use std::fmt::{self, Write};
@@ -160,6 +161,15 @@ pub struct LayerBlockRule {
     pub source_location: SourceLocation,
 }
 
+impl LayerBlockRule {
+    /// Measure heap usage.
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+        self.name.size_of(ops) +
+            self.rules.unconditional_shallow_size_of(ops) +
+            self.rules.read_with(guard).size_of(guard, ops)
+    }
+}
+
 impl ToCssWithGuard for LayerBlockRule {
     fn to_css(
         &self,
@@ -207,6 +217,13 @@ pub struct LayerStatementRule {
     pub source_location: SourceLocation,
 }
 
+impl LayerStatementRule {
+    /// Measure heap usage.
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.names.size_of(ops)
+    }
+}
+
 impl ToCssWithGuard for LayerStatementRule {
     fn to_css(
         &self,
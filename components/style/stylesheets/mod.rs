@@ -22,6 +22,8 @@ mod page_rule;
 mod rule_list;
 mod rule_parser;
 mod rules_iterator;
+pub mod scope_rule;
+mod starting_style_rule;
 mod style_rule;
 mod stylesheet;
 pub mod supports_rule;
@@ -36,8 +38,7 @@ use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
 use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
 use crate::str::CssStringWriter;
 use cssparser::{parse_one_rule, Parser, ParserInput};
-#[cfg(feature = "gecko")]
-use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use servo_arc::Arc;
 use std::fmt;
 #[cfg(feature = "gecko")]
@@ -66,7 +67,10 @@ pub use self::rules_iterator::{AllRules, EffectiveRules};
 pub use self::rules_iterator::{
     EffectiveRulesIterator, NestedRuleIterationCondition, RulesIterator,
 };
+pub use self::scope_rule::ScopeRule;
+pub use self::starting_style_rule::StartingStyleRule;
 pub use self::style_rule::StyleRule;
+pub use crate::properties_and_values::rule::PropertyRule;
 pub use self::stylesheet::{AllowImportRules, SanitizationData, SanitizationKind};
 pub use self::stylesheet::{DocumentStyleSheet, Namespaces, Stylesheet};
 pub use self::stylesheet::{StylesheetContents, StylesheetInDocument, UserAgentStylesheets};
@@ -75,7 +79,7 @@ pub use self::viewport_rule::ViewportRule;
 
 /// The CORS mode used for a CSS load.
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq, ToShmem)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, ToShmem)]
 pub enum CorsMode {
     /// No CORS mode, so cross-origin loads can be done.
     None,
@@ -160,6 +164,12 @@ impl UrlExtraData {
         UrlExtraData(ptr as usize)
     }
 
+    /// Returns the extra data of the user-agent style sheet with the given
+    /// id, which is in URLExtraData_sShared in every process.
+    pub fn for_user_agent_sheet(sheet_id: usize) -> UrlExtraData {
+        UrlExtraData((sheet_id << 1) | 1)
+    }
+
     /// True if this URL scheme is chrome.
     #[inline]
     pub fn chrome_rules_enabled(&self) -> bool {
@@ -267,20 +277,49 @@ pub enum CssRule {
     Document(Arc<Locked<DocumentRule>>),
     LayerBlock(Arc<Locked<LayerBlockRule>>),
     LayerStatement(Arc<Locked<LayerStatementRule>>),
+    Property(Arc<Locked<PropertyRule>>),
+    Scope(Arc<Locked<ScopeRule>>),
+    StartingStyle(Arc<Locked<StartingStyleRule>>),
 }
 
 impl CssRule {
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
-    fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
         match *self {
-            // Not all fields are currently fully measured. Extra measurement
-            // may be added later.
-            CssRule::Namespace(_) => 0,
+            // The prefix and URL of a namespace rule are atoms, so there's
+            // nothing to measure besides the rule itself.
+            CssRule::Namespace(ref lock) => lock.unconditional_shallow_size_of(ops),
+
+            // Viewport descriptors only own heap data for calc() lengths, which
+            // we don't bother measuring.
+            CssRule::Viewport(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) +
+                    lock.read_with(guard).declarations.shallow_size_of(ops)
+            },
+
+            CssRule::Import(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
 
-            // We don't need to measure ImportRule::stylesheet because we measure
-            // it on the C++ side in the child list of the ServoStyleSheet.
-            CssRule::Import(_) => 0,
+            CssRule::FontFace(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
+
+            CssRule::FontFeatureValues(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
+
+            CssRule::FontPaletteValues(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
+
+            CssRule::CounterStyle(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
+
+            CssRule::Property(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
 
             CssRule::Style(ref lock) => {
                 lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
@@ -294,12 +333,17 @@ impl CssRule {
                 lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
             },
 
-            CssRule::FontFace(_) => 0,
-            CssRule::FontFeatureValues(_) => 0,
-            CssRule::FontPaletteValues(_) => 0,
-            CssRule::CounterStyle(_) => 0,
-            CssRule::Viewport(_) => 0,
-            CssRule::Keyframes(_) => 0,
+            CssRule::Scope(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
+            },
+
+            CssRule::StartingStyle(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
+            },
+
+            CssRule::Keyframes(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
+            },
 
             CssRule::Supports(ref lock) => {
                 lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
@@ -313,8 +357,13 @@ impl CssRule {
                 lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
             },
 
-            // TODO(emilio): Add memory reporting for these rules.
-            CssRule::LayerBlock(_) | CssRule::LayerStatement(_) => 0,
+            CssRule::LayerBlock(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(guard, ops)
+            },
+
+            CssRule::LayerStatement(ref lock) => {
+                lock.unconditional_shallow_size_of(ops) + lock.read_with(guard).size_of(ops)
+            },
         }
     }
 }
@@ -353,6 +402,12 @@ pub enum CssRuleType {
     LayerStatement = 17,
     Container = 18,
     FontPaletteValues = 19,
+    // https://drafts.css-houdini.org/css-properties-values-api-1/#the-css-property-rule-interface
+    Property = 20,
+    // https://drafts.csswg.org/css-cascade-6/#the-cssscoperule-interface
+    Scope = 21,
+    // https://drafts.csswg.org/css-transitions-2/#the-cssstartingstylerule-interface
+    StartingStyle = 22,
 }
 
 #[allow(missing_docs)]
@@ -383,6 +438,9 @@ impl CssRule {
             CssRule::LayerBlock(_) => CssRuleType::LayerBlock,
             CssRule::LayerStatement(_) => CssRuleType::LayerStatement,
             CssRule::Container(_) => CssRuleType::Container,
+            CssRule::Property(_) => CssRuleType::Property,
+            CssRule::Scope(_) => CssRuleType::Scope,
+            CssRule::StartingStyle(_) => CssRuleType::StartingStyle,
         }
     }
 
@@ -472,6 +530,18 @@ impl DeepCloneWithLock for CssRule {
                     lock.wrap(rule.deep_clone_with_lock(lock, guard, params)),
                 ))
             },
+            CssRule::Scope(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::Scope(Arc::new(
+                    lock.wrap(rule.deep_clone_with_lock(lock, guard, params)),
+                ))
+            },
+            CssRule::StartingStyle(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::StartingStyle(Arc::new(
+                    lock.wrap(rule.deep_clone_with_lock(lock, guard, params)),
+                ))
+            },
             CssRule::FontFace(ref arc) => {
                 let rule = arc.read_with(guard);
                 CssRule::FontFace(Arc::new(lock.wrap(rule.clone())))
@@ -484,6 +554,10 @@ impl DeepCloneWithLock for CssRule {
                 let rule = arc.read_with(guard);
                 CssRule::FontPaletteValues(Arc::new(lock.wrap(rule.clone())))
             },
+            CssRule::Property(ref arc) => {
+                let rule = arc.read_with(guard);
+                CssRule::Property(Arc::new(lock.wrap(rule.clone())))
+            },
             CssRule::CounterStyle(ref arc) => {
                 let rule = arc.read_with(guard);
                 CssRule::CounterStyle(Arc::new(lock.wrap(rule.clone())))
@@ -552,6 +626,9 @@ impl ToCssWithGuard for CssRule {
             CssRule::LayerBlock(ref lock) => lock.read_with(guard).to_css(guard, dest),
             CssRule::LayerStatement(ref lock) => lock.read_with(guard).to_css(guard, dest),
             CssRule::Container(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::Property(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::Scope(ref lock) => lock.read_with(guard).to_css(guard, dest),
+            CssRule::StartingStyle(ref lock) => lock.read_with(guard).to_css(guard, dest),
         }
     }
 }
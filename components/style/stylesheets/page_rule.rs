@@ -13,7 +13,6 @@ use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
 use crate::str::CssStringWriter;
 use crate::values::{AtomIdent, CustomIdent};
 use cssparser::{Parser, SourceLocation};
-#[cfg(feature = "gecko")]
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use servo_arc::Arc;
 use std::fmt::{self, Write};
@@ -101,9 +100,7 @@ pub struct PageRule {
 
 impl PageRule {
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
-        // Measurement of other fields may be added later.
         self.block.unconditional_shallow_size_of(ops) +
             self.block.read_with(guard).size_of(ops) +
             self.selectors.size_of(ops)
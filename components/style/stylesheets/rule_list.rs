@@ -11,7 +11,6 @@ use crate::stylesheets::loader::StylesheetLoader;
 use crate::stylesheets::rule_parser::{InsertRuleContext, State};
 use crate::stylesheets::stylesheet::StylesheetContents;
 use crate::stylesheets::{AllowImportRules, CssRule, RulesMutateError};
-#[cfg(feature = "gecko")]
 use malloc_size_of::{MallocShallowSizeOf, MallocSizeOfOps};
 use servo_arc::{Arc, RawOffsetArc};
 use std::fmt::{self, Write};
@@ -45,7 +44,6 @@ impl DeepCloneWithLock for CssRules {
 
 impl CssRules {
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
         let mut n = self.0.shallow_size_of(ops);
         for rule in self.0.iter() {
@@ -103,13 +101,42 @@ impl CssRules {
     ) -> fmt::Result {
         dest.write_str(" {")?;
         for rule in self.0.iter() {
-            dest.write_str("\n  ")?;
-            rule.to_css(guard, dest)?;
+            rule.to_css_indented(guard, dest)?;
         }
         dest.write_str("\n}")
     }
 }
 
+impl CssRule {
+    /// Serializes this rule in its own line of a block of rules, indenting
+    /// each of its lines, so that rules nested at any depth line up.
+    pub(crate) fn to_css_indented(
+        &self,
+        guard: &SharedRwLockReadGuard,
+        dest: &mut CssStringWriter,
+    ) -> fmt::Result {
+        let css = self.to_css_string(guard);
+        write!(IndentedWriter(dest), "\n{}", css)
+    }
+}
+
+/// A writer that indents every line it starts by two spaces.
+struct IndentedWriter<'a, W: Write>(&'a mut W);
+
+impl<'a, W: Write> Write for IndentedWriter<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.0.write_str(first)?;
+        }
+        for line in lines {
+            self.0.write_str("\n  ")?;
+            self.0.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
 /// A trait to implement helpers for `Arc<Locked<CssRules>>`.
 pub trait CssRulesHelpers {
     /// <https://drafts.csswg.org/cssom/#insert-a-css-rule>
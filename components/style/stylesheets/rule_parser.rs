@@ -9,7 +9,10 @@ use crate::error_reporting::ContextualParseError;
 use crate::font_face::parse_font_face_block;
 use crate::media_queries::MediaList;
 use crate::parser::{Parse, ParserContext};
-use crate::properties::parse_property_declaration_list;
+use crate::properties::{
+    parse_property_declaration_list, parse_property_declaration_list_with_rules,
+};
+use crate::properties_and_values::rule::PropertyRuleName;
 use crate::selector_parser::{SelectorImpl, SelectorParser};
 use crate::shared_lock::{Locked, SharedRwLock};
 use crate::str::starts_with_ignore_ascii_case;
@@ -19,13 +22,15 @@ use crate::stylesheets::font_feature_values_rule::parse_family_name_list;
 use crate::stylesheets::import_rule::ImportLayer;
 use crate::stylesheets::keyframes_rule::parse_keyframe_list;
 use crate::stylesheets::layer_rule::{LayerBlockRule, LayerName, LayerStatementRule};
+use crate::stylesheets::scope_rule::{ScopeBounds, ScopeRule};
+use crate::stylesheets::starting_style_rule::StartingStyleRule;
 use crate::stylesheets::stylesheet::Namespaces;
 use crate::stylesheets::supports_rule::SupportsCondition;
 use crate::stylesheets::{
     viewport_rule, AllowImportRules, CorsMode, CssRule, CssRuleType, CssRules, DocumentRule,
     FontFeatureValuesRule, FontPaletteValuesRule, KeyframesRule, MediaRule, NamespaceRule,
-    PageRule, PageSelectors, RulesMutateError, StyleRule, StylesheetLoader, SupportsRule,
-    ViewportRule,
+    PageRule, PageSelectors, PropertyRule, RulesMutateError, StyleRule, StylesheetLoader,
+    SupportsRule, ViewportRule,
 };
 use crate::values::computed::font::FamilyName;
 use crate::values::{CssUrl, CustomIdent, DashedIdent, KeyframesName};
@@ -34,6 +39,7 @@ use cssparser::{
     AtRuleParser, BasicParseError, BasicParseErrorKind, CowRcStr, Parser, ParserState,
     QualifiedRuleParser, RuleListParser, SourcePosition,
 };
+use selectors::parser::ParseRelative;
 use selectors::SelectorList;
 use servo_arc::Arc;
 use style_traits::{ParseError, StyleParseErrorKind};
@@ -109,6 +115,8 @@ impl<'b> TopLevelRuleParser<'b> {
             shared_lock: self.shared_lock,
             context: &self.context,
             namespaces: &self.namespaces,
+            in_style_rule: false,
+            in_scope_rule: false,
         }
     }
 
@@ -189,10 +197,16 @@ pub enum AtRulePrelude {
     FontPaletteValues(DashedIdent),
     /// A @counter-style rule prelude, with its counter style name.
     CounterStyle(CustomIdent),
+    /// A @property rule prelude, with its custom property name.
+    Property(PropertyRuleName),
     /// A @media rule prelude, with its media queries.
     Media(Arc<Locked<MediaList>>),
     /// A @container rule prelude.
     Container(Arc<ContainerCondition>),
+    /// A @scope rule prelude, with its scoping roots and limits.
+    Scope(Arc<ScopeBounds>),
+    /// A @starting-style rule prelude.
+    StartingStyle,
     /// An @supports rule, with its conditional
     Supports(SupportsCondition),
     /// A @viewport rule prelude.
@@ -417,6 +431,18 @@ struct NestedRuleParser<'a, 'b: 'a> {
     shared_lock: &'a SharedRwLock,
     context: &'a ParserContext<'b>,
     namespaces: &'a Namespaces,
+    /// Whether we're parsing rules nested (directly or through conditional
+    /// group rules) inside a style rule.
+    in_style_rule: bool,
+    /// Whether we're parsing rules nested (directly or through conditional
+    /// group rules) inside a scope rule.
+    in_scope_rule: bool,
+}
+
+/// Whether CSS Nesting is enabled.
+#[inline]
+fn nesting_enabled() -> bool {
+    static_prefs::pref!("layout.css.nesting.enabled")
 }
 
 impl<'a, 'b> NestedRuleParser<'a, 'b> {
@@ -427,12 +453,18 @@ impl<'a, 'b> NestedRuleParser<'a, 'b> {
     ) -> Arc<Locked<CssRules>> {
         let context = ParserContext::new_with_rule_type(self.context, rule_type, self.namespaces);
 
-        let nested_parser = NestedRuleParser {
+        let mut nested_parser = NestedRuleParser {
             shared_lock: self.shared_lock,
             context: &context,
             namespaces: self.namespaces,
+            in_style_rule: self.in_style_rule,
+            in_scope_rule: self.in_scope_rule,
         };
 
+        if self.in_style_rule {
+            return nested_parser.parse_nested_style_rule_body(input);
+        }
+
         let mut iter = RuleListParser::new_for_nested_rule(input, nested_parser);
         let mut rules = Vec::new();
         while let Some(result) = iter.next() {
@@ -447,6 +479,31 @@ impl<'a, 'b> NestedRuleParser<'a, 'b> {
         }
         CssRules::new(rules, self.shared_lock)
     }
+
+    /// Parses the body of a conditional group rule nested inside a style
+    /// rule. Declarations directly inside it are wrapped in a nested `&`
+    /// style rule, which goes before any other nested rule.
+    ///
+    /// https://drafts.csswg.org/css-nesting/#conditionals
+    fn parse_nested_style_rule_body(&mut self, input: &mut Parser) -> Arc<Locked<CssRules>> {
+        let context =
+            ParserContext::new_with_rule_type(self.context, CssRuleType::Style, self.namespaces);
+        let start_location = input.current_source_location();
+        let (declarations, mut rules) =
+            parse_property_declaration_list_with_rules(&context, input, None, self);
+        if !declarations.declarations().is_empty() {
+            rules.insert(
+                0,
+                CssRule::Style(Arc::new(self.shared_lock.wrap(StyleRule {
+                    selectors: SelectorList::ampersand(),
+                    block: Arc::new(self.shared_lock.wrap(declarations)),
+                    rules: None,
+                    source_location: start_location,
+                }))),
+            );
+        }
+        CssRules::new(rules, self.shared_lock)
+    }
 }
 
 impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
@@ -459,6 +516,19 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
         name: CowRcStr<'i>,
         input: &mut Parser<'i, 't>,
     ) -> Result<Self::Prelude, ParseError<'i>> {
+        if self.in_style_rule {
+            // Only conditional group rules, @starting-style and @layer blocks
+            // can be nested inside style rules.
+            let allowed = match_ignore_ascii_case! { &*name,
+                "media" | "supports" | "container" | "layer" | "starting-style" => true,
+                _ => false,
+            };
+            if !allowed {
+                return Err(
+                    input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone()))
+                );
+            }
+        }
         Ok(match_ignore_ascii_case! { &*name,
             "media" => {
                 let media_queries = MediaList::parse(self.context, input);
@@ -476,6 +546,13 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                 let condition = Arc::new(ContainerCondition::parse(self.context, input)?);
                 AtRulePrelude::Container(condition)
             },
+            "scope" if static_prefs::pref!("layout.css.at-scope.enabled") => {
+                let bounds = Arc::new(ScopeBounds::parse(self.context, input, self.namespaces)?);
+                AtRulePrelude::Scope(bounds)
+            },
+            "starting-style" if static_prefs::pref!("layout.css.starting-style-at-rules.enabled") => {
+                AtRulePrelude::StartingStyle
+            },
             "layer" if static_prefs::pref!("layout.css.cascade-layers.enabled") => {
                 let names = input.try_parse(|input| {
                     input.parse_comma_separated(|input| {
@@ -492,10 +569,14 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                 let name = DashedIdent::parse(self.context, input)?;
                 AtRulePrelude::FontPaletteValues(name)
             },
-            "counter-style" if cfg!(feature = "gecko") => {
+            "counter-style" => {
                 let name = parse_counter_style_name_definition(input)?;
                 AtRulePrelude::CounterStyle(name)
             },
+            "property" if static_prefs::pref!("layout.css.properties-and-values.enabled") => {
+                let name = PropertyRuleName::parse(self.context, input)?;
+                AtRulePrelude::Property(name)
+            },
             "viewport" if viewport_rule::enabled() => {
                 AtRulePrelude::Viewport
             },
@@ -594,6 +675,17 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                     ),
                 )))
             },
+            AtRulePrelude::Property(name) => {
+                let context = ParserContext::new_with_rule_type(
+                    self.context,
+                    CssRuleType::Property,
+                    self.namespaces,
+                );
+
+                Ok(CssRule::Property(Arc::new(self.shared_lock.wrap(
+                    PropertyRule::parse(&context, input, name, start.source_location())?,
+                ))))
+            },
             AtRulePrelude::Media(media_queries) => {
                 Ok(CssRule::Media(Arc::new(self.shared_lock.wrap(MediaRule {
                     media_queries,
@@ -678,6 +770,23 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                     source_location: start.source_location(),
                 }),
             ))),
+            AtRulePrelude::Scope(bounds) => {
+                let mut nested_parser = NestedRuleParser {
+                    in_scope_rule: true,
+                    ..self.clone()
+                };
+                Ok(CssRule::Scope(Arc::new(self.shared_lock.wrap(ScopeRule {
+                    bounds,
+                    rules: nested_parser.parse_nested_rules(input, CssRuleType::Scope),
+                    source_location: start.source_location(),
+                }))))
+            },
+            AtRulePrelude::StartingStyle => Ok(CssRule::StartingStyle(Arc::new(
+                self.shared_lock.wrap(StartingStyleRule {
+                    rules: self.parse_nested_rules(input, CssRuleType::StartingStyle),
+                    source_location: start.source_location(),
+                }),
+            ))),
             AtRulePrelude::Layer(names) => {
                 let name = match names.len() {
                     0 | 1 => names.into_iter().next(),
@@ -706,7 +815,7 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
     ) -> Result<Self::AtRule, ()> {
         Ok(match prelude {
             AtRulePrelude::Layer(names) => {
-                if names.is_empty() {
+                if names.is_empty() || self.in_style_rule {
                     return Err(());
                 }
                 CssRule::LayerStatement(Arc::new(self.shared_lock.wrap(LayerStatementRule {
@@ -769,7 +878,14 @@ impl<'a, 'b, 'i> QualifiedRuleParser<'i> for NestedRuleParser<'a, 'b> {
             url_data: self.context.url_data,
             for_supports_rule: false,
         };
-        let selectors = SelectorList::parse(&selector_parser, input)?;
+        let parse_relative = if self.in_style_rule {
+            ParseRelative::ForNesting
+        } else if self.in_scope_rule {
+            ParseRelative::ForScope
+        } else {
+            ParseRelative::No
+        };
+        let selectors = SelectorList::parse(&selector_parser, input, parse_relative)?;
         if self.context.error_reporting_enabled() {
             check_for_useless_selector(input, &self.context, &selectors);
         }
@@ -785,11 +901,35 @@ impl<'a, 'b, 'i> QualifiedRuleParser<'i> for NestedRuleParser<'a, 'b> {
         let context =
             ParserContext::new_with_rule_type(self.context, CssRuleType::Style, self.namespaces);
 
-        let declarations = parse_property_declaration_list(&context, input, Some(&selectors));
+        let (declarations, rules) = if nesting_enabled() {
+            let mut nested_parser = NestedRuleParser {
+                shared_lock: self.shared_lock,
+                context: &context,
+                namespaces: self.namespaces,
+                in_style_rule: true,
+                in_scope_rule: self.in_scope_rule,
+            };
+            let (declarations, rules) = parse_property_declaration_list_with_rules(
+                &context,
+                input,
+                Some(&selectors),
+                &mut nested_parser,
+            );
+            let rules = if rules.is_empty() {
+                None
+            } else {
+                Some(CssRules::new(rules, self.shared_lock))
+            };
+            (declarations, rules)
+        } else {
+            let declarations = parse_property_declaration_list(&context, input, Some(&selectors));
+            (declarations, None)
+        };
         let block = Arc::new(self.shared_lock.wrap(declarations));
         Ok(CssRule::Style(Arc::new(self.shared_lock.wrap(StyleRule {
             selectors,
             block,
+            rules,
             source_location: start.source_location(),
         }))))
     }
@@ -63,7 +63,6 @@ where
         *effective = true;
         match *rule {
             CssRule::Namespace(_) |
-            CssRule::Style(_) |
             CssRule::FontFace(_) |
             CssRule::CounterStyle(_) |
             CssRule::Viewport(_) |
@@ -71,7 +70,15 @@ where
             CssRule::Page(_) |
             CssRule::LayerStatement(_) |
             CssRule::FontFeatureValues(_) |
-            CssRule::FontPaletteValues(_) => None,
+            CssRule::FontPaletteValues(_) |
+            CssRule::Property(_) => None,
+            CssRule::Style(ref style_rule) => {
+                let style_rule = style_rule.read_with(guard);
+                style_rule
+                    .rules
+                    .as_ref()
+                    .map(|r| r.read_with(guard).0.iter())
+            },
             CssRule::Import(ref import_rule) => {
                 let import_rule = import_rule.read_with(guard);
                 if !C::process_import(guard, device, quirks_mode, import_rule) {
@@ -112,6 +119,14 @@ where
                 let layer_rule = lock.read_with(guard);
                 Some(layer_rule.rules.read_with(guard).0.iter())
             },
+            CssRule::Scope(ref lock) => {
+                let scope_rule = lock.read_with(guard);
+                Some(scope_rule.rules.read_with(guard).0.iter())
+            },
+            CssRule::StartingStyle(ref lock) => {
+                let starting_style_rule = lock.read_with(guard);
+                Some(starting_style_rule.rules.read_with(guard).0.iter())
+            },
         }
     }
 }
@@ -0,0 +1,195 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A [`@scope`][scope] rule.
+//!
+//! [scope]: https://drafts.csswg.org/css-cascade-6/#scoped-styles
+
+use crate::dom::TElement;
+use crate::parser::ParserContext;
+use crate::selector_parser::{SelectorImpl, SelectorParser};
+use crate::shared_lock::{
+    DeepCloneParams, DeepCloneWithLock, Locked, SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard,
+};
+use crate::str::CssStringWriter;
+use crate::stylesheets::{CssRules, Namespaces};
+use cssparser::{Parser, SourceLocation, ToCss};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use selectors::matching::{matches_selector_list, MatchingContext};
+use selectors::parser::ParseRelative;
+use selectors::{Element, OpaqueElement, SelectorList};
+use servo_arc::Arc;
+use std::fmt::{self, Write};
+use style_traits::ParseError;
+
+/// A scope rule.
+#[derive(Debug, ToShmem)]
+pub struct ScopeRule {
+    /// The selectors of the scoping roots and limits.
+    pub bounds: Arc<ScopeBounds>,
+    /// The nested rules inside the block.
+    pub rules: Arc<Locked<CssRules>>,
+    /// The source position where this rule was found.
+    pub source_location: SourceLocation,
+}
+
+impl ScopeRule {
+    /// Measure heap usage.
+    pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
+        self.bounds.unconditional_shallow_size_of(ops) +
+            self.bounds.size_of(ops) +
+            self.rules.unconditional_shallow_size_of(ops) +
+            self.rules.read_with(guard).size_of(guard, ops)
+    }
+}
+
+impl DeepCloneWithLock for ScopeRule {
+    fn deep_clone_with_lock(
+        &self,
+        lock: &SharedRwLock,
+        guard: &SharedRwLockReadGuard,
+        params: &DeepCloneParams,
+    ) -> Self {
+        let rules = self.rules.read_with(guard);
+        Self {
+            bounds: self.bounds.clone(),
+            rules: Arc::new(lock.wrap(rules.deep_clone_with_lock(lock, guard, params))),
+            source_location: self.source_location.clone(),
+        }
+    }
+}
+
+impl ToCssWithGuard for ScopeRule {
+    fn to_css(&self, guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        dest.write_str("@scope")?;
+        if let Some(ref start) = self.bounds.start {
+            dest.write_str(" (")?;
+            start.to_css(dest)?;
+            dest.write_char(')')?;
+        }
+        if let Some(ref end) = self.bounds.end {
+            dest.write_str(" to (")?;
+            end.to_css(dest)?;
+            dest.write_char(')')?;
+        }
+        self.rules.read_with(guard).to_css_block(guard, dest)
+    }
+}
+
+/// The prelude of a scope rule: the selectors of the scoping roots, and of the
+/// scoping limits, which are relative to the scoping root.
+///
+/// <https://drafts.csswg.org/css-cascade-6/#scope-syntax>
+#[derive(Debug, ToShmem)]
+pub struct ScopeBounds {
+    /// The selectors of the scoping roots, or `None` if the root of the tree
+    /// is the scoping root.
+    pub start: Option<SelectorList<SelectorImpl>>,
+    /// The selectors of the scoping limits, if any.
+    pub end: Option<SelectorList<SelectorImpl>>,
+}
+
+impl ScopeBounds {
+    /// Parses the prelude of a scope rule, that is,
+    /// `[(<scope-start>)]? [to (<scope-end>)]?`.
+    pub fn parse<'i>(
+        context: &ParserContext,
+        input: &mut Parser<'i, '_>,
+        namespaces: &Namespaces,
+    ) -> Result<Self, ParseError<'i>> {
+        let selector_parser = SelectorParser {
+            stylesheet_origin: context.stylesheet_origin,
+            namespaces,
+            url_data: context.url_data,
+            for_supports_rule: false,
+        };
+        let start = if input
+            .try_parse(|input| input.expect_parenthesis_block())
+            .is_ok()
+        {
+            Some(input.parse_nested_block(|input| {
+                SelectorList::parse(&selector_parser, input, ParseRelative::No)
+            })?)
+        } else {
+            None
+        };
+        let end = if input
+            .try_parse(|input| input.expect_ident_matching("to"))
+            .is_ok()
+        {
+            input.expect_parenthesis_block()?;
+            Some(input.parse_nested_block(|input| {
+                SelectorList::parse(&selector_parser, input, ParseRelative::ForScope)
+            })?)
+        } else {
+            None
+        };
+        Ok(Self { start, end })
+    }
+
+    /// Measure heap usage.
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let mut n = 0;
+        if let Some(ref start) = self.start {
+            n += start.0.size_of(ops);
+        }
+        if let Some(ref end) = self.end {
+            n += end.0.size_of(ops);
+        }
+        n
+    }
+
+    /// Returns whether `element` is a scoping root of this scope, given the
+    /// scoping root of the enclosing scope rule, if any, which `:scope`
+    /// resolves to.
+    pub fn is_root<E>(
+        &self,
+        element: E,
+        outer_root: Option<OpaqueElement>,
+        context: &mut MatchingContext<E::Impl>,
+    ) -> bool
+    where
+        E: TElement,
+    {
+        match self.start {
+            Some(ref start) => context.with_scope_element(outer_root, |context| {
+                matches_selector_list(start, &element, context)
+            }),
+            None => element.parent_element().is_none(),
+        }
+    }
+
+    /// Returns whether `element`, an inclusive descendant of the scoping
+    /// root `root`, is out of the scope because it is an inclusive descendant
+    /// of a scoping limit.
+    pub fn is_out_of_limits<E>(
+        &self,
+        root: E,
+        element: E,
+        context: &mut MatchingContext<E::Impl>,
+    ) -> bool
+    where
+        E: TElement,
+    {
+        let end = match self.end {
+            Some(ref end) => end,
+            None => return false,
+        };
+        context.with_scope_element(Some(root.opaque()), |context| {
+            let mut current = element;
+            loop {
+                if matches_selector_list(end, &current, context) {
+                    return true;
+                }
+                if current == root {
+                    return false;
+                }
+                current = match current.parent_element() {
+                    Some(parent) => parent,
+                    None => return false,
+                };
+            }
+        })
+    }
+}
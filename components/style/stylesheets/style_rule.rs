@@ -9,10 +9,9 @@ use crate::selector_parser::SelectorImpl;
 use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
 use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard, ToCssWithGuard};
 use crate::str::CssStringWriter;
+use crate::stylesheets::CssRules;
 use cssparser::SourceLocation;
-#[cfg(feature = "gecko")]
 use malloc_size_of::MallocUnconditionalShallowSizeOf;
-#[cfg(feature = "gecko")]
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use selectors::SelectorList;
 use servo_arc::Arc;
@@ -25,6 +24,9 @@ pub struct StyleRule {
     pub selectors: SelectorList<SelectorImpl>,
     /// The declaration block with the properties it contains.
     pub block: Arc<Locked<PropertyDeclarationBlock>>,
+    /// The nested rules to this style rule. Only non-`None` when nesting is
+    /// enabled and the rule actually has nested rules.
+    pub rules: Option<Arc<Locked<CssRules>>>,
     /// The location in the sheet where it was found.
     pub source_location: SourceLocation,
 }
@@ -35,11 +37,15 @@ impl DeepCloneWithLock for StyleRule {
         &self,
         lock: &SharedRwLock,
         guard: &SharedRwLockReadGuard,
-        _params: &DeepCloneParams,
+        params: &DeepCloneParams,
     ) -> StyleRule {
         StyleRule {
             selectors: self.selectors.clone(),
             block: Arc::new(lock.wrap(self.block.read_with(guard).clone())),
+            rules: self.rules.as_ref().map(|rules| {
+                let rules = rules.read_with(guard);
+                Arc::new(lock.wrap(rules.deep_clone_with_lock(lock, guard, params)))
+            }),
             source_location: self.source_location.clone(),
         }
     }
@@ -47,12 +53,15 @@ impl DeepCloneWithLock for StyleRule {
 
 impl StyleRule {
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
         let mut n = 0;
         n += self.selectors.0.size_of(ops);
         n += self.block.unconditional_shallow_size_of(ops) +
             self.block.read_with(guard).size_of(ops);
+        if let Some(ref rules) = self.rules {
+            n += rules.unconditional_shallow_size_of(ops) +
+                rules.read_with(guard).size_of(guard, ops);
+        }
         n
     }
 }
@@ -64,16 +73,35 @@ impl ToCssWithGuard for StyleRule {
 
         // Step 1
         self.selectors.to_css(dest)?;
-        // Step 2
-        dest.write_str(" { ")?;
-        // Step 3
         let declaration_block = self.block.read_with(guard);
-        declaration_block.to_css(dest)?;
-        // Step 4
+        let rules = match self.rules {
+            Some(ref rules) => rules.read_with(guard),
+            None => {
+                // Step 2
+                dest.write_str(" { ")?;
+                // Step 3
+                declaration_block.to_css(dest)?;
+                // Step 4
+                if !declaration_block.declarations().is_empty() {
+                    dest.write_char(' ')?;
+                }
+                // Step 5
+                return dest.write_char('}');
+            },
+        };
+
+        // Serialization of nested rules, per
+        // https://drafts.csswg.org/cssom/#serialize-a-css-rule with the
+        // changes from https://drafts.csswg.org/css-nesting/#cssom: each
+        // non-empty declaration block and nested rule goes in its own line.
+        dest.write_str(" {")?;
         if !declaration_block.declarations().is_empty() {
-            dest.write_char(' ')?;
+            dest.write_str("\n  ")?;
+            declaration_block.to_css(dest)?;
+        }
+        for rule in rules.0.iter() {
+            rule.to_css_indented(guard, dest)?;
         }
-        // Step 5
-        dest.write_char('}')
+        dest.write_str("\n}")
     }
 }
@@ -5,6 +5,7 @@
 use crate::context::QuirksMode;
 use crate::error_reporting::{ContextualParseError, ParseErrorReporter};
 use crate::media_queries::{Device, MediaList};
+use crate::memory_report::MemoryReport;
 use crate::parser::ParserContext;
 use crate::shared_lock::{DeepCloneParams, DeepCloneWithLock, Locked};
 use crate::shared_lock::{SharedRwLock, SharedRwLockReadGuard};
@@ -17,8 +18,9 @@ use crate::use_counters::UseCounters;
 use crate::{Namespace, Prefix};
 use cssparser::{Parser, ParserInput, RuleListParser};
 use fxhash::FxHashMap;
-#[cfg(feature = "gecko")]
-use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use malloc_size_of::{
+    MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf,
+};
 use parking_lot::RwLock;
 use servo_arc::Arc;
 use std::mem;
@@ -35,6 +37,41 @@ pub struct UserAgentStylesheets {
     pub quirks_mode_stylesheet: DocumentStyleSheet,
 }
 
+impl UserAgentStylesheets {
+    /// Creates the user-agent and user stylesheets from the given ones,
+    /// followed by a user-agent stylesheet with the rules the style system
+    /// relies on, like the `!important` ones for `::placeholder`.
+    #[cfg(feature = "servo")]
+    pub fn new(
+        shared_lock: SharedRwLock,
+        mut user_or_user_agent_stylesheets: Vec<DocumentStyleSheet>,
+        quirks_mode_stylesheet: DocumentStyleSheet,
+    ) -> Self {
+        use crate::servo::selector_parser::PLACEHOLDER_USER_AGENT_RULES;
+
+        let url_data = quirks_mode_stylesheet.0.contents.url_data.read().clone();
+        let media = Arc::new(shared_lock.wrap(MediaList::empty()));
+        let sheet = Stylesheet::from_str(
+            PLACEHOLDER_USER_AGENT_RULES,
+            url_data,
+            Origin::UserAgent,
+            media,
+            shared_lock.clone(),
+            None,
+            None,
+            QuirksMode::NoQuirks,
+            0,
+            AllowImportRules::No,
+        );
+        user_or_user_agent_stylesheets.push(DocumentStyleSheet(Arc::new(sheet)));
+        Self {
+            shared_lock,
+            user_or_user_agent_stylesheets,
+            quirks_mode_stylesheet,
+        }
+    }
+}
+
 /// A set of namespaces applying to a given stylesheet.
 ///
 /// The namespace id is used in gecko
@@ -151,14 +188,51 @@ impl StylesheetContents {
     }
 
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
         if self.rules.is_static() {
             return 0;
         }
-        // Measurement of other fields may be added later.
         self.rules.unconditional_shallow_size_of(ops) +
-            self.rules.read_with(guard).size_of(guard, ops)
+            self.rules.read_with(guard).size_of(guard, ops) +
+            self.namespaces.read().size_of(ops) +
+            self.source_map_url.read().size_of(ops) +
+            self.source_url.read().size_of(ops)
+    }
+
+    /// Builds a report of the heap usage of this stylesheet, named after its
+    /// URL, and broken down by the type of its top-level rules.
+    ///
+    /// In Servo, the stylesheets imported by this one are reported as children
+    /// of the report too.
+    pub fn memory_report(
+        &self,
+        guard: &SharedRwLockReadGuard,
+        ops: &mut MallocSizeOfOps,
+    ) -> MemoryReport {
+        #[cfg(feature = "gecko")]
+        let name = format!("{:?}", *self.url_data.read());
+        #[cfg(feature = "servo")]
+        let name = self.url_data.read().as_str().to_owned();
+
+        let mut report = MemoryReport::new(name);
+        if self.rules.is_static() {
+            return report;
+        }
+
+        let rules = self.rules.read_with(guard);
+        report.size = self.rules.unconditional_shallow_size_of(ops) + rules.0.shallow_size_of(ops);
+        for rule in rules.0.iter() {
+            report.add(&format!("{:?}", rule.rule_type()), rule.size_of(guard, ops));
+
+            #[cfg(feature = "servo")]
+            {
+                if let CssRule::Import(ref lock) = *rule {
+                    let import_rule = lock.read_with(guard);
+                    report.add_child(import_rule.stylesheet.0.contents.memory_report(guard, ops));
+                }
+            }
+        }
+        report
     }
 }
 
@@ -360,6 +434,8 @@ impl SanitizationKind {
             CssRule::Supports(..) |
             CssRule::Import(..) |
             CssRule::Container(..) |
+            CssRule::Scope(..) |
+            CssRule::StartingStyle(..) |
             // TODO(emilio): Perhaps Layer should not be always sanitized? But
             // we sanitize @media and co, so this seems safer for now.
             CssRule::LayerStatement(..) |
@@ -371,6 +447,7 @@ impl SanitizationKind {
             CssRule::Page(..) |
             CssRule::FontFeatureValues(..) |
             CssRule::FontPaletteValues(..) |
+            CssRule::Property(..) |
             CssRule::Viewport(..) |
             CssRule::CounterStyle(..) => !is_standard,
         }
@@ -15,8 +15,7 @@ use crate::stylesheets::{CssRuleType, CssRules, Namespaces};
 use cssparser::parse_important;
 use cssparser::{Delimiter, Parser, SourceLocation, Token};
 use cssparser::{ParseError as CssParseError, ParserInput};
-#[cfg(feature = "gecko")]
-use malloc_size_of::{MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use selectors::parser::{Selector, SelectorParseErrorKind};
 use servo_arc::Arc;
 use std::ffi::{CStr, CString};
@@ -41,10 +40,9 @@ pub struct SupportsRule {
 
 impl SupportsRule {
     /// Measure heap usage.
-    #[cfg(feature = "gecko")]
     pub fn size_of(&self, guard: &SharedRwLockReadGuard, ops: &mut MallocSizeOfOps) -> usize {
-        // Measurement of other fields may be added later.
-        self.rules.unconditional_shallow_size_of(ops) +
+        self.condition.size_of(ops) +
+            self.rules.unconditional_shallow_size_of(ops) +
             self.rules.read_with(guard).size_of(guard, ops)
     }
 }
@@ -77,7 +75,7 @@ impl DeepCloneWithLock for SupportsRule {
 /// An @supports condition
 ///
 /// <https://drafts.csswg.org/css-conditional-3/#at-supports>
-#[derive(Clone, Debug, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToShmem)]
 pub enum SupportsCondition {
     /// `not (condition)`
     Not(Box<SupportsCondition>),
@@ -340,7 +338,7 @@ impl ToCss for SupportsCondition {
     }
 }
 
-#[derive(Clone, Debug, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToShmem)]
 /// A possibly-invalid CSS selector.
 pub struct RawSelector(pub String);
 
@@ -376,7 +374,7 @@ impl RawSelector {
     }
 }
 
-#[derive(Clone, Debug, ToShmem)]
+#[derive(Clone, Debug, MallocSizeOf, ToShmem)]
 /// A possibly-invalid property declaration
 pub struct Declaration(pub String);
 
@@ -5,9 +5,11 @@
 //! Selector matching.
 
 use crate::applicable_declarations::{
-    ApplicableDeclarationBlock, ApplicableDeclarationList, CascadePriority,
+    ApplicableDeclarationBlock, ApplicableDeclarationList, CascadePriority, ScopeProximity,
 };
+use crate::cascade_trace::{self, CascadeTrace, CascadeTracer, RuleProvenance};
 use crate::context::{CascadeInputs, QuirksMode};
+use crate::counter_style::CounterStyleRegistry;
 use crate::dom::{TElement, TShadowRoot};
 #[cfg(feature = "gecko")]
 use crate::gecko_bindings::structs::{ServoStyleSetSizes, StyleRuleInclusion};
@@ -17,8 +19,10 @@ use crate::invalidation::media_queries::{
 };
 use crate::invalidation::stylesheets::RuleChangeKind;
 use crate::media_queries::Device;
+use crate::memory_report::MemoryReport;
 use crate::properties::{self, CascadeMode, ComputedValues};
-use crate::properties::{AnimationDeclarations, PropertyDeclarationBlock};
+use crate::properties::{AnimationDeclarations, PropertyDeclarationBlock, PropertyId};
+use crate::properties_and_values::registry::PropertyRegistration;
 use crate::rule_cache::{RuleCache, RuleCacheConditions};
 use crate::rule_collector::{containing_shadow_ignoring_svg_use, RuleCollector};
 use crate::rule_tree::{CascadeLevel, RuleTree, StrongRuleNode, StyleSource};
@@ -30,32 +34,34 @@ use crate::stylesheet_set::{DocumentStylesheetFlusher, SheetCollectionFlusher};
 use crate::stylesheets::container_rule::ContainerCondition;
 use crate::stylesheets::keyframes_rule::KeyframesAnimation;
 use crate::stylesheets::layer_rule::{LayerName, LayerOrder};
+use crate::stylesheets::scope_rule::ScopeBounds;
 use crate::stylesheets::viewport_rule::{self, MaybeNew, ViewportRule};
 #[cfg(feature = "gecko")]
+use crate::stylesheets::{FontFaceRule, FontFeatureValuesRule, FontPaletteValuesRule, PageRule};
 use crate::stylesheets::{
-    CounterStyleRule, FontFaceRule, FontFeatureValuesRule, FontPaletteValuesRule, PageRule,
-};
-use crate::stylesheets::{
-    CssRule, EffectiveRulesIterator, Origin, OriginSet, PerOrigin, PerOriginIter,
+    CounterStyleRule, CssRule, EffectiveRulesIterator, Origin, OriginSet, PerOrigin, PerOriginIter,
 };
 use crate::stylesheets::{StyleRule, StylesheetContents, StylesheetInDocument};
 use crate::AllocErr;
 use crate::{Atom, LocalName, Namespace, ShrinkIfNeeded, WeakAtom};
+use cssparser::ToCss;
 use dom::{DocumentState, ElementState};
 use fxhash::FxHashMap;
 use malloc_size_of::MallocSizeOf;
-#[cfg(feature = "gecko")]
 use malloc_size_of::{MallocShallowSizeOf, MallocSizeOfOps, MallocUnconditionalShallowSizeOf};
 use selectors::attr::{CaseSensitivity, NamespaceConstraint};
 use selectors::bloom::BloomFilter;
-use selectors::matching::VisitedHandlingMode;
 use selectors::matching::{matches_selector, MatchingContext, MatchingMode, NeedsSelectorFlags};
-use selectors::parser::{AncestorHashes, Combinator, Component, Selector, SelectorIter};
+use selectors::matching::{MatchingForInvalidation, SelectorCaches, VisitedHandlingMode};
+use selectors::parser::{
+    AncestorHashes, Combinator, Component, RelativeSelector, Selector, SelectorIter, SelectorList,
+};
 use selectors::visitor::SelectorVisitor;
-use selectors::NthIndexCache;
+use selectors::OpaqueElement;
 use servo_arc::{Arc, ArcBorrow};
 use smallbitvec::SmallBitVec;
 use smallvec::SmallVec;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 use std::sync::Mutex;
@@ -303,6 +309,16 @@ impl CascadeDataCacheEntry for UserAgentCascadeData {
     }
 }
 
+impl UserAgentCascadeData {
+    fn add_to_memory_report(&self, ops: &mut MallocSizeOfOps, report: &mut MemoryReport) {
+        self.cascade_data.add_to_memory_report(ops, report);
+        report.add(
+            "precomputed-pseudos",
+            self.precomputed_pseudo_element_decls.size_of(ops),
+        );
+    }
+}
+
 type UserAgentCascadeDataCache = CascadeDataCache<UserAgentCascadeData>;
 
 type PrecomputedPseudoElementDeclarations = PerPseudoElementMap<Vec<ApplicableDeclarationBlock>>;
@@ -333,6 +349,10 @@ pub struct DocumentCascadeData {
     user: CascadeData,
     author: CascadeData,
     per_origin: PerOrigin<()>,
+    /// The counter styles defined by the @counter-style rules of all the
+    /// origins, resolved together so that a style can extend, or fall back
+    /// to, a style of a less important origin.
+    counter_style_registry: CounterStyleRegistry,
 }
 
 /// An iterator over the cascade data of a given document.
@@ -428,14 +448,36 @@ impl DocumentCascadeData {
             guards.author,
         )?;
 
+        self.rebuild_counter_style_registry(guards);
+
         Ok(())
     }
 
+    /// Resolves the effective @counter-style rules of every origin. Rules of
+    /// more important origins come last, so that they win over the rules of
+    /// less important origins with the same name.
+    fn rebuild_counter_style_registry(&mut self, guards: &StylesheetGuards) {
+        let origins = [
+            (&self.user_agent.cascade_data, guards.ua_or_user),
+            (&self.user, guards.ua_or_user),
+            (&self.author, guards.author),
+        ];
+        self.counter_style_registry =
+            CounterStyleRegistry::from_rules(origins.iter().flat_map(|&(data, guard)| {
+                data.extra_data
+                    .counter_styles
+                    .values()
+                    .filter_map(|rules| rules.last())
+                    .map(move |&(ref rule, _)| rule.read_with(guard))
+            }));
+    }
+
     /// Measures heap usage.
     #[cfg(feature = "gecko")]
     pub fn add_size_of(&self, ops: &mut MallocSizeOfOps, sizes: &mut ServoStyleSetSizes) {
         self.user.add_size_of(ops, sizes);
         self.author.add_size_of(ops, sizes);
+        sizes.mOther += self.counter_style_registry.size_of(ops);
     }
 }
 
@@ -549,11 +591,17 @@ impl From<StyleRuleInclusion> for RuleInclusion {
 }
 
 /// A struct containing state from ancestor rules like @layer / @import /
-/// @container.
+/// @container / @scope / nesting.
 struct ContainingRuleState {
     layer_name: LayerName,
     layer_id: LayerId,
     container_condition_id: ContainerConditionId,
+    scope_condition_id: ScopeConditionId,
+    /// Whether we're inside a @starting-style rule.
+    in_starting_style: bool,
+    /// The selectors of the ancestor style rules, with their own parent
+    /// selectors already replaced, used to resolve `&` in nested rules.
+    ancestor_selector_lists: SmallVec<[SelectorList<SelectorImpl>; 2]>,
 }
 
 impl Default for ContainingRuleState {
@@ -562,6 +610,9 @@ impl Default for ContainingRuleState {
             layer_name: LayerName::new_empty(),
             layer_id: LayerId::root(),
             container_condition_id: ContainerConditionId::none(),
+            scope_condition_id: ScopeConditionId::none(),
+            in_starting_style: false,
+            ancestor_selector_lists: Default::default(),
         }
     }
 }
@@ -570,6 +621,9 @@ struct SavedContainingRuleState {
     layer_name_len: usize,
     layer_id: LayerId,
     container_condition_id: ContainerConditionId,
+    scope_condition_id: ScopeConditionId,
+    in_starting_style: bool,
+    ancestor_selector_lists_len: usize,
 }
 
 impl ContainingRuleState {
@@ -578,14 +632,22 @@ impl ContainingRuleState {
             layer_name_len: self.layer_name.0.len(),
             layer_id: self.layer_id,
             container_condition_id: self.container_condition_id,
+            scope_condition_id: self.scope_condition_id,
+            in_starting_style: self.in_starting_style,
+            ancestor_selector_lists_len: self.ancestor_selector_lists.len(),
         }
     }
 
     fn restore(&mut self, saved: &SavedContainingRuleState) {
         debug_assert!(self.layer_name.0.len() >= saved.layer_name_len);
+        debug_assert!(self.ancestor_selector_lists.len() >= saved.ancestor_selector_lists_len);
         self.layer_name.0.truncate(saved.layer_name_len);
         self.layer_id = saved.layer_id;
         self.container_condition_id = saved.container_condition_id;
+        self.scope_condition_id = saved.scope_condition_id;
+        self.in_starting_style = saved.in_starting_style;
+        self.ancestor_selector_lists
+            .truncate(saved.ancestor_selector_lists_len);
     }
 }
 
@@ -658,6 +720,13 @@ impl Stylist {
         ExtraStyleDataIterator(self.cascade_data.iter_origins_rev())
     }
 
+    /// Returns the registry of counter styles defined by the effective
+    /// @counter-style rules, used to generate counter representations.
+    #[inline]
+    pub fn counter_style_registry(&self) -> &CounterStyleRegistry {
+        &self.cascade_data.counter_style_registry
+    }
+
     /// Returns the number of selectors.
     pub fn num_selectors(&self) -> usize {
         self.cascade_data
@@ -691,7 +760,9 @@ impl Stylist {
     pub fn num_invalidations(&self) -> usize {
         self.cascade_data
             .iter_origins()
-            .map(|(data, _)| data.invalidation_map.len())
+            .map(|(data, _)| {
+                data.invalidation_map.len() + data.relative_selector_invalidation_map.len()
+            })
             .sum()
     }
 
@@ -1087,7 +1158,7 @@ impl Stylist {
         //
         // FIXME(emilio): We should assert that it holds if pseudo.is_none()!
         properties::cascade::<E>(
-            &self.device,
+            self,
             pseudo,
             inputs.rules.as_ref().unwrap_or(self.rule_tree.root()),
             guards,
@@ -1124,7 +1195,7 @@ impl Stylist {
     {
         debug_assert!(pseudo.is_lazy());
 
-        let mut nth_index_cache = Default::default();
+        let mut selector_caches = Default::default();
         // No need to bother setting the selector flags when we're computing
         // default styles.
         let needs_selector_flags = if rule_inclusion == RuleInclusion::DefaultOnly {
@@ -1137,9 +1208,10 @@ impl Stylist {
         let mut matching_context = MatchingContext::<'_, E::Impl>::new(
             MatchingMode::ForStatelessPseudoElement,
             None,
-            &mut nth_index_cache,
+            &mut selector_caches,
             self.quirks_mode,
             needs_selector_flags,
+            MatchingForInvalidation::No,
         );
 
         matching_context.pseudo_element_matching_fn = matching_fn;
@@ -1166,15 +1238,16 @@ impl Stylist {
         let mut visited_rules = None;
         if parent_style.visited_style().is_some() {
             let mut declarations = ApplicableDeclarationList::new();
-            let mut nth_index_cache = Default::default();
+            let mut selector_caches = Default::default();
 
             let mut matching_context = MatchingContext::<'_, E::Impl>::new_for_visited(
                 MatchingMode::ForStatelessPseudoElement,
                 None,
-                &mut nth_index_cache,
+                &mut selector_caches,
                 VisitedHandlingMode::RelevantLinkVisited,
                 self.quirks_mode,
                 needs_selector_flags,
+                MatchingForInvalidation::No,
             );
             matching_context.pseudo_element_matching_fn = matching_fn;
             matching_context.extra_data.originating_element_style =
@@ -1402,13 +1475,59 @@ impl Stylist {
         None
     }
 
+    /// Returns the registration of the given custom property, if any.
+    ///
+    /// Registrations are global to the document, so unlike `@keyframes`
+    /// rules, `@property` rules in shadow trees aren't taken into account.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api-1/#determining-registration>
+    #[inline]
+    pub fn get_custom_property_registration(&self, name: &Atom) -> Option<&PropertyRegistration> {
+        self.cascade_data
+            .author
+            .custom_property_registrations
+            .get(name)
+            .or_else(|| self.cascade_data.user.custom_property_registrations.get(name))
+            .or_else(|| {
+                self.cascade_data
+                    .user_agent
+                    .cascade_data
+                    .custom_property_registrations
+                    .get(name)
+            })
+    }
+
+    /// Iterates over the effective custom property registrations, that is,
+    /// taking into account that author registrations override user ones, and
+    /// user registrations override user-agent ones.
+    pub fn custom_property_registrations(
+        &self,
+    ) -> impl Iterator<Item = (&Atom, &PropertyRegistration)> {
+        let author = &self.cascade_data.author.custom_property_registrations;
+        let user = &self.cascade_data.user.custom_property_registrations;
+        let user_agent = &self
+            .cascade_data
+            .user_agent
+            .cascade_data
+            .custom_property_registrations;
+        let author_names = author.keys();
+        let user_names = user.keys().filter(move |name| !author.contains_key(*name));
+        let user_agent_names = user_agent
+            .keys()
+            .filter(move |name| !author.contains_key(*name) && !user.contains_key(*name));
+        author_names
+            .chain(user_names)
+            .chain(user_agent_names)
+            .filter_map(move |name| Some((name, self.get_custom_property_registration(name)?)))
+    }
+
     /// Computes the match results of a given element against the set of
     /// revalidation selectors.
     pub fn match_revalidation_selectors<E>(
         &self,
         element: E,
         bloom: Option<&BloomFilter>,
-        nth_index_cache: &mut NthIndexCache,
+        selector_caches: &mut SelectorCaches,
         needs_selector_flags: NeedsSelectorFlags,
     ) -> SmallBitVec
     where
@@ -1419,9 +1538,10 @@ impl Stylist {
         let mut matching_context = MatchingContext::new(
             MatchingMode::Normal,
             bloom,
-            nth_index_cache,
+            selector_caches,
             self.quirks_mode,
             needs_selector_flags,
+            MatchingForInvalidation::No,
         );
 
         // Note that, by the time we're revalidating, we're guaranteed that the
@@ -1431,24 +1551,39 @@ impl Stylist {
         // this in the caller by asserting that the bitvecs are same-length.
         let mut results = SmallBitVec::new();
 
+        self.each_revalidation_selector(element, |selector_and_hashes, host| {
+            results.push(matching_context.with_shadow_host(host, |matching_context| {
+                matches_selector(
+                    &selector_and_hashes.selector,
+                    selector_and_hashes.selector_offset,
+                    Some(&selector_and_hashes.hashes),
+                    &element,
+                    matching_context,
+                )
+            }));
+        });
+
+        results
+    }
+
+    /// Calls `f` with each revalidation selector that may match the given
+    /// element, in the order of the results of `match_revalidation_selectors`,
+    /// along with the shadow host of the tree its rule comes from, if any.
+    fn each_revalidation_selector<E, F>(&self, element: E, mut f: F)
+    where
+        E: TElement,
+        F: FnMut(&RevalidationSelectorAndHashes, Option<E>),
+    {
         let matches_document_rules =
             element.each_applicable_non_document_style_rule_data(|data, host| {
-                matching_context.with_shadow_host(Some(host), |matching_context| {
-                    data.selectors_for_cache_revalidation.lookup(
-                        element,
-                        self.quirks_mode,
-                        |selector_and_hashes| {
-                            results.push(matches_selector(
-                                &selector_and_hashes.selector,
-                                selector_and_hashes.selector_offset,
-                                Some(&selector_and_hashes.hashes),
-                                &element,
-                                matching_context,
-                            ));
-                            true
-                        },
-                    );
-                })
+                data.selectors_for_cache_revalidation.lookup(
+                    element,
+                    self.quirks_mode,
+                    |selector_and_hashes| {
+                        f(selector_and_hashes, Some(host));
+                        true
+                    },
+                );
             });
 
         for (data, origin) in self.cascade_data.iter_origins() {
@@ -1460,19 +1595,29 @@ impl Stylist {
                 element,
                 self.quirks_mode,
                 |selector_and_hashes| {
-                    results.push(matches_selector(
-                        &selector_and_hashes.selector,
-                        selector_and_hashes.selector_offset,
-                        Some(&selector_and_hashes.hashes),
-                        &element,
-                        &mut matching_context,
-                    ));
+                    f(selector_and_hashes, None);
                     true
                 },
             );
         }
+    }
 
-        results
+    /// Returns the serialization of the revalidation selectors at the given
+    /// indices of the result of `match_revalidation_selectors` for the given
+    /// element.
+    pub fn revalidation_selectors_at<E>(&self, element: E, indices: &[usize]) -> Vec<String>
+    where
+        E: TElement,
+    {
+        let mut selectors = vec![];
+        let mut index = 0;
+        self.each_revalidation_selector(element, |selector_and_hashes, _| {
+            if indices.contains(&index) {
+                selectors.push(selector_and_hashes.selector.to_css_string());
+            }
+            index += 1;
+        });
+        selectors
     }
 
     /// Computes styles for a given declaration with parent_style.
@@ -1500,7 +1645,7 @@ impl Stylist {
         // reversing this as it shouldn't be slow anymore, and should avoid
         // generating two instantiations of apply_declarations.
         properties::apply_declarations::<E, _>(
-            &self.device,
+            self,
             /* pseudo = */ None,
             self.rule_tree.root(),
             guards,
@@ -1556,12 +1701,174 @@ impl Stylist {
         // We may measure other fields in the future if DMD says it's worth it.
     }
 
+    /// Builds a hierarchical report of the heap usage of the stylist.
+    ///
+    /// The stylesheets are reported per origin and per stylesheet (see
+    /// `StylesheetContents::memory_report`), and the cascade data per origin
+    /// and per data structure. The cascade data of shadow trees is merged
+    /// into a single `author-data-cache` node.
+    ///
+    /// The rule caches are owned by the thread-local style contexts rather
+    /// than by the stylist, so the caller passes the ones it wants reported
+    /// under the `rule-caches` node.
+    ///
+    /// `ops` must be able to tell whether a pointer has been seen before,
+    /// since the values of declarations with `var()` references can be shared.
+    pub fn memory_report(
+        &self,
+        guards: &StylesheetGuards,
+        rule_caches: &[&RuleCache],
+        ops: &mut MallocSizeOfOps,
+    ) -> MemoryReport {
+        let mut report = MemoryReport::new("stylist");
+
+        let stylesheets = report.child_mut("stylesheets");
+        for (sheet, origin) in self.stylesheets.iter() {
+            let guard = guards.for_origin(origin);
+            stylesheets
+                .child_mut(origin_report_name(origin))
+                .add_child(sheet.contents().memory_report(guard, ops));
+        }
+
+        let cascade_data = report.child_mut("cascade-data");
+        // The user agent data is owned by the shared cache, but it's measured
+        // here so that documents can see what the UA sheets cost them.
+        self.cascade_data
+            .user_agent
+            .add_to_memory_report(ops, cascade_data.child_mut("user-agent"));
+        self.cascade_data
+            .user
+            .add_to_memory_report(ops, cascade_data.child_mut("user"));
+        self.cascade_data
+            .author
+            .add_to_memory_report(ops, cascade_data.child_mut("author"));
+        cascade_data.add(
+            "counter-styles",
+            self.cascade_data.counter_style_registry.size_of(ops),
+        );
+
+        let author_data_cache = report.child_mut("author-data-cache");
+        author_data_cache.size += self.author_data_cache.entries.shallow_size_of(ops);
+        for entry in self.author_data_cache.entries.values() {
+            author_data_cache.size += entry.unconditional_shallow_size_of(ops);
+            entry.add_to_memory_report(ops, author_data_cache);
+        }
+
+        report.add("rule-tree", self.rule_tree.size_of(ops));
+        let rule_caches_size = rule_caches.iter().map(|cache| cache.size_of(ops)).sum();
+        report.add("rule-caches", rule_caches_size);
+        report
+    }
+
+    /// Explains the computed value of `property` in `style`, the style of
+    /// `element` or of its `pseudo` pseudo-element, by listing the
+    /// declarations of the property that took part in the cascade.
+    ///
+    /// Selector matching is re-run to find out the specificity of each rule,
+    /// so this is meant for developer tools rather than for styling. Returns
+    /// `None` for shorthands, whose value doesn't go through the cascade.
+    pub fn trace_cascade<E>(
+        &self,
+        element: E,
+        pseudo: Option<&PseudoElement>,
+        style: &ComputedValues,
+        property: &PropertyId,
+        guards: &StylesheetGuards,
+    ) -> Option<CascadeTrace>
+    where
+        E: TElement,
+    {
+        let property = property.as_shorthand().err()?;
+
+        let mut declarations = ApplicableDeclarationList::new();
+        let mut selector_caches = Default::default();
+        let matching_mode = if pseudo.is_some() {
+            MatchingMode::ForStatelessPseudoElement
+        } else {
+            MatchingMode::Normal
+        };
+        let mut matching_context = MatchingContext::<'_, E::Impl>::new(
+            matching_mode,
+            None,
+            &mut selector_caches,
+            self.quirks_mode,
+            NeedsSelectorFlags::No,
+            MatchingForInvalidation::No,
+        );
+        self.push_applicable_declarations(
+            element,
+            pseudo,
+            element.style_attribute(),
+            /* smil_override = */ None,
+            /* animation_declarations = */ Default::default(),
+            RuleInclusion::All,
+            &mut declarations,
+            &mut matching_context,
+        );
+
+        let tracer = CascadeTracer::new(style, pseudo, guards);
+        Some(tracer.trace(property, |source, priority| {
+            let level = priority.cascade_level();
+            let origin = level.origin();
+            let mut provenance = RuleProvenance {
+                specificity: declarations
+                    .iter()
+                    .find(|d| d.source == *source)
+                    .map_or(0, |d| d.specificity),
+                ..Default::default()
+            };
+
+            let layer_order = priority.layer_order();
+            let in_document = origin != Origin::Author ||
+                level == CascadeLevel::same_tree_author_normal() ||
+                level == CascadeLevel::same_tree_author_important();
+            if in_document &&
+                layer_order != LayerOrder::root() &&
+                layer_order != LayerOrder::style_attribute()
+            {
+                provenance.layer = self
+                    .cascade_data
+                    .borrow_for_origin(origin)
+                    .layer_name(layer_order)
+                    .cloned();
+            }
+
+            if let Some(rule) = source.as_rule() {
+                let guard = guards.for_origin(origin);
+                let rule_ref = rule.read_with(guard);
+                provenance.selectors = Some(rule_ref.selectors.to_css_string());
+                provenance.source_location = Some(rule_ref.source_location);
+                provenance.stylesheet = self
+                    .stylesheets
+                    .iter()
+                    .filter(|&(_, sheet_origin)| sheet_origin == origin)
+                    .find_map(|(sheet, _)| {
+                        cascade_trace::find_style_rule(
+                            sheet.contents(),
+                            rule.get(),
+                            &self.device,
+                            guard,
+                        )
+                    });
+            }
+            provenance
+        }))
+    }
+
     /// Shutdown the static data that this module stores.
     pub fn shutdown() {
         let _entries = UA_CASCADE_DATA_CACHE.lock().unwrap().take_all();
     }
 }
 
+fn origin_report_name(origin: Origin) -> &'static str {
+    match origin {
+        Origin::UserAgent => "user-agent",
+        Origin::User => "user",
+        Origin::Author => "author",
+    }
+}
+
 /// A vector that is sorted in layer order.
 #[derive(Clone, Debug, Deref, MallocSizeOf)]
 pub struct LayerOrderedVec<T>(Vec<(T, LayerId)>);
@@ -1702,7 +2009,10 @@ pub struct ExtraStyleData {
     pub font_palette_values: LayerOrderedVec<Arc<Locked<FontPaletteValuesRule>>>,
 
     /// A map of effective counter-style rules.
-    #[cfg(feature = "gecko")]
+    #[cfg_attr(
+        feature = "servo",
+        ignore_malloc_size_of = "Arc, stylesheet measures as primary ref"
+    )]
     pub counter_styles: LayerOrderedMap<Arc<Locked<CounterStyleRule>>>,
 
     /// A map of effective page rules.
@@ -1710,14 +2020,15 @@ pub struct ExtraStyleData {
     pub pages: PageRuleMap,
 }
 
-#[cfg(feature = "gecko")]
 impl ExtraStyleData {
     /// Add the given @font-face rule.
+    #[cfg(feature = "gecko")]
     fn add_font_face(&mut self, rule: &Arc<Locked<FontFaceRule>>, layer: LayerId) {
         self.font_faces.push(rule.clone(), layer);
     }
 
     /// Add the given @font-feature-values rule.
+    #[cfg(feature = "gecko")]
     fn add_font_feature_values(
         &mut self,
         rule: &Arc<Locked<FontFeatureValuesRule>>,
@@ -1727,6 +2038,7 @@ impl ExtraStyleData {
     }
 
     /// Add the given @font-palette-values rule.
+    #[cfg(feature = "gecko")]
     fn add_font_palette_values(
         &mut self,
         rule: &Arc<Locked<FontPaletteValuesRule>>,
@@ -1747,6 +2059,7 @@ impl ExtraStyleData {
     }
 
     /// Add the given @page rule.
+    #[cfg(feature = "gecko")]
     fn add_page(
         &mut self,
         guard: &SharedRwLockReadGuard,
@@ -1774,11 +2087,14 @@ impl ExtraStyleData {
     }
 
     fn sort_by_layer(&mut self, layers: &[CascadeLayer]) {
-        self.font_faces.sort(layers);
-        self.font_feature_values.sort(layers);
-        self.font_palette_values.sort(layers);
+        #[cfg(feature = "gecko")]
+        {
+            self.font_faces.sort(layers);
+            self.font_feature_values.sort(layers);
+            self.font_palette_values.sort(layers);
+            self.pages.global.sort(layers);
+        }
         self.counter_styles.sort(layers);
-        self.pages.global.sort(layers);
     }
 
     fn clear(&mut self) {
@@ -1787,9 +2103,9 @@ impl ExtraStyleData {
             self.font_faces.clear();
             self.font_feature_values.clear();
             self.font_palette_values.clear();
-            self.counter_styles.clear();
             self.pages.clear();
         }
+        self.counter_styles.clear();
     }
 }
 
@@ -1831,13 +2147,9 @@ impl MallocSizeOf for ExtraStyleData {
 }
 
 /// SelectorMapEntry implementation for use in our revalidation selector map.
-#[cfg_attr(feature = "gecko", derive(MallocSizeOf))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, MallocSizeOf)]
 struct RevalidationSelectorAndHashes {
-    #[cfg_attr(
-        feature = "gecko",
-        ignore_malloc_size_of = "CssRules have primary refs, we measure there"
-    )]
+    #[ignore_malloc_size_of = "CssRules have primary refs, we measure there"]
     selector: Selector<SelectorImpl>,
     selector_offset: usize,
     hashes: AncestorHashes,
@@ -1917,7 +2229,8 @@ fn component_needs_revalidation(
         Component::AttributeOther(_) |
         Component::Empty |
         Component::Nth(_) |
-        Component::NthOf(_) => true,
+        Component::NthOf(_) |
+        Component::Has(_) => true,
         Component::NonTSPseudoClass(ref p) => p.needs_cache_revalidation(),
         _ => false,
     }
@@ -1955,6 +2268,24 @@ impl<'a> SelectorVisitor for StylistSelectorVisitor<'a> {
         true
     }
 
+    fn visit_relative_selector_list(&mut self, list: &[RelativeSelector<Self::Impl>]) -> bool {
+        for relative_selector in list {
+            // None of the compounds of a relative selector match the element
+            // `:has()` applies to, so none of them is the rightmost one.
+            let mut nested = StylistSelectorVisitor {
+                passed_rightmost_selector: true,
+                needs_revalidation: &mut *self.needs_revalidation,
+                attribute_dependencies: &mut *self.attribute_dependencies,
+                state_dependencies: &mut *self.state_dependencies,
+                document_state_dependencies: &mut *self.document_state_dependencies,
+                mapped_ids: &mut *self.mapped_ids,
+            };
+            let _ret = relative_selector.selector.visit(&mut nested);
+            debug_assert!(_ret, "We never return false");
+        }
+        true
+    }
+
     fn visit_attribute_selector(
         &mut self,
         _ns: &NamespaceConstraint<&Namespace>,
@@ -2039,16 +2370,21 @@ impl<Map: Default + MallocSizeOf> GenericElementAndPseudoRules<Map> {
         }
     }
 
-    /// Measures heap usage.
-    #[cfg(feature = "gecko")]
-    fn add_size_of(&self, ops: &mut MallocSizeOfOps, sizes: &mut ServoStyleSetSizes) {
-        sizes.mElementAndPseudosMaps += self.element_map.size_of(ops);
-
+    /// Measures the heap usage of the element and pseudo-element maps.
+    fn maps_size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let mut n = self.element_map.size_of(ops);
         for elem in self.pseudos_map.iter() {
             if let Some(ref elem) = *elem {
-                sizes.mElementAndPseudosMaps += <Box<_> as MallocSizeOf>::size_of(elem, ops);
+                n += <Box<_> as MallocSizeOf>::size_of(elem, ops);
             }
         }
+        n
+    }
+
+    /// Measures heap usage.
+    #[cfg(feature = "gecko")]
+    fn add_size_of(&self, ops: &mut MallocSizeOfOps, sizes: &mut ServoStyleSetSizes) {
+        sizes.mElementAndPseudosMaps += self.maps_size_of(ops);
     }
 }
 
@@ -2137,6 +2473,54 @@ impl ContainerConditionReference {
     }
 }
 
+/// The id of a given scope condition, a sequentially-increasing identifier
+/// for a given style set.
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq, PartialOrd, Ord)]
+pub struct ScopeConditionId(u16);
+
+impl ScopeConditionId {
+    /// A special id that represents no scope rule at all.
+    pub const fn none() -> Self {
+        Self(0)
+    }
+}
+
+#[derive(Clone, Debug, MallocSizeOf)]
+struct ScopeConditionReference {
+    parent: ScopeConditionId,
+    #[ignore_malloc_size_of = "Arc"]
+    bounds: Option<Arc<ScopeBounds>>,
+}
+
+impl ScopeConditionReference {
+    const fn none() -> Self {
+        Self {
+            parent: ScopeConditionId::none(),
+            bounds: None,
+        }
+    }
+}
+
+/// A scoping root of an element for a given scope condition.
+#[derive(Clone, Copy, Debug)]
+struct ScopeRoot {
+    element: OpaqueElement,
+    proximity: ScopeProximity,
+}
+
+/// The scoping roots of an element for a given scope condition, from the
+/// nearest to the farthest one.
+type ScopeRoots = SmallVec<[ScopeRoot; 1]>;
+
+/// A cache of the scoping roots of elements, keyed by the element, the cascade
+/// data and the scope condition, stored in the matching context.
+///
+/// Every rule inside a scope rule needs the scoping roots of the element being
+/// matched, and so do the roots of nested scope rules, so without this we'd
+/// look for them once per rule and nesting level.
+#[derive(Debug, Default)]
+pub struct ScopeRootsCache(FxHashMap<(OpaqueElement, usize, ScopeConditionId), ScopeRoots>);
+
 /// Data resulting from performing the CSS cascade that is specific to a given
 /// origin.
 ///
@@ -2170,6 +2554,10 @@ pub struct CascadeData {
     /// The invalidation map for these rules.
     invalidation_map: InvalidationMap,
 
+    /// The invalidation map for the relative selectors (the arguments of
+    /// `:has()`) in these rules, whose changes invalidate anchors up the tree.
+    relative_selector_invalidation_map: InvalidationMap,
+
     /// The attribute local names that appear in attribute selectors.  Used
     /// to avoid taking element snapshots when an irrelevant attribute changes.
     /// (We don't bother storing the namespace, since namespaced attributes are
@@ -2202,6 +2590,10 @@ pub struct CascadeData {
     /// by name.
     animations: LayerOrderedMap<KeyframesAnimation>,
 
+    /// A map with all the custom property registrations at this
+    /// `CascadeData`'s origin, indexed by name (without the leading `--`).
+    custom_property_registrations: LayerOrderedMap<PropertyRegistration>,
+
     /// A map from cascade layer name to layer order.
     layer_id: FxHashMap<LayerName, LayerId>,
 
@@ -2211,6 +2603,15 @@ pub struct CascadeData {
     /// The list of container conditions, indexed by their id.
     container_conditions: SmallVec<[ContainerConditionReference; 1]>,
 
+    /// The list of scope conditions, indexed by their id.
+    scope_conditions: SmallVec<[ScopeConditionReference; 1]>,
+
+    /// Whether there's any rule inside a @starting-style rule.
+    any_starting_style_rules: bool,
+
+    /// Whether any of the container conditions contains a `style()` query.
+    any_style_container_query: bool,
+
     /// Effective media query results cached from the last rebuild.
     effective_media_query_results: EffectiveMediaQueryResults,
 
@@ -2237,6 +2638,7 @@ impl CascadeData {
             slotted_rules: None,
             part_rules: None,
             invalidation_map: InvalidationMap::new(),
+            relative_selector_invalidation_map: InvalidationMap::new(),
             attribute_dependencies: PrecomputedHashSet::default(),
             state_dependencies: ElementState::empty(),
             document_state_dependencies: DocumentState::empty(),
@@ -2251,9 +2653,13 @@ impl CascadeData {
             // somewhat gnarly.
             selectors_for_cache_revalidation: SelectorMap::new_without_attribute_bucketing(),
             animations: Default::default(),
+            custom_property_registrations: Default::default(),
             layer_id: Default::default(),
             layers: smallvec::smallvec![CascadeLayer::root()],
             container_conditions: smallvec::smallvec![ContainerConditionReference::none()],
+            scope_conditions: smallvec::smallvec![ScopeConditionReference::none()],
+            any_starting_style_rules: false,
+            any_style_container_query: false,
             extra_data: ExtraStyleData::default(),
             effective_media_query_results: EffectiveMediaQueryResults::new(),
             rules_source_order: 0,
@@ -2310,6 +2716,11 @@ impl CascadeData {
         &self.invalidation_map
     }
 
+    /// Returns the invalidation map for relative selectors.
+    pub fn relative_selector_invalidation_map(&self) -> &InvalidationMap {
+        &self.relative_selector_invalidation_map
+    }
+
     /// Returns whether the given ElementState bit is relied upon by a selector
     /// of some rule.
     #[inline]
@@ -2352,6 +2763,11 @@ impl CascadeData {
         self.slotted_rules.is_some()
     }
 
+    /// Whether there's any @starting-style rule in this scope.
+    pub fn any_starting_style_rules(&self) -> bool {
+        self.any_starting_style_rules
+    }
+
     /// Returns the parts rule map for a given pseudo-element.
     #[inline]
     pub fn part_rules(&self, pseudo: Option<&PseudoElement>) -> Option<&PartMap> {
@@ -2368,6 +2784,21 @@ impl CascadeData {
         self.layers[id.0 as usize].order
     }
 
+    /// Returns the name of the cascade layer with the given order, if any.
+    pub fn layer_name(&self, order: LayerOrder) -> Option<&LayerName> {
+        let layer = self.layers.iter().skip(1).find(|l| l.order == order)?;
+        self.layer_id
+            .iter()
+            .find(|&(_, id)| *id == layer.id)
+            .map(|(name, _)| name)
+    }
+
+    /// Whether any of the container conditions at this origin contains a
+    /// `style()` query.
+    pub fn any_style_container_query(&self) -> bool {
+        self.any_style_container_query
+    }
+
     pub(crate) fn container_condition_matches<E>(
         &self,
         mut id: ContainerConditionId,
@@ -2386,7 +2817,7 @@ impl CascadeData {
             };
             let matches = condition
                 .matches(
-                    stylist.device(),
+                    stylist,
                     element,
                     context.extra_data.originating_element_style,
                     &mut context.extra_data.cascade_input_flags,
@@ -2399,6 +2830,113 @@ impl CascadeData {
         }
     }
 
+    /// Returns the scoping roots of `element` for the given scope condition,
+    /// from the nearest to the farthest one.
+    ///
+    /// https://drafts.csswg.org/css-cascade-6/#scoped-styles
+    fn scope_roots<E>(
+        &self,
+        id: ScopeConditionId,
+        element: E,
+        context: &mut MatchingContext<E::Impl>,
+    ) -> ScopeRoots
+    where
+        E: TElement,
+    {
+        // The address of the cascade data tells apart the scope conditions of
+        // different origins and shadow trees, which share ids.
+        let key = (element.opaque(), self as *const Self as usize, id);
+        if let Some(roots) = context.extra_data.scope_roots.0.get(&key) {
+            return roots.clone();
+        }
+        let roots = self.compute_scope_roots(id, element, context);
+        context.extra_data.scope_roots.0.insert(key, roots.clone());
+        roots
+    }
+
+    fn compute_scope_roots<E>(
+        &self,
+        id: ScopeConditionId,
+        element: E,
+        context: &mut MatchingContext<E::Impl>,
+    ) -> ScopeRoots
+    where
+        E: TElement,
+    {
+        let mut roots = SmallVec::new();
+        let condition_ref = &self.scope_conditions[id.0 as usize];
+        let bounds = match condition_ref.bounds {
+            Some(ref bounds) => bounds,
+            None => return roots,
+        };
+
+        // The scoping roots of a nested scope rule need to be in the scope of
+        // the enclosing one.
+        let outer_roots = if condition_ref.parent != ScopeConditionId::none() {
+            let outer_roots = self.scope_roots(condition_ref.parent, element, context);
+            if outer_roots.is_empty() {
+                return roots;
+            }
+            Some(outer_roots)
+        } else {
+            None
+        };
+
+        let mut generations = 0;
+        let mut current = Some(element);
+        while let Some(candidate) = current {
+            // Outer roots are inclusive ancestors of the element, sorted by
+            // proximity, so the first that isn't nearer than the candidate
+            // is its nearest inclusive ancestor.
+            let outer_root = match outer_roots {
+                Some(ref outer_roots) => {
+                    let proximity = ScopeProximity::new(generations);
+                    match outer_roots.iter().find(|root| root.proximity <= proximity) {
+                        Some(root) => Some(root.element),
+                        None => break,
+                    }
+                },
+                None => None,
+            };
+            if bounds.is_root(candidate, outer_root, context) &&
+                !bounds.is_out_of_limits(candidate, element, context)
+            {
+                roots.push(ScopeRoot {
+                    element: candidate.opaque(),
+                    proximity: ScopeProximity::new(generations),
+                });
+            }
+            generations += 1;
+            current = selectors::Element::parent_element(&candidate);
+        }
+        roots
+    }
+
+    /// Matches a rule inside a scope rule against the given element, returning
+    /// the proximity of the nearest scoping root for which the rule matches,
+    /// if any.
+    pub(crate) fn scoped_rule_matches<E>(
+        &self,
+        rule: &Rule,
+        element: E,
+        context: &mut MatchingContext<E::Impl>,
+    ) -> Option<ScopeProximity>
+    where
+        E: TElement,
+    {
+        debug_assert_ne!(rule.scope_condition_id, ScopeConditionId::none());
+        let roots = self.scope_roots(rule.scope_condition_id, element, context);
+        for root in roots {
+            let matches = context.with_scope_element(Some(root.element), |context| {
+                matches_selector(&rule.selector, 0, Some(&rule.hashes), &element, context)
+            });
+            if matches {
+                return Some(root.proximity);
+            }
+        }
+        None
+    }
+
     fn did_finish_rebuild(&mut self) {
         self.shrink_maps_if_needed();
         self.compute_layer_order();
@@ -2413,6 +2951,7 @@ impl CascadeData {
             slotted_rules.shrink_if_needed();
         }
         self.invalidation_map.shrink_if_needed();
+        self.relative_selector_invalidation_map.shrink_if_needed();
         self.attribute_dependencies.shrink_if_needed();
         self.mapped_ids.shrink_if_needed();
         self.layer_id.shrink_if_needed();
@@ -2459,6 +2998,7 @@ impl CascadeData {
         self.extra_data.sort_by_layer(&self.layers);
         self.animations
             .sort_with(&self.layers, compare_keyframes_in_same_layer);
+        self.custom_property_registrations.sort(&self.layers);
     }
 
     /// Collects all the applicable media query results into `results`.
@@ -2508,6 +3048,75 @@ impl CascadeData {
         }
     }
 
+    /// Notes the dependencies of the scoping roots and limits of a scope rule.
+    ///
+    /// Whether an element is a scoping root or limit affects which rules
+    /// match its descendants, and the element itself, so we note both for
+    /// invalidation, and for style sharing purposes.
+    fn note_scope_bounds(
+        &mut self,
+        bounds: &ScopeBounds,
+        quirks_mode: QuirksMode,
+    ) -> Result<(), AllocErr> {
+        for selector in bounds
+            .start
+            .iter()
+            .chain(bounds.end.iter())
+            .flat_map(|l| l.0.iter())
+        {
+            self.invalidation_map.note_selector(
+                selector,
+                quirks_mode,
+                &mut self.relative_selector_invalidation_map,
+            )?;
+            let mut needs_revalidation = false;
+            let mut visitor = StylistSelectorVisitor {
+                needs_revalidation: &mut needs_revalidation,
+                passed_rightmost_selector: false,
+                attribute_dependencies: &mut self.attribute_dependencies,
+                state_dependencies: &mut self.state_dependencies,
+                document_state_dependencies: &mut self.document_state_dependencies,
+                mapped_ids: &mut self.mapped_ids,
+            };
+            selector.visit(&mut visitor);
+            self.selectors_for_cache_revalidation.insert(
+                RevalidationSelectorAndHashes::new(
+                    selector.clone(),
+                    AncestorHashes::new(selector, quirks_mode),
+                ),
+                quirks_mode,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Notes the selector of a style rule inside a scope rule with its `&`
+    /// resolved against the scoping roots and limits, so that changes to
+    /// them invalidate the elements the rule may match.
+    fn note_scoped_selector(
+        &mut self,
+        selector: &Selector<SelectorImpl>,
+        id: ScopeConditionId,
+        quirks_mode: QuirksMode,
+    ) -> Result<(), AllocErr> {
+        if id == ScopeConditionId::none() || !selector.has_parent_selector() {
+            return Ok(());
+        }
+        let bounds = match self.scope_conditions[id.0 as usize].bounds {
+            Some(ref bounds) => bounds.clone(),
+            None => return Ok(()),
+        };
+        for list in bounds.start.iter().chain(bounds.end.iter()) {
+            let resolved = selector.replace_parent_selector(&list.0);
+            self.invalidation_map.note_selector(
+                &resolved,
+                quirks_mode,
+                &mut self.relative_selector_invalidation_map,
+            )?;
+        }
+        Ok(())
+    }
+
     fn add_rule_list<S>(
         &mut self,
         rules: std::slice::Iter<'_, CssRule>,
@@ -2526,11 +3135,20 @@ impl CascadeData {
             // Handle leaf rules first, as those are by far the most common
             // ones, and are always effective, so we can skip some checks.
             let mut handled = true;
+            let mut selectors_for_nested_rules = None;
             match *rule {
                 CssRule::Style(ref locked) => {
                     let style_rule = locked.read_with(&guard);
                     self.num_declarations += style_rule.block.read_with(&guard).len();
-                    for selector in &style_rule.selectors.0 {
+
+                    // Resolve the `&` in nested rules against the selectors of
+                    // the parent style rule.
+                    let selectors = match containing_rule_state.ancestor_selector_lists.last() {
+                        Some(parent) => style_rule.selectors.replace_parent_selector(&parent.0),
+                        None => Cow::Borrowed(&style_rule.selectors),
+                    };
+
+                    for selector in &selectors.0 {
                         self.num_selectors += 1;
 
                         let pseudo_element = selector.pseudo_element();
@@ -2551,6 +3169,7 @@ impl CascadeData {
                                         CascadeLevel::UANormal,
                                         selector.specificity(),
                                         LayerOrder::root(),
+                                        ScopeProximity::infinity(),
                                     ));
                                 continue;
                             }
@@ -2568,10 +3187,21 @@ impl CascadeData {
                             self.rules_source_order,
                             containing_rule_state.layer_id,
                             containing_rule_state.container_condition_id,
+                            containing_rule_state.scope_condition_id,
+                            containing_rule_state.in_starting_style,
                         );
 
                         if rebuild_kind.should_rebuild_invalidation() {
-                            self.invalidation_map.note_selector(selector, quirks_mode)?;
+                            self.invalidation_map.note_selector(
+                                selector,
+                                quirks_mode,
+                                &mut self.relative_selector_invalidation_map,
+                            )?;
+                            self.note_scoped_selector(
+                                selector,
+                                containing_rule_state.scope_condition_id,
+                                quirks_mode,
+                            )?;
                             let mut needs_revalidation = false;
                             let mut visitor = StylistSelectorVisitor {
                                 needs_revalidation: &mut needs_revalidation,
@@ -2632,6 +3262,11 @@ impl CascadeData {
                         }
                     }
                     self.rules_source_order += 1;
+
+                    if style_rule.rules.is_some() {
+                        selectors_for_nested_rules = Some(selectors.into_owned());
+                        handled = false;
+                    }
                 },
                 CssRule::Keyframes(ref keyframes_rule) => {
                     debug!("Found valid keyframes rule: {:?}", *keyframes_rule);
@@ -2649,6 +3284,15 @@ impl CascadeData {
                         compare_keyframes_in_same_layer,
                     )?;
                 },
+                CssRule::Property(ref rule) => {
+                    let rule = rule.read_with(guard);
+                    let registration = PropertyRegistration::new(&rule, device, quirks_mode);
+                    self.custom_property_registrations.try_insert(
+                        rule.name.0.clone(),
+                        registration,
+                        containing_rule_state.layer_id,
+                    )?;
+                },
                 #[cfg(feature = "gecko")]
                 CssRule::FontFace(ref rule) => {
                     // NOTE(emilio): We don't care about container_condition_id
@@ -2674,7 +3318,6 @@ impl CascadeData {
                     self.extra_data
                         .add_font_palette_values(rule, containing_rule_state.layer_id);
                 },
-                #[cfg(feature = "gecko")]
                 CssRule::CounterStyle(ref rule) => {
                     self.extra_data.add_counter_style(
                         guard,
@@ -2777,6 +3420,12 @@ impl CascadeData {
 
             let saved_containing_rule_state = containing_rule_state.save();
             match *rule {
+                CssRule::Style(..) => {
+                    let selectors = selectors_for_nested_rules
+                        .take()
+                        .expect("Should only get here for style rules with nested rules");
+                    containing_rule_state.ancestor_selector_lists.push(selectors);
+                },
                 CssRule::Import(ref lock) => {
                     let import_rule = lock.read_with(guard);
                     if rebuild_kind.should_rebuild_invalidation() {
@@ -2807,6 +3456,9 @@ impl CascadeData {
                 },
                 CssRule::Container(ref lock) => {
                     let container_rule = lock.read_with(guard);
+                    if container_rule.condition.has_style_query() {
+                        self.any_style_container_query = true;
+                    }
                     let id = ContainerConditionId(self.container_conditions.len() as u16);
                     self.container_conditions.push(ContainerConditionReference {
                         parent: containing_rule_state.container_condition_id,
@@ -2814,6 +3466,22 @@ impl CascadeData {
                     });
                     containing_rule_state.container_condition_id = id;
                 },
+                CssRule::Scope(ref lock) => {
+                    let scope_rule = lock.read_with(guard);
+                    let id = ScopeConditionId(self.scope_conditions.len() as u16);
+                    self.scope_conditions.push(ScopeConditionReference {
+                        parent: containing_rule_state.scope_condition_id,
+                        bounds: Some(scope_rule.bounds.clone()),
+                    });
+                    containing_rule_state.scope_condition_id = id;
+                    if rebuild_kind.should_rebuild_invalidation() {
+                        self.note_scope_bounds(&scope_rule.bounds, quirks_mode)?;
+                    }
+                },
+                CssRule::StartingStyle(..) => {
+                    containing_rule_state.in_starting_style = true;
+                    self.any_starting_style_rules = true;
+                },
                 // We don't care about any other rule.
                 _ => {},
             }
@@ -2917,6 +3585,8 @@ impl CascadeData {
                 CssRule::Namespace(..) |
                 CssRule::FontFace(..) |
                 CssRule::Container(..) |
+                CssRule::Scope(..) |
+                CssRule::StartingStyle(..) |
                 CssRule::CounterStyle(..) |
                 CssRule::Supports(..) |
                 CssRule::Keyframes(..) |
@@ -2926,7 +3596,8 @@ impl CascadeData {
                 CssRule::LayerBlock(..) |
                 CssRule::LayerStatement(..) |
                 CssRule::FontPaletteValues(..) |
-                CssRule::FontFeatureValues(..) => {
+                CssRule::FontFeatureValues(..) |
+                CssRule::Property(..) => {
                     // Not affected by device changes.
                     continue;
                 },
@@ -2991,12 +3662,17 @@ impl CascadeData {
             host_rules.clear();
         }
         self.animations.clear();
+        self.custom_property_registrations.clear();
         self.layer_id.clear();
         self.layers.clear();
         self.layers.push(CascadeLayer::root());
         self.container_conditions.clear();
         self.container_conditions
             .push(ContainerConditionReference::none());
+        self.scope_conditions.clear();
+        self.scope_conditions.push(ScopeConditionReference::none());
+        self.any_starting_style_rules = false;
+        self.any_style_container_query = false;
         self.extra_data.clear();
         self.rules_source_order = 0;
         self.num_selectors = 0;
@@ -3006,6 +3682,7 @@ impl CascadeData {
     fn clear(&mut self) {
         self.clear_cascade_data();
         self.invalidation_map.clear();
+        self.relative_selector_invalidation_map.clear();
         self.attribute_dependencies.clear();
         self.state_dependencies = ElementState::empty();
         self.document_state_dependencies = DocumentState::empty();
@@ -3053,11 +3730,58 @@ impl CascadeDataCacheEntry for CascadeData {
             host_rules.add_size_of(ops, sizes);
         }
         sizes.mInvalidationMap += self.invalidation_map.size_of(ops);
+        sizes.mInvalidationMap += self.relative_selector_invalidation_map.size_of(ops);
         sizes.mRevalidationSelectors += self.selectors_for_cache_revalidation.size_of(ops);
         sizes.mOther += self.animations.size_of(ops);
+        sizes.mOther += self.custom_property_registrations.size_of(ops);
         sizes.mOther += self.effective_media_query_results.size_of(ops);
         sizes.mOther += self.extra_data.size_of(ops);
     }
+
+    /// Adds the heap usage of this cascade data to `report`, broken down by
+    /// the kind of data structure measured.
+    fn add_to_memory_report(&self, ops: &mut MallocSizeOfOps, report: &mut MemoryReport) {
+        report.add(
+            "element-and-pseudo-maps",
+            self.normal_rules.maps_size_of(ops),
+        );
+        if let Some(ref slotted_rules) = self.slotted_rules {
+            report.add("slotted-maps", slotted_rules.maps_size_of(ops));
+        }
+        if let Some(ref part_rules) = self.part_rules {
+            report.add("part-maps", part_rules.maps_size_of(ops));
+        }
+        if let Some(ref host_rules) = self.host_rules {
+            report.add("host-maps", host_rules.maps_size_of(ops));
+        }
+        report.add("invalidation-map", self.invalidation_map.size_of(ops));
+        report.add(
+            "relative-selector-invalidation-map",
+            self.relative_selector_invalidation_map.size_of(ops),
+        );
+        report.add(
+            "revalidation-selectors",
+            self.selectors_for_cache_revalidation.size_of(ops),
+        );
+        report.add("animations", self.animations.size_of(ops));
+        report.add(
+            "custom-property-registrations",
+            self.custom_property_registrations.size_of(ops),
+        );
+        report.add(
+            "effective-media-query-results",
+            self.effective_media_query_results.size_of(ops),
+        );
+        report.add("extra-data", self.extra_data.size_of(ops));
+
+        let mut other = self.attribute_dependencies.size_of(ops);
+        other += self.mapped_ids.size_of(ops);
+        other += self.layer_id.size_of(ops);
+        other += self.layers.size_of(ops);
+        other += self.container_conditions.size_of(ops);
+        other += self.scope_conditions.size_of(ops);
+        report.add("other", other);
+    }
 }
 
 impl Default for CascadeData {
@@ -3091,6 +3815,13 @@ pub struct Rule {
     /// The current @container rule id.
     pub container_condition_id: ContainerConditionId,
 
+    /// The current @scope rule id.
+    pub scope_condition_id: ScopeConditionId,
+
+    /// Whether this rule is inside a @starting-style rule, and thus only
+    /// applies when computing starting styles.
+    pub is_starting_style: bool,
+
     /// The actual style rule.
     #[cfg_attr(
         feature = "gecko",
@@ -3113,10 +3844,11 @@ impl Rule {
     }
 
     /// Turns this rule into an `ApplicableDeclarationBlock` for the given
-    /// cascade level.
+    /// cascade level and scope proximity.
     pub fn to_applicable_declaration_block(
         &self,
         level: CascadeLevel,
+        scope_proximity: ScopeProximity,
         cascade_data: &CascadeData,
     ) -> ApplicableDeclarationBlock {
         let source = StyleSource::from_rule(self.style_rule.clone());
@@ -3126,6 +3858,7 @@ impl Rule {
             level,
             self.specificity(),
             cascade_data.layer_order_for(self.layer_id),
+            scope_proximity,
         )
     }
 
@@ -3137,6 +3870,8 @@ impl Rule {
         source_order: u32,
         layer_id: LayerId,
         container_condition_id: ContainerConditionId,
+        scope_condition_id: ScopeConditionId,
+        is_starting_style: bool,
     ) -> Self {
         Rule {
             selector,
@@ -3145,6 +3880,8 @@ impl Rule {
             source_order,
             layer_id,
             container_condition_id,
+            scope_condition_id,
+            is_starting_style,
         }
     }
 }
@@ -14,7 +14,7 @@ use crate::sharing::StyleSharingTarget;
 use crate::style_resolver::{PseudoElementResolution, StyleResolverForElement};
 use crate::stylist::RuleInclusion;
 use crate::traversal_flags::TraversalFlags;
-use selectors::NthIndexCache;
+use selectors::matching::SelectorCaches;
 use smallvec::SmallVec;
 use std::collections::HashMap;
 
@@ -161,6 +161,20 @@ pub trait DomTraversal<E: TElement>: Sync {
 
         let traversal_flags = shared_context.traversal_flags;
 
+        // Relative selectors invalidate elements up the tree, so we need to
+        // process them before the traversal (and before borrowing the root's
+        // data, since they may invalidate the root itself).
+        #[cfg(feature = "servo")]
+        {
+            use crate::invalidation::element::relative_selector;
+            if !traversal_flags.for_animation_only() {
+                relative_selector::invalidate_relative_selectors_in_dirty_subtree(
+                    root,
+                    shared_context,
+                );
+            }
+        }
+
         let mut data = root.mutate_data();
         let mut data = data.as_mut().map(|d| &mut **d);
 
@@ -172,7 +186,7 @@ pub trait DomTraversal<E: TElement>: Sync {
                     root,
                     shared_context,
                     None,
-                    &mut NthIndexCache::default(),
+                    &mut SelectorCaches::default(),
                 );
 
                 if invalidation_result.has_invalidated_siblings() {
@@ -804,7 +818,7 @@ fn note_children<E, D, F>(
                 child,
                 &context.shared,
                 Some(&context.thread_local.stack_limit_checker),
-                &mut context.thread_local.nth_index_cache,
+                &mut context.thread_local.selector_caches,
             );
         }
 
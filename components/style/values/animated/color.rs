@@ -30,6 +30,9 @@ pub struct AnimatedRGBA {
 
 impl From<AbsoluteColor> for AnimatedRGBA {
     fn from(value: AbsoluteColor) -> Self {
+        // Animated colors may be out of the sRGB gamut, for example while
+        // interpolating past the end points, so this doesn't map them into
+        // it. That only happens once a color is computed.
         let srgb = value.to_color_space(ColorSpace::Srgb);
 
         Self::new(
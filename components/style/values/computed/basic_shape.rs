@@ -8,7 +8,7 @@
 //! [basic-shape]: https://drafts.csswg.org/css-shapes/#typedef-basic-shape
 
 use crate::values::computed::url::ComputedUrl;
-use crate::values::computed::{Image, LengthPercentage, NonNegativeLengthPercentage};
+use crate::values::computed::{Angle, Image, LengthPercentage, NonNegativeLengthPercentage};
 use crate::values::generics::basic_shape as generic;
 
 /// A computed alias for FillRule.
@@ -24,8 +24,10 @@ pub type ShapeOutside = generic::GenericShapeOutside<BasicShape, Image>;
 pub type BasicShape = generic::GenericBasicShape<
     LengthPercentage,
     LengthPercentage,
+    Angle,
     LengthPercentage,
     NonNegativeLengthPercentage,
+    InsetRect,
 >;
 
 /// The computed value of `inset()`
@@ -40,3 +42,9 @@ pub type Ellipse =
 
 /// The computed value of `ShapeRadius`
 pub type ShapeRadius = generic::GenericShapeRadius<NonNegativeLengthPercentage>;
+
+/// The computed value of `shape()`.
+pub type ShapeFunction = generic::GenericShapeFunction<Angle, LengthPercentage>;
+
+/// The computed value of a `shape()` command.
+pub type ShapeCommand = generic::GenericShapeCommand<Angle, LengthPercentage>;
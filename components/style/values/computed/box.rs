@@ -7,18 +7,22 @@
 use crate::values::animated::{Animate, Procedure};
 use crate::values::computed::length::{LengthPercentage, NonNegativeLength};
 use crate::values::computed::{Context, Integer, ToComputedValue};
+#[cfg(feature = "servo")]
+use crate::values::generics::box_::GenericAnimationTimeline;
 use crate::values::generics::box_::{
-    GenericContainIntrinsicSize, GenericLineClamp, GenericPerspective, GenericVerticalAlign,
+    GenericAnimationRange, GenericContainIntrinsicSize, GenericLineClamp, GenericPerspective,
+    GenericVerticalAlign,
 };
 use crate::values::specified::box_ as specified;
 
+#[cfg(feature = "gecko")]
+pub use crate::values::specified::box_::AnimationTimeline;
 pub use crate::values::specified::box_::{
-    AnimationName, AnimationTimeline, Appearance, BreakBetween, BreakWithin,
-    Clear as SpecifiedClear, Contain, ContainerName, ContainerType, ContentVisibility, Display,
-    Float as SpecifiedFloat, Overflow, OverflowAnchor, OverflowClipBox, OverscrollBehavior,
-    ScrollAxis, ScrollSnapAlign, ScrollSnapAxis, ScrollSnapStop, ScrollSnapStrictness,
-    ScrollSnapType, ScrollTimelineName, ScrollbarGutter, TouchAction, TransitionProperty,
-    WillChange,
+    AnimationName, Appearance, BreakBetween, BreakWithin, Clear as SpecifiedClear, Contain,
+    ContainerName, ContainerType, ContentVisibility, Display, Float as SpecifiedFloat, Overflow,
+    OverflowAnchor, OverflowClipBox, OverscrollBehavior, ScrollAxis, ScrollSnapAlign,
+    ScrollSnapAxis, ScrollSnapStop, ScrollSnapStrictness, ScrollSnapType, ScrollTimelineName,
+    Scroller, ScrollbarGutter, TouchAction, TransitionProperty, WillChange,
 };
 
 use std::fmt::{self, Write};
@@ -27,6 +31,14 @@ use style_traits::{ToCss, CssWriter};
 /// A computed value for the `vertical-align` property.
 pub type VerticalAlign = GenericVerticalAlign<LengthPercentage>;
 
+/// A computed value for the `animation-timeline` property.
+#[cfg(feature = "servo")]
+pub type AnimationTimeline = GenericAnimationTimeline<LengthPercentage>;
+
+/// A computed value for the `animation-range-start` or `animation-range-end`
+/// property.
+pub type AnimationRange = GenericAnimationRange<LengthPercentage>;
+
 /// A computed value for the `animation-iteration-count` property.
 #[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq, ToResolvedValue, ToShmem)]
 #[repr(C)]
@@ -7,6 +7,10 @@
 //!
 //! [image]: https://drafts.csswg.org/css-images/#image-values
 
+use crate::color::mix::{self, ColorInterpolationMethod};
+use crate::color::AbsoluteColor;
+use crate::values::animated::color::AnimatedRGBA;
+use crate::values::animated::ToAnimatedValue;
 use crate::values::computed::percentage::Percentage;
 use crate::values::computed::position::Position;
 use crate::values::computed::url::ComputedImageUrl;
@@ -14,12 +18,14 @@ use crate::values::computed::url::ComputedImageUrl;
 use crate::values::computed::NumberOrPercentage;
 use crate::values::computed::{Angle, Color, Context};
 use crate::values::computed::{
-    AngleOrPercentage, LengthPercentage, NonNegativeLength, NonNegativeLengthPercentage,
+    AngleOrPercentage, Length, LengthPercentage, NonNegativeLength, NonNegativeLengthPercentage,
     Resolution, ToComputedValue,
 };
 use crate::values::generics::image::{self as generic, GradientCompatMode};
 use crate::values::specified::image as specified;
 use crate::values::specified::position::{HorizontalPositionKeyword, VerticalPositionKeyword};
+use crate::values::CSSFloat;
+use cssparser::RGBA;
 use std::f32::consts::PI;
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ToCss};
@@ -140,6 +146,210 @@ pub type MozImageRect = generic::GenericMozImageRect<NumberOrPercentage, Compute
 #[cfg(not(feature = "gecko"))]
 pub type MozImageRect = specified::MozImageRect;
 
+/// A color stop with its position resolved to a fraction of the gradient
+/// line, as per <https://drafts.csswg.org/css-images-4/#color-stop-fixup>.
+struct ResolvedColorStop {
+    color: AbsoluteColor,
+    position: CSSFloat,
+    /// The interpolation hint between this stop and the next one, if any.
+    hint: Option<CSSFloat>,
+}
+
+impl Gradient {
+    /// Returns the method used to interpolate between the color stops of
+    /// this gradient.
+    pub fn color_interpolation_method(&self) -> &ColorInterpolationMethod {
+        match *self {
+            generic::Gradient::Linear {
+                ref color_interpolation_method,
+                ..
+            } |
+            generic::Gradient::Radial {
+                ref color_interpolation_method,
+                ..
+            } |
+            generic::Gradient::Conic {
+                ref color_interpolation_method,
+                ..
+            } => color_interpolation_method,
+        }
+    }
+
+    /// Samples the color stop list of this gradient at `position`,
+    /// interpolating in the gradient's color interpolation method.
+    ///
+    /// `position` is a fraction of the gradient line for linear and radial
+    /// gradients, and a fraction of a turn for conic gradients. Length stop
+    /// positions are resolved against `line_length`, and `currentcolor` stops
+    /// against `current_color`.
+    pub fn sample(&self, position: CSSFloat, line_length: Length, current_color: RGBA) -> RGBA {
+        let (stops, repeating) = match *self {
+            generic::Gradient::Linear {
+                ref items,
+                repeating,
+                ..
+            } |
+            generic::Gradient::Radial {
+                ref items,
+                repeating,
+                ..
+            } => {
+                let stops = resolve_color_stops(items, current_color, |position| {
+                    if line_length.px() == 0. {
+                        return 0.;
+                    }
+                    position.resolve(line_length).px() / line_length.px()
+                });
+                (stops, repeating)
+            },
+            generic::Gradient::Conic {
+                ref items,
+                repeating,
+                ..
+            } => {
+                let stops = resolve_color_stops(items, current_color, |position| match *position {
+                    AngleOrPercentage::Angle(ref angle) => angle.degrees() / 360.,
+                    AngleOrPercentage::Percentage(ref percentage) => percentage.0,
+                });
+                (stops, repeating)
+            },
+        };
+
+        let color = sample_color_stops(
+            &stops,
+            position,
+            repeating,
+            self.color_interpolation_method(),
+        );
+        RGBA::from_animated_value(color.into())
+    }
+}
+
+fn resolve_color_stops<T>(
+    items: &[generic::GradientItem<Color, T>],
+    current_color: RGBA,
+    resolve_position: impl Fn(&T) -> CSSFloat,
+) -> Vec<ResolvedColorStop> {
+    let mut stops = Vec::<ResolvedColorStop>::with_capacity(items.len());
+    let mut positions = Vec::with_capacity(items.len());
+    for item in items {
+        let (color, position) = match *item {
+            generic::GradientItem::SimpleColorStop(ref color) => (color, None),
+            generic::GradientItem::ComplexColorStop {
+                ref color,
+                ref position,
+            } => (color, Some(resolve_position(position))),
+            generic::GradientItem::InterpolationHint(ref hint) => {
+                if let Some(stop) = stops.last_mut() {
+                    stop.hint = Some(resolve_position(hint));
+                }
+                continue;
+            },
+        };
+        let color = color.clone().into_rgba(current_color);
+        stops.push(ResolvedColorStop {
+            color: AbsoluteColor::from(color.to_animated_value()),
+            position: 0.,
+            hint: None,
+        });
+        positions.push(position);
+    }
+
+    if positions.is_empty() {
+        return stops;
+    }
+
+    // If the first or last stops have no position, they're at the start
+    // and end of the gradient line respectively.
+    let last = positions.len() - 1;
+    positions[0].get_or_insert(0.);
+    positions[last].get_or_insert(1.);
+
+    // A stop can't be positioned before any stop preceding it.
+    let mut max_position = CSSFloat::MIN;
+    for position in positions.iter_mut().flatten() {
+        if *position < max_position {
+            *position = max_position;
+        }
+        max_position = *position;
+    }
+
+    // Stops without a position are spread evenly between the positioned
+    // stops around them.
+    let mut start = 0;
+    while start < last {
+        let end = start + 1 + positions[start + 1..]
+            .iter()
+            .position(|p| p.is_some())
+            .unwrap();
+        let from = positions[start].unwrap();
+        let to = positions[end].unwrap();
+        let count = (end - start) as CSSFloat;
+        for (i, position) in positions[start + 1..end].iter_mut().enumerate() {
+            *position = Some(from + (to - from) * (i + 1) as CSSFloat / count);
+        }
+        start = end;
+    }
+
+    for (stop, position) in stops.iter_mut().zip(positions) {
+        stop.position = position.unwrap();
+    }
+    stops
+}
+
+fn sample_color_stops(
+    stops: &[ResolvedColorStop],
+    mut position: CSSFloat,
+    repeating: bool,
+    interpolation: &ColorInterpolationMethod,
+) -> AbsoluteColor {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return AnimatedRGBA::transparent().into(),
+    };
+
+    if repeating {
+        let period = last.position - first.position;
+        if period > 0. {
+            position = first.position + (position - first.position).rem_euclid(period);
+        }
+    }
+
+    if position <= first.position {
+        return first.color.clone();
+    }
+
+    for pair in stops.windows(2) {
+        let (start, end) = (&pair[0], &pair[1]);
+        if position >= end.position {
+            continue;
+        }
+        let length = end.position - start.position;
+        let mut progress = (position - start.position) / length;
+        if let Some(hint) = start.hint {
+            // https://drafts.csswg.org/css-images-4/#coloring-gradient-line
+            let hint = (hint.max(start.position).min(end.position) - start.position) / length;
+            progress = if hint <= 0. {
+                1.
+            } else if hint >= 1. {
+                0.
+            } else {
+                progress.powf(0.5f32.ln() / hint.ln())
+            };
+        }
+        return mix::mix(
+            interpolation,
+            &start.color,
+            1. - progress,
+            &end.color,
+            progress,
+            /* normalize_weights = */ false,
+        );
+    }
+
+    last.color.clone()
+}
+
 impl generic::LineDirection for LineDirection {
     fn points_downwards(&self, compat_mode: GradientCompatMode) -> bool {
         match *self {
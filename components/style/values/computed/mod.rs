@@ -22,6 +22,7 @@ use crate::media_queries::Device;
 use crate::properties;
 use crate::properties::{ComputedValues, LonghandId, StyleBuilder};
 use crate::rule_cache::RuleCacheConditions;
+use crate::stylist::Stylist;
 use crate::stylesheets::container_rule::{
     ContainerInfo, ContainerSizeQuery, ContainerSizeQueryResult,
 };
@@ -48,15 +49,15 @@ pub use self::border::{BorderCornerRadius, BorderRadius, BorderSpacing};
 pub use self::border::{BorderImageRepeat, BorderImageSideWidth};
 pub use self::border::{BorderImageSlice, BorderImageWidth};
 pub use self::box_::{
-    AnimationIterationCount, AnimationName, AnimationTimeline, Contain, ContainerName,
-    ContainerType,
+    AnimationIterationCount, AnimationName, AnimationRange, AnimationTimeline, Contain,
+    ContainerName, ContainerType,
 };
 pub use self::box_::{
     Appearance, BreakBetween, BreakWithin, Clear, ContainIntrinsicSize, ContentVisibility, Float,
 };
 pub use self::box_::{Display, LineClamp, Overflow, OverflowAnchor, TransitionProperty};
 pub use self::box_::{OverflowClipBox, OverscrollBehavior, Perspective, Resize, ScrollbarGutter};
-pub use self::box_::{ScrollAxis, ScrollSnapAlign, ScrollSnapAxis, ScrollSnapStop};
+pub use self::box_::{ScrollAxis, ScrollSnapAlign, ScrollSnapAxis, ScrollSnapStop, Scroller};
 pub use self::box_::{ScrollSnapStrictness, ScrollSnapType, ScrollTimelineName};
 pub use self::box_::{TouchAction, VerticalAlign, WillChange};
 pub use self::color::{Color, ColorOrAuto, ColorPropertyValue, ColorScheme, PrintColorAdjust, ForcedColorAdjust};
@@ -79,7 +80,7 @@ pub use self::length::{NonNegativeLengthPercentage, NonNegativeLengthPercentageO
 #[cfg(feature = "gecko")]
 pub use self::list::ListStyleType;
 pub use self::list::Quotes;
-pub use self::motion::{OffsetPath, OffsetRotate};
+pub use self::motion::{OffsetPath, OffsetPosition, OffsetRotate};
 pub use self::outline::OutlineStyle;
 pub use self::page::{PageName, PageOrientation, PageSize, PageSizeOrientation, PaperSize};
 pub use self::percentage::{NonNegativePercentage, Percentage};
@@ -99,6 +100,8 @@ pub use self::text::{InitialLetter, LetterSpacing, LineBreak, LineHeight};
 pub use self::text::{OverflowWrap, RubyPosition, TextOverflow, WordBreak, WordSpacing};
 pub use self::text::{TextAlign, TextAlignLast, TextEmphasisPosition, TextEmphasisStyle};
 pub use self::text::{TextDecorationLength, TextDecorationSkipInk, TextJustify};
+pub use self::text::{TextSpacingTrim, TextWrapMode, TextWrapStyle};
+pub use self::text::{WhiteSpaceCollapse, WhiteSpaceTrim};
 pub use self::time::Time;
 pub use self::transform::{Rotate, Scale, Transform, TransformOperation};
 pub use self::transform::{TransformOrigin, TransformStyle, Translate};
@@ -184,6 +187,10 @@ pub struct Context<'a> {
     /// Returns the container information to evaluate a given container query.
     pub container_info: Option<ContainerInfo>,
 
+    /// The stylist, only available when evaluating container queries, so
+    /// that style queries can look up custom property registrations.
+    pub stylist: Option<&'a Stylist>,
+
     /// The property we are computing a value for, if it is a non-inherited
     /// property.  None if we are computed a value for an inherited property
     /// or not computing for a property at all (e.g. in a media query
@@ -222,6 +229,7 @@ impl<'a> Context<'a> {
             quirks_mode,
             for_smil_animation: false,
             container_info: None,
+            stylist: None,
             for_non_inherited_property: None,
             rule_cache_conditions: RefCell::new(&mut conditions),
             container_size_query: RefCell::new(ContainerSizeQuery::none()),
@@ -232,7 +240,7 @@ impl<'a> Context<'a> {
     /// Creates a suitable context for container query evaluation for the style
     /// specified.
     pub fn for_container_query_evaluation<F, R>(
-        device: &Device,
+        stylist: &Stylist,
         container_info_and_style: Option<(ContainerInfo, Arc<ComputedValues>)>,
         container_size_query: ContainerSizeQuery,
         f: F,
@@ -248,6 +256,7 @@ impl<'a> Context<'a> {
         };
 
         let style = style.as_ref().map(|s| &**s);
+        let device = stylist.device();
         let quirks_mode = device.quirks_mode();
         let context = Context {
             builder: StyleBuilder::for_inheritance(device, style, None),
@@ -257,6 +266,7 @@ impl<'a> Context<'a> {
             quirks_mode,
             for_smil_animation: false,
             container_info,
+            stylist: Some(stylist),
             for_non_inherited_property: None,
             rule_cache_conditions: RefCell::new(&mut conditions),
             container_size_query: RefCell::new(container_size_query),
@@ -279,6 +289,7 @@ impl<'a> Context<'a> {
             in_container_query: false,
             quirks_mode,
             container_info: None,
+            stylist: None,
             for_smil_animation: false,
             for_non_inherited_property: None,
             rule_cache_conditions: RefCell::new(rule_cache_conditions),
@@ -301,6 +312,7 @@ impl<'a> Context<'a> {
             in_container_query: false,
             quirks_mode,
             container_info: None,
+            stylist: None,
             for_smil_animation,
             for_non_inherited_property: None,
             rule_cache_conditions: RefCell::new(rule_cache_conditions),
@@ -4,12 +4,17 @@
 
 //! Computed types for CSS values that are related to motion path.
 
-use crate::values::computed::Angle;
-use crate::values::generics::motion::GenericOffsetPath;
+use crate::values::computed::basic_shape::BasicShape;
+use crate::values::computed::url::ComputedUrl;
+use crate::values::computed::{Angle, Position};
+use crate::values::generics::motion as generics;
 use crate::Zero;
 
 /// The computed value of `offset-path`.
-pub type OffsetPath = GenericOffsetPath<Angle>;
+pub type OffsetPath = generics::GenericOffsetPath<Angle, BasicShape, ComputedUrl>;
+
+/// The computed value of `offset-position`.
+pub type OffsetPosition = generics::GenericOffsetPosition<Position>;
 
 #[inline]
 fn is_auto_zero_angle(auto: &bool, angle: &Angle) -> bool {
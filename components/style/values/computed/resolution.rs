@@ -14,7 +14,7 @@ use style_traits::{CssWriter, ToCss};
 
 /// A computed `<resolution>`.
 #[repr(C)]
-#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToResolvedValue, ToShmem)]
+#[derive(Animate, Clone, Debug, MallocSizeOf, PartialEq, ToResolvedValue, ToShmem)]
 pub struct Resolution(CSSFloat);
 
 impl Resolution {
@@ -26,6 +26,8 @@ pub use crate::values::specified::HyphenateCharacter;
 pub use crate::values::specified::{LineBreak, OverflowWrap, RubyPosition, WordBreak};
 pub use crate::values::specified::{TextDecorationLine, TextEmphasisPosition};
 pub use crate::values::specified::{TextDecorationSkipInk, TextJustify, TextTransform};
+pub use crate::values::specified::{TextSpacingTrim, TextWrapMode, TextWrapStyle};
+pub use crate::values::specified::{WhiteSpaceCollapse, WhiteSpaceTrim};
 
 /// A computed value for the `initial-letter` property.
 pub type InitialLetter = GenericInitialLetter<CSSFloat, CSSInteger>;
@@ -160,8 +160,10 @@ pub use self::GenericShapeOutside as ShapeOutside;
     Clone,
     ComputeSquaredDistance,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -170,12 +172,17 @@ pub use self::GenericShapeOutside as ShapeOutside;
     ToShmem,
 )]
 #[repr(C, u8)]
-pub enum GenericBasicShape<H, V, LengthPercentage, NonNegativeLengthPercentage> {
-    Inset(
-        #[css(field_bound)]
-        #[shmem(field_bound)]
-        InsetRect<LengthPercentage, NonNegativeLengthPercentage>,
-    ),
+pub enum GenericBasicShape<
+    H,
+    V,
+    Angle,
+    LengthPercentage,
+    NonNegativeLengthPercentage,
+    BasicShapeRect,
+> {
+    /// The `inset()`, `rect()` and `xywh()` functions, all of which compute
+    /// to `inset()`.
+    Inset(BasicShapeRect),
     Circle(
         #[css(field_bound)]
         #[shmem(field_bound)]
@@ -187,6 +194,8 @@ pub enum GenericBasicShape<H, V, LengthPercentage, NonNegativeLengthPercentage>
         Ellipse<H, V, NonNegativeLengthPercentage>,
     ),
     Polygon(GenericPolygon<LengthPercentage>),
+    #[css(function)]
+    Shape(GenericShapeFunction<Angle, LengthPercentage>),
 }
 
 pub use self::GenericBasicShape as BasicShape;
@@ -198,8 +207,10 @@ pub use self::GenericBasicShape as BasicShape;
     Clone,
     ComputeSquaredDistance,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -222,8 +233,10 @@ pub struct InsetRect<LengthPercentage, NonNegativeLengthPercentage> {
     ComputeSquaredDistance,
     Copy,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -245,8 +258,10 @@ pub struct Circle<H, V, NonNegativeLengthPercentage> {
     ComputeSquaredDistance,
     Copy,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -269,9 +284,11 @@ pub struct Ellipse<H, V, NonNegativeLengthPercentage> {
     ComputeSquaredDistance,
     Copy,
     Debug,
+    Deserialize,
     MallocSizeOf,
     Parse,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -296,8 +313,10 @@ pub use self::GenericShapeRadius as ShapeRadius;
 #[derive(
     Clone,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -324,8 +343,10 @@ pub use self::GenericPolygon as Polygon;
     Clone,
     ComputeSquaredDistance,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -341,15 +362,16 @@ pub struct PolygonCoord<LengthPercentage>(pub LengthPercentage, pub LengthPercen
 // https://www.w3.org/TR/SVG/painting.html#FillRuleProperty
 // says that it can also be `inherit`
 #[allow(missing_docs)]
-#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
 #[derive(
     Clone,
     Copy,
     Debug,
+    Deserialize,
     Eq,
     MallocSizeOf,
     Parse,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -371,8 +393,10 @@ pub enum FillRule {
     Clone,
     ComputeSquaredDistance,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedValue,
     ToComputedValue,
@@ -391,6 +415,229 @@ pub struct Path {
     pub path: SVGPathData,
 }
 
+/// The shape function defined in css-shape-2.
+///
+/// https://drafts.csswg.org/css-shapes-2/#shape-function
+#[derive(
+    Clone,
+    Debug,
+    Deserialize,
+    MallocSizeOf,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedValue,
+    ToComputedValue,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(C)]
+pub struct GenericShapeFunction<Angle, LengthPercentage> {
+    /// The filling rule for this shape.
+    pub fill: FillRule,
+    /// The starting point of the shape.
+    pub start: CoordinatePair<LengthPercentage>,
+    /// The commands drawing the shape.
+    pub commands: crate::OwnedSlice<GenericShapeCommand<Angle, LengthPercentage>>,
+}
+
+pub use self::GenericShapeFunction as ShapeFunction;
+
+/// A command of the `shape()` function.
+///
+/// https://drafts.csswg.org/css-shapes-2/#typedef-shape-command
+#[allow(missing_docs)]
+#[derive(
+    Animate,
+    Clone,
+    ComputeSquaredDistance,
+    Debug,
+    Deserialize,
+    MallocSizeOf,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedValue,
+    ToComputedValue,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(C, u8)]
+pub enum GenericShapeCommand<Angle, LengthPercentage> {
+    /// `move <by-to> <coordinate-pair>`
+    Move {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+    },
+    /// `line <by-to> <coordinate-pair>`
+    Line {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+    },
+    /// `hline <by-to> <length-percentage>`
+    HLine {
+        #[animation(constant)]
+        by_to: ByTo,
+        x: LengthPercentage,
+    },
+    /// `vline <by-to> <length-percentage>`
+    VLine {
+        #[animation(constant)]
+        by_to: ByTo,
+        y: LengthPercentage,
+    },
+    /// `curve <by-to> <coordinate-pair> via <coordinate-pair> <coordinate-pair>`
+    CubicCurve {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+        control1: CoordinatePair<LengthPercentage>,
+        control2: CoordinatePair<LengthPercentage>,
+    },
+    /// `curve <by-to> <coordinate-pair> via <coordinate-pair>`
+    QuadCurve {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+        control1: CoordinatePair<LengthPercentage>,
+    },
+    /// `smooth <by-to> <coordinate-pair> via <coordinate-pair>`
+    SmoothCubic {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+        control2: CoordinatePair<LengthPercentage>,
+    },
+    /// `smooth <by-to> <coordinate-pair>`
+    SmoothQuad {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+    },
+    /// `arc <by-to> <coordinate-pair> of <length-percentage>{1,2}
+    ///  [<arc-sweep> || <arc-size> || rotate <angle>]?`
+    Arc {
+        #[animation(constant)]
+        by_to: ByTo,
+        point: CoordinatePair<LengthPercentage>,
+        radii: CoordinatePair<LengthPercentage>,
+        #[animation(constant)]
+        arc_sweep: ArcSweep,
+        #[animation(constant)]
+        arc_size: ArcSize,
+        rotate: Angle,
+    },
+    /// `close`
+    Close,
+}
+
+pub use self::GenericShapeCommand as ShapeCommand;
+
+/// Whether the points of a shape command are relative to the current point
+/// or to the reference box.
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedValue,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum ByTo {
+    By,
+    To,
+}
+
+/// https://drafts.csswg.org/css-shapes-2/#typedef-shape-arc-sweep
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedValue,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum ArcSweep {
+    Ccw,
+    Cw,
+}
+
+/// https://drafts.csswg.org/css-shapes-2/#typedef-shape-arc-size
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedValue,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum ArcSize {
+    Small,
+    Large,
+}
+
+/// A pair of coordinates, as used by the `shape()` function.
+///
+/// https://drafts.csswg.org/css-shapes-2/#typedef-shape-coordinate-pair
+#[allow(missing_docs)]
+#[derive(
+    Animate,
+    Clone,
+    ComputeSquaredDistance,
+    Copy,
+    Debug,
+    Deserialize,
+    MallocSizeOf,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedValue,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(C)]
+pub struct CoordinatePair<LengthPercentage> {
+    pub x: LengthPercentage,
+    pub y: LengthPercentage,
+}
+
 impl<B, U> ToAnimatedZero for ClipPath<B, U> {
     fn to_animated_zero(&self) -> Result<Self, ()> {
         Err(())
@@ -499,6 +746,156 @@ where
     }
 }
 
+impl<Angle, LengthPercentage> ToCss for ShapeFunction<Angle, LengthPercentage>
+where
+    Angle: ToCss + Zero,
+    LengthPercentage: ToCss + PartialEq,
+{
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        if !is_default(&self.fill) {
+            self.fill.to_css(dest)?;
+            dest.write_char(' ')?;
+        }
+        dest.write_str("from ")?;
+        self.start.to_css(dest)?;
+        for command in self.commands.iter() {
+            dest.write_str(", ")?;
+            command.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Angle, LengthPercentage> ToCss for ShapeCommand<Angle, LengthPercentage>
+where
+    Angle: ToCss + Zero,
+    LengthPercentage: ToCss + PartialEq,
+{
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        use self::ShapeCommand::*;
+        let (name, by_to) = match *self {
+            Move { by_to, .. } => ("move ", by_to),
+            Line { by_to, .. } => ("line ", by_to),
+            HLine { by_to, .. } => ("hline ", by_to),
+            VLine { by_to, .. } => ("vline ", by_to),
+            CubicCurve { by_to, .. } | QuadCurve { by_to, .. } => ("curve ", by_to),
+            SmoothCubic { by_to, .. } | SmoothQuad { by_to, .. } => ("smooth ", by_to),
+            Arc { by_to, .. } => ("arc ", by_to),
+            Close => return dest.write_str("close"),
+        };
+        dest.write_str(name)?;
+        by_to.to_css(dest)?;
+        dest.write_char(' ')?;
+        match *self {
+            Move { ref point, .. } |
+            Line { ref point, .. } |
+            SmoothQuad { ref point, .. } => point.to_css(dest),
+            HLine { ref x, .. } => x.to_css(dest),
+            VLine { ref y, .. } => y.to_css(dest),
+            CubicCurve {
+                ref point,
+                ref control1,
+                ref control2,
+                ..
+            } => {
+                point.to_css(dest)?;
+                dest.write_str(" via ")?;
+                control1.to_css(dest)?;
+                dest.write_char(' ')?;
+                control2.to_css(dest)
+            },
+            QuadCurve {
+                ref point,
+                ref control1,
+                ..
+            } => {
+                point.to_css(dest)?;
+                dest.write_str(" via ")?;
+                control1.to_css(dest)
+            },
+            SmoothCubic {
+                ref point,
+                ref control2,
+                ..
+            } => {
+                point.to_css(dest)?;
+                dest.write_str(" via ")?;
+                control2.to_css(dest)
+            },
+            Arc {
+                ref point,
+                ref radii,
+                arc_sweep,
+                arc_size,
+                ref rotate,
+                ..
+            } => {
+                point.to_css(dest)?;
+                dest.write_str(" of ")?;
+                radii.x.to_css(dest)?;
+                if radii.x != radii.y {
+                    dest.write_char(' ')?;
+                    radii.y.to_css(dest)?;
+                }
+                if arc_sweep != ArcSweep::Ccw {
+                    dest.write_char(' ')?;
+                    arc_sweep.to_css(dest)?;
+                }
+                if arc_size != ArcSize::Small {
+                    dest.write_char(' ')?;
+                    arc_size.to_css(dest)?;
+                }
+                if !rotate.is_zero() {
+                    dest.write_str(" rotate ")?;
+                    rotate.to_css(dest)?;
+                }
+                Ok(())
+            },
+            Close => unreachable!(),
+        }
+    }
+}
+
+impl<Angle, LengthPercentage> Animate for ShapeFunction<Angle, LengthPercentage>
+where
+    Angle: Animate,
+    LengthPercentage: Animate,
+{
+    fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()> {
+        if self.fill != other.fill {
+            return Err(());
+        }
+        let start = self.start.animate(&other.start, procedure)?;
+        let commands =
+            lists::by_computed_value::animate(&self.commands, &other.commands, procedure)?;
+        Ok(ShapeFunction {
+            fill: self.fill,
+            start,
+            commands,
+        })
+    }
+}
+
+impl<Angle, LengthPercentage> ComputeSquaredDistance for ShapeFunction<Angle, LengthPercentage>
+where
+    Angle: ComputeSquaredDistance,
+    LengthPercentage: ComputeSquaredDistance,
+{
+    fn compute_squared_distance(&self, other: &Self) -> Result<SquaredDistance, ()> {
+        if self.fill != other.fill {
+            return Err(());
+        }
+        Ok(self.start.compute_squared_distance(&other.start)? +
+            lists::by_computed_value::squared_distance(&self.commands, &other.commands)?)
+    }
+}
+
 impl Default for FillRule {
     #[inline]
     fn default() -> Self {
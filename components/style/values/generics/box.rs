@@ -5,6 +5,9 @@
 //! Generic types for box properties.
 
 use crate::values::animated::ToAnimatedZero;
+use crate::values::generics::ui::GenericViewTimelineInset;
+use crate::values::specified::box_::{ScrollAxis, Scroller};
+use crate::values::TimelineName;
 use std::fmt::{self, Write};
 use style_traits::{CssWriter, ToCss};
 
@@ -206,3 +209,157 @@ impl<L> Perspective<L> {
         Perspective::None
     }
 }
+
+#[inline]
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == Default::default()
+}
+
+/// A value for the view() function used in animation-timeline.
+///
+/// https://drafts.csswg.org/scroll-animations-1/#view-notation
+#[derive(
+    Clone,
+    Debug,
+    MallocSizeOf,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(C)]
+pub struct GenericViewFunction<LengthPercent> {
+    /// The axis of the scroll container that the view progress timeline tracks.
+    #[css(skip_if = "is_default")]
+    pub axis: ScrollAxis,
+    /// The insets applied to the scrollport when computing the view progress
+    /// visibility range.
+    #[css(field_bound, skip_if = "GenericViewTimelineInset::is_auto")]
+    pub inset: GenericViewTimelineInset<LengthPercent>,
+}
+
+pub use self::GenericViewFunction as ViewFunction;
+
+/// A value for the <single-animation-timeline>, as supported by Servo.
+///
+/// Unlike the Gecko value, this supports the view() notation, which needs the
+/// length type as a parameter.
+///
+/// https://drafts.csswg.org/css-animations-2/#typedef-single-animation-timeline
+#[cfg(feature = "servo")]
+#[derive(
+    Clone,
+    Debug,
+    MallocSizeOf,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(C, u8)]
+pub enum GenericAnimationTimeline<LengthPercent> {
+    /// Use default timeline. The animation’s timeline is a DocumentTimeline.
+    Auto,
+    /// The scroll-timeline name or view-timeline-name.
+    /// https://drafts.csswg.org/scroll-animations-1/#scroll-timelines-named
+    /// https://drafts.csswg.org/scroll-animations-1/#view-timeline-name
+    Timeline(TimelineName),
+    /// The scroll() notation.
+    /// https://drafts.csswg.org/scroll-animations-1/#scroll-notation
+    #[css(function)]
+    Scroll(
+        #[css(skip_if = "is_default")] ScrollAxis,
+        #[css(skip_if = "is_default")] Scroller,
+    ),
+    /// The view() notation.
+    /// https://drafts.csswg.org/scroll-animations-1/#view-notation
+    #[css(function)]
+    View(#[css(field_bound)] GenericViewFunction<LengthPercent>),
+}
+
+#[cfg(feature = "servo")]
+pub use self::GenericAnimationTimeline as AnimationTimeline;
+
+#[cfg(feature = "servo")]
+impl<LengthPercent> AnimationTimeline<LengthPercent> {
+    /// Returns the `auto` value.
+    pub fn auto() -> Self {
+        Self::Auto
+    }
+
+    /// Returns true if it is auto (i.e. the default value).
+    pub fn is_auto(&self) -> bool {
+        matches!(self, Self::Auto)
+    }
+}
+
+/// A named timeline range, used in animation-range.
+///
+/// https://drafts.csswg.org/scroll-animations-1/#named-timeline-range
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum TimelineRangeName {
+    Cover,
+    Contain,
+    Entry,
+    Exit,
+    EntryCrossing,
+    ExitCrossing,
+}
+
+/// A value for animation-range-start or animation-range-end.
+///
+/// https://drafts.csswg.org/scroll-animations-1/#animation-range-start
+/// https://drafts.csswg.org/scroll-animations-1/#animation-range-end
+#[derive(
+    Clone,
+    Debug,
+    MallocSizeOf,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+pub enum GenericAnimationRange<LengthPercent> {
+    /// The start (or end) of the animation's timeline.
+    Normal,
+    /// An offset into the full range of the animation's timeline.
+    LengthPercentage(LengthPercent),
+    /// An offset into a named timeline range. If the offset is omitted, it
+    /// is 0% for animation-range-start and 100% for animation-range-end.
+    Named(
+        TimelineRangeName,
+        #[css(skip_if = "Option::is_none")] Option<LengthPercent>,
+    ),
+}
+
+pub use self::GenericAnimationRange as AnimationRange;
+
+impl<LengthPercent> AnimationRange<LengthPercent> {
+    /// Returns the `normal` value.
+    #[inline]
+    pub fn normal() -> Self {
+        Self::Normal
+    }
+}
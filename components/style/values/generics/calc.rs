@@ -192,6 +192,28 @@ pub enum GenericCalcNode<L> {
         /// Is the function mod or rem?
         op: ModRemOp,
     },
+    /// A `hypot()` function.
+    ///
+    /// This and the variants below are Servo-only until the C++ side of
+    /// GenericCalcNode handles them, so that they're not exported to Gecko.
+    #[cfg(feature = "servo")]
+    Hypot(crate::OwnedSlice<GenericCalcNode<L>>),
+    /// An `abs()` function.
+    #[cfg(feature = "servo")]
+    Abs(Box<GenericCalcNode<L>>),
+    /// The negation of a node that can't be multiplied by a negative number
+    /// in place, like `abs()` in `calc(1px - abs(10% - 20px))`.
+    #[cfg(feature = "servo")]
+    Negate(Box<GenericCalcNode<L>>),
+    /// A value multiplied by a `sign()` function whose argument's sign is
+    /// unknown at parse time, like `calc(10px * sign(10% - 1em))`.
+    #[cfg(feature = "servo")]
+    Sign {
+        /// The value that gets multiplied.
+        value: Box<GenericCalcNode<L>>,
+        /// The argument of the `sign()` function.
+        argument: Box<GenericCalcNode<L>>,
+    },
 }
 
 pub use self::GenericCalcNode as CalcNode;
@@ -357,6 +379,21 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
                     op,
                 }
             },
+            #[cfg(feature = "servo")]
+            Self::Hypot(ref c) => CalcNode::Hypot(map_children(c, map)),
+            #[cfg(feature = "servo")]
+            Self::Abs(ref c) => CalcNode::Abs(Box::new(c.map_leaves_internal(map))),
+            #[cfg(feature = "servo")]
+            Self::Negate(ref c) => CalcNode::Negate(Box::new(c.map_leaves_internal(map))),
+            #[cfg(feature = "servo")]
+            Self::Sign {
+                ref value,
+                ref argument,
+            } => {
+                let value = Box::new(value.map_leaves_internal(map));
+                let argument = Box::new(argument.map_leaves_internal(map));
+                CalcNode::Sign { value, argument }
+            },
         }
     }
 
@@ -535,6 +572,32 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
                     ModRemOp::Rem => dividend - divisor * (dividend / divisor).trunc(),
                 }
             },
+            #[cfg(feature = "servo")]
+            Self::Hypot(ref c) => {
+                let mut result: O = Zero::zero();
+                for child in &**c {
+                    result = result.hypot(child.resolve_internal(leaf_to_output_fn)?);
+                }
+                result
+            },
+            #[cfg(feature = "servo")]
+            Self::Abs(ref c) => c.resolve_internal(leaf_to_output_fn)?.abs(),
+            #[cfg(feature = "servo")]
+            Self::Negate(ref c) => -c.resolve_internal(leaf_to_output_fn)?,
+            #[cfg(feature = "servo")]
+            Self::Sign {
+                ref value,
+                ref argument,
+            } => {
+                let value = value.resolve_internal(leaf_to_output_fn)?;
+                let argument = argument.resolve_internal(leaf_to_output_fn)?;
+                // sign() preserves zeros and NaN, unlike signum().
+                if argument.is_nan() || argument.is_zero() {
+                    value * argument
+                } else {
+                    value * argument.signum()
+                }
+            },
         })
     }
 
@@ -559,6 +622,15 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
         }
     }
 
+    /// Returns the negated node, if this is a `Negate` node.
+    fn as_negation(&self) -> Option<&Self> {
+        match *self {
+            #[cfg(feature = "servo")]
+            Self::Negate(ref c) => Some(c),
+            _ => None,
+        }
+    }
+
     /// Multiplies the node by a scalar.
     pub fn mul_by(&mut self, scalar: f32) {
         match *self {
@@ -613,6 +685,31 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
                 dividend.mul_by(scalar);
                 divisor.mul_by(scalar);
             },
+            // These are never negative, so a negative factor can't go inside.
+            #[cfg(feature = "servo")]
+            Self::Hypot(..) | Self::Abs(..) if scalar < 0. => {
+                let dummy = Self::MinMax(Default::default(), MinMaxOp::Max);
+                let mut node = mem::replace(self, dummy);
+                node.mul_by(-scalar);
+                *self = Self::Negate(Box::new(node));
+            },
+            #[cfg(feature = "servo")]
+            Self::Hypot(ref mut children) => {
+                for node in &mut **children {
+                    node.mul_by(scalar);
+                }
+            },
+            #[cfg(feature = "servo")]
+            Self::Abs(ref mut child) => child.mul_by(scalar),
+            #[cfg(feature = "servo")]
+            Self::Negate(ref mut child) => {
+                let dummy = Self::MinMax(Default::default(), MinMaxOp::Max);
+                let mut node = mem::replace(&mut **child, dummy);
+                node.mul_by(-scalar);
+                *self = node;
+            },
+            #[cfg(feature = "servo")]
+            Self::Sign { ref mut value, .. } => value.mul_by(scalar),
         }
     }
 
@@ -652,6 +749,24 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
                 dividend.visit_depth_first_internal(f);
                 divisor.visit_depth_first_internal(f);
             },
+            #[cfg(feature = "servo")]
+            Self::Abs(ref mut child) | Self::Negate(ref mut child) => {
+                child.visit_depth_first_internal(f);
+            },
+            #[cfg(feature = "servo")]
+            Self::Sign {
+                ref mut value,
+                ref mut argument,
+            } => {
+                value.visit_depth_first_internal(f);
+                argument.visit_depth_first_internal(f);
+            },
+            #[cfg(feature = "servo")]
+            Self::Hypot(ref mut children) => {
+                for child in &mut **children {
+                    child.visit_depth_first_internal(f);
+                }
+            },
             Self::Sum(ref mut children) | Self::MinMax(ref mut children, _) => {
                 for child in &mut **children {
                     child.visit_depth_first_internal(f);
@@ -910,6 +1025,69 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
 
                 replace_self_with!(&mut children[result]);
             },
+            #[cfg(feature = "servo")]
+            Self::Hypot(ref children) => {
+                // Only leaves that can be compared, and thus whose sign is
+                // known, can be combined. This excludes percentages, whose
+                // basis might be negative.
+                if children.iter().any(|c| c.partial_cmp(c).is_none()) {
+                    return;
+                }
+
+                let mut result = match children[0].try_op(&children[0], |a, _| a.abs()) {
+                    Ok(res) => res,
+                    Err(..) => return,
+                };
+                for child in children.iter().skip(1) {
+                    result = match result.try_op(child, f32::hypot) {
+                        Ok(res) => res,
+                        Err(..) => return,
+                    };
+                }
+
+                replace_self_with!(&mut result);
+            },
+            #[cfg(feature = "servo")]
+            Self::Abs(ref child) => {
+                if child.partial_cmp(child).is_none() {
+                    return;
+                }
+
+                let mut result = match child.try_op(child, |a, _| a.abs()) {
+                    Ok(res) => res,
+                    Err(..) => return,
+                };
+
+                replace_self_with!(&mut result);
+            },
+            #[cfg(feature = "servo")]
+            Self::Negate(ref mut child) => {
+                if let Self::Leaf(ref mut l) = **child {
+                    l.negate();
+                    replace_self_with!(&mut **child);
+                }
+            },
+            #[cfg(feature = "servo")]
+            Self::Sign {
+                ref mut value,
+                ref argument,
+            } => {
+                // Percentages can't be compared, since their basis might be
+                // negative, so their sign is unknown too.
+                let argument = match argument.as_leaf() {
+                    Some(l) if l.is_nan() || argument.partial_cmp(argument).is_some() => {
+                        l.unitless_value()
+                    },
+                    _ => return,
+                };
+                // sign() preserves zeros and NaN, unlike signum().
+                value.mul_by(if argument.is_nan() || argument == 0. {
+                    argument
+                } else {
+                    argument.signum()
+                });
+                replace_self_with!(&mut **value);
+            },
             Self::Sum(ref mut children_slot) => {
                 let mut sums_to_merge = SmallVec::<[_; 3]>::new();
                 let mut extra_kids = 0;
@@ -973,6 +1151,21 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
         self.visit_depth_first(|node| node.simplify_and_sort_direct_children())
     }
 
+    fn children_to_css<W>(children: &[Self], dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        let mut first = true;
+        for child in children {
+            if !first {
+                dest.write_str(", ")?;
+            }
+            first = false;
+            child.to_css_impl(dest, false)?;
+        }
+        Ok(())
+    }
+
     fn to_css_impl<W>(&self, dest: &mut CssWriter<W>, is_outermost: bool) -> fmt::Result
     where
         W: Write,
@@ -1007,6 +1200,16 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
 
                 true
             },
+            #[cfg(feature = "servo")]
+            Self::Hypot(_) => {
+                dest.write_str("hypot(")?;
+                true
+            },
+            #[cfg(feature = "servo")]
+            Self::Abs(_) => {
+                dest.write_str("abs(")?;
+                true
+            },
             _ => {
                 if is_outermost {
                     dest.write_str("calc(")?;
@@ -1016,21 +1219,17 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
         };
 
         match *self {
-            Self::MinMax(ref children, _) => {
-                let mut first = true;
-                for child in &**children {
-                    if !first {
-                        dest.write_str(", ")?;
-                    }
-                    first = false;
-                    child.to_css_impl(dest, false)?;
-                }
-            },
+            Self::MinMax(ref children, _) => Self::children_to_css(children, dest)?,
+            #[cfg(feature = "servo")]
+            Self::Hypot(ref children) => Self::children_to_css(children, dest)?,
             Self::Sum(ref children) => {
                 let mut first = true;
                 for child in &**children {
                     if !first {
-                        if child.is_negative_leaf() {
+                        if let Some(c) = child.as_negation() {
+                            dest.write_str(" - ")?;
+                            c.to_css_impl(dest, false)?;
+                        } else if child.is_negative_leaf() {
                             dest.write_str(" - ")?;
                             let mut c = child.clone();
                             c.negate();
@@ -1074,6 +1273,29 @@ impl<L: CalcNodeLeaf> CalcNode<L> {
                 dest.write_str(", ")?;
                 divisor.to_css_impl(dest, false)?;
             },
+            #[cfg(feature = "servo")]
+            Self::Abs(ref child) => child.to_css_impl(dest, false)?,
+            #[cfg(feature = "servo")]
+            Self::Negate(ref child) => {
+                dest.write_str("-1 * ")?;
+                child.to_css_impl(dest, false)?;
+            },
+            #[cfg(feature = "servo")]
+            Self::Sign {
+                ref value,
+                ref argument,
+            } => {
+                if let Self::Sum(..) = **value {
+                    dest.write_char('(')?;
+                    value.to_css_impl(dest, false)?;
+                    dest.write_char(')')?;
+                } else {
+                    value.to_css_impl(dest, false)?;
+                }
+                dest.write_str(" * sign(")?;
+                argument.to_css_impl(dest, false)?;
+                dest.write_char(')')?;
+            },
             Self::Leaf(ref l) => l.to_css(dest)?,
         }
 
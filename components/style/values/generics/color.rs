@@ -5,7 +5,8 @@
 //! Generic types for color properties.
 
 use crate::color::mix::ColorInterpolationMethod;
-use crate::color::AbsoluteColor;
+use crate::color::relative::RelativeChannels;
+use crate::color::{AbsoluteColor, ColorSpace};
 use crate::values::animated::color::AnimatedRGBA;
 use crate::values::animated::ToAnimatedValue;
 use crate::values::specified::percentage::ToPercentage;
@@ -110,10 +111,43 @@ impl<RGBA, Percentage> ColorMix<GenericColor<RGBA, Percentage>, Percentage> {
             self.normalize_weights,
         );
 
+        // The mix can be out of the sRGB gamut if it's not interpolated in
+        // sRGB, so it needs mapping before it's clamped.
+        let mixed = mixed.to_color_space_mapped(ColorSpace::Srgb);
         Some(ToAnimatedValue::from_animated_value(mixed.into()))
     }
 }
 
+/// A color function with an origin color, like `rgb(from red r g b / 50%)`.
+///
+/// These are resolved at computed-value time, so they only exist as specified
+/// values.
+///
+/// https://drafts.csswg.org/css-color-5/#relative-colors
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToShmem)]
+pub struct GenericRelativeColor<Color> {
+    /// The origin color.
+    pub origin: Color,
+    /// The color space and channels of the color function.
+    pub channels: RelativeChannels,
+}
+
+pub use self::GenericRelativeColor as RelativeColor;
+
+impl<Color: ToCss> ToCss for RelativeColor<Color> {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        dest.write_str(self.channels.function_name())?;
+        dest.write_str("(from ")?;
+        self.origin.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.channels.to_css(dest)?;
+        dest.write_char(')')
+    }
+}
+
 pub use self::GenericColor as Color;
 
 impl<RGBA, Percentage> Color<RGBA, Percentage> {
@@ -134,4 +134,10 @@ impl<Integer, Number, LinearStops> TimingFunction<Integer, Number, LinearStops>
     pub fn ease() -> Self {
         TimingFunction::Keyword(TimingKeyword::Ease)
     }
+
+    /// `linear`
+    #[inline]
+    pub fn linear() -> Self {
+        TimingFunction::Keyword(TimingKeyword::Linear)
+    }
 }
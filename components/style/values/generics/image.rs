@@ -6,6 +6,7 @@
 //!
 //! [images]: https://drafts.csswg.org/css-images/#image-values
 
+use crate::color::mix::ColorInterpolationMethod;
 use crate::custom_properties;
 use crate::values::generics::position::PositionComponent;
 use crate::values::generics::Optional;
@@ -176,6 +177,8 @@ pub enum GenericGradient<
     Linear {
         /// Line direction
         direction: LineDirection,
+        /// Method to use for color interpolation.
+        color_interpolation_method: ColorInterpolationMethod,
         /// The color stops and interpolation hints.
         items: crate::OwnedSlice<GenericGradientItem<Color, LengthPercentage>>,
         /// True if this is a repeating gradient.
@@ -189,6 +192,8 @@ pub enum GenericGradient<
         shape: GenericEndingShape<NonNegativeLength, NonNegativeLengthPercentage>,
         /// Center of gradient
         position: Position,
+        /// Method to use for color interpolation.
+        color_interpolation_method: ColorInterpolationMethod,
         /// The color stops and interpolation hints.
         items: crate::OwnedSlice<GenericGradientItem<Color, LengthPercentage>>,
         /// True if this is a repeating gradient.
@@ -202,6 +207,8 @@ pub enum GenericGradient<
         angle: Angle,
         /// Center of gradient
         position: Position,
+        /// Method to use for color interpolation.
+        color_interpolation_method: ColorInterpolationMethod,
         /// The color stops and interpolation hints.
         items: crate::OwnedSlice<GenericGradientItem<Color, AngleOrPercentage>>,
         /// True if this is a repeating gradient.
@@ -483,6 +490,7 @@ where
         match *self {
             Gradient::Linear {
                 ref direction,
+                ref color_interpolation_method,
                 ref items,
                 compat_mode,
                 ..
@@ -494,6 +502,13 @@ where
                 } else {
                     true
                 };
+                if !omit_interpolation_method(color_interpolation_method) {
+                    if !skip_comma {
+                        dest.write_char(' ')?;
+                    }
+                    color_interpolation_method.to_css(dest)?;
+                    skip_comma = false;
+                }
                 for item in &**items {
                     if !skip_comma {
                         dest.write_str(", ")?;
@@ -505,6 +520,7 @@ where
             Gradient::Radial {
                 ref shape,
                 ref position,
+                ref color_interpolation_method,
                 ref items,
                 compat_mode,
                 ..
@@ -539,6 +555,13 @@ where
                     }
                 }
                 let mut skip_comma = omit_shape && omit_position;
+                if !omit_interpolation_method(color_interpolation_method) {
+                    if !skip_comma {
+                        dest.write_char(' ')?;
+                    }
+                    color_interpolation_method.to_css(dest)?;
+                    skip_comma = false;
+                }
                 for item in &**items {
                     if !skip_comma {
                         dest.write_str(", ")?;
@@ -550,6 +573,7 @@ where
             Gradient::Conic {
                 ref angle,
                 ref position,
+                ref color_interpolation_method,
                 ref items,
                 ..
             } => {
@@ -568,6 +592,13 @@ where
                     position.to_css(dest)?;
                 }
                 let mut skip_comma = omit_angle && omit_position;
+                if !omit_interpolation_method(color_interpolation_method) {
+                    if !skip_comma {
+                        dest.write_char(' ')?;
+                    }
+                    color_interpolation_method.to_css(dest)?;
+                    skip_comma = false;
+                }
                 for item in &**items {
                     if !skip_comma {
                         dest.write_str(", ")?;
@@ -581,6 +612,12 @@ where
     }
 }
 
+/// Gradients interpolate in sRGB unless told otherwise, so there's no need
+/// to serialize that.
+fn omit_interpolation_method(method: &ColorInterpolationMethod) -> bool {
+    *method == ColorInterpolationMethod::srgb()
+}
+
 /// The direction of a linear gradient.
 pub trait LineDirection {
     /// Whether this direction points towards, and thus can be omitted.
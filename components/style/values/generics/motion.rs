@@ -4,7 +4,10 @@
 
 //! Generic types for CSS Motion Path.
 
+use crate::values::animated::ToAnimatedZero;
 use crate::values::specified::SVGPathData;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ToCss};
 
 /// The <size> in ray() function.
 ///
@@ -35,7 +38,17 @@ pub enum RaySize {
     Sides,
 }
 
-/// The `ray()` function, `ray( [ <angle> && <size> && contain? ] )`
+impl RaySize {
+    /// Returns true if it is the default value, `closest-side`.
+    #[inline]
+    fn is_default(&self) -> bool {
+        *self == RaySize::ClosestSide
+    }
+}
+
+/// The `ray()` function, `ray( [ <angle> && <size>? && contain? ] )`
+///
+/// The starting point of the ray comes from `offset-position`.
 ///
 /// https://drafts.fxtf.org/motion-1/#valdef-offsetpath-ray
 #[derive(
@@ -50,7 +63,6 @@ pub enum RaySize {
     SpecifiedValueInfo,
     ToAnimatedZero,
     ToComputedValue,
-    ToCss,
     ToResolvedValue,
     ToShmem,
 )]
@@ -70,6 +82,68 @@ pub struct RayFunction<Angle> {
     pub contain: bool,
 }
 
+impl<Angle> ToCss for RayFunction<Angle>
+where
+    Angle: ToCss,
+{
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        self.angle.to_css(dest)?;
+
+        if !self.size.is_default() {
+            dest.write_char(' ')?;
+            self.size.to_css(dest)?;
+        }
+
+        if self.contain {
+            dest.write_str(" contain")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The `<coord-box>` value, which provides the reference box for
+/// `offset-path`.
+///
+/// https://drafts.fxtf.org/motion-1/#coord-box
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum CoordBox {
+    ContentBox,
+    PaddingBox,
+    BorderBox,
+    FillBox,
+    StrokeBox,
+    ViewBox,
+}
+
+impl CoordBox {
+    /// Returns true if it is the default value, `border-box`.
+    #[inline]
+    pub fn is_default(&self) -> bool {
+        *self == CoordBox::BorderBox
+    }
+}
+
 /// The offset-path value.
 ///
 /// https://drafts.fxtf.org/motion-1/#offset-path-property
@@ -83,16 +157,13 @@ pub struct RayFunction<Angle> {
     PartialEq,
     Serialize,
     SpecifiedValueInfo,
-    ToAnimatedZero,
     ToComputedValue,
     ToCss,
     ToResolvedValue,
     ToShmem,
 )]
 #[repr(C, u8)]
-pub enum GenericOffsetPath<Angle> {
-    // We could merge SVGPathData into ShapeSource, so we could reuse them. However,
-    // we don't want to support other value for offset-path, so use SVGPathData only for now.
+pub enum GenericOffsetPath<Angle, BasicShape, U> {
     /// Path value for path(<string>).
     #[css(function)]
     Path(SVGPathData),
@@ -102,15 +173,87 @@ pub enum GenericOffsetPath<Angle> {
     /// None value.
     #[animation(error)]
     None,
-    // Bug 1186329: Implement <basic-shape>, <geometry-box>, and <url>.
+    /// `<basic-shape> || <coord-box>`.
+    Shape {
+        /// The shape.
+        shape: Box<BasicShape>,
+        /// The reference box, `border-box` if omitted.
+        #[animation(constant)]
+        #[css(skip_if = "CoordBox::is_default")]
+        coord_box: CoordBox,
+    },
+    /// Only `<coord-box>`, which uses the shape of the reference box.
+    #[animation(error)]
+    CoordBox(CoordBox),
+    /// A URL reference to an SVG shape element. If the URL does not reference
+    /// a shape element, this behaves as path("m 0 0").
+    #[animation(error)]
+    Url(U),
 }
 
 pub use self::GenericOffsetPath as OffsetPath;
 
-impl<Angle> OffsetPath<Angle> {
+impl<Angle, BasicShape, U> OffsetPath<Angle, BasicShape, U> {
     /// Return None.
     #[inline]
     pub fn none() -> Self {
         OffsetPath::None
     }
 }
+
+impl<Angle, BasicShape, U> ToAnimatedZero for OffsetPath<Angle, BasicShape, U> {
+    #[inline]
+    fn to_animated_zero(&self) -> Result<Self, ()> {
+        Err(())
+    }
+}
+
+/// The offset-position property, which specifies the initial position of
+/// the box when `offset-path` is `ray()`.
+///
+/// https://drafts.fxtf.org/motion-1/#offset-position-property
+#[derive(
+    Animate,
+    Clone,
+    ComputeSquaredDistance,
+    Copy,
+    Debug,
+    Deserialize,
+    MallocSizeOf,
+    PartialEq,
+    Serialize,
+    SpecifiedValueInfo,
+    ToAnimatedZero,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(C, u8)]
+pub enum GenericOffsetPosition<Pos> {
+    /// The element does not have an offset starting position.
+    #[animation(error)]
+    Normal,
+    /// The offset starting position is the top-left corner of the box.
+    #[animation(error)]
+    Auto,
+    /// The offset starting position is the result of using the <position>
+    /// to position a 0x0 object area within the box's containing block.
+    Position(Pos),
+}
+
+pub use self::GenericOffsetPosition as OffsetPosition;
+
+impl<Pos> OffsetPosition<Pos> {
+    /// Returns the initial value, normal.
+    #[inline]
+    pub fn normal() -> Self {
+        OffsetPosition::Normal
+    }
+
+    /// Returns true if it is normal.
+    #[inline]
+    pub fn is_normal(&self) -> bool {
+        matches!(*self, OffsetPosition::Normal)
+    }
+}
@@ -18,8 +18,10 @@ use style_traits::{CssWriter, ParseError, ToCss};
     ComputeSquaredDistance,
     Copy,
     Debug,
+    Deserialize,
     MallocSizeOf,
     PartialEq,
+    Serialize,
     SpecifiedValueInfo,
     ToAnimatedZero,
     ToAnimatedValue,
@@ -153,6 +153,23 @@ pub struct GenericViewTimelineInset<LengthPercent> {
 
 pub use self::GenericViewTimelineInset as ViewTimelineInset;
 
+impl<LengthPercent> ViewTimelineInset<LengthPercent> {
+    /// Returns `auto`, which is the default inset of the view() function.
+    #[inline]
+    pub fn auto() -> Self {
+        Self {
+            start: GenericLengthPercentageOrAuto::auto(),
+            end: GenericLengthPercentageOrAuto::auto(),
+        }
+    }
+
+    /// Returns true if both insets are `auto`.
+    #[inline]
+    pub fn is_auto(&self) -> bool {
+        self.start.is_auto() && self.end.is_auto()
+    }
+}
+
 impl<LengthPercent> ToCss for ViewTimelineInset<LengthPercent>
 where
     LengthPercent: ToCss + PartialEq,
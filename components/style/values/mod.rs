@@ -456,7 +456,7 @@ impl CustomIdent {
         }
     }
 
-    fn is_valid(ident: &str, excluding: &[&str]) -> bool {
+    pub(crate) fn is_valid(ident: &str, excluding: &[&str]) -> bool {
         use crate::properties::CSSWideKeyword;
         // https://drafts.csswg.org/css-values-4/#custom-idents:
         //
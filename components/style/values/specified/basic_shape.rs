@@ -8,18 +8,25 @@
 //! [basic-shape]: https://drafts.csswg.org/css-shapes/#typedef-basic-shape
 
 use crate::parser::{Parse, ParserContext};
+use crate::values::computed::basic_shape::InsetRect as ComputedInsetRect;
+use crate::values::computed::{self, Context, ToComputedValue};
 use crate::values::generics::basic_shape as generic;
-use crate::values::generics::basic_shape::{Path, PolygonCoord};
+use crate::values::generics::basic_shape::{ArcSize, ArcSweep, ByTo, Path, PolygonCoord};
+use crate::values::generics::length::LengthPercentageOrAuto as GenericLengthPercentageOrAuto;
 use crate::values::generics::rect::Rect;
 use crate::values::specified::border::BorderRadius;
 use crate::values::specified::image::Image;
 use crate::values::specified::position::{HorizontalPosition, Position, VerticalPosition};
 use crate::values::specified::url::SpecifiedUrl;
 use crate::values::specified::SVGPathData;
-use crate::values::specified::{LengthPercentage, NonNegativeLengthPercentage};
+use crate::values::specified::{
+    Angle, LengthPercentage, LengthPercentageOrAuto, NonNegativeLengthPercentage,
+};
 use crate::Zero;
-use cssparser::Parser;
-use style_traits::{ParseError, StyleParseErrorKind};
+use cssparser::{Parser, Token};
+use std::fmt::{self, Write};
+use style_traits::values::specified::AllowedNumericType;
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
 
 /// A specified alias for FillRule.
 pub use crate::values::generics::basic_shape::FillRule;
@@ -34,13 +41,48 @@ pub type ShapeOutside = generic::GenericShapeOutside<BasicShape, Image>;
 pub type BasicShape = generic::GenericBasicShape<
     HorizontalPosition,
     VerticalPosition,
+    Angle,
     LengthPercentage,
     NonNegativeLengthPercentage,
+    BasicShapeRect,
 >;
 
 /// The specified value of `inset()`
 pub type InsetRect = generic::InsetRect<LengthPercentage, NonNegativeLengthPercentage>;
 
+/// The specified value of `inset()`, `rect()` or `xywh()`, all of which
+/// compute to `inset()`.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo, ToCss, ToShmem)]
+pub enum BasicShapeRect {
+    Inset(InsetRect),
+    Xywh(Xywh),
+    Rect(ShapeRectFunction),
+}
+
+/// The specified value of `xywh()`.
+///
+/// https://drafts.csswg.org/css-shapes-1/#funcdef-basic-shape-xywh
+#[allow(missing_docs)]
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo, ToShmem)]
+pub struct Xywh {
+    pub x: LengthPercentage,
+    pub y: LengthPercentage,
+    pub width: NonNegativeLengthPercentage,
+    pub height: NonNegativeLengthPercentage,
+    pub round: BorderRadius,
+}
+
+/// The specified value of `rect()`.
+///
+/// https://drafts.csswg.org/css-shapes-1/#funcdef-basic-shape-rect
+#[allow(missing_docs)]
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo, ToShmem)]
+pub struct ShapeRectFunction {
+    pub rect: Rect<LengthPercentageOrAuto>,
+    pub round: BorderRadius,
+}
+
 /// A specified circle.
 pub type Circle =
     generic::Circle<HorizontalPosition, VerticalPosition, NonNegativeLengthPercentage>;
@@ -55,6 +97,23 @@ pub type ShapeRadius = generic::ShapeRadius<NonNegativeLengthPercentage>;
 /// The specified value of `Polygon`
 pub type Polygon = generic::GenericPolygon<LengthPercentage>;
 
+/// The specified value of `shape()`.
+pub type ShapeFunction = generic::GenericShapeFunction<Angle, LengthPercentage>;
+
+/// The specified value of a `shape()` command.
+pub type ShapeCommand = generic::GenericShapeCommand<Angle, LengthPercentage>;
+
+/// The specified value of a coordinate pair of a `shape()` command.
+pub type CoordinatePair = generic::CoordinatePair<LengthPercentage>;
+
+fn basic_shape_rect_enabled() -> bool {
+    static_prefs::pref!("layout.css.basic-shape-rect.enabled")
+}
+
+fn basic_shape_shape_enabled() -> bool {
+    static_prefs::pref!("layout.css.basic-shape-shape.enabled")
+}
+
 /// A helper for both clip-path and shape-outside parsing of shapes.
 fn parse_shape_or_box<'i, 't, R, ReferenceBox>(
     context: &ParserContext,
@@ -136,7 +195,16 @@ impl Parse for ShapeOutside {
             return Ok(ShapeOutside::Image(image));
         }
 
-        parse_shape_or_box(context, input, ShapeOutside::Shape, ShapeOutside::Box)
+        let location = input.current_source_location();
+        let shape_outside =
+            parse_shape_or_box(context, input, ShapeOutside::Shape, ShapeOutside::Box)?;
+        // Like `path()`, `shape()` only applies to clip-path.
+        if let ShapeOutside::Shape(ref shape, _) = shape_outside {
+            if let generic::BasicShape::Shape(..) = **shape {
+                return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+            }
+        }
+        Ok(shape_outside)
     }
 }
 
@@ -149,10 +217,17 @@ impl Parse for BasicShape {
         let function = input.expect_function()?.clone();
         input.parse_nested_block(move |i| {
             (match_ignore_ascii_case! { &function,
-                "inset" => return InsetRect::parse_function_arguments(context, i).map(generic::BasicShape::Inset),
+                "inset" => return InsetRect::parse_function_arguments(context, i)
+                    .map(|r| generic::BasicShape::Inset(BasicShapeRect::Inset(r))),
+                "xywh" if basic_shape_rect_enabled() => return Xywh::parse_function_arguments(context, i)
+                    .map(|r| generic::BasicShape::Inset(BasicShapeRect::Xywh(r))),
+                "rect" if basic_shape_rect_enabled() => return ShapeRectFunction::parse_function_arguments(context, i)
+                    .map(|r| generic::BasicShape::Inset(BasicShapeRect::Rect(r))),
                 "circle" => return Circle::parse_function_arguments(context, i).map(generic::BasicShape::Circle),
                 "ellipse" => return Ellipse::parse_function_arguments(context, i).map(generic::BasicShape::Ellipse),
                 "polygon" => return Polygon::parse_function_arguments(context, i).map(generic::BasicShape::Polygon),
+                "shape" if basic_shape_shape_enabled() => return ShapeFunction::parse_function_arguments(context, i)
+                    .map(generic::BasicShape::Shape),
                 _ => Err(())
             }).map_err(|()| {
                 location.new_custom_error(StyleParseErrorKind::UnexpectedFunction(function.clone()))
@@ -171,6 +246,33 @@ impl Parse for InsetRect {
     }
 }
 
+/// Parse the optional `round <'border-radius'>` of the rectangle functions.
+fn parse_round<'i, 't>(
+    context: &ParserContext,
+    input: &mut Parser<'i, 't>,
+) -> Result<BorderRadius, ParseError<'i>> {
+    if input
+        .try_parse(|i| i.expect_ident_matching("round"))
+        .is_ok()
+    {
+        return BorderRadius::parse(context, input);
+    }
+    Ok(BorderRadius::zero())
+}
+
+/// Serialize the `round <'border-radius'>` of the rectangle functions, if
+/// needed.
+fn serialize_round<W>(round: &BorderRadius, dest: &mut CssWriter<W>) -> fmt::Result
+where
+    W: Write,
+{
+    if round.is_zero() {
+        return Ok(());
+    }
+    dest.write_str(" round ")?;
+    round.to_css(dest)
+}
+
 impl InsetRect {
     /// Parse the inner function arguments of `inset()`
     fn parse_function_arguments<'i, 't>(
@@ -178,18 +280,152 @@ impl InsetRect {
         input: &mut Parser<'i, 't>,
     ) -> Result<Self, ParseError<'i>> {
         let rect = Rect::parse_with(context, input, LengthPercentage::parse)?;
-        let round = if input
-            .try_parse(|i| i.expect_ident_matching("round"))
-            .is_ok()
-        {
-            BorderRadius::parse(context, input)?
-        } else {
-            BorderRadius::zero()
-        };
+        let round = parse_round(context, input)?;
         Ok(generic::InsetRect { rect, round })
     }
 }
 
+impl Xywh {
+    /// Parse the inner function arguments of `xywh()`
+    fn parse_function_arguments<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let x = LengthPercentage::parse(context, input)?;
+        let y = LengthPercentage::parse(context, input)?;
+        let width = NonNegativeLengthPercentage::parse(context, input)?;
+        let height = NonNegativeLengthPercentage::parse(context, input)?;
+        let round = parse_round(context, input)?;
+        Ok(Xywh {
+            x,
+            y,
+            width,
+            height,
+            round,
+        })
+    }
+}
+
+impl ToCss for Xywh {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        dest.write_str("xywh(")?;
+        self.x.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.y.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.width.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.height.to_css(dest)?;
+        serialize_round(&self.round, dest)?;
+        dest.write_char(')')
+    }
+}
+
+impl ShapeRectFunction {
+    /// Parse the inner function arguments of `rect()`
+    fn parse_function_arguments<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let top = LengthPercentageOrAuto::parse(context, input)?;
+        let right = LengthPercentageOrAuto::parse(context, input)?;
+        let bottom = LengthPercentageOrAuto::parse(context, input)?;
+        let left = LengthPercentageOrAuto::parse(context, input)?;
+        let round = parse_round(context, input)?;
+        Ok(ShapeRectFunction {
+            rect: Rect::new(top, right, bottom, left),
+            round,
+        })
+    }
+}
+
+impl ToCss for ShapeRectFunction {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        dest.write_str("rect(")?;
+        self.rect.0.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.rect.1.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.rect.2.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.rect.3.to_css(dest)?;
+        serialize_round(&self.round, dest)?;
+        dest.write_char(')')
+    }
+}
+
+impl ToComputedValue for BasicShapeRect {
+    type ComputedValue = ComputedInsetRect;
+
+    fn to_computed_value(&self, context: &Context) -> Self::ComputedValue {
+        use computed::LengthPercentage as ComputedLengthPercentage;
+
+        match *self {
+            BasicShapeRect::Inset(ref inset) => inset.to_computed_value(context),
+            // https://drafts.csswg.org/css-shapes-1/#basic-shape-computed-values
+            //
+            //     A pair of insets in either dimension that add up to more
+            //     than the used dimension [...] are reduced proportionally,
+            //     so no need to clamp here.
+            BasicShapeRect::Xywh(ref xywh) => {
+                let x = xywh.x.to_computed_value(context);
+                let y = xywh.y.to_computed_value(context);
+                let width = xywh.width.to_computed_value(context);
+                let height = xywh.height.to_computed_value(context);
+                let right = ComputedLengthPercentage::hundred_percent_minus_list(
+                    &[&x, &width.0],
+                    AllowedNumericType::All,
+                );
+                let bottom = ComputedLengthPercentage::hundred_percent_minus_list(
+                    &[&y, &height.0],
+                    AllowedNumericType::All,
+                );
+                generic::InsetRect {
+                    rect: Rect::new(y, right, bottom, x),
+                    round: xywh.round.to_computed_value(context),
+                }
+            },
+            BasicShapeRect::Rect(ref rect) => {
+                // An `auto` edge is at the corresponding edge of the
+                // reference box, so it's a zero inset.
+                let inset = |edge: &LengthPercentageOrAuto, from_far_edge: bool| match *edge {
+                    GenericLengthPercentageOrAuto::Auto => ComputedLengthPercentage::zero_percent(),
+                    GenericLengthPercentageOrAuto::LengthPercentage(ref lp) => {
+                        let lp = lp.to_computed_value(context);
+                        if from_far_edge {
+                            ComputedLengthPercentage::hundred_percent_minus(
+                                lp,
+                                AllowedNumericType::All,
+                            )
+                        } else {
+                            lp
+                        }
+                    },
+                };
+                generic::InsetRect {
+                    rect: Rect::new(
+                        inset(&rect.rect.0, false),
+                        inset(&rect.rect.1, true),
+                        inset(&rect.rect.2, true),
+                        inset(&rect.rect.3, false),
+                    ),
+                    round: rect.round.to_computed_value(context),
+                }
+            },
+        }
+    }
+
+    fn from_computed_value(computed: &Self::ComputedValue) -> Self {
+        BasicShapeRect::Inset(ToComputedValue::from_computed_value(computed))
+    }
+}
+
 impl Parse for Circle {
     fn parse<'i, 't>(
         context: &ParserContext,
@@ -319,3 +555,141 @@ impl Path {
         Ok(Path { fill, path })
     }
 }
+
+impl ShapeFunction {
+    /// Parse the inner arguments of a `shape` function.
+    /// shape() = shape(<fill-rule>? from <coordinate-pair>, <shape-command>#)
+    fn parse_function_arguments<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let fill = input.try_parse(|i| FillRule::parse(i)).unwrap_or_default();
+        input.expect_ident_matching("from")?;
+        let start = CoordinatePair::parse(context, input)?;
+        input.expect_comma()?;
+        let commands = input.parse_comma_separated(|i| ShapeCommand::parse(context, i))?;
+        Ok(generic::ShapeFunction {
+            fill,
+            start,
+            commands: commands.into(),
+        })
+    }
+}
+
+impl Parse for ShapeCommand {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        use crate::values::generics::basic_shape::GenericShapeCommand::*;
+
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?.clone();
+        if ident.eq_ignore_ascii_case("close") {
+            return Ok(Close);
+        }
+        let by_to = ByTo::parse(input)?;
+        Ok(match_ignore_ascii_case! { &ident,
+            "move" => Move {
+                by_to,
+                point: CoordinatePair::parse(context, input)?,
+            },
+            "line" => Line {
+                by_to,
+                point: CoordinatePair::parse(context, input)?,
+            },
+            "hline" => HLine {
+                by_to,
+                x: LengthPercentage::parse(context, input)?,
+            },
+            "vline" => VLine {
+                by_to,
+                y: LengthPercentage::parse(context, input)?,
+            },
+            "curve" => {
+                let point = CoordinatePair::parse(context, input)?;
+                input.expect_ident_matching("via")?;
+                let control1 = CoordinatePair::parse(context, input)?;
+                match input.try_parse(|i| CoordinatePair::parse(context, i)) {
+                    Ok(control2) => CubicCurve {
+                        by_to,
+                        point,
+                        control1,
+                        control2,
+                    },
+                    Err(_) => QuadCurve {
+                        by_to,
+                        point,
+                        control1,
+                    },
+                }
+            },
+            "smooth" => {
+                let point = CoordinatePair::parse(context, input)?;
+                if input.try_parse(|i| i.expect_ident_matching("via")).is_ok() {
+                    SmoothCubic {
+                        by_to,
+                        point,
+                        control2: CoordinatePair::parse(context, input)?,
+                    }
+                } else {
+                    SmoothQuad { by_to, point }
+                }
+            },
+            "arc" => {
+                let point = CoordinatePair::parse(context, input)?;
+                input.expect_ident_matching("of")?;
+                let rx = LengthPercentage::parse(context, input)?;
+                let ry = input
+                    .try_parse(|i| LengthPercentage::parse(context, i))
+                    .unwrap_or_else(|_| rx.clone());
+
+                let mut arc_sweep = None;
+                let mut arc_size = None;
+                let mut rotate = None;
+                loop {
+                    if arc_sweep.is_none() {
+                        arc_sweep = input.try_parse(ArcSweep::parse).ok();
+                        if arc_sweep.is_some() {
+                            continue;
+                        }
+                    }
+                    if arc_size.is_none() {
+                        arc_size = input.try_parse(ArcSize::parse).ok();
+                        if arc_size.is_some() {
+                            continue;
+                        }
+                    }
+                    if rotate.is_none() &&
+                        input.try_parse(|i| i.expect_ident_matching("rotate")).is_ok()
+                    {
+                        rotate = Some(Angle::parse(context, input)?);
+                        continue;
+                    }
+                    break;
+                }
+
+                Arc {
+                    by_to,
+                    point,
+                    radii: generic::CoordinatePair { x: rx, y: ry },
+                    arc_sweep: arc_sweep.unwrap_or(ArcSweep::Ccw),
+                    arc_size: arc_size.unwrap_or(ArcSize::Small),
+                    rotate: rotate.unwrap_or(Angle::zero()),
+                }
+            },
+            _ => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+        })
+    }
+}
+
+impl Parse for CoordinatePair {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let x = LengthPercentage::parse(context, input)?;
+        let y = LengthPercentage::parse(context, input)?;
+        Ok(generic::CoordinatePair { x, y })
+    }
+}
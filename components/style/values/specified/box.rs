@@ -9,10 +9,12 @@ use crate::parser::{Parse, ParserContext};
 use crate::properties::{LonghandId, PropertyDeclarationId};
 use crate::properties::{PropertyId, ShorthandId};
 use crate::values::generics::box_::{
-    GenericLineClamp, GenericPerspective, GenericContainIntrinsicSize, GenericVerticalAlign,
-    VerticalAlignKeyword,
+    self as generics, GenericLineClamp, GenericPerspective, GenericContainIntrinsicSize,
+    GenericVerticalAlign, TimelineRangeName, VerticalAlignKeyword,
 };
 use crate::values::specified::length::{LengthPercentage, NonNegativeLength};
+#[cfg(feature = "servo")]
+use crate::values::specified::ui::ViewTimelineInset;
 use crate::values::specified::{AllowQuirks, Integer, NonNegativeNumber};
 use crate::values::{CustomIdent, KeyframesName, TimelineName};
 use crate::Atom;
@@ -40,6 +42,19 @@ fn flexbox_enabled() -> bool {
         .unwrap_or(false)
 }
 
+#[cfg(feature = "gecko")]
+fn grid_enabled() -> bool {
+    true
+}
+
+#[cfg(feature = "servo-layout-2020")]
+fn grid_enabled() -> bool {
+    servo_config::prefs::pref_map()
+        .get("layout.grid.enabled")
+        .as_bool()
+        .unwrap_or(false)
+}
+
 /// Defines an element’s display type, which consists of
 /// the two basic qualities of how an element generates boxes
 /// <https://drafts.csswg.org/css-display/#propdef-display>
@@ -70,7 +85,7 @@ pub enum DisplayInside {
     Flow,
     FlowRoot,
     Flex,
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     Grid,
     #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     Table,
@@ -141,9 +156,9 @@ impl Display {
     pub const FlowRoot: Self = Self::new(DisplayOutside::Block, DisplayInside::FlowRoot);
     pub const Flex: Self = Self::new(DisplayOutside::Block, DisplayInside::Flex);
     pub const InlineFlex: Self = Self::new(DisplayOutside::Inline, DisplayInside::Flex);
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     pub const Grid: Self = Self::new(DisplayOutside::Block, DisplayInside::Grid);
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     pub const InlineGrid: Self = Self::new(DisplayOutside::Inline, DisplayInside::Grid);
     #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const Table: Self = Self::new(DisplayOutside::Block, DisplayInside::Table);
@@ -303,6 +318,8 @@ impl Display {
     pub fn is_atomic_inline_level(&self) -> bool {
         match *self {
             Display::InlineBlock | Display::InlineFlex => true,
+            #[cfg(feature = "servo-layout-2020")]
+            Display::InlineGrid => true,
             #[cfg(any(feature = "servo-layout-2013"))]
             Display::InlineTable => true,
             _ => false,
@@ -316,7 +333,7 @@ impl Display {
     pub fn is_item_container(&self) -> bool {
         match self.inside() {
             DisplayInside::Flex => true,
-            #[cfg(feature = "gecko")]
+            #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
             DisplayInside::Grid => true,
             #[cfg(feature = "gecko")]
             DisplayInside::MozBox => true,
@@ -416,7 +433,7 @@ impl ToCss for Display {
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             Display::TableCaption => dest.write_str("table-caption"),
             _ => match (outside, inside) {
-                #[cfg(feature = "gecko")]
+                #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
                 (DisplayOutside::Inline, DisplayInside::Grid) => dest.write_str("inline-grid"),
                 (DisplayOutside::Inline, DisplayInside::Flex) => dest.write_str("inline-flex"),
                 #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
@@ -455,8 +472,8 @@ fn parse_display_inside<'i, 't>(
         "flow-root" => DisplayInside::FlowRoot,
         #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
         "table" => DisplayInside::Table,
-        #[cfg(feature = "gecko")]
-        "grid" => DisplayInside::Grid,
+        #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
+        "grid" if grid_enabled() => DisplayInside::Grid,
         #[cfg(feature = "gecko")]
         "ruby" => DisplayInside::Ruby,
     })
@@ -557,8 +574,8 @@ impl Parse for Display {
             "inline-table" => Display::InlineTable,
             "-webkit-flex" if flexbox_enabled() => Display::Flex,
             "inline-flex" | "-webkit-inline-flex" if flexbox_enabled() => Display::InlineFlex,
-            #[cfg(feature = "gecko")]
-            "inline-grid" => Display::InlineGrid,
+            #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
+            "inline-grid" if grid_enabled() => Display::InlineGrid,
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             "table-caption" => Display::TableCaption,
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
@@ -734,6 +751,7 @@ impl Parse for AnimationName {
 /// https://drafts.csswg.org/scroll-animations-1/rewrite#typedef-scroller
 #[derive(
     Clone,
+    Copy,
     Debug,
     Eq,
     Hash,
@@ -770,6 +788,7 @@ impl Default for Scroller {
 /// https://drafts.csswg.org/scroll-animations-1/#view-timeline-axis
 #[derive(
     Clone,
+    Copy,
     Debug,
     Eq,
     Hash,
@@ -800,6 +819,7 @@ impl Default for ScrollAxis {
     }
 }
 
+#[cfg(feature = "gecko")]
 #[inline]
 fn is_default<T: Default + PartialEq>(value: &T) -> bool {
     *value == Default::default()
@@ -808,6 +828,7 @@ fn is_default<T: Default + PartialEq>(value: &T) -> bool {
 /// A value for the <single-animation-timeline>.
 ///
 /// https://drafts.csswg.org/css-animations-2/#typedef-single-animation-timeline
+#[cfg(feature = "gecko")]
 #[derive(
     Clone,
     Debug,
@@ -838,6 +859,7 @@ pub enum AnimationTimeline {
     ),
 }
 
+#[cfg(feature = "gecko")]
 impl AnimationTimeline {
     /// Returns the `auto` value.
     pub fn auto() -> Self {
@@ -850,6 +872,10 @@ impl AnimationTimeline {
     }
 }
 
+/// A specified value for the `animation-timeline` property.
+#[cfg(feature = "servo")]
+pub type AnimationTimeline = generics::GenericAnimationTimeline<LengthPercentage>;
+
 impl Parse for AnimationTimeline {
     fn parse<'i, 't>(
         context: &ParserContext,
@@ -876,6 +902,39 @@ impl Parse for AnimationTimeline {
             });
         }
 
+        // https://drafts.csswg.org/scroll-animations-1/#view-notation
+        #[cfg(feature = "servo")]
+        if input
+            .try_parse(|i| i.expect_function_matching("view"))
+            .is_ok()
+        {
+            return input.parse_nested_block(|i| {
+                let mut axis = None;
+                let mut inset = None;
+                loop {
+                    if axis.is_none() {
+                        axis = i.try_parse(ScrollAxis::parse).ok();
+                        if axis.is_some() {
+                            continue;
+                        }
+                    }
+                    if inset.is_none() {
+                        inset = i
+                            .try_parse(|i| ViewTimelineInset::parse(context, i))
+                            .ok();
+                        if inset.is_some() {
+                            continue;
+                        }
+                    }
+                    break;
+                }
+                Ok(Self::View(generics::ViewFunction {
+                    axis: axis.unwrap_or_default(),
+                    inset: inset.unwrap_or_else(ViewTimelineInset::auto),
+                }))
+            });
+        }
+
         TimelineName::parse(context, input).map(AnimationTimeline::Timeline)
     }
 }
@@ -883,6 +942,30 @@ impl Parse for AnimationTimeline {
 /// A value for the scroll-timeline-name or view-timeline-name.
 pub type ScrollTimelineName = AnimationName;
 
+/// A specified value for the `animation-range-start` or `animation-range-end`
+/// property.
+pub type AnimationRange = generics::GenericAnimationRange<LengthPercentage>;
+
+impl Parse for AnimationRange {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if input.try_parse(|i| i.expect_ident_matching("normal")).is_ok() {
+            return Ok(Self::Normal);
+        }
+
+        if let Ok(name) = input.try_parse(TimelineRangeName::parse) {
+            let offset = input
+                .try_parse(|i| LengthPercentage::parse(context, i))
+                .ok();
+            return Ok(Self::Named(name, offset));
+        }
+
+        LengthPercentage::parse(context, input).map(Self::LengthPercentage)
+    }
+}
+
 /// https://drafts.csswg.org/css-scroll-snap-1/#snap-axis
 #[allow(missing_docs)]
 #[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
@@ -1261,7 +1344,8 @@ fn change_bits_for_longhand(longhand: LonghandId) -> WillChangeBits {
         LonghandId::Translate |
         LonghandId::Rotate |
         LonghandId::Scale |
-        LonghandId::OffsetPath => WillChangeBits::TRANSFORM,
+        LonghandId::OffsetPath |
+        LonghandId::OffsetPosition => WillChangeBits::TRANSFORM,
         LonghandId::BackdropFilter | LonghandId::Filter => {
             WillChangeBits::STACKING_CONTEXT_UNCONDITIONAL | WillChangeBits::FIXPOS_CB_NON_SVG
         },
@@ -6,6 +6,7 @@
 //!
 //! [calc]: https://drafts.csswg.org/css-values/#calc-notation
 
+use crate::color::relative::ChannelKeyword;
 use crate::parser::ParserContext;
 use crate::values::generics::calc as generic;
 use crate::values::generics::calc::{MinMaxOp, ModRemOp, RoundingStrategy, SortKey};
@@ -59,6 +60,20 @@ pub enum MathFunction {
     Atan,
     /// `atan2()`: https://drafts.csswg.org/css-values-4/#funcdef-atan2
     Atan2,
+    /// `pow()`: https://drafts.csswg.org/css-values-4/#funcdef-pow
+    Pow,
+    /// `sqrt()`: https://drafts.csswg.org/css-values-4/#funcdef-sqrt
+    Sqrt,
+    /// `hypot()`: https://drafts.csswg.org/css-values-4/#funcdef-hypot
+    Hypot,
+    /// `log()`: https://drafts.csswg.org/css-values-4/#funcdef-log
+    Log,
+    /// `exp()`: https://drafts.csswg.org/css-values-4/#funcdef-exp
+    Exp,
+    /// `abs()`: https://drafts.csswg.org/css-values-4/#funcdef-abs
+    Abs,
+    /// `sign()`: https://drafts.csswg.org/css-values-4/#funcdef-sign
+    Sign,
 }
 
 /// A leaf node inside a `Calc` expression's AST.
@@ -74,6 +89,9 @@ pub enum Leaf {
     Percentage(CSSFloat),
     /// `<number>`
     Number(CSSFloat),
+    /// A channel keyword of the origin color of a relative color, multiplied
+    /// by a factor. These are only resolved once the origin color is known.
+    ColorChannel(ChannelKeyword, CSSFloat),
 }
 
 impl Leaf {
@@ -83,6 +101,34 @@ impl Leaf {
             _ => None,
         }
     }
+
+    /// Serializes a leaf with an infinite or NaN value, as `infinity`,
+    /// `-infinity` or `NaN` multiplied by one unit of its type.
+    ///
+    /// https://drafts.csswg.org/css-values/#calc-serialize
+    fn serialize_non_finite<W>(&self, value: CSSFloat, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        use generic::CalcNodeLeaf;
+
+        dest.write_str(if value.is_nan() {
+            "NaN"
+        } else if value > 0. {
+            "infinity"
+        } else {
+            "-infinity"
+        })?;
+        match *self {
+            Self::Number(..) => Ok(()),
+            Self::Percentage(..) => dest.write_str(" * 1%"),
+            _ => {
+                dest.write_str(" * ")?;
+                let one = self.try_op(self, |_, _| 1.).map_err(|()| fmt::Error)?;
+                one.to_css(dest)
+            },
+        }
+    }
 }
 
 impl ToCss for Leaf {
@@ -90,12 +136,26 @@ impl ToCss for Leaf {
     where
         W: Write,
     {
+        use generic::CalcNodeLeaf;
+
+        let value = self.unitless_value();
+        if !value.is_finite() && !matches!(*self, Self::ColorChannel(..)) {
+            return self.serialize_non_finite(value, dest);
+        }
+
         match *self {
             Self::Length(ref l) => l.to_css(dest),
             Self::Number(ref n) => n.to_css(dest),
             Self::Percentage(p) => crate::values::serialize_percentage(p, dest),
             Self::Angle(ref a) => a.to_css(dest),
             Self::Time(ref t) => t.to_css(dest),
+            Self::ColorChannel(keyword, factor) => {
+                if factor != 1. {
+                    factor.to_css(dest)?;
+                    dest.write_str(" * ")?;
+                }
+                keyword.to_css(dest)
+            },
         }
     }
 }
@@ -111,6 +171,8 @@ bitflags! {
         const PERCENTAGE = 1 << 1;
         const ANGLE = 1 << 2;
         const TIME = 1 << 3;
+        /// Channel keywords of relative colors, see `Leaf::ColorChannel`.
+        const COLOR_CHANNEL = 1 << 4;
 
         const LENGTH_PERCENTAGE = Self::LENGTH.bits | Self::PERCENTAGE.bits;
         // NOTE: When you add to this, make sure to make Atan2 deal with these.
@@ -172,9 +234,13 @@ impl PartialOrd for Leaf {
             (&Angle(ref one), &Angle(ref other)) => one.degrees().partial_cmp(&other.degrees()),
             (&Time(ref one), &Time(ref other)) => one.seconds().partial_cmp(&other.seconds()),
             (&Number(ref one), &Number(ref other)) => one.partial_cmp(other),
+            // The channels of the origin color are unknown, and may be
+            // negative.
+            (&ColorChannel(..), &ColorChannel(..)) => None,
             _ => {
                 match *self {
-                    Length(..) | Percentage(..) | Angle(..) | Time(..) | Number(..) => {},
+                    Length(..) | Percentage(..) | Angle(..) | Time(..) | Number(..) |
+                    ColorChannel(..) => {},
                 }
                 unsafe {
                     debug_unreachable!("Forgot a branch?");
@@ -188,7 +254,7 @@ impl generic::CalcNodeLeaf for Leaf {
     fn unitless_value(&self) -> f32 {
         match *self {
             Self::Length(ref l) => l.unitless_value(),
-            Self::Percentage(n) | Self::Number(n) => n,
+            Self::Percentage(n) | Self::Number(n) | Self::ColorChannel(_, n) => n,
             Self::Angle(ref a) => a.degrees(),
             Self::Time(ref t) => t.seconds(),
         }
@@ -201,7 +267,7 @@ impl generic::CalcNodeLeaf for Leaf {
                 // lengths into pixels.
                 *l = *l * scalar;
             },
-            Self::Number(ref mut n) => {
+            Self::Number(ref mut n) | Self::ColorChannel(_, ref mut n) => {
                 *n *= scalar;
             },
             Self::Angle(ref mut a) => {
@@ -220,6 +286,7 @@ impl generic::CalcNodeLeaf for Leaf {
         match *self {
             Self::Number(..) => SortKey::Number,
             Self::Percentage(..) => SortKey::Percentage,
+            Self::ColorChannel(..) => SortKey::Other,
             Self::Time(..) => SortKey::Sec,
             Self::Angle(..) => SortKey::Deg,
             Self::Length(ref l) => match *l {
@@ -302,9 +369,19 @@ impl generic::CalcNodeLeaf for Leaf {
             (&mut Length(ref mut one), &Length(ref other)) => {
                 *one = one.try_op(other, std::ops::Add::add)?;
             },
+            (
+                &mut ColorChannel(one_keyword, ref mut one),
+                &ColorChannel(other_keyword, ref other),
+            ) => {
+                if one_keyword != other_keyword {
+                    return Err(());
+                }
+                *one += *other;
+            },
             _ => {
                 match *other {
-                    Number(..) | Percentage(..) | Angle(..) | Time(..) | Length(..) => {},
+                    Number(..) | Percentage(..) | Angle(..) | Time(..) | Length(..) |
+                    ColorChannel(..) => {},
                 }
                 unsafe {
                     debug_unreachable!();
@@ -347,9 +424,12 @@ impl generic::CalcNodeLeaf for Leaf {
             (&Length(ref one), &Length(ref other)) => {
                 return Ok(Leaf::Length(one.try_op(other, op)?));
             },
+            // The channels of the origin color are unknown.
+            (&ColorChannel(..), &ColorChannel(..)) => Err(()),
             _ => {
                 match *other {
-                    Number(..) | Percentage(..) | Angle(..) | Time(..) | Length(..) => {},
+                    Number(..) | Percentage(..) | Angle(..) | Time(..) | Length(..) |
+                    ColorChannel(..) => {},
                 }
                 unsafe {
                     debug_unreachable!();
@@ -410,6 +490,11 @@ impl CalcNode {
                 CalcNode::parse(context, input, function, allowed_units)
             },
             &Token::Ident(ref ident) => {
+                if allowed_units.intersects(CalcUnits::COLOR_CHANNEL) {
+                    if let Ok(keyword) = ChannelKeyword::from_ident(ident) {
+                        return Ok(CalcNode::Leaf(Leaf::ColorChannel(keyword, 1.)));
+                    }
+                }
                 let number = match_ignore_ascii_case! { &**ident,
                     "e" if trig_enabled() => std::f32::consts::E,
                     "pi" if trig_enabled() => std::f32::consts::PI,
@@ -602,10 +687,83 @@ impl CalcNode {
 
                     Ok(Self::Leaf(Leaf::Angle(Angle::from_radians(radians))))
                 },
+                MathFunction::Pow => {
+                    let a = Self::parse_number_argument(context, input)?;
+                    input.expect_comma()?;
+                    let b = Self::parse_number_argument(context, input)?;
+                    Ok(Self::Leaf(Leaf::Number(a.powf(b))))
+                },
+                MathFunction::Sqrt => {
+                    let a = Self::parse_number_argument(context, input)?;
+                    Ok(Self::Leaf(Leaf::Number(a.sqrt())))
+                },
+                MathFunction::Log => {
+                    let a = Self::parse_number_argument(context, input)?;
+                    let number = match input.try_parse(|input| input.expect_comma()) {
+                        Ok(()) => a.log(Self::parse_number_argument(context, input)?),
+                        Err(..) => a.ln(),
+                    };
+                    Ok(Self::Leaf(Leaf::Number(number)))
+                },
+                MathFunction::Exp => {
+                    let a = Self::parse_number_argument(context, input)?;
+                    Ok(Self::Leaf(Leaf::Number(a.exp())))
+                },
+                #[cfg(feature = "servo")]
+                MathFunction::Hypot => {
+                    let arguments = input.parse_comma_separated(|input| {
+                        Self::parse_argument(context, input, allowed_units)
+                    })?;
+                    Ok(Self::Hypot(arguments.into()))
+                },
+                #[cfg(feature = "servo")]
+                MathFunction::Abs => {
+                    let argument = Self::parse_argument(context, input, allowed_units)?;
+                    Ok(Self::Abs(Box::new(argument)))
+                },
+                #[cfg(feature = "servo")]
+                MathFunction::Sign => {
+                    // The result is a number whatever the type of the
+                    // argument. Percentages are only allowed where they
+                    // resolve against something.
+                    let mut argument = Self::parse_argument(
+                        context,
+                        input,
+                        allowed_units | CalcUnits::LENGTH | CalcUnits::ANGLE | CalcUnits::TIME,
+                    )?;
+                    argument.simplify_and_sort();
+
+                    // This resolves to a number if the sign of the argument
+                    // is known at parse time. Otherwise, the sign() is kept
+                    // until computed or used-value time, and needs to be
+                    // multiplied by a dimension, like in
+                    // `calc(10px * sign(10% - 1em))`.
+                    let mut sign = Self::Sign {
+                        value: Box::new(Self::Leaf(Leaf::Number(1.))),
+                        argument: Box::new(argument),
+                    };
+                    sign.simplify_and_sort_direct_children();
+                    Ok(sign)
+                },
+                #[cfg(feature = "gecko")]
+                MathFunction::Hypot | MathFunction::Abs | MathFunction::Sign => {
+                    unreachable!("math_function() doesn't return these in Gecko")
+                },
             }
         })
     }
 
+    /// Parses an argument of a mathematical function that only takes
+    /// `<number>`s, like `pow()` or `log()`.
+    fn parse_number_argument<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<CSSFloat, ParseError<'i>> {
+        Self::parse_argument(context, input, CalcUnits::empty())?
+            .to_number()
+            .map_err(|()| input.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+    }
+
     fn parse_argument<'i, 't>(
         context: &ParserContext,
         input: &mut Parser<'i, 't>,
@@ -678,8 +836,11 @@ impl CalcNode {
                         node.mul_by(number);
                     } else {
                         // One of the two parts of the multiplication has to be
-                        // a number, at least until we implement unit math.
-                        return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                        // a number, at least until we implement unit math. That
+                        // includes a sign() whose argument's sign is unknown.
+                        node = Self::try_mul_sign(node, rhs).map_err(|()| {
+                            input.new_custom_error(StyleParseErrorKind::UnspecifiedError)
+                        })?;
                     }
                 },
                 Ok(&Token::Delim('/')) => {
@@ -707,6 +868,40 @@ impl CalcNode {
         Ok(node)
     }
 
+    /// Returns the factor of a `sign()` function which is still a number, like
+    /// in `2 * sign(1em - 1px)`.
+    #[cfg(feature = "servo")]
+    fn number_sign_factor(&self) -> Option<CSSFloat> {
+        match *self {
+            CalcNode::Sign { ref value, .. } => value.to_number().ok(),
+            _ => None,
+        }
+    }
+
+    /// Multiplies two nodes, one of which is a `sign()` function whose factor
+    /// is still a number, like in `10px * sign(1em - 1px)`.
+    #[cfg(feature = "gecko")]
+    fn try_mul_sign(_: Self, _: Self) -> Result<Self, ()> {
+        Err(())
+    }
+
+    /// Multiplies two nodes, one of which is a `sign()` function whose factor
+    /// is still a number, like in `10px * sign(1em - 1px)`.
+    #[cfg(feature = "servo")]
+    fn try_mul_sign(a: Self, b: Self) -> Result<Self, ()> {
+        let (mut sign, other) = if a.number_sign_factor().is_some() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let number = sign.number_sign_factor().ok_or(())?;
+        if let CalcNode::Sign { ref mut value, .. } = sign {
+            **value = other;
+            value.mul_by(number);
+        }
+        Ok(sign)
+    }
+
     /// Tries to simplify this expression into a `<length>` or `<percentage>`
     /// value.
     fn into_length_or_percentage(
@@ -789,6 +984,14 @@ impl CalcNode {
             static_prefs::pref!("layout.css.round.enabled")
         } else if matches!(function, Mod | Rem) {
             static_prefs::pref!("layout.css.mod-rem.enabled")
+        } else if matches!(function, Pow | Sqrt | Log | Exp) {
+            static_prefs::pref!("layout.css.exp.enabled")
+        } else if matches!(function, Hypot) {
+            // hypot(), abs() and sign() may be kept as calc nodes that Gecko
+            // doesn't support yet, see GenericCalcNode::Hypot.
+            cfg!(feature = "servo") && static_prefs::pref!("layout.css.exp.enabled")
+        } else if matches!(function, Abs | Sign) {
+            cfg!(feature = "servo") && static_prefs::pref!("layout.css.abs-sign.enabled")
         } else {
             true
         };
@@ -897,6 +1100,24 @@ impl CalcNode {
         }
     }
 
+    /// Convenience parsing function for a channel of a relative color, that
+    /// is, a `<number>`, `<percentage>` or `<angle>` which may reference the
+    /// channels of the origin color.
+    ///
+    /// The result is kept unresolved, and type-checked by the caller.
+    pub fn parse_color_channel<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        function: MathFunction,
+    ) -> Result<Self, ParseError<'i>> {
+        Self::parse(
+            context,
+            input,
+            function,
+            CalcUnits::PERCENTAGE | CalcUnits::ANGLE | CalcUnits::COLOR_CHANNEL,
+        )
+    }
+
     /// Convenience parsing function for `<number>` or `<angle>`.
     pub fn parse_angle_or_number<'i, 't>(
         context: &ParserContext,
@@ -6,11 +6,15 @@
 
 use super::AllowQuirks;
 use crate::color::mix::ColorInterpolationMethod;
+use crate::color::relative::RelativeChannels;
 use crate::color::{AbsoluteColor, ColorComponents, ColorSpace};
 use crate::media_queries::Device;
 use crate::parser::{Parse, ParserContext};
+use crate::values::animated::ToAnimatedValue;
 use crate::values::computed::{Color as ComputedColor, Context, ToComputedValue};
-use crate::values::generics::color::{GenericCaretColor, GenericColorMix, GenericColorOrAuto};
+use crate::values::generics::color::{
+    GenericCaretColor, GenericColorMix, GenericColorOrAuto, GenericRelativeColor,
+};
 use crate::values::specified::calc::CalcNode;
 use crate::values::specified::Percentage;
 use crate::values::CustomIdent;
@@ -90,6 +94,36 @@ impl ColorMix {
     }
 }
 
+/// A specified relative color.
+pub type RelativeColor = GenericRelativeColor<Color>;
+
+impl RelativeColor {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        preserve_authored: PreserveAuthored,
+    ) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        let name = input.expect_function()?.clone();
+        let color_space = match RelativeChannels::color_space_for_function(&name) {
+            Ok(color_space) => color_space,
+            Err(()) => return Err(location.new_unexpected_token_error(Token::Function(name))),
+        };
+
+        // Relative colors go along with the rest of the new color syntax.
+        if !static_prefs::pref!("layout.css.more_color_4.enabled") {
+            return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+
+        input.parse_nested_block(|input| {
+            input.expect_ident_matching("from")?;
+            let origin = Color::parse_internal(context, input, preserve_authored)?;
+            let channels = RelativeChannels::parse(context, input, color_space)?;
+            Ok(RelativeColor { origin, channels })
+        })
+    }
+}
+
 impl AbsoluteColor {
     /// Convenience function to create a color in the sRGB color space.
     pub fn from_rgba(rgba: RGBA) -> Self {
@@ -104,9 +138,10 @@ impl AbsoluteColor {
         )
     }
 
-    /// Convert the color to sRGB color space and return it in the RGBA struct.
+    /// Convert the color to sRGB color space, mapping it into the sRGB gamut
+    /// if needed, and return it in the RGBA struct.
     pub fn to_rgba(&self) -> RGBA {
-        let rgba = self.to_color_space(ColorSpace::Srgb);
+        let rgba = self.to_color_space_mapped(ColorSpace::Srgb);
 
         let red = (rgba.components.0 * 255.0).round() as u8;
         let green = (rgba.components.1 * 255.0).round() as u8;
@@ -151,6 +186,8 @@ pub enum Color {
     System(SystemColor),
     /// A color mix.
     ColorMix(Box<ColorMix>),
+    /// A relative color, like `rgb(from red r g b / 50%)`.
+    Relative(Box<RelativeColor>),
     /// Quirksmode-only rule for inheriting color from the body
     #[cfg(feature = "gecko")]
     InheritFromBodyQuirk,
@@ -483,6 +520,7 @@ impl<'a, 'b: 'a, 'i: 'a> ::cssparser::ColorComponentParser<'i> for ColorComponen
 
 /// Whether to preserve authored colors during parsing. That's useful only if we
 /// plan to serialize the color back.
+#[derive(Clone, Copy)]
 enum PreserveAuthored {
     No,
     Yes,
@@ -546,6 +584,12 @@ impl Color {
                     return Ok(Color::ColorMix(Box::new(mix)));
                 }
 
+                if let Ok(relative) =
+                    input.try_parse(|i| RelativeColor::parse(context, i, preserve_authored))
+                {
+                    return Ok(Color::Relative(Box::new(relative)));
+                }
+
                 match e.kind {
                     ParseErrorKind::Basic(BasicParseErrorKind::UnexpectedToken(t)) => {
                         Err(e.location.new_custom_error(StyleParseErrorKind::ValueError(
@@ -618,6 +662,7 @@ impl ToCss for Color {
             Color::CurrentColor => cssparser::ToCss::to_css(&CSSParserColor::CurrentColor, dest),
             Color::Absolute(ref absolute) => absolute.to_css(dest),
             Color::ColorMix(ref mix) => mix.to_css(dest),
+            Color::Relative(ref relative) => relative.to_css(dest),
             #[cfg(feature = "gecko")]
             Color::System(system) => system.to_css(dest),
             #[cfg(feature = "gecko")]
@@ -637,6 +682,9 @@ impl Color {
                 mix.left.honored_in_forced_colors_mode(allow_transparent) &&
                     mix.right.honored_in_forced_colors_mode(allow_transparent)
             },
+            Color::Relative(ref relative) => relative
+                .origin
+                .honored_in_forced_colors_mode(allow_transparent),
         }
     }
 
@@ -774,6 +822,16 @@ impl Color {
                 color.simplify(None);
                 color
             },
+            Color::Relative(ref relative) => {
+                // Computed colors can't hold relative colors, so an origin
+                // color that depends on currentcolor is resolved against the
+                // current color (the parent's one for the color property).
+                let mut origin = relative.origin.to_computed_color(context)?;
+                let current_color = context.map(|c| c.builder.get_inherited_text().clone_color());
+                origin.simplify(current_color.as_ref());
+                let origin = AbsoluteColor::from(origin.as_numeric()?.to_animated_value());
+                ComputedColor::Numeric(relative.channels.resolve(&origin).to_rgba())
+            },
             #[cfg(feature = "gecko")]
             Color::System(system) => system.compute(context?),
             #[cfg(feature = "gecko")]
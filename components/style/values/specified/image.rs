@@ -7,6 +7,7 @@
 //!
 //! [image]: https://drafts.csswg.org/css-images/#image-values
 
+use crate::color::mix::ColorInterpolationMethod;
 use crate::custom_properties::SpecifiedValue;
 use crate::parser::{Parse, ParserContext};
 use crate::stylesheets::CorsMode;
@@ -640,6 +641,7 @@ impl Gradient {
 
                 generic::Gradient::Linear {
                     direction,
+                    color_interpolation_method: ColorInterpolationMethod::srgb(),
                     items,
                     repeating: false,
                     compat_mode: GradientCompatMode::Modern,
@@ -668,6 +670,7 @@ impl Gradient {
                 generic::Gradient::Radial {
                     shape,
                     position,
+                    color_interpolation_method: ColorInterpolationMethod::srgb(),
                     items,
                     repeating: false,
                     compat_mode: GradientCompatMode::Modern,
@@ -779,6 +782,24 @@ impl Gradient {
         Ok(items)
     }
 
+    /// Tries to parse a `<color-interpolation-method>`, which is only
+    /// allowed in the modern gradient syntax.
+    /// <https://drafts.csswg.org/css-images-4/#linear-gradient-syntax>
+    fn try_parse_color_interpolation_method<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+        compat_mode: GradientCompatMode,
+    ) -> Option<ColorInterpolationMethod> {
+        if compat_mode != GradientCompatMode::Modern ||
+            !static_prefs::pref!("layout.css.gradient-color-interpolation-method.enabled")
+        {
+            return None;
+        }
+        input
+            .try_parse(|i| ColorInterpolationMethod::parse(context, i))
+            .ok()
+    }
+
     /// Parses a linear gradient.
     /// GradientCompatMode can change during `-moz-` prefixed gradient parsing if it come across a `to` keyword.
     fn parse_linear<'i, 't>(
@@ -787,23 +808,30 @@ impl Gradient {
         repeating: bool,
         mut compat_mode: GradientCompatMode,
     ) -> Result<Self, ParseError<'i>> {
-        let direction = if let Ok(d) =
-            input.try_parse(|i| LineDirection::parse(context, i, &mut compat_mode))
-        {
+        let mut color_interpolation_method =
+            Self::try_parse_color_interpolation_method(context, input, compat_mode);
+        let direction = input
+            .try_parse(|i| LineDirection::parse(context, i, &mut compat_mode))
+            .ok();
+        if direction.is_some() && color_interpolation_method.is_none() {
+            color_interpolation_method =
+                Self::try_parse_color_interpolation_method(context, input, compat_mode);
+        }
+        if direction.is_some() || color_interpolation_method.is_some() {
             input.expect_comma()?;
-            d
-        } else {
-            match compat_mode {
-                GradientCompatMode::Modern => {
-                    LineDirection::Vertical(VerticalPositionKeyword::Bottom)
-                },
-                _ => LineDirection::Vertical(VerticalPositionKeyword::Top),
-            }
-        };
+        }
+
+        let direction = direction.unwrap_or(match compat_mode {
+            GradientCompatMode::Modern => LineDirection::Vertical(VerticalPositionKeyword::Bottom),
+            _ => LineDirection::Vertical(VerticalPositionKeyword::Top),
+        });
+        let color_interpolation_method =
+            color_interpolation_method.unwrap_or(ColorInterpolationMethod::srgb());
         let items = Gradient::parse_stops(context, input)?;
 
         Ok(Gradient::Linear {
             direction,
+            color_interpolation_method,
             items,
             repeating,
             compat_mode,
@@ -817,6 +845,8 @@ impl Gradient {
         repeating: bool,
         compat_mode: GradientCompatMode,
     ) -> Result<Self, ParseError<'i>> {
+        let mut color_interpolation_method =
+            Self::try_parse_color_interpolation_method(context, input, compat_mode);
         let (shape, position) = match compat_mode {
             GradientCompatMode::Modern => {
                 let shape = input.try_parse(|i| EndingShape::parse(context, i, compat_mode));
@@ -838,7 +868,11 @@ impl Gradient {
             },
         };
 
-        if shape.is_ok() || position.is_some() {
+        if (shape.is_ok() || position.is_some()) && color_interpolation_method.is_none() {
+            color_interpolation_method =
+                Self::try_parse_color_interpolation_method(context, input, compat_mode);
+        }
+        if shape.is_ok() || position.is_some() || color_interpolation_method.is_some() {
             input.expect_comma()?;
         }
 
@@ -847,12 +881,15 @@ impl Gradient {
         });
 
         let position = position.unwrap_or(Position::center());
+        let color_interpolation_method =
+            color_interpolation_method.unwrap_or(ColorInterpolationMethod::srgb());
 
         let items = Gradient::parse_stops(context, input)?;
 
         Ok(Gradient::Radial {
             shape,
             position,
+            color_interpolation_method,
             items,
             repeating,
             compat_mode,
@@ -863,6 +900,8 @@ impl Gradient {
         input: &mut Parser<'i, 't>,
         repeating: bool,
     ) -> Result<Self, ParseError<'i>> {
+        let mut color_interpolation_method =
+            Self::try_parse_color_interpolation_method(context, input, GradientCompatMode::Modern);
         let angle = input.try_parse(|i| {
             i.expect_ident_matching("from")?;
             // Spec allows unitless zero start angles
@@ -873,12 +912,21 @@ impl Gradient {
             i.expect_ident_matching("at")?;
             Position::parse(context, i)
         });
-        if angle.is_ok() || position.is_ok() {
+        if (angle.is_ok() || position.is_ok()) && color_interpolation_method.is_none() {
+            color_interpolation_method = Self::try_parse_color_interpolation_method(
+                context,
+                input,
+                GradientCompatMode::Modern,
+            );
+        }
+        if angle.is_ok() || position.is_ok() || color_interpolation_method.is_some() {
             input.expect_comma()?;
         }
 
         let angle = angle.unwrap_or(Angle::zero());
         let position = position.unwrap_or(Position::center());
+        let color_interpolation_method =
+            color_interpolation_method.unwrap_or(ColorInterpolationMethod::srgb());
         let items = generic::GradientItem::parse_comma_separated(
             context,
             input,
@@ -892,6 +940,7 @@ impl Gradient {
         Ok(Gradient::Conic {
             angle,
             position,
+            color_interpolation_method,
             items,
             repeating,
         })
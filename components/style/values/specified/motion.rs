@@ -7,14 +7,21 @@
 use crate::parser::{Parse, ParserContext};
 use crate::values::computed::motion::OffsetRotate as ComputedOffsetRotate;
 use crate::values::computed::{Context, ToComputedValue};
-use crate::values::generics::motion::{GenericOffsetPath, RayFunction, RaySize};
+use crate::values::generics::motion as generics;
+use crate::values::generics::motion::{CoordBox, RayFunction, RaySize};
+use crate::values::specified::basic_shape::BasicShape;
+use crate::values::specified::position::Position;
+use crate::values::specified::url::SpecifiedUrl;
 use crate::values::specified::{Angle, SVGPathData};
 use crate::Zero;
 use cssparser::Parser;
 use style_traits::{ParseError, StyleParseErrorKind};
 
 /// The specified value of `offset-path`.
-pub type OffsetPath = GenericOffsetPath<Angle>;
+pub type OffsetPath = generics::GenericOffsetPath<Angle, BasicShape, SpecifiedUrl>;
+
+/// The specified value of `offset-position`.
+pub type OffsetPosition = generics::GenericOffsetPosition<Position>;
 
 #[cfg(feature = "gecko")]
 fn is_ray_enabled() -> bool {
@@ -25,6 +32,10 @@ fn is_ray_enabled() -> bool {
     false
 }
 
+fn is_offset_position_enabled() -> bool {
+    static_prefs::pref!("layout.css.motion-path-offset-position.enabled")
+}
+
 impl Parse for RayFunction<Angle> {
     fn parse<'i, 't>(
         context: &ParserContext,
@@ -60,13 +71,14 @@ impl Parse for RayFunction<Angle> {
             break;
         }
 
-        if angle.is_none() || size.is_none() {
-            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
-        }
+        let angle = match angle {
+            Some(angle) => angle,
+            None => return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError)),
+        };
 
         Ok(RayFunction {
-            angle: angle.unwrap(),
-            size: size.unwrap(),
+            angle,
+            size: size.unwrap_or(RaySize::ClosestSide),
             contain,
         })
     }
@@ -82,22 +94,81 @@ impl Parse for OffsetPath {
             return Ok(OffsetPath::none());
         }
 
-        // Parse possible functions.
-        let location = input.current_source_location();
-        let function = input.expect_function()?.clone();
-        input.parse_nested_block(move |i| {
-            match_ignore_ascii_case! { &function,
-                // Bug 1186329: Implement the parser for <basic-shape>, <geometry-box>,
-                // and <url>.
-                "path" => SVGPathData::parse(context, i).map(GenericOffsetPath::Path),
-                "ray" => RayFunction::parse(context, i).map(GenericOffsetPath::Ray),
-                _ => {
-                    Err(location.new_custom_error(
-                        StyleParseErrorKind::UnexpectedFunction(function.clone())
-                    ))
-                },
+        if let Ok(url) = input.try_parse(|i| SpecifiedUrl::parse(context, i)) {
+            return Ok(generics::OffsetPath::Url(url));
+        }
+
+        // Parse path() and ray().
+        if let Ok(path) = input.try_parse(|i| {
+            let location = i.current_source_location();
+            let function = i.expect_function()?.clone();
+            i.parse_nested_block(move |i| {
+                match_ignore_ascii_case! { &function,
+                    "path" => SVGPathData::parse(context, i).map(generics::OffsetPath::Path),
+                    "ray" => RayFunction::parse(context, i).map(generics::OffsetPath::Ray),
+                    _ => {
+                        Err(location.new_custom_error(
+                            StyleParseErrorKind::UnexpectedFunction(function.clone())
+                        ))
+                    },
+                }
+            })
+        }) {
+            return Ok(path);
+        }
+
+        // Parse `<basic-shape> || <coord-box>`.
+        let mut shape = None;
+        let mut coord_box = None;
+        loop {
+            if shape.is_none() {
+                shape = input.try_parse(|i| BasicShape::parse(context, i)).ok();
+                if shape.is_some() {
+                    continue;
+                }
             }
-        })
+
+            if coord_box.is_none() {
+                coord_box = input.try_parse(CoordBox::parse).ok();
+                if coord_box.is_some() {
+                    continue;
+                }
+            }
+            break;
+        }
+
+        match (shape, coord_box) {
+            (Some(shape), coord_box) => Ok(generics::OffsetPath::Shape {
+                shape: Box::new(shape),
+                coord_box: coord_box.unwrap_or(CoordBox::BorderBox),
+            }),
+            (None, Some(coord_box)) => Ok(generics::OffsetPath::CoordBox(coord_box)),
+            (None, None) => Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError)),
+        }
+    }
+}
+
+impl Parse for OffsetPosition {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if !is_offset_position_enabled() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+
+        if input
+            .try_parse(|i| i.expect_ident_matching("normal"))
+            .is_ok()
+        {
+            return Ok(generics::OffsetPosition::Normal);
+        }
+
+        if input.try_parse(|i| i.expect_ident_matching("auto")).is_ok() {
+            return Ok(generics::OffsetPosition::Auto);
+        }
+
+        Position::parse(context, input).map(generics::OffsetPosition::Position)
     }
 }
 
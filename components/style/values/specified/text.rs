@@ -941,6 +941,160 @@ pub enum OverflowWrap {
     Anywhere,
 }
 
+/// Values for the `white-space-collapse` property.
+///
+/// https://drafts.csswg.org/css-text-4/#white-space-collapsing
+#[repr(u8)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[allow(missing_docs)]
+pub enum WhiteSpaceCollapse {
+    Collapse,
+    Discard,
+    Preserve,
+    PreserveBreaks,
+    PreserveSpaces,
+    BreakSpaces,
+}
+
+impl WhiteSpaceCollapse {
+    /// Whether segment breaks are preserved as forced line breaks.
+    #[inline]
+    pub fn preserve_newlines(self) -> bool {
+        matches!(
+            self,
+            Self::Preserve | Self::PreserveBreaks | Self::BreakSpaces
+        )
+    }
+
+    /// Whether spaces and tabs are preserved rather than collapsed.
+    #[inline]
+    pub fn preserve_spaces(self) -> bool {
+        matches!(
+            self,
+            Self::Preserve | Self::PreserveSpaces | Self::BreakSpaces
+        )
+    }
+}
+
+/// Values for the `text-wrap-mode` property.
+///
+/// https://drafts.csswg.org/css-text-4/#text-wrap-mode
+#[repr(u8)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[allow(missing_docs)]
+pub enum TextWrapMode {
+    Wrap,
+    Nowrap,
+}
+
+impl TextWrapMode {
+    /// Whether lines may be broken at soft wrap opportunities.
+    #[inline]
+    pub fn allow_wrap(self) -> bool {
+        self == TextWrapMode::Wrap
+    }
+}
+
+/// Values for the `text-wrap-style` property.
+///
+/// https://drafts.csswg.org/css-text-4/#text-wrap-style
+#[repr(u8)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[allow(missing_docs)]
+pub enum TextWrapStyle {
+    Auto,
+    Balance,
+    Stable,
+    Pretty,
+}
+
+bitflags! {
+    #[derive(MallocSizeOf, Parse, SpecifiedValueInfo, ToCss, ToComputedValue, ToResolvedValue, ToShmem)]
+    #[css(bitflags(single = "none", mixed = "discard-before,discard-after,discard-inner"))]
+    #[repr(C)]
+    /// Values for the `white-space-trim` property.
+    ///
+    /// https://drafts.csswg.org/css-text-4/#white-space-trim
+    pub struct WhiteSpaceTrim: u8 {
+        /// No white space is trimmed.
+        const NONE = 0;
+        /// White space at the beginning of the box is removed.
+        const DISCARD_BEFORE = 1 << 0;
+        /// White space at the end of the box is removed.
+        const DISCARD_AFTER = 1 << 1;
+        /// White space at the beginning and end of each line is removed.
+        const DISCARD_INNER = 1 << 2;
+    }
+}
+
+/// Values for the `text-spacing-trim` property.
+///
+/// https://drafts.csswg.org/css-text-4/#text-spacing-trim-property
+#[repr(u8)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[allow(missing_docs)]
+pub enum TextSpacingTrim {
+    Normal,
+    SpaceAll,
+    SpaceFirst,
+    TrimStart,
+    TrimBoth,
+    TrimAll,
+    Auto,
+}
+
 /// Implements text-decoration-skip-ink which takes the keywords auto | none | all
 ///
 /// https://drafts.csswg.org/css-text-decor-4/#text-decoration-skip-ink-property
@@ -194,6 +194,7 @@ impl<'a> ErrorHelpers<'a> for ContextualParseError<'a> {
             ContextualParseError::UnsupportedFontFaceDescriptor(s, err) |
             ContextualParseError::UnsupportedFontFeatureValuesDescriptor(s, err) |
             ContextualParseError::UnsupportedFontPaletteValuesDescriptor(s, err) |
+            ContextualParseError::UnsupportedPropertyDescriptor(s, err) |
             ContextualParseError::InvalidKeyframeRule(s, err) |
             ContextualParseError::InvalidFontFeatureValuesRule(s, err) |
             ContextualParseError::UnsupportedKeyframePropertyDeclaration(s, err) |
@@ -403,6 +404,7 @@ impl<'a> ErrorHelpers<'a> for ContextualParseError<'a> {
             ContextualParseError::InvalidCounterStyleExtendsWithAdditiveSymbols |
             ContextualParseError::UnsupportedFontFeatureValuesDescriptor(..) |
             ContextualParseError::UnsupportedFontPaletteValuesDescriptor(..) |
+            ContextualParseError::UnsupportedPropertyDescriptor(..) |
             ContextualParseError::InvalidFontFeatureValuesRule(..) => {
                 (cstr!("PEUnknownAtRule"), Action::Skip)
             },
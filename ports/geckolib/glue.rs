@@ -10,10 +10,12 @@ use cssparser::{Parser, ParserInput, SourceLocation, UnicodeRange};
 use dom::{DocumentState, ElementState};
 use malloc_size_of::MallocSizeOfOps;
 use nsstring::{nsCString, nsString};
-use selectors::{NthIndexCache, SelectorList};
+use selectors::matching::SelectorCaches;
+use selectors::parser::ParseRelative;
+use selectors::SelectorList;
 use servo_arc::{Arc, ArcBorrow, RawOffsetArc};
 use smallvec::SmallVec;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Write;
 use std::iter;
 use std::os::raw::c_void;
@@ -100,6 +102,9 @@ use style::gecko_bindings::sugar::refptr::RefPtr;
 use style::global_style_data::{
     GlobalStyleData, StyleThreadPool, GLOBAL_STYLE_DATA, STYLE_THREAD_POOL,
 };
+use style::invalidation::element::relative_selector::{
+    RelativeSelectorDomMutationInvalidator, RelativeSelectorInvalidator,
+};
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::invalidation::stylesheets::RuleChangeKind;
 use style::media_queries::MediaList;
@@ -745,7 +750,7 @@ pub extern "C" fn Servo_AnimationValue_Serialize(
             buffer,
             None,
             None, /* No extra custom properties */
-            &data.stylist.device(),
+            &data.stylist,
         );
     debug_assert!(rv.is_ok());
 }
@@ -923,6 +928,17 @@ pub unsafe extern "C" fn Servo_AnimationValue_GetOffsetAnchor(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn Servo_AnimationValue_GetOffsetPosition(
+    value: &RawServoAnimationValue,
+) -> *const computed::motion::OffsetPosition {
+    let value = AnimationValue::as_arc(&value);
+    match **value {
+        AnimationValue::OffsetPosition(ref value) => value,
+        _ => unreachable!("Expected offset-position"),
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn Servo_AnimationValue_Rotate(
     r: &computed::Rotate,
@@ -979,6 +995,13 @@ pub unsafe extern "C" fn Servo_AnimationValue_OffsetAnchor(
     Arc::new(AnimationValue::OffsetAnchor(p.clone())).into_strong()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn Servo_AnimationValue_OffsetPosition(
+    p: &computed::motion::OffsetPosition,
+) -> Strong<RawServoAnimationValue> {
+    Arc::new(AnimationValue::OffsetPosition(p.clone())).into_strong()
+}
+
 #[no_mangle]
 pub extern "C" fn Servo_AnimationValue_DeepEqual(
     this: &RawServoAnimationValue,
@@ -1100,6 +1123,12 @@ impl_basic_serde_funcs!(
     computed::position::PositionOrAuto
 );
 
+impl_basic_serde_funcs!(
+    Servo_StyleOffsetPosition_Serialize,
+    Servo_StyleOffsetPosition_Deserialize,
+    computed::motion::OffsetPosition
+);
+
 impl_basic_serde_funcs!(
     Servo_StyleComputedTimingFunction_Serialize,
     Servo_StyleComputedTimingFunction_Deserialize,
@@ -1799,6 +1828,14 @@ pub extern "C" fn Servo_StyleSet_RemoveUniqueEntriesFromAuthorStylesCache(
         .remove_unique_author_data_cache_entries();
 }
 
+/// A `have_seen_ptr` callback for measurements that Gecko doesn't give a set
+/// of seen pointers to, so that the values shared by declarations within the
+/// measured object are only measured once.
+fn local_have_seen_ptr() -> Box<dyn FnMut(*const c_void) -> bool> {
+    let mut seen = HashSet::new();
+    Box::new(move |ptr| !seen.insert(ptr))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn Servo_DeclarationBlock_SizeOfIncludingThis(
     malloc_size_of: GeckoMallocSizeOf,
@@ -1814,7 +1851,7 @@ pub unsafe extern "C" fn Servo_DeclarationBlock_SizeOfIncludingThis(
     let mut ops = MallocSizeOfOps::new(
         malloc_size_of.unwrap(),
         Some(malloc_enclosing_size_of.unwrap()),
-        None,
+        Some(local_have_seen_ptr()),
     );
 
     Locked::<PropertyDeclarationBlock>::as_arc(&declarations).with_arc(|declarations| {
@@ -2054,7 +2091,7 @@ pub extern "C" fn Servo_StyleSheet_SizeOfIncludingThis(
     let mut ops = MallocSizeOfOps::new(
         malloc_size_of.unwrap(),
         Some(malloc_enclosing_size_of.unwrap()),
-        None,
+        Some(local_have_seen_ptr()),
     );
     // TODO(emilio): We're not measuring the size of the Arc<StyleSheetContents>
     // allocation itself here.
@@ -2525,7 +2562,8 @@ pub extern "C" fn Servo_StyleRule_SelectorMatchesElement(
     relevant_link_visited: bool,
 ) -> bool {
     use selectors::matching::{
-        matches_selector, MatchingContext, MatchingMode, NeedsSelectorFlags, VisitedHandlingMode,
+        matches_selector, MatchingContext, MatchingForInvalidation, MatchingMode,
+        NeedsSelectorFlags, VisitedHandlingMode,
     };
     read_locked_arc(rule, |rule: &StyleRule| {
         let index = index as usize;
@@ -2558,7 +2596,7 @@ pub extern "C" fn Servo_StyleRule_SelectorMatchesElement(
 
         let element = GeckoElement(element);
         let quirks_mode = element.as_node().owner_doc().quirks_mode();
-        let mut nth_index_cache = Default::default();
+        let mut selector_caches = Default::default();
         let visited_mode = if relevant_link_visited {
             VisitedHandlingMode::RelevantLinkVisited
         } else {
@@ -2567,15 +2605,58 @@ pub extern "C" fn Servo_StyleRule_SelectorMatchesElement(
         let mut ctx = MatchingContext::new_for_visited(
             matching_mode,
             None,
-            &mut nth_index_cache,
+            &mut selector_caches,
             visited_mode,
             quirks_mode,
             NeedsSelectorFlags::No,
+            MatchingForInvalidation::No,
         );
         matches_selector(selector, 0, None, &element, &mut ctx)
     })
 }
 
+/// Returns how the selector of the style rule `target` should be parsed,
+/// which depends on its closest ancestor style or @scope rule, or None if
+/// `target` isn't in `rules`.
+fn parse_relative_for_style_rule(
+    rules: &[CssRule],
+    target: &Locked<StyleRule>,
+    parse_relative: ParseRelative,
+    guard: &SharedRwLockReadGuard,
+) -> Option<ParseRelative> {
+    for rule in rules {
+        let (children, children_parse_relative) = match *rule {
+            CssRule::Style(ref style_rule) => {
+                if ptr::eq(&**style_rule, target) {
+                    return Some(parse_relative);
+                }
+                match style_rule.read_with(guard).rules {
+                    Some(ref rules) => (rules, ParseRelative::ForNesting),
+                    None => continue,
+                }
+            },
+            CssRule::Scope(ref rule) => (&rule.read_with(guard).rules, ParseRelative::ForScope),
+            CssRule::Media(ref rule) => (&rule.read_with(guard).rules, parse_relative),
+            CssRule::Supports(ref rule) => (&rule.read_with(guard).rules, parse_relative),
+            CssRule::Container(ref rule) => (&rule.read_with(guard).rules, parse_relative),
+            CssRule::Document(ref rule) => (&rule.read_with(guard).rules, parse_relative),
+            CssRule::LayerBlock(ref rule) => (&rule.read_with(guard).rules, parse_relative),
+            CssRule::StartingStyle(ref rule) => (&rule.read_with(guard).rules, parse_relative),
+            _ => continue,
+        };
+        let result = parse_relative_for_style_rule(
+            &children.read_with(guard).0,
+            target,
+            children_parse_relative,
+            guard,
+        );
+        if result.is_some() {
+            return result;
+        }
+    }
+    None
+}
+
 #[no_mangle]
 pub extern "C" fn Servo_StyleRule_SetSelectorText(
     sheet: &RawServoStyleSheetContents,
@@ -2583,6 +2664,18 @@ pub extern "C" fn Servo_StyleRule_SetSelectorText(
     text: &nsACString,
 ) -> bool {
     let value_str = unsafe { text.as_str_unchecked() };
+    let parse_relative = {
+        let global_style_data = &*GLOBAL_STYLE_DATA;
+        let guard = global_style_data.shared_lock.read();
+        let contents = StylesheetContents::as_arc(&sheet);
+        parse_relative_for_style_rule(
+            contents.rules(&guard),
+            Locked::<StyleRule>::as_arc(&rule),
+            ParseRelative::No,
+            &guard,
+        )
+        .unwrap_or(ParseRelative::No)
+    };
 
     write_locked_arc(rule, |rule: &mut StyleRule| {
         use style::selector_parser::SelectorParser;
@@ -2598,7 +2691,11 @@ pub extern "C" fn Servo_StyleRule_SetSelectorText(
         };
 
         let mut parser_input = ParserInput::new(&value_str);
-        match SelectorList::parse(&parser, &mut Parser::new(&mut parser_input)) {
+        match SelectorList::parse(
+            &parser,
+            &mut Parser::new(&mut parser_input),
+            parse_relative,
+        ) {
             Ok(selectors) => {
                 rule.selectors = selectors;
                 true
@@ -4730,7 +4827,7 @@ pub extern "C" fn Servo_DeclarationBlock_SerializeOneValue(
         buffer,
         computed_values,
         custom_properties,
-        &data.stylist.device(),
+        &data.stylist,
     );
     debug_assert!(rv.is_ok());
 }
@@ -6145,7 +6242,8 @@ pub extern "C" fn Servo_GetComputedKeyframeValues(
                 let guard =
                     Locked::<PropertyDeclarationBlock>::as_arc(&raw_custom_properties_block)
                         .read_with(&guard);
-                custom_properties = guard.cascade_custom_properties_with_context(&context);
+                custom_properties =
+                    guard.cascade_custom_properties_with_context(&context, &data.stylist);
                 // There should be one PropertyDeclarationBlock for custom properties.
                 break;
             }
@@ -6927,9 +7025,6 @@ pub extern "C" fn Servo_ProcessInvalidations(
     debug_assert!(element.has_snapshot());
     debug_assert!(!element.handled_snapshot());
 
-    let mut data = element.mutate_data();
-    debug_assert!(data.is_some());
-
     let global_style_data = &*GLOBAL_STYLE_DATA;
     let guard = global_style_data.shared_lock.read();
     let per_doc_data = PerDocumentStyleData::from_ffi(set).borrow();
@@ -6940,6 +7035,19 @@ pub extern "C" fn Servo_ProcessInvalidations(
         TraversalFlags::empty(),
         unsafe { &*snapshots },
     );
+
+    // Relative selectors invalidate elements up the tree, which we can only
+    // do before the traversal. This needs to happen before borrowing the
+    // element data, since it can invalidate the element itself (if it's a
+    // later sibling of an anchor).
+    let invalidator = RelativeSelectorInvalidator {
+        element,
+        shared_context: &shared_style_context,
+    };
+    invalidator.invalidate_relative_selectors_for_this(note_relative_selector_anchor_dirty);
+
+    let mut data = element.mutate_data();
+    debug_assert!(data.is_some());
     let mut data = data.as_mut().map(|d| &mut **d);
 
     if let Some(ref mut data) = data {
@@ -6949,7 +7057,7 @@ pub extern "C" fn Servo_ProcessInvalidations(
             element,
             &shared_style_context,
             None,
-            &mut NthIndexCache::default(),
+            &mut SelectorCaches::default(),
         );
 
         if result.has_invalidated_siblings() {
@@ -6968,6 +7076,50 @@ pub extern "C" fn Servo_ProcessInvalidations(
     }
 }
 
+/// Notes that an anchor of a relative selector (or an element its style
+/// affects) needs to be restyled with the given hint.
+fn note_relative_selector_anchor_dirty(anchor: GeckoElement, hint: RestyleHint) {
+    let mut data = match anchor.mutate_data() {
+        Some(data) => data,
+        None => return,
+    };
+    // The restyle hint takes care of the descendants, if needed.
+    unsafe { bindings::Gecko_NoteDirtyElement(anchor.0) };
+    data.hint.insert(hint);
+}
+
+fn invalidate_relative_selectors_for_dom_mutation(
+    raw_data: &RawServoStyleSet,
+    element: &RawGeckoElement,
+) {
+    let data = PerDocumentStyleData::from_ffi(raw_data).borrow();
+    let invalidator = RelativeSelectorDomMutationInvalidator {
+        element: GeckoElement(element),
+        stylist: &data.stylist,
+    };
+    invalidator.invalidate_relative_selectors_for_dom_mutation(note_relative_selector_anchor_dirty);
+}
+
+/// Invalidates the anchors of the relative selectors that may match the given
+/// element or its descendants, once it's been inserted into the tree.
+#[no_mangle]
+pub extern "C" fn Servo_StyleSet_MaybeInvalidateRelativeSelectorForInsertion(
+    raw_data: &RawServoStyleSet,
+    element: &RawGeckoElement,
+) {
+    invalidate_relative_selectors_for_dom_mutation(raw_data, element);
+}
+
+/// Invalidates the anchors of the relative selectors that may match the given
+/// element or its descendants, before it's removed from the tree.
+#[no_mangle]
+pub extern "C" fn Servo_StyleSet_MaybeInvalidateRelativeSelectorForRemoval(
+    raw_data: &RawServoStyleSet,
+    element: &RawGeckoElement,
+) {
+    invalidate_relative_selectors_for_dom_mutation(raw_data, element);
+}
+
 #[no_mangle]
 pub extern "C" fn Servo_HasPendingRestyleAncestor(
     element: &RawGeckoElement,
@@ -7364,12 +7516,12 @@ pub unsafe extern "C" fn Servo_InvalidateStyleForDocStateChanges(
             &*styles.data
         }));
 
-    let mut nth_index_cache = Default::default();
+    let mut selector_caches = Default::default();
     let root = GeckoElement(root);
     let mut processor = DocumentStateInvalidationProcessor::new(
         iter,
         DocumentState::from_bits_truncate(states_changed),
-        &mut nth_index_cache,
+        &mut selector_caches,
         root.as_node().owner_doc().quirks_mode(),
     );
 
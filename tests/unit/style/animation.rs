@@ -0,0 +1,179 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use servo_arc::Arc;
+use style::animation::{AnimationSetKey, DocumentAnimationSet, EffectTiming, ScriptAnimationEventType};
+use style::animation::{ScriptAnimationId, ScriptAnimationPlayState, ScriptKeyframe};
+use style::dom::OpaqueNode;
+use style::properties::{ComputedValues, LonghandId};
+use style::properties::animated_properties::AnimationValue;
+use style::properties::longhands::animation_composition::computed_value::single_value::T as AnimationComposition;
+use style::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
+use style::properties::longhands::animation_fill_mode::computed_value::single_value::T as AnimationFillMode;
+use style::values::computed::TimingFunction;
+use style::values::generics::easing::StepPosition;
+
+fn keyframe(offset: Option<f64>, opacity: Option<f32>) -> ScriptKeyframe {
+    ScriptKeyframe {
+        offset,
+        easing: TimingFunction::linear(),
+        composite: None,
+        values: opacity.into_iter().map(AnimationValue::Opacity).collect(),
+    }
+}
+
+fn add_animation(
+    set: &DocumentAnimationSet,
+    keyframes: &[ScriptKeyframe],
+    timing: EffectTiming,
+) -> Result<ScriptAnimationId, ()> {
+    let base_style = Arc::new(ComputedValues::initial_values().clone());
+    set.add_script_animation(key(), keyframes, timing, AnimationComposition::Replace, &base_style)
+}
+
+fn key() -> AnimationSetKey {
+    AnimationSetKey::new_for_non_pseudo(OpaqueNode(1))
+}
+
+fn opacity(set: &DocumentAnimationSet, now: f64) -> Option<f32> {
+    let sets = set.sets.read();
+    let map = sets.get(&key())?.get_value_map_for_active_animations(now)?;
+    match map.get(&LonghandId::Opacity)? {
+        AnimationValue::Opacity(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn assert_opacity(set: &DocumentAnimationSet, now: f64, expected: Option<f32>) {
+    let value = opacity(set, now);
+    assert!(match (value, expected) {
+        (Some(value), Some(expected)) => (value - expected).abs() < 1e-5,
+        (value, expected) => value == expected,
+    }, "opacity at {} is {:?}, expected {:?}", now, value, expected);
+}
+
+#[test]
+fn test_script_animation_keyframe_offsets() {
+    let set = DocumentAnimationSet::default();
+    let timing = EffectTiming { duration: 4., ..Default::default() };
+
+    assert!(add_animation(&set, &[keyframe(Some(0.5), None), keyframe(Some(0.2), None)], timing.clone()).is_err());
+    assert!(add_animation(&set, &[keyframe(Some(1.5), None)], timing.clone()).is_err());
+
+    // The offsets of the keyframes are 0, 0.25, 0.5 and 1, and the second
+    // keyframe doesn't take part in the interpolation of opacity.
+    let keyframes = [
+        keyframe(None, Some(0.)),
+        keyframe(None, None),
+        keyframe(Some(0.5), Some(1.)),
+        keyframe(None, Some(0.)),
+    ];
+    let id = add_animation(&set, &keyframes, timing).unwrap();
+    set.with_script_animation(id, |animation| animation.play(0.)).unwrap().unwrap();
+    assert_opacity(&set, 0.5, Some(0.25));
+    assert_opacity(&set, 1., Some(0.5));
+    assert_opacity(&set, 2., Some(1.));
+    assert_opacity(&set, 3., Some(0.5));
+
+    // A single keyframe animates from the underlying value.
+    let set = DocumentAnimationSet::default();
+    let timing = EffectTiming { duration: 2., ..Default::default() };
+    let id = add_animation(&set, &[keyframe(None, Some(0.))], timing).unwrap();
+    set.with_script_animation(id, |animation| animation.play(0.)).unwrap().unwrap();
+    assert_opacity(&set, 0.5, Some(0.75));
+}
+
+#[test]
+fn test_script_animation_property_specific_keyframes() {
+    let set = DocumentAnimationSet::default();
+    let timing = EffectTiming { duration: 4., ..Default::default() };
+
+    // The easing and composite operation of a keyframe which doesn't specify
+    // opacity don't apply to it.
+    let mut stepped = keyframe(Some(0.5), None);
+    stepped.easing = TimingFunction::Steps(1, StepPosition::End);
+    stepped.composite = Some(AnimationComposition::Add);
+    let keyframes = [keyframe(Some(0.), Some(0.)), stepped, keyframe(Some(1.), Some(1.))];
+    let id = add_animation(&set, &keyframes, timing.clone()).unwrap();
+    set.with_script_animation(id, |animation| animation.play(0.)).unwrap().unwrap();
+    assert_opacity(&set, 3., Some(0.75));
+
+    // The composite operation of each keyframe applies to its own value.
+    let set = DocumentAnimationSet::default();
+    let mut added = keyframe(Some(0.), Some(-0.5));
+    added.composite = Some(AnimationComposition::Add);
+    let id = add_animation(&set, &[added, keyframe(Some(1.), Some(0.))], timing).unwrap();
+    set.with_script_animation(id, |animation| animation.play(0.)).unwrap().unwrap();
+    assert_opacity(&set, 0., Some(0.5));
+    assert_opacity(&set, 2., Some(0.25));
+}
+
+#[test]
+fn test_script_animation_effect_timing() {
+    let set = DocumentAnimationSet::default();
+    let timing = EffectTiming {
+        delay: 1.,
+        duration: 2.,
+        iterations: 2.,
+        direction: AnimationDirection::Alternate,
+        fill: AnimationFillMode::Both,
+        ..Default::default()
+    };
+    let id = add_animation(&set, &[keyframe(None, Some(0.)), keyframe(None, Some(1.))], timing).unwrap();
+    assert_opacity(&set, 0., None);
+
+    set.with_script_animation(id, |animation| animation.play(0.)).unwrap().unwrap();
+    assert_opacity(&set, 0.5, Some(0.));
+    assert_opacity(&set, 2., Some(0.5));
+    assert_opacity(&set, 3.5, Some(0.75));
+    assert_opacity(&set, 6., Some(0.));
+}
+
+#[test]
+fn test_script_animation_playback_control() {
+    let set = DocumentAnimationSet::default();
+    let timing = EffectTiming { duration: 10., ..Default::default() };
+    let id = add_animation(&set, &[keyframe(None, Some(0.)), keyframe(None, Some(1.))], timing).unwrap();
+    let play_state = |now| set.with_script_animation(id, |animation| animation.play_state(now)).unwrap();
+    assert_eq!(play_state(0.), ScriptAnimationPlayState::Idle);
+
+    set.with_script_animation(id, |animation| animation.play(0.)).unwrap().unwrap();
+    assert_eq!(play_state(5.), ScriptAnimationPlayState::Running);
+    assert_opacity(&set, 5., Some(0.5));
+
+    set.with_script_animation(id, |animation| animation.pause(5.)).unwrap().unwrap();
+    assert_eq!(play_state(8.), ScriptAnimationPlayState::Paused);
+    assert_opacity(&set, 8., Some(0.5));
+
+    set.with_script_animation(id, |animation| {
+        animation.play(8.).unwrap();
+        animation.set_playback_rate(8., 2.);
+    });
+    assert_opacity(&set, 9., Some(0.7));
+
+    set.with_script_animation(id, |animation| animation.reverse(9.)).unwrap().unwrap();
+    assert_opacity(&set, 10., Some(0.5));
+    assert!(set.update_script_animations(12.).is_empty());
+
+    let events = set.update_script_animations(14.);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].id, id);
+    assert_eq!(events[0].event_type, ScriptAnimationEventType::Finish);
+    assert_eq!(events[0].current_time, Some(0.));
+    assert!(set.update_script_animations(15.).is_empty());
+
+    set.with_script_animation(id, |animation| animation.set_current_time(15., 3.));
+    assert_eq!(play_state(15.), ScriptAnimationPlayState::Running);
+    assert_opacity(&set, 15., Some(0.3));
+
+    set.with_script_animation(id, |animation| animation.cancel());
+    assert_eq!(play_state(15.), ScriptAnimationPlayState::Idle);
+    assert_opacity(&set, 15., None);
+    let events = set.update_script_animations(15.);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, ScriptAnimationEventType::Cancel);
+
+    set.remove_script_animation(id);
+    assert!(set.with_script_animation(id, |_| ()).is_none());
+}
@@ -0,0 +1,182 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ParserInput, SourceLocation};
+use euclid::{Scale, Size2D};
+use mock_dom::MockElement;
+use selectors::matching::{MatchingContext, MatchingForInvalidation, MatchingMode};
+use selectors::matching::{NeedsSelectorFlags, SelectorCaches};
+use servo_arc::Arc;
+use servo_config::prefs::{PREFS, PrefValue};
+use servo_url::ServoUrl;
+use style::applicable_declarations::ApplicableDeclarationList;
+use style::cascade_trace::{CascadeCandidate, CascadeTrace, RuleProvenance};
+use style::context::{CascadeInputs, QuirksMode};
+use style::custom_properties::{Name, VariableValue};
+use style::media_queries::{Device, MediaList, MediaType};
+use style::properties::{longhands, ComputedValues, LonghandId, PropertyDeclaration, PropertyId};
+use style::rule_cache::RuleCacheConditions;
+use style::rule_tree::CascadeLevel;
+use style::shared_lock::{SharedRwLock, StylesheetGuards};
+use style::stylesheets::{AllowImportRules, DocumentStyleSheet, Origin, Stylesheet};
+use style::stylist::{RuleInclusion, Stylist};
+use style::thread_state::{self, ThreadState};
+use style_traits::ToCss;
+
+fn candidate(level: CascadeLevel, overridden: bool) -> CascadeCandidate {
+    CascadeCandidate {
+        declaration: PropertyDeclaration::Display(longhands::display::SpecifiedValue::Block),
+        level,
+        origin: level.origin(),
+        important: level.is_important(),
+        provenance: RuleProvenance::default(),
+        overridden,
+        references: vec![],
+    }
+}
+
+#[test]
+fn test_applied_candidate() {
+    let trace = CascadeTrace {
+        computed_value: "block".into(),
+        candidates: vec![
+            candidate(CascadeLevel::UANormal, true),
+            candidate(CascadeLevel::same_tree_author_normal(), false),
+            candidate(CascadeLevel::same_tree_author_important(), true),
+        ],
+    };
+    let applied = trace.applied().unwrap();
+    assert_eq!(applied.origin, Origin::Author);
+    assert!(!applied.important);
+
+    let trace = CascadeTrace {
+        computed_value: "inline".into(),
+        candidates: vec![candidate(CascadeLevel::UANormal, true)],
+    };
+    assert!(trace.applied().is_none());
+}
+
+#[test]
+fn test_variable_value_references() {
+    let mut input = ParserInput::new("var(--a) calc(var(--b, var(--c)) * 2)");
+    let value = VariableValue::parse(&mut Parser::new(&mut input)).unwrap();
+    let references = value.references();
+    assert_eq!(references.len(), 3);
+    for name in &["a", "b", "c"] {
+        assert!(references.contains(&Name::from(*name)), "--{} is not referenced", name);
+    }
+}
+
+fn sheet(lock: &SharedRwLock, css: &str, url: &ServoUrl, origin: Origin) -> DocumentStyleSheet {
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    DocumentStyleSheet(Arc::new(Stylesheet::from_str(css, url.clone(), origin, media, lock.clone(), None, None,
+                                                     QuirksMode::NoQuirks, 0, AllowImportRules::Yes)))
+}
+
+fn style_for(stylist: &Stylist, element: MockElement, guards: &StylesheetGuards) -> Arc<ComputedValues> {
+    let mut declarations = ApplicableDeclarationList::new();
+    let mut caches = SelectorCaches::default();
+    let mut context = MatchingContext::new(MatchingMode::Normal, None, &mut caches, QuirksMode::NoQuirks,
+                                           NeedsSelectorFlags::No, MatchingForInvalidation::No);
+    stylist.push_applicable_declarations(element, None, None, None, Default::default(),
+                                         RuleInclusion::All, &mut declarations, &mut context);
+    let inputs = CascadeInputs {
+        rules: Some(stylist.rule_tree().compute_rule_node(&mut declarations, guards)),
+        ..Default::default()
+    };
+    stylist.cascade_style_and_visited(Some(element), None, inputs, guards, None, None, None, None, None,
+                                      &mut RuleCacheConditions::default())
+}
+
+#[test]
+fn test_trace_cascade() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    PREFS.set("layout.css.cascade-layers.enabled", PrefValue::Boolean(true));
+    let lock = SharedRwLock::new();
+    let ua_url = ServoUrl::parse("https://example.com/ua.css").unwrap();
+    let author_url = ServoUrl::parse("https://example.com/author.css").unwrap();
+    let author_css = "@layer base, top;\n\
+                      @layer top { div { color: blue; display: revert-layer; text-align: revert-layer } }\n\
+                      @layer base { div { color: green !important; display: flex; text-align: center } }\n\
+                      div { color: red; display: revert }\n\
+                      div { --a: var(--b); --b: var(--a) 1px; --c: 2px; width: calc(var(--a, 1px) + var(--c)) }";
+    let mut stylist = Stylist::new(
+        Device::new(MediaType::screen(), Size2D::new(800f32, 600f32), Scale::new(1.0)),
+        QuirksMode::NoQuirks,
+    );
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    stylist.append_stylesheet(sheet(&lock, "div { display: block }", &ua_url, Origin::UserAgent), &guard);
+    stylist.append_stylesheet(sheet(&lock, author_css, &author_url, Origin::Author), &guard);
+    stylist.flush(&guards, None::<MockElement>, None);
+
+    let element = MockElement::new(&lock, "div", None, &[]);
+    let style = style_for(&stylist, element, &guards);
+    let trace = |id: LonghandId| {
+        stylist.trace_cascade(element, None, &style, &PropertyId::Longhand(id), &guards).unwrap()
+    };
+    let layer = |candidate: &CascadeCandidate| candidate.provenance.layer.as_ref().map(|l| l.to_css_string());
+
+    // An !important declaration in an earlier layer wins over the normal
+    // declarations, unlayered ones included.
+    let color = trace(LonghandId::Color);
+    assert_eq!(color.candidates.len(), 3);
+    let (top, unlayered, base) = (&color.candidates[0], &color.candidates[1], &color.candidates[2]);
+    assert!(top.overridden && unlayered.overridden && !base.overridden);
+    assert_eq!(layer(top).as_deref(), Some("top"));
+    assert_eq!(layer(unlayered), None);
+    assert_eq!(layer(base).as_deref(), Some("base"));
+    assert!(base.important && !top.important && !unlayered.important);
+    assert_eq!(base.level, CascadeLevel::same_tree_author_important());
+    assert_eq!(base.origin, Origin::Author);
+    assert_eq!(base.provenance.stylesheet.as_ref(), Some(&author_url));
+    assert_eq!(base.provenance.selectors.as_deref(), Some("div"));
+    assert_eq!(base.provenance.specificity, 1);
+    assert_eq!(base.provenance.source_location.map(|l| l.line), Some(2));
+    assert_eq!(unlayered.provenance.source_location, Some(SourceLocation { line: 3, column: 1 }));
+
+    // `revert` in the unlayered rule rolls back the whole author origin,
+    // `revert-layer` included, to the user agent declaration.
+    let display = trace(LonghandId::Display);
+    assert_eq!(display.computed_value, "block");
+    assert_eq!(display.candidates.len(), 4);
+    assert!(display.candidates[1..].iter().all(|c| c.origin == Origin::Author && c.overridden));
+    let applied = display.applied().unwrap();
+    assert_eq!(applied.origin, Origin::UserAgent);
+    assert_eq!(applied.provenance.stylesheet.as_ref(), Some(&ua_url));
+    assert_eq!(layer(applied), None);
+
+    // `revert-layer` only rolls back to the previous layer.
+    let text_align = trace(LonghandId::TextAlign);
+    assert_eq!(text_align.computed_value, "center");
+    assert_eq!(text_align.candidates.len(), 2);
+    let applied = text_align.applied().unwrap();
+    assert_eq!(layer(applied).as_deref(), Some("base"));
+    assert!(text_align.candidates[1].overridden);
+    assert_eq!(layer(&text_align.candidates[1]).as_deref(), Some("top"));
+
+    // --a and --b reference each other, so they're guaranteed-invalid and
+    // the fallback applies. The expansion stops where the cycle closes.
+    let width = trace(LonghandId::Width);
+    assert_eq!(width.computed_value, "3px");
+    let references = &width.applied().unwrap().references;
+    assert_eq!(references.len(), 2);
+    let a = references.iter().find(|r| r.name == Name::from("a")).unwrap();
+    assert_eq!(a.specified_value.as_deref(), Some("var(--b)"));
+    assert_eq!(a.computed_value, None);
+    assert_eq!(a.references.len(), 1);
+    let b = &a.references[0];
+    assert_eq!(b.name, Name::from("b"));
+    assert_eq!(b.specified_value.as_deref(), Some("var(--a) 1px"));
+    assert_eq!(b.computed_value, None);
+    assert_eq!(b.references.len(), 1);
+    let cycle = &b.references[0];
+    assert_eq!(cycle.name, Name::from("a"));
+    assert_eq!(cycle.specified_value, None);
+    assert!(cycle.references.is_empty());
+    let c = references.iter().find(|r| r.name == Name::from("c")).unwrap();
+    assert_eq!(c.specified_value.as_deref(), Some("2px"));
+    assert_eq!(c.computed_value.as_deref(), Some("2px"));
+    assert!(c.references.is_empty());
+}
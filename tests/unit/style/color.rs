@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use style::color::{AbsoluteColor, ColorComponents, ColorSpace};
+use style::values::animated::color::AnimatedRGBA;
+
+fn color(color_space: ColorSpace, c1: f32, c2: f32, c3: f32) -> AbsoluteColor {
+    AbsoluteColor::new(color_space, ColorComponents(c1, c2, c3), 1.0)
+}
+
+#[test]
+fn test_gamut_mapping_in_gamut() {
+    let srgb = color(ColorSpace::Srgb, 0.5, 0.2, 0.1);
+    assert!(srgb.in_gamut());
+    assert_eq!(srgb.to_color_space_mapped(ColorSpace::Srgb), srgb);
+
+    let lab = color(ColorSpace::Lab, 50., 20., -30.);
+    let p3 = lab.to_color_space(ColorSpace::DisplayP3);
+    assert_eq!(lab.to_color_space_mapped(ColorSpace::DisplayP3), p3);
+
+    // Colors are never out of the gamut of unbounded color spaces.
+    let oklch = color(ColorSpace::Oklch, 0.7, 0.4, 150.);
+    assert!(oklch.in_gamut());
+    assert_eq!(oklch.to_color_space_mapped(ColorSpace::Lab), oklch.to_color_space(ColorSpace::Lab));
+}
+
+#[test]
+fn test_gamut_mapping_reduces_chroma() {
+    let oklch = color(ColorSpace::Oklch, 0.7, 0.4, 150.);
+    assert!(!oklch.to_color_space(ColorSpace::Srgb).in_gamut());
+
+    let mapped = oklch.to_color_space_mapped(ColorSpace::Srgb);
+    assert_eq!(mapped.color_space, ColorSpace::Srgb);
+    assert!(mapped.in_gamut());
+
+    // The lightness and hue are kept, unlike when clipping.
+    let ColorComponents(lightness, chroma, hue) = mapped.to_color_space(ColorSpace::Oklch).components;
+    assert!((lightness - 0.7).abs() < 0.02, "lightness changed to {}", lightness);
+    assert!((hue - 150.).abs() < 5., "hue changed to {}", hue);
+    assert!(chroma < 0.4);
+
+    let rec2020 = color(ColorSpace::Rec2020, 0., 1., 0.);
+    assert!(rec2020.to_color_space_mapped(ColorSpace::DisplayP3).in_gamut());
+    assert!(rec2020.to_color_space_mapped(ColorSpace::Hsl).in_gamut());
+}
+
+#[test]
+fn test_gamut_mapping_lightness_limits() {
+    let white = color(ColorSpace::Oklch, 1.2, 0.1, 0.).to_color_space_mapped(ColorSpace::Srgb);
+    let ColorComponents(r, g, b) = white.components;
+    for c in &[r, g, b] {
+        assert!((c - 1.).abs() < 0.001, "{:?} is not white", white);
+    }
+
+    let black = color(ColorSpace::Oklch, -0.1, 0.1, 0.).to_color_space_mapped(ColorSpace::Srgb);
+    let ColorComponents(r, g, b) = black.components;
+    for c in &[r, g, b] {
+        assert!(c.abs() < 0.001, "{:?} is not black", black);
+    }
+}
+
+#[test]
+fn test_map_into_gamut() {
+    let srgb = color(ColorSpace::Srgb, 1.2, 0.5, -0.1);
+    assert!(!srgb.in_gamut());
+
+    let mapped = srgb.map_into_gamut();
+    assert_eq!(mapped.color_space, ColorSpace::Srgb);
+    assert!(mapped.in_gamut());
+}
+
+#[test]
+fn test_animated_colors_are_not_mapped() {
+    // Intermediate animated values keep their out-of-gamut components, and
+    // are only clamped once computed.
+    let animated = AnimatedRGBA::from(color(ColorSpace::Srgb, 1.2, 0.5, -0.1));
+    assert_eq!(animated, AnimatedRGBA::new(1.2, 0.5, -0.1, 1.0));
+    assert_eq!(AbsoluteColor::from(animated), color(ColorSpace::Srgb, 1.2, 0.5, -0.1));
+}
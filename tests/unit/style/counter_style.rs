@@ -0,0 +1,140 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use servo_arc::Arc;
+use servo_atoms::Atom;
+use servo_url::ServoUrl;
+use style::context::QuirksMode;
+use style::counter_style::{CounterStyleRegistry, Symbol, Symbols};
+use style::media_queries::MediaList;
+use style::shared_lock::SharedRwLock;
+use style::stylesheets::{AllowImportRules, CssRule, Origin, Stylesheet};
+use style::values::CustomIdent;
+use style::values::generics::{CounterStyle, SymbolsType};
+
+fn registry(css: &str) -> CounterStyleRegistry {
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, media, lock.clone(),
+                                          None, None, QuirksMode::NoQuirks, 0,
+                                          AllowImportRules::Yes);
+    let guard = lock.read();
+    let rules = stylesheet.contents.rules.read_with(&guard);
+    CounterStyleRegistry::from_rules(rules.0.iter().filter_map(|rule| match *rule {
+        CssRule::CounterStyle(ref rule) => Some(rule.read_with(&guard)),
+        _ => None,
+    }))
+}
+
+fn name(name: &str) -> CounterStyle {
+    CounterStyle::Name(CustomIdent(Atom::from(name)))
+}
+
+#[test]
+fn test_predefined_counter_styles() {
+    let registry = CounterStyleRegistry::new();
+    let counter = |style: &str, value| registry.counter_representation(&name(style), value);
+
+    assert_eq!(counter("decimal", 42), "42");
+    assert_eq!(counter("decimal", -5), "-5");
+    assert_eq!(counter("decimal-leading-zero", 5), "05");
+    assert_eq!(counter("decimal-leading-zero", -5), "-5");
+    assert_eq!(counter("lower-roman", 1994), "mcmxciv");
+    assert_eq!(counter("upper-roman", 4), "IV");
+    assert_eq!(counter("lower-roman", 4000), "4000");
+    assert_eq!(counter("lower-alpha", 28), "ab");
+    assert_eq!(counter("lower-alpha", 0), "0");
+    assert_eq!(counter("lower-greek", 2), "β");
+    assert_eq!(counter("upper-armenian", 1986), "ՌՋՁԶ");
+    assert_eq!(counter("georgian", 11), "ია");
+    assert_eq!(counter("hebrew", 15), "טו");
+    assert_eq!(counter("arabic-indic", 12), "١٢");
+    assert_eq!(counter("not-defined", 3), "3");
+
+    assert_eq!(registry.marker_representation(&name("lower-roman"), 3), "iii. ");
+    assert_eq!(registry.marker_representation(&name("disc"), 3), "\u{2022} ");
+    assert_eq!(registry.marker_representation(&name("cjk-decimal"), 10), "一〇、");
+}
+
+#[test]
+fn test_cjk_and_ethiopic_counter_styles() {
+    let registry = CounterStyleRegistry::new();
+    let counter = |style: &str, value| registry.counter_representation(&name(style), value);
+
+    assert_eq!(counter("simp-chinese-informal", 0), "零");
+    assert_eq!(counter("simp-chinese-informal", 12), "十二");
+    assert_eq!(counter("simp-chinese-informal", 105), "一百零五");
+    assert_eq!(counter("simp-chinese-informal", -7), "负七");
+    assert_eq!(counter("trad-chinese-formal", 1234), "壹仟貳佰參拾肆");
+    assert_eq!(counter("cjk-ideographic", 2010), "二千零一十");
+    assert_eq!(counter("japanese-informal", 111), "百十一");
+    assert_eq!(counter("japanese-informal", 1001), "千一");
+    assert_eq!(counter("japanese-informal", -3), "マイナス三");
+    assert_eq!(counter("korean-hangul-formal", 21), "이십일");
+    assert_eq!(counter("simp-chinese-informal", 10000), "一〇〇〇〇");
+
+    assert_eq!(counter("ethiopic-numeric", 1), "\u{1369}");
+    assert_eq!(counter("ethiopic-numeric", 12), "\u{1372}\u{136A}");
+    assert_eq!(counter("ethiopic-numeric", 100), "\u{137B}");
+    assert_eq!(counter("ethiopic-numeric", 123), "\u{137B}\u{1373}\u{136B}");
+    assert_eq!(counter("ethiopic-numeric", 10000), "\u{137C}");
+    assert_eq!(counter("ethiopic-numeric", 0), "0");
+}
+
+#[test]
+fn test_counter_style_rules() {
+    let registry = registry(r#"
+        @counter-style stars { system: symbolic; symbols: "*" "+"; suffix: " "; }
+        @counter-style letters { system: fixed 3; symbols: a b c; fallback: lower-roman; }
+        @counter-style tally { system: additive; additive-symbols: 5 V, 1 I; range: 1 20; }
+        @counter-style more-stars { system: extends stars; symbols: x; suffix: "!"; }
+        @counter-style parens { system: extends decimal; negative: "(" ")"; prefix: "["; pad: 3 "0"; }
+        @counter-style loop-a { system: extends loop-b; }
+        @counter-style loop-b { system: extends loop-a; suffix: "!"; }
+        @counter-style fallback-a { system: fixed; symbols: x; fallback: fallback-b; }
+        @counter-style fallback-b { system: fixed; symbols: y; fallback: fallback-a; }
+        @counter-style lower-roman { system: cyclic; symbols: "?"; }
+    "#);
+    let counter = |style: &str, value| registry.counter_representation(&name(style), value);
+
+    assert_eq!(counter("stars", 3), "**");
+    assert_eq!(counter("stars", 4), "++");
+    assert_eq!(counter("stars", -1), "-1");
+    assert_eq!(registry.marker_representation(&name("stars"), 1), "* ");
+    assert_eq!(counter("more-stars", 3), "**");
+    assert_eq!(registry.marker_representation(&name("more-stars"), 1), "*!");
+
+    assert_eq!(counter("letters", 4), "b");
+    assert_eq!(counter("letters", 6), "?");
+
+    assert_eq!(counter("tally", 7), "VII");
+    assert_eq!(counter("tally", 21), "21");
+
+    assert_eq!(counter("parens", 7), "007");
+    assert_eq!(counter("parens", -7), "(7)");
+    assert_eq!(registry.marker_representation(&name("parens"), 7), "[007. ");
+
+    assert_eq!(registry.marker_representation(&name("loop-a"), 2), "2. ");
+    assert_eq!(registry.marker_representation(&name("loop-b"), 2), "2!");
+
+    assert_eq!(counter("fallback-a", 1), "x");
+    assert_eq!(counter("fallback-a", 2), "2");
+}
+
+#[test]
+fn test_symbols_function() {
+    let registry = CounterStyleRegistry::new();
+    let symbols = |symbols: &[&str]| {
+        Symbols(symbols.iter().map(|s| Symbol::String(s.to_string().into())).collect::<Vec<_>>().into())
+    };
+
+    let alphabetic = CounterStyle::Symbols(SymbolsType::Alphabetic, symbols(&["a", "b"]));
+    assert_eq!(registry.counter_representation(&alphabetic, 3), "aa");
+    assert_eq!(registry.marker_representation(&alphabetic, 3), "aa ");
+
+    let fixed = CounterStyle::Symbols(SymbolsType::Fixed, symbols(&["x", "y"]));
+    assert_eq!(registry.counter_representation(&fixed, 2), "y");
+    assert_eq!(registry.counter_representation(&fixed, 3), "3");
+}
@@ -42,3 +42,26 @@ fn cascade_custom_simple(b: &mut Bencher) {
         ], parent.as_ref()))
     })
 }
+
+#[test]
+fn same_tokens() {
+    fn parse(value: &str) -> SpecifiedValue {
+        let mut input = ParserInput::new(value);
+        SpecifiedValue::parse(&mut Parser::new(&mut input)).unwrap()
+    }
+    fn same_tokens(a: &str, b: &str) -> bool {
+        parse(a).same_tokens_as(&parse(b))
+    }
+
+    assert!(same_tokens("dark", " dark "));
+    assert!(same_tokens("1px  solid", "1px solid"));
+    assert!(same_tokens("1px/**/ /**/solid", "1px solid"));
+    assert!(same_tokens("foo(  a,b )", "foo(a,b)"));
+    assert!(same_tokens("[a  b]", "[a b]"));
+    assert!(!same_tokens("1px solid", "1pxsolid"));
+    assert!(!same_tokens("1px solid", "1px"));
+    assert!(!same_tokens("foo(a)", "foo(b)"));
+    assert!(!same_tokens("foo(a)", "bar(a)"));
+    assert!(!same_tokens("(a) b", "(a b)"));
+    assert!(!same_tokens("10px", "10.0px"));
+}
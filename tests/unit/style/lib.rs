@@ -6,9 +6,12 @@
 #![feature(test)]
 
 extern crate app_units;
+extern crate atomic_refcell;
 extern crate cssparser;
+extern crate dom;
 extern crate euclid;
 #[macro_use] extern crate html5ever;
+extern crate malloc_size_of;
 extern crate parking_lot;
 extern crate rayon;
 extern crate selectors;
@@ -22,12 +25,19 @@ extern crate style_traits;
 extern crate test;
 
 mod animated_properties;
+mod animation;
 mod attr;
+mod cascade_trace;
+mod color;
+mod counter_style;
 mod custom_properties;
 mod logical_geometry;
+mod memory_report;
+mod mock_dom;
 mod parsing;
 mod properties;
 mod rule_tree;
+mod sharing;
 mod size_of;
 mod specified_values;
 mod str;
@@ -393,6 +393,74 @@ fn test_matching_invalid() {
     let device = Device::new(MediaType::screen(), Size2D::new(200.0, 100.0), Scale::new(1.0));
 
     media_query_test(&device, "@media fridge { a { color: red; } }", 0);
-    media_query_test(&device, "@media screen and (height: 100px) { a { color: red; } }", 0);
+    media_query_test(&device, "@media screen and (orientation: 100px) { a { color: red; } }", 0);
     media_query_test(&device, "@media not print and (width: 100) { a { color: red; } }", 0);
 }
+
+#[test]
+fn test_matching_viewport_and_screen() {
+    let mut device = Device::new(MediaType::screen(), Size2D::new(200.0, 100.0), Scale::new(2.0));
+
+    media_query_test(&device, "@media (height: 100px) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (min-aspect-ratio: 2/1) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (orientation: landscape) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (resolution: 2dppx) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (-webkit-min-device-pixel-ratio: 2) { a { color: red; } }", 1);
+
+    // Without a screen size, the device size is the viewport size.
+    media_query_test(&device, "@media (device-width: 200px) { a { color: red; } }", 1);
+    device.set_screen_size(Size2D::new(1920.0, 1080.0));
+    media_query_test(&device, "@media (device-width: 1920px) and (device-height: 1080px) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (device-aspect-ratio: 16/9) { a { color: red; } }", 1);
+}
+
+#[test]
+fn test_matching_color() {
+    let mut device = Device::new(MediaType::screen(), Size2D::new(200.0, 100.0), Scale::new(1.0));
+
+    media_query_test(&device, "@media (color) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (min-color: 8) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (monochrome) { a { color: red; } }", 0);
+
+    device.set_monochrome_bits(1);
+    media_query_test(&device, "@media (color) { a { color: red; } }", 0);
+    media_query_test(&device, "@media (monochrome: 1) { a { color: red; } }", 1);
+}
+
+#[test]
+fn test_matching_user_preferences() {
+    let mut device = Device::new(MediaType::screen(), Size2D::new(200.0, 100.0), Scale::new(1.0));
+
+    media_query_test(&device, "@media (prefers-color-scheme: light) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (prefers-reduced-motion) { a { color: red; } }", 0);
+    media_query_test(&device, "@media (prefers-contrast) { a { color: red; } }", 0);
+    media_query_test(&device, "@media (forced-colors: none) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (display-mode: browser) { a { color: red; } }", 1);
+
+    device.set_prefers_color_scheme(PrefersColorScheme::Dark);
+    device.set_prefers_reduced_motion(true);
+    device.set_prefers_contrast(PrefersContrast::More);
+    device.set_forced_colors(true);
+    device.set_display_mode(DisplayMode::Standalone);
+
+    media_query_test(&device, "@media (prefers-color-scheme: dark) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (prefers-reduced-motion: reduce) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (prefers-contrast: more) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (forced-colors: active) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (display-mode: standalone) { a { color: red; } }", 1);
+}
+
+#[test]
+fn test_matching_interaction() {
+    let mut device = Device::new(MediaType::screen(), Size2D::new(200.0, 100.0), Scale::new(1.0));
+
+    media_query_test(&device, "@media (pointer: fine) and (hover) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (any-pointer: coarse) { a { color: red; } }", 0);
+
+    device.set_pointer_capabilities(
+        PointerCapabilities::COARSE,
+        PointerCapabilities::COARSE | PointerCapabilities::FINE | PointerCapabilities::HOVER,
+    );
+    media_query_test(&device, "@media (pointer: coarse) and (hover: none) { a { color: red; } }", 1);
+    media_query_test(&device, "@media (any-pointer: fine) and (any-hover: hover) { a { color: red; } }", 1);
+}
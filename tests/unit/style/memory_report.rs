@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Scale, Size2D};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use servo_arc::Arc;
+use servo_url::ServoUrl;
+use std::collections::HashSet;
+use std::os::raw::c_void;
+use style::context::QuirksMode;
+use style::media_queries::{Device, MediaList, MediaType};
+use style::memory_report::MemoryReport;
+use style::properties::parse_style_attribute;
+use style::rule_cache::RuleCache;
+use style::shared_lock::{SharedRwLock, StylesheetGuards};
+use style::stylesheets::{AllowImportRules, CssRuleType, DocumentStyleSheet, Origin, Stylesheet};
+use style::stylist::Stylist;
+use style::thread_state::{self, ThreadState};
+
+// Every heap allocation is reported as a single byte.
+unsafe extern "C" fn usable_size(_ptr: *const c_void) -> usize {
+    1
+}
+
+fn new_ops() -> MallocSizeOfOps {
+    let mut seen = HashSet::new();
+    MallocSizeOfOps::new(usable_size, None, Some(Box::new(move |ptr| !seen.insert(ptr))))
+}
+
+#[test]
+fn test_memory_report_tree() {
+    let mut report = MemoryReport::with_size("root", 1);
+    report.add("a", 3);
+    report.add("a", 2);
+    report.child_mut("b").add("c", 4);
+    report.add_child(MemoryReport::with_size("b", 8));
+
+    assert_eq!(report.total(), 18);
+    assert_eq!(report.child("a").unwrap().size, 5);
+    assert_eq!(report.child("b").unwrap().total(), 4);
+    assert_eq!(report.find(&["b", "c"]).unwrap().size, 4);
+    assert!(report.find(&["a", "c"]).is_none());
+    assert_eq!(report.to_string(), "18 root\n  8 b\n  5 a\n  4 b\n    4 c\n");
+}
+
+#[test]
+fn test_stylist_memory_report() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let css = "
+        @namespace svg url(http://www.w3.org/2000/svg);
+        @layer base, components;
+        @layer base { div { color: red } }
+        @media screen { p { color: blue } }
+        @supports (display: grid) { p { display: grid } }
+        @keyframes fade { from { opacity: 0 } to { opacity: 1 } }
+        @font-face { font-family: foo; src: url(foo.woff) }
+        span { color: green }
+    ";
+    let url = ServoUrl::parse("https://example.com/test.css").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let sheet = Stylesheet::from_str(css, url, Origin::Author, media, lock.clone(),
+                                     None, None, QuirksMode::NoQuirks, 0, AllowImportRules::Yes);
+
+    let device = Device::new(MediaType::screen(), QuirksMode::NoQuirks,
+                             Size2D::new(800f32, 600f32), Scale::new(1.0));
+    let mut stylist = Stylist::new(device, QuirksMode::NoQuirks);
+    let guard = lock.read();
+    stylist.append_stylesheet(DocumentStyleSheet(Arc::new(sheet)), &guard);
+
+    let rule_cache = RuleCache::new();
+    let mut ops = new_ops();
+    let report = stylist.memory_report(&StylesheetGuards::same(&guard), &[&rule_cache], &mut ops);
+    assert_eq!(report.name, "stylist");
+
+    let sheet = report.find(&["stylesheets", "author", "https://example.com/test.css"]).unwrap();
+    assert!(sheet.size > 0);
+    let rule_types = ["Namespace", "LayerStatement", "LayerBlock", "Media", "Supports", "Keyframes",
+                      "FontFace", "Style"];
+    for rule_type in &rule_types {
+        assert!(sheet.child(rule_type).unwrap().size > 0, "{} rules were not measured", rule_type);
+    }
+    assert!(sheet.child("Import").is_none());
+    // More than the rule itself: the descriptors are measured too.
+    assert!(sheet.child("FontFace").unwrap().size > 1);
+
+    for origin in &["user-agent", "user", "author"] {
+        let cascade_data = report.find(&["cascade-data", *origin]).unwrap();
+        assert!(cascade_data.child("invalidation-map").is_some());
+        assert!(cascade_data.child("custom-property-registrations").is_some());
+    }
+    assert!(report.child("rule-tree").is_some());
+    assert!(report.child("rule-caches").is_some());
+    assert!(report.total() >= sheet.total());
+}
+
+#[test]
+fn test_unparsed_declarations_size_of() {
+    let url = ServoUrl::parse("https://example.com/").unwrap();
+    let block = parse_style_attribute("--x: 1px; margin: var(--x)", &url, None,
+                                      QuirksMode::NoQuirks, CssRuleType::Style);
+    assert_eq!(block.declarations().len(), 5);
+
+    // The value's Arc and its CSS text, measured once for the custom property,
+    // and once for the four longhands of `margin`, which share their value.
+    let mut ops = new_ops();
+    let sizes: Vec<_> = block.declarations().iter().map(|d| d.size_of(&mut ops)).collect();
+    assert_eq!(sizes, [2, 2, 0, 0, 0]);
+}
@@ -0,0 +1,437 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A minimal element tree implementing the style system's DOM traits, for
+//! tests that need to match and restyle real elements.
+//!
+//! Nodes are leaked, so elements are `Copy` like the layout wrappers the style
+//! system normally runs over.
+
+use app_units::Au;
+use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use dom::ElementState;
+use euclid::default::Size2D;
+use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
+use selectors::matching::{ElementSelectorFlags, MatchingContext, VisitedHandlingMode};
+use selectors::sink::Push;
+use selectors::{Element, OpaqueElement};
+use servo_arc::{Arc, ArcBorrow};
+use servo_atoms::Atom;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ptr;
+use std::vec;
+use style::applicable_declarations::ApplicableDeclarationBlock;
+use style::context::{QuirksMode, SharedStyleContext};
+use style::data::ElementData;
+use style::dom::{LayoutIterator, NodeInfo, OpaqueNode, TDocument, TElement, TNode, TShadowRoot};
+use style::properties::PropertyDeclarationBlock;
+use style::selector_parser::{AttrValue, Lang, NonTSPseudoClass, PseudoElement, SelectorImpl};
+use style::shared_lock::{Locked, SharedRwLock};
+use style::stylist::CascadeData;
+use style::values::computed::Display;
+use style::values::AtomIdent;
+use style::{LocalName, Namespace};
+
+/// Implements trait methods that ignore their arguments and return a constant.
+macro_rules! constant_methods {
+    ($($name:ident($($arg:ty),*) -> $ty:ty = $value:expr;)*) => {
+        $(
+            fn $name(&self, $(_: $arg),*) -> $ty {
+                $value
+            }
+        )*
+    };
+}
+
+struct MockNode {
+    lock: SharedRwLock,
+    local_name: LocalName,
+    namespace: Namespace,
+    id: Option<Atom>,
+    classes: Vec<AtomIdent>,
+    state: Cell<ElementState>,
+    parent: Cell<Option<MockElement>>,
+    children: RefCell<Vec<MockElement>>,
+    dirty_descendants: Cell<bool>,
+    has_snapshot: Cell<bool>,
+    handled_snapshot: Cell<bool>,
+    data: AtomicRefCell<Option<ElementData>>,
+}
+
+/// An element of the mock tree, which is also its own node type.
+#[derive(Clone, Copy)]
+pub struct MockElement(&'static MockNode);
+
+impl MockElement {
+    /// Creates a detached HTML element, with the given id and classes.
+    pub fn new(lock: &SharedRwLock, local_name: &str, id: Option<&str>, classes: &[&str]) -> Self {
+        MockElement(Box::leak(Box::new(MockNode {
+            lock: lock.clone(),
+            local_name: LocalName::from(local_name),
+            namespace: Namespace::from("http://www.w3.org/1999/xhtml"),
+            id: id.map(Atom::from),
+            classes: classes.iter().map(|c| AtomIdent::from(*c)).collect(),
+            state: Cell::new(ElementState::empty()),
+            parent: Cell::new(None),
+            children: RefCell::new(vec![]),
+            dirty_descendants: Cell::new(false),
+            has_snapshot: Cell::new(false),
+            handled_snapshot: Cell::new(false),
+            data: AtomicRefCell::new(None),
+        })))
+    }
+
+    pub fn append_child(&self, child: MockElement) {
+        debug_assert!(child.0.parent.get().is_none());
+        child.0.parent.set(Some(*self));
+        self.0.children.borrow_mut().push(child);
+    }
+
+    pub fn remove_child(&self, child: MockElement) {
+        child.0.parent.set(None);
+        self.0.children.borrow_mut().retain(|c| *c != child);
+    }
+
+    pub fn set_state(&self, state: ElementState) {
+        self.0.state.set(state);
+    }
+
+    /// Marks this element as having a snapshot in the snapshot map the test
+    /// restyles with.
+    pub fn set_has_snapshot(&self) {
+        self.0.has_snapshot.set(true);
+        self.0.handled_snapshot.set(false);
+    }
+
+    fn sibling(&self, offset: isize) -> Option<MockElement> {
+        let siblings = self.0.parent.get()?.0.children.borrow().clone();
+        let index = siblings.iter().position(|s| s == self)? as isize + offset;
+        if index < 0 {
+            return None;
+        }
+        siblings.get(index as usize).cloned()
+    }
+}
+
+impl fmt::Debug for MockElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{:?}> ({:#x})", self.0.local_name, self.debug_id())
+    }
+}
+
+impl PartialEq for MockElement {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for MockElement {}
+
+impl Hash for MockElement {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.0 as *const MockNode).hash(state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct MockDocument(&'static MockNode);
+
+impl TDocument for MockDocument {
+    type ConcreteNode = MockElement;
+
+    fn as_node(&self) -> MockElement {
+        unreachable!("The mock tree has no document node")
+    }
+
+    fn shared_lock(&self) -> &SharedRwLock {
+        &self.0.lock
+    }
+
+    constant_methods! {
+        is_html_document() -> bool = true;
+        quirks_mode() -> QuirksMode = QuirksMode::NoQuirks;
+    }
+}
+
+/// The mock tree has no shadow roots.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MockShadowRoot {}
+
+impl TShadowRoot for MockShadowRoot {
+    type ConcreteNode = MockElement;
+
+    fn as_node(&self) -> MockElement {
+        match *self {}
+    }
+
+    fn host(&self) -> MockElement {
+        match *self {}
+    }
+
+    fn style_data<'a>(&self) -> Option<&'a CascadeData>
+    where
+        Self: 'a,
+    {
+        match *self {}
+    }
+}
+
+impl NodeInfo for MockElement {
+    constant_methods! {
+        is_element() -> bool = true;
+        is_text_node() -> bool = false;
+    }
+}
+
+impl TNode for MockElement {
+    type ConcreteElement = Self;
+    type ConcreteDocument = MockDocument;
+    type ConcreteShadowRoot = MockShadowRoot;
+
+    fn parent_node(&self) -> Option<Self> {
+        self.0.parent.get()
+    }
+
+    fn traversal_parent(&self) -> Option<Self> {
+        self.0.parent.get()
+    }
+
+    fn first_child(&self) -> Option<Self> {
+        self.0.children.borrow().first().cloned()
+    }
+
+    fn last_child(&self) -> Option<Self> {
+        self.0.children.borrow().last().cloned()
+    }
+
+    fn prev_sibling(&self) -> Option<Self> {
+        self.sibling(-1)
+    }
+
+    fn next_sibling(&self) -> Option<Self> {
+        self.sibling(1)
+    }
+
+    fn owner_doc(&self) -> MockDocument {
+        MockDocument(self.0)
+    }
+
+    fn opaque(&self) -> OpaqueNode {
+        OpaqueNode(self.debug_id())
+    }
+
+    fn as_element(&self) -> Option<Self> {
+        Some(*self)
+    }
+
+    constant_methods! {
+        is_in_document() -> bool = true;
+        as_document() -> Option<MockDocument> = None;
+        as_shadow_root() -> Option<MockShadowRoot> = None;
+    }
+
+    fn debug_id(self) -> usize {
+        self.0 as *const MockNode as usize
+    }
+}
+
+impl Element for MockElement {
+    type Impl = SelectorImpl;
+
+    fn opaque(&self) -> OpaqueElement {
+        OpaqueElement::new(self.0)
+    }
+
+    fn parent_element(&self) -> Option<Self> {
+        self.0.parent.get()
+    }
+
+    fn prev_sibling_element(&self) -> Option<Self> {
+        self.sibling(-1)
+    }
+
+    fn next_sibling_element(&self) -> Option<Self> {
+        self.sibling(1)
+    }
+
+    fn first_element_child(&self) -> Option<Self> {
+        self.first_child()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.children.borrow().is_empty()
+    }
+
+    fn is_root(&self) -> bool {
+        self.0.parent.get().is_none()
+    }
+
+    constant_methods! {
+        containing_shadow_host() -> Option<Self> = None;
+        parent_node_is_shadow_root() -> bool = false;
+        is_pseudo_element() -> bool = false;
+        is_html_element_in_html_document() -> bool = true;
+        is_link() -> bool = false;
+        is_html_slot_element() -> bool = false;
+        attr_matches(&NamespaceConstraint<&Namespace>, &LocalName, &AttrSelectorOperation<&String>) -> bool = false;
+        match_pseudo_element(&PseudoElement, &mut MatchingContext<SelectorImpl>) -> bool = false;
+        imported_part(&Atom) -> Option<Atom> = None;
+        is_part(&Atom) -> bool = false;
+    }
+
+    fn has_local_name(&self, local_name: &LocalName) -> bool {
+        self.0.local_name == *local_name
+    }
+
+    fn has_namespace(&self, ns: &Namespace) -> bool {
+        self.0.namespace == *ns
+    }
+
+    fn is_same_type(&self, other: &Self) -> bool {
+        self.0.local_name == other.0.local_name && self.0.namespace == other.0.namespace
+    }
+
+    fn match_non_ts_pseudo_class(
+        &self,
+        pc: &NonTSPseudoClass,
+        _context: &mut MatchingContext<SelectorImpl>,
+    ) -> bool {
+        let flag = pc.state_flag();
+        !flag.is_empty() && self.0.state.get().intersects(flag)
+    }
+
+    fn set_selector_flags(&self, _flags: ElementSelectorFlags) {}
+
+    fn has_id(&self, id: &Atom, _case_sensitivity: CaseSensitivity) -> bool {
+        self.0.id.as_ref() == Some(id)
+    }
+
+    fn has_class(&self, name: &Atom, _case_sensitivity: CaseSensitivity) -> bool {
+        self.0.classes.iter().any(|c| c.0 == *name)
+    }
+}
+
+impl TElement for MockElement {
+    type ConcreteNode = Self;
+    type TraversalChildrenIterator = vec::IntoIter<Self>;
+
+    fn as_node(&self) -> Self {
+        *self
+    }
+
+    fn traversal_children(&self) -> LayoutIterator<Self::TraversalChildrenIterator> {
+        LayoutIterator(self.0.children.borrow().clone().into_iter())
+    }
+
+    fn state(&self) -> ElementState {
+        self.0.state.get()
+    }
+
+    fn id(&self) -> Option<&Atom> {
+        self.0.id.as_ref()
+    }
+
+    fn local_name(&self) -> &LocalName {
+        &self.0.local_name
+    }
+
+    fn namespace(&self) -> &Namespace {
+        &self.0.namespace
+    }
+
+    fn has_dirty_descendants(&self) -> bool {
+        self.0.dirty_descendants.get()
+    }
+
+    fn has_snapshot(&self) -> bool {
+        self.0.has_snapshot.get()
+    }
+
+    fn handled_snapshot(&self) -> bool {
+        self.0.handled_snapshot.get()
+    }
+
+    fn has_data(&self) -> bool {
+        self.0.data.borrow().is_some()
+    }
+
+    constant_methods! {
+        is_html_element() -> bool = true;
+        is_mathml_element() -> bool = false;
+        is_svg_element() -> bool = false;
+        is_html_document_body_element() -> bool = false;
+        has_part_attr() -> bool = false;
+        exports_any_part() -> bool = false;
+        skip_item_display_fixup() -> bool = false;
+        may_have_animations() -> bool = false;
+        did_process_child() -> isize = 0;
+        style_attribute() -> Option<ArcBorrow<Locked<PropertyDeclarationBlock>>> = None;
+        shadow_root() -> Option<MockShadowRoot> = None;
+        containing_shadow() -> Option<MockShadowRoot> = None;
+        lang_attr() -> Option<AttrValue> = None;
+        animation_rule(&SharedStyleContext) -> Option<Arc<Locked<PropertyDeclarationBlock>>> = None;
+        transition_rule(&SharedStyleContext) -> Option<Arc<Locked<PropertyDeclarationBlock>>> = None;
+        has_attr(&Namespace, &LocalName) -> bool = false;
+        has_animations(&SharedStyleContext) -> bool = false;
+        has_css_animations(&SharedStyleContext, Option<PseudoElement>) -> bool = false;
+        has_css_transitions(&SharedStyleContext, Option<PseudoElement>) -> bool = false;
+        match_element_lang(Option<Option<AttrValue>>, &Lang) -> bool = false;
+        query_container_size(&Display) -> Size2D<Option<Au>> = Size2D::new(None, None);
+    }
+
+    fn each_class<F: FnMut(&AtomIdent)>(&self, mut callback: F) {
+        self.0.classes.iter().for_each(|class| callback(class))
+    }
+
+    fn each_attr_name<F: FnMut(&AtomIdent)>(&self, _callback: F) {}
+
+    unsafe fn set_handled_snapshot(&self) {
+        self.0.handled_snapshot.set(true);
+    }
+
+    unsafe fn set_dirty_descendants(&self) {
+        self.0.dirty_descendants.set(true);
+    }
+
+    unsafe fn unset_dirty_descendants(&self) {
+        self.0.dirty_descendants.set(false);
+    }
+
+    fn store_children_to_process(&self, _n: isize) {}
+
+    unsafe fn ensure_data(&self) -> AtomicRefMut<ElementData> {
+        let mut data = self.0.data.borrow_mut();
+        if data.is_none() {
+            *data = Some(ElementData::default());
+        }
+        AtomicRefMut::map(data, |data| data.as_mut().unwrap())
+    }
+
+    unsafe fn clear_data(&self) {
+        *self.0.data.borrow_mut() = None;
+    }
+
+    fn borrow_data(&self) -> Option<AtomicRef<ElementData>> {
+        let data = self.0.data.borrow();
+        data.as_ref()?;
+        Some(AtomicRef::map(data, |data| data.as_ref().unwrap()))
+    }
+
+    fn mutate_data(&self) -> Option<AtomicRefMut<ElementData>> {
+        let data = self.0.data.borrow_mut();
+        data.as_ref()?;
+        Some(AtomicRefMut::map(data, |data| data.as_mut().unwrap()))
+    }
+
+    fn synthesize_presentational_hints_for_legacy_attributes<V>(
+        &self,
+        _: VisitedHandlingMode,
+        _: &mut V,
+    ) where
+        V: Push<ApplicableDeclarationBlock>,
+    {
+    }
+}
@@ -7,7 +7,7 @@ use servo_atoms::Atom;
 use style::parser::Parse;
 use style::properties::longhands::animation_name;
 use style::values::{KeyframesName, CustomIdent};
-use style::values::specified::AnimationIterationCount;
+use style::values::specified::{AnimationIterationCount, AnimationRange, AnimationTimeline};
 use style_traits::ToCss;
 
 #[test]
@@ -19,3 +19,32 @@ fn test_animation_iteration() {
     // Negative numbers are invalid
     assert!(parse(AnimationIterationCount::parse, "-1").is_err());
 }
+
+#[test]
+fn test_animation_timeline() {
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "auto");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "none");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "my-timeline");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "scroll()");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "scroll(block nearest)", "scroll()");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "scroll(inline root)");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "view()");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "view(x)");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "view(10px y)", "view(y 10px)");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "view(auto 20%)");
+    assert_roundtrip_with_context!(AnimationTimeline::parse, "view(inline 5px 5px)", "view(inline 5px)");
+
+    assert!(parse(AnimationTimeline::parse, "view(x y)").is_err());
+}
+
+#[test]
+fn test_animation_range() {
+    assert_roundtrip_with_context!(AnimationRange::parse, "normal");
+    assert_roundtrip_with_context!(AnimationRange::parse, "25%");
+    assert_roundtrip_with_context!(AnimationRange::parse, "100px");
+    assert_roundtrip_with_context!(AnimationRange::parse, "entry");
+    assert_roundtrip_with_context!(AnimationRange::parse, "exit-crossing 50%");
+    assert_roundtrip_with_context!(AnimationRange::parse, "contain calc(10% + 5px)");
+
+    assert!(parse(AnimationRange::parse, "auto").is_err());
+}
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use parsing::parse;
+use servo_config::prefs::{PREFS, PrefValue};
+use style::parser::Parse;
+use style::values::specified::basic_shape::*;
+use style_traits::ToCss;
+
+#[test]
+fn test_rect_and_xywh() {
+    PREFS.set("layout.css.basic-shape-rect.enabled", PrefValue::Boolean(true));
+
+    assert_roundtrip_with_context!(BasicShape::parse, "rect(10px auto 20% 0px)");
+    assert_roundtrip_with_context!(BasicShape::parse, "rect(auto auto auto auto round 5px)");
+    assert_roundtrip_with_context!(BasicShape::parse, "xywh(10px 20% 30px 40% round 5px 10px)");
+    assert_roundtrip_with_context!(ClipPath::parse, "xywh(0px 0px 100% 100%) content-box");
+
+    assert!(parse(BasicShape::parse, "rect(10px 20px 30px)").is_err());
+    assert!(parse(BasicShape::parse, "xywh(10px 20px -30px 40px)").is_err());
+    assert!(parse(BasicShape::parse, "xywh(10px 20px auto 40px)").is_err());
+}
+
+#[test]
+fn test_path() {
+    assert_roundtrip_with_context!(ClipPath::parse, "path(\"M 0 0 L 10 10 Z\")");
+    assert_roundtrip_with_context!(ClipPath::parse, "path(evenodd, \"M 0 0 L 10 10 Z\")");
+
+    assert!(parse(BasicShape::parse, "path(\"M 0 0 L 10 10 Z\")").is_err());
+    assert!(parse(ShapeOutside::parse, "path(\"M 0 0 L 10 10 Z\")").is_err());
+}
+
+#[test]
+fn test_shape() {
+    PREFS.set("layout.css.basic-shape-shape.enabled", PrefValue::Boolean(true));
+
+    assert_roundtrip_with_context!(BasicShape::parse, "shape(from 0px 0px, line to 10px 10px, close)");
+    assert_roundtrip_with_context!(BasicShape::parse,
+                                   "shape(evenodd from 10% 10%, move by 5px 5px, hline to 50%, vline by 20px)");
+    assert_roundtrip_with_context!(BasicShape::parse,
+                                   "shape(from 0px 0px, curve to 10px 10px via 5px 0px, \
+                                    curve by 10px 10px via 5px 0px 10px 5px)");
+    assert_roundtrip_with_context!(BasicShape::parse,
+                                   "shape(from 0px 0px, smooth to 10px 10px, smooth by 10px 10px via 5px 5px)");
+    assert_roundtrip_with_context!(BasicShape::parse,
+                                   "shape(from 0px 0px, arc to 10px 10px of 5px)");
+    assert_roundtrip_with_context!(BasicShape::parse,
+                                   "shape(from 0px 0px, arc to 10px 10px of 5px 5px ccw small rotate 0deg)",
+                                   "shape(from 0px 0px, arc to 10px 10px of 5px)");
+    assert_roundtrip_with_context!(BasicShape::parse,
+                                   "shape(from 0px 0px, arc by 10px 10px of 5px 10% rotate 45deg large cw)",
+                                   "shape(from 0px 0px, arc by 10px 10px of 5px 10% cw large rotate 45deg)");
+
+    assert_roundtrip_with_context!(ClipPath::parse, "shape(from 0px 0px, line to 10px 10px) content-box");
+    assert!(parse(ShapeOutside::parse, "shape(from 0px 0px, line to 10px 10px)").is_err());
+
+    assert!(parse(BasicShape::parse, "shape(from 0px 0px)").is_err());
+    assert!(parse(BasicShape::parse, "shape(0px 0px, line to 10px 10px)").is_err());
+    assert!(parse(BasicShape::parse, "shape(from 0px 0px, line 10px 10px)").is_err());
+    assert!(parse(BasicShape::parse, "shape(from 0px 0px, curve to 10px 10px)").is_err());
+    assert!(parse(BasicShape::parse, "shape(from 0px 0px, arc to 10px 10px of 5px cw ccw)").is_err());
+}
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::{Scale, Size2D};
+use parsing::parse;
+use style::context::QuirksMode;
+use style::media_queries::{Device, MediaType};
+use style::parser::Parse;
+use style::values::computed::{self, Context, Length, ToComputedValue};
+use style::values::specified::{LengthPercentage, Number};
+use style_traits::ToCss;
+
+#[test]
+fn test_abs_and_hypot() {
+    assert_roundtrip_with_context!(LengthPercentage::parse, "abs(-10px)", "calc(10px)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "hypot(3px, 4px)", "calc(5px)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "abs(10% - 20px)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "hypot(10%, 1em)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(1px - abs(10% - 20px))");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(-2 * hypot(10%, 1em))",
+                                   "calc(-1 * hypot(20%, 2em))");
+
+    assert!(parse(LengthPercentage::parse, "abs(2)").is_err());
+    assert!(parse(LengthPercentage::parse, "hypot(1px, 2)").is_err());
+    assert!(parse(Number::parse, "hypot(1px)").is_err());
+}
+
+#[test]
+fn test_sign() {
+    assert_roundtrip_with_context!(Number::parse, "sign(-5px)", "calc(-1)");
+    assert_roundtrip_with_context!(Number::parse, "sign(1cm - 10px)", "calc(1)");
+    assert_roundtrip_with_context!(Number::parse, "sign(0deg)", "calc(0)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(10px * sign(-1em))", "calc(-10px)");
+
+    // The sign of these is only known at computed or used-value time.
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(10px * sign(10% - 1em))");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(sign(1em - 1px) * -2em)",
+                                   "calc(-2em * sign(1em - 1px))");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(1px + 2 * sign(10%) * (1em + 1px))",
+                                   "calc(1px + (2em + 2px) * sign(10%))");
+
+    // sign() is a number, so it can't be used where a dimension is expected.
+    assert!(parse(LengthPercentage::parse, "sign(10%)").is_err());
+    assert!(parse(LengthPercentage::parse, "calc(1px + sign(1em - 1px))").is_err());
+    assert!(parse(LengthPercentage::parse, "sign(10px)").is_err());
+    // Numbers are resolved at parse time, and percentages have no basis there.
+    assert!(parse(Number::parse, "sign(1em - 1px)").is_err());
+    assert!(parse(Number::parse, "sign(10%)").is_err());
+}
+
+#[test]
+fn test_sign_resolution() {
+    let device = Device::new(MediaType::screen(), QuirksMode::NoQuirks,
+                             Size2D::new(800., 600.), Scale::new(1.0));
+    let compute = |s| -> computed::LengthPercentage {
+        let specified = parse(LengthPercentage::parse, s).unwrap();
+        Context::for_media_query_evaluation(&device, QuirksMode::NoQuirks, |context| {
+            specified.to_computed_value(context)
+        })
+    };
+
+    // 1em is 16px here.
+    assert_eq!(compute("calc(2px * sign(1em - 1px))").to_length(), Some(Length::new(2.)));
+    let value = compute("calc(10px * sign(10% - 1em))");
+    assert_eq!(value.to_length(), None);
+    assert_eq!(value.resolve(Length::new(200.)), Length::new(10.));
+    assert_eq!(value.resolve(Length::new(100.)), Length::new(-10.));
+}
+
+#[test]
+fn test_exponential_functions() {
+    assert_roundtrip_with_context!(Number::parse, "pow(2, 3)", "calc(8)");
+    assert_roundtrip_with_context!(Number::parse, "sqrt(16)", "calc(4)");
+    assert_roundtrip_with_context!(Number::parse, "exp(0)", "calc(1)");
+    assert_roundtrip_with_context!(Number::parse, "log(1)", "calc(0)");
+    assert_roundtrip_with_context!(Number::parse, "log(4, 2)", "calc(2)");
+
+    assert!(parse(Number::parse, "pow(2px, 2)").is_err());
+    assert!(parse(Number::parse, "sqrt(4, 2)").is_err());
+}
+
+#[test]
+fn test_non_finite_values() {
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(infinity * 1px)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(-infinity * 1%)");
+    assert_roundtrip_with_context!(LengthPercentage::parse, "calc(1px * log(0))",
+                                   "calc(-infinity * 1px)");
+}
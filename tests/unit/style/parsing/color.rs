@@ -0,0 +1,51 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cssparser::RGBA;
+use parsing::parse;
+use servo_config::prefs::{PREFS, PrefValue};
+use style::values::computed::Color as ComputedColor;
+use style::values::specified::Color;
+use style_traits::ToCss;
+
+#[test]
+fn test_relative_color() {
+    PREFS.set("layout.css.more_color_4.enabled", PrefValue::Boolean(true));
+
+    assert_roundtrip_with_context!(Color::parse, "rgb(from red r g b)");
+    assert_roundtrip_with_context!(Color::parse, "rgb(from red r g calc(b + 20))");
+    assert_roundtrip_with_context!(Color::parse, "rgba(from red r g b / 50%)", "rgb(from red r g b / 50%)");
+    assert_roundtrip_with_context!(Color::parse, "hsl(from currentcolor h s l / alpha)");
+    assert_roundtrip_with_context!(Color::parse, "oklch(from blue l c calc(h + 90))");
+    assert_roundtrip_with_context!(Color::parse, "color(from red display-p3 r g none)");
+
+    assert!(parse(Color::parse, "rgb(from red r g)").is_err());
+    assert!(parse(Color::parse, "rgb(from red r g 10deg)").is_err());
+    assert!(parse(Color::parse, "hsl(from red 10% s l)").is_err());
+    assert!(parse(Color::parse, "lab(from red l x b)").is_err());
+    assert!(parse(Color::parse, "oklch(from blue l c calc(h + 90deg))").is_err());
+    assert!(parse(Color::parse, "color(from red hsl h s l)").is_err());
+}
+
+#[test]
+fn test_relative_color_computed_value() {
+    PREFS.set("layout.css.more_color_4.enabled", PrefValue::Boolean(true));
+
+    fn compute(s: &'static str) -> Option<ComputedColor> {
+        parse(|context, input| Ok(Color::parse_and_compute(context, input, None)), s).unwrap()
+    }
+
+    assert_eq!(
+        compute("rgb(from rgb(10 20 30) calc(r + 10) g b / 50%)"),
+        Some(ComputedColor::rgba(RGBA::new(20, 20, 30, 0.5)))
+    );
+    assert_eq!(
+        compute("rgb(from rgb(10 20 30) b r g)"),
+        Some(ComputedColor::rgba(RGBA::new(30, 10, 20, 1.0)))
+    );
+
+    // Without a style to take the current color from, a relative color whose
+    // origin depends on currentcolor can't be computed.
+    assert_eq!(compute("rgb(from currentcolor r g b / 50%)"), None);
+}
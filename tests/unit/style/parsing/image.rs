@@ -2,8 +2,17 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use cssparser::RGBA;
+use parsing::parse;
+use servo_config::prefs::{PREFS, PrefValue};
+use style::color::mix::{ColorInterpolationMethod, HueInterpolationMethod};
+use style::color::ColorSpace;
 use style::parser::Parse;
+use style::values::computed::image::{Gradient as ComputedGradient, LineDirection};
+use style::values::computed::{Color as ComputedColor, Length, LengthPercentage, Percentage};
+use style::values::generics::image::{GradientCompatMode, GradientItem};
 use style::values::specified::image::*;
+use style::values::specified::position::VerticalPositionKeyword;
 use style_traits::ToCss;
 
 #[test]
@@ -102,3 +111,71 @@ fn test_radial_gradient() {
                                    "repeating-radial-gradient(red, green)",
                                    "repeating-radial-gradient(at center center, red, green)");
 }
+
+#[test]
+fn test_gradient_color_interpolation_method() {
+    PREFS.set("layout.css.gradient-color-interpolation-method.enabled", PrefValue::Boolean(true));
+
+    assert_roundtrip_with_context!(Image::parse, "linear-gradient(in oklab, red, green)");
+    assert_roundtrip_with_context!(Image::parse, "linear-gradient(to left in oklab, red, green)");
+    assert_roundtrip_with_context!(Image::parse,
+                                   "linear-gradient(in oklab to left, red, green)",
+                                   "linear-gradient(to left in oklab, red, green)");
+    assert_roundtrip_with_context!(Image::parse,
+                                   "linear-gradient(in srgb, red, green)",
+                                   "linear-gradient(red, green)");
+    assert_roundtrip_with_context!(Image::parse,
+                                   "radial-gradient(circle at 20px 30px in hsl longer hue, red, green)");
+    assert_roundtrip_with_context!(Image::parse, "conic-gradient(from 90deg in lch, red, green)");
+    assert_roundtrip_with_context!(Image::parse,
+                                   "repeating-conic-gradient(in oklch decreasing hue, red, green)");
+
+    assert!(parse(Image::parse, "linear-gradient(in oklab in srgb, red, green)").is_err());
+    assert!(parse(Image::parse, "linear-gradient(in oklab, to left, red, green)").is_err());
+    assert!(parse(Image::parse, "-webkit-linear-gradient(in oklab, red, green)").is_err());
+}
+
+#[test]
+fn test_gradient_sample() {
+    fn gradient(space: ColorSpace, items: Vec<GradientItem<ComputedColor, LengthPercentage>>) -> ComputedGradient {
+        ComputedGradient::Linear {
+            direction: LineDirection::Vertical(VerticalPositionKeyword::Bottom),
+            color_interpolation_method: ColorInterpolationMethod {
+                space,
+                hue: HueInterpolationMethod::Shorter,
+            },
+            items: items.into(),
+            repeating: false,
+            compat_mode: GradientCompatMode::Modern,
+        }
+    }
+
+    let red = ComputedColor::rgba(RGBA::new(255, 0, 0, 1.0));
+    let blue = ComputedColor::rgba(RGBA::new(0, 0, 255, 1.0));
+    let current_color = RGBA::new(0, 0, 0, 1.0);
+    let line_length = Length::new(100.);
+
+    let srgb = gradient(ColorSpace::Srgb, vec![
+        GradientItem::SimpleColorStop(red.clone()),
+        GradientItem::SimpleColorStop(blue.clone()),
+    ]);
+    assert_eq!(srgb.sample(0., line_length, current_color), RGBA::new(255, 0, 0, 1.0));
+    assert_eq!(srgb.sample(0.5, line_length, current_color), RGBA::new(128, 0, 128, 1.0));
+    assert_eq!(srgb.sample(2., line_length, current_color), RGBA::new(0, 0, 255, 1.0));
+
+    let hinted = gradient(ColorSpace::Srgb, vec![
+        GradientItem::SimpleColorStop(red.clone()),
+        GradientItem::InterpolationHint(LengthPercentage::new_length(Length::new(25.))),
+        GradientItem::ComplexColorStop {
+            color: blue.clone(),
+            position: LengthPercentage::new_percent(Percentage(1.)),
+        },
+    ]);
+    assert_eq!(hinted.sample(0.25, line_length, current_color), RGBA::new(128, 0, 128, 1.0));
+
+    let oklab = gradient(ColorSpace::Oklab, vec![
+        GradientItem::SimpleColorStop(red),
+        GradientItem::SimpleColorStop(blue),
+    ]);
+    assert_ne!(oklab.sample(0.5, line_length, current_color), RGBA::new(128, 0, 128, 1.0));
+}
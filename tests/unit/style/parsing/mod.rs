@@ -111,14 +111,19 @@ macro_rules! parse_longhand {
 
 mod animation;
 mod background;
+mod basic_shape;
 mod border;
 mod box_;
+mod calc;
+mod color;
 mod column;
 mod effects;
 mod image;
 mod inherited_text;
+mod motion;
 mod outline;
 mod position;
+mod property_rule;
 mod selectors;
 mod supports;
 mod text_overflow;
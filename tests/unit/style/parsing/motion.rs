@@ -0,0 +1,38 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use parsing::{parse, parse_entirely};
+use servo_config::prefs::{PREFS, PrefValue};
+use style::parser::Parse;
+use style::values::specified::motion::*;
+use style_traits::ToCss;
+
+#[test]
+fn test_offset_path() {
+    assert_roundtrip_with_context!(OffsetPath::parse, "none");
+    assert_roundtrip_with_context!(OffsetPath::parse, "path(\"M 0 0 L 10 10\")");
+    assert_roundtrip_with_context!(OffsetPath::parse, "circle(10px) content-box");
+    assert_roundtrip_with_context!(OffsetPath::parse, "inset(10px)");
+    assert_roundtrip_with_context!(OffsetPath::parse, "url(\"path.svg#a\")");
+    assert_roundtrip_with_context!(OffsetPath::parse, "fill-box");
+
+    // border-box is the default reference box.
+    assert_roundtrip_with_context!(OffsetPath::parse, "border-box inset(5px)", "inset(5px)");
+    assert_roundtrip_with_context!(OffsetPath::parse, "border-box", "border-box");
+
+    assert!(parse(OffsetPath::parse, "margin-box").is_err());
+    assert!(parse_entirely(OffsetPath::parse, "content-box padding-box").is_err());
+    assert!(parse_entirely(OffsetPath::parse, "none content-box").is_err());
+}
+
+#[test]
+fn test_offset_position() {
+    PREFS.set("layout.css.motion-path-offset-position.enabled", PrefValue::Boolean(true));
+
+    assert_roundtrip_with_context!(OffsetPosition::parse, "normal");
+    assert_roundtrip_with_context!(OffsetPosition::parse, "auto");
+    assert_roundtrip_with_context!(OffsetPosition::parse, "left 10px top 20%");
+
+    assert!(parse(OffsetPosition::parse, "none").is_err());
+}
@@ -143,3 +143,23 @@ fn test_vertical_position() {
     assert!(parse(VerticalPosition::parse, "y-start").is_err());
     assert!(parse(VerticalPosition::parse, "y-end").is_err());
 }
+
+#[test]
+fn test_grid_template_component() {
+    use style::values::specified::GridTemplateComponent;
+
+    assert_roundtrip_with_context!(GridTemplateComponent::parse, "none");
+    assert_roundtrip_with_context!(GridTemplateComponent::parse, "[a] 10px repeat(2, 1fr) [b]");
+    assert_roundtrip_with_context!(GridTemplateComponent::parse, "minmax(10px, auto) fit-content(20%)");
+    assert_roundtrip_with_context!(GridTemplateComponent::parse, "subgrid [a] [b c]");
+    assert_roundtrip_with_context!(GridTemplateComponent::parse, "subgrid repeat(auto-fill, [a])");
+    assert!(parse_entirely(GridTemplateComponent::parse, "masonry").is_err());
+}
+
+#[test]
+fn test_grid_template_areas() {
+    assert_roundtrip_with_context!(GridTemplateAreas::parse, "none");
+    assert_roundtrip_with_context!(GridTemplateAreas::parse, "\"a a\" \"b c\"");
+    assert!(parse(GridTemplateAreas::parse, "\"a b a\"").is_err());
+    assert!(parse(GridTemplateAreas::parse, "\"a b\" \"c\"").is_err());
+}
@@ -0,0 +1,46 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use parsing::parse_entirely;
+use style::properties_and_values::syntax::{Descriptor, ParseError};
+use style::properties_and_values::value::SpecifiedValue;
+use style_traits::ToCss;
+
+#[test]
+fn test_syntax_descriptor() {
+    assert!(Descriptor::from_str("*").unwrap().is_universal());
+    assert!(Descriptor::from_str(" * ").unwrap().is_universal());
+    assert_eq!(Descriptor::from_str("<length> | auto").unwrap().components().len(), 2);
+    assert_eq!(Descriptor::from_str("<color>#").unwrap().components().len(), 1);
+    assert_eq!(Descriptor::from_str("big | BIG").unwrap().components().len(), 2);
+
+    assert_eq!(Descriptor::from_str("  "), Err(ParseError::EmptyInput));
+    assert_eq!(Descriptor::from_str("<length> |"), Err(ParseError::UnexpectedEOF));
+    assert_eq!(Descriptor::from_str("| <length>"), Err(ParseError::UnexpectedPipe));
+    assert_eq!(Descriptor::from_str("<length"), Err(ParseError::UnclosedDataTypeName));
+    assert_eq!(Descriptor::from_str("<foo>"), Err(ParseError::UnknownDataTypeName));
+    assert_eq!(Descriptor::from_str("<transform-list>+"), Err(ParseError::UnexpectedMultiplier));
+    assert_eq!(Descriptor::from_str("inherit"), Err(ParseError::InvalidCustomIdent));
+    assert_eq!(Descriptor::from_str("<length> +"), Err(ParseError::ExpectedPipeBetweenComponents));
+    assert_eq!(Descriptor::from_str("<length> <number>"), Err(ParseError::ExpectedPipeBetweenComponents));
+}
+
+#[test]
+fn test_registered_value() {
+    fn parse_value(syntax: &'static str, value: &'static str) -> Result<String, ()> {
+        let syntax = Descriptor::from_str(syntax).unwrap();
+        parse_entirely(|context, input| SpecifiedValue::parse(context, input, &syntax), value)
+            .map(|value| value.to_css_string())
+            .map_err(|_| ())
+    }
+
+    assert_eq!(parse_value("<length>", "10px"), Ok("10px".to_owned()));
+    assert_eq!(parse_value("<length> | auto", "auto"), Ok("auto".to_owned()));
+    assert_eq!(parse_value("<number>+", "1 2  3"), Ok("1 2 3".to_owned()));
+    assert_eq!(parse_value("<integer>#", "1,2"), Ok("1, 2".to_owned()));
+    assert!(parse_value("<length>", "10").is_err());
+    assert!(parse_value("<length>", "10px 10px").is_err());
+    assert!(parse_value("big", "BIG").is_err());
+    assert!(parse_value("<transform-list>", "none").is_err());
+}
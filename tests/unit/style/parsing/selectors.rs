@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use cssparser::{Parser, ParserInput, ToCss};
-use selectors::parser::SelectorList;
+use selectors::parser::{ParseRelative, SelectorList};
 use style::selector_parser::{SelectorImpl, SelectorParser};
 use style::stylesheets::{Origin, Namespaces};
 use style_traits::ParseError;
@@ -16,7 +16,7 @@ fn parse_selector<'i, 't>(input: &mut Parser<'i, 't>) -> Result<SelectorList<Sel
         namespaces: &ns,
         url_data: None,
     };
-    SelectorList::parse(&parser, input)
+    SelectorList::parse(&parser, input, ParseRelative::No)
 }
 
 #[test]
@@ -28,3 +28,22 @@ fn test_selectors() {
     assert_roundtrip!(parse_selector, "* > *");
     assert_roundtrip!(parse_selector, "*|* + *", "* + *");
 }
+
+#[test]
+fn test_tree_abiding_pseudo_elements() {
+    assert_roundtrip!(parse_selector, "p::first-letter");
+    assert_roundtrip!(parse_selector, "p:first-line", "p::first-line");
+    assert_roundtrip!(parse_selector, "li::marker");
+    assert_roundtrip!(parse_selector, "input::placeholder");
+}
+
+#[test]
+fn test_form_and_state_pseudo_classes() {
+    assert_roundtrip!(parse_selector, "form:focus-within > input:focus-visible");
+    assert_roundtrip!(parse_selector, "input:required:invalid, input:optional:valid");
+    assert_roundtrip!(parse_selector, "input:in-range, input:out-of-range, option:default");
+    assert_roundtrip!(parse_selector, "input:-webkit-autofill", "input:autofill");
+    assert_roundtrip!(parse_selector, "p:dir(rtl), p:dir(LTR)", "p:dir(rtl), p:dir(ltr)");
+    assert_roundtrip!(parse_selector, "dialog:modal, details:open, div:popover-open");
+    assert_roundtrip!(parse_selector, "video:playing, audio:paused");
+}
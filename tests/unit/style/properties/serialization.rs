@@ -679,6 +679,57 @@ mod shorthand_serialization {
         }
     }
 
+    mod transition {
+        pub use super::*;
+
+        #[test]
+        fn serialize_transition_behavior() {
+            let block_text = "transition: display 1s allow-discrete, opacity 2s normal;";
+
+            let block = parse(|c, i| Ok(parse_property_declaration_list(c, i)), block_text).unwrap();
+
+            let serialization = block.to_css_string();
+
+            assert_eq!(serialization, "transition: display 1s ease 0s allow-discrete, opacity 2s ease 0s;");
+        }
+    }
+
+    mod white_space {
+        pub use super::*;
+
+        fn serialize(block_text: &'static str) -> String {
+            let block = parse(|c, i| Ok(parse_property_declaration_list(c, i)), block_text).unwrap();
+            block.to_css_string()
+        }
+
+        #[test]
+        fn serialize_legacy_white_space_keywords() {
+            assert_eq!(serialize("white-space: pre-line;"), "white-space: pre-line;");
+            assert_eq!(serialize("white-space: preserve nowrap;"), "white-space: pre;");
+            assert_eq!(serialize("white-space: collapse;"), "white-space: normal;");
+        }
+
+        #[test]
+        fn serialize_white_space_longhands() {
+            assert_eq!(serialize("white-space: nowrap preserve-spaces;"), "white-space: preserve-spaces nowrap;");
+            assert_eq!(serialize("white-space: preserve discard-after discard-before;"),
+                       "white-space: preserve discard-before discard-after;");
+        }
+
+        #[test]
+        fn serialize_text_wrap() {
+            assert_eq!(serialize("text-wrap: wrap;"), "text-wrap: wrap;");
+            assert_eq!(serialize("text-wrap: balance;"), "text-wrap: balance;");
+            assert_eq!(serialize("text-wrap: pretty nowrap;"), "text-wrap: nowrap pretty;");
+        }
+
+        #[test]
+        fn serialize_text_spacing_trim() {
+            assert_eq!(serialize("text-spacing-trim: auto;"), "text-spacing-trim: auto;");
+            assert_eq!(serialize("text-spacing-trim: trim-start;"), "text-spacing-trim: trim-start;");
+        }
+    }
+
     mod keywords {
         pub use super::*;
         #[test]
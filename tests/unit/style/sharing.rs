@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use serde_json::json;
+use style::sharing::{SharingMissReason, StyleSharingStatistics};
+
+fn statistics(hits: u32, rejections: &[(SharingMissReason, u32)], selectors: &[&str]) -> StyleSharingStatistics {
+    let mut statistics = StyleSharingStatistics::default();
+    statistics.lookups = 4;
+    statistics.hits = hits;
+    statistics.candidates_tested = 8;
+    statistics.rejections = rejections.iter().cloned().collect();
+    statistics.revalidation_failures = selectors.iter().map(|s| (s.to_string(), 1)).collect();
+    statistics
+}
+
+#[test]
+fn test_style_sharing_statistics() {
+    let mut aggregate = statistics(1, &[(SharingMissReason::Class, 3), (SharingMissReason::Revalidation, 2)],
+                                   &[":first-child", "[type=text]"]);
+    aggregate += statistics(3, &[(SharingMissReason::Revalidation, 1)], &[":first-child"]);
+
+    assert_eq!(aggregate.lookups, 8);
+    assert_eq!(aggregate.misses(), 4);
+    assert_eq!(aggregate.hit_rate(), 0.5);
+    assert_eq!(serde_json::to_value(&aggregate).unwrap(), json!({
+        "lookups": 8,
+        "skipped": 0,
+        "hits": 4,
+        "candidates_tested": 16,
+        "rejections": { "Class": 3, "Revalidation": 3 },
+        "revalidation_failures": { ":first-child": 2, "[type=text]": 1 },
+    }));
+}
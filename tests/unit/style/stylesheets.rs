@@ -15,16 +15,16 @@ use std::borrow::ToOwned;
 use std::cell::RefCell;
 use std::sync::atomic::AtomicBool;
 use style::context::QuirksMode;
-use style::error_reporting::{ParseErrorReporter, ContextualParseError};
+use style::error_reporting::{CollectingReporter, ParseErrorReporter, ContextualParseError, Severity};
 use style::media_queries::MediaList;
 use style::properties::{CSSWideKeyword, CustomDeclaration};
 use style::properties::{CustomDeclarationValue, Importance};
 use style::properties::{PropertyDeclaration, PropertyDeclarationBlock};
 use style::properties::longhands::{self, animation_timing_function};
-use style::shared_lock::SharedRwLock;
-use style::stylesheets::{Origin, Namespaces};
+use style::shared_lock::{SharedRwLock, ToCssWithGuard};
+use style::stylesheets::{AllowImportRules, Origin, Namespaces};
 use style::stylesheets::{Stylesheet, StylesheetContents, NamespaceRule, CssRule, CssRules, StyleRule, KeyframesRule};
-use style::stylesheets::keyframes_rule::{Keyframe, KeyframeSelector, KeyframePercentage};
+use style::stylesheets::keyframes_rule::{Keyframe, KeyframeSelector, KeyframePercentage, KeyframesAnimation};
 use style::values::{KeyframesName, CustomIdent};
 use style::values::computed::Percentage;
 use style::values::specified::{LengthPercentageOrAuto, PositionComponent};
@@ -118,6 +118,7 @@ fn test_parse_stylesheet() {
                             Importance::Important,
                         ),
                     ]))),
+                    rules: None,
                     source_location: SourceLocation {
                         line: 3,
                         column: 9,
@@ -145,6 +146,7 @@ fn test_parse_stylesheet() {
                         (PropertyDeclaration::Display(longhands::display::SpecifiedValue::Block),
                          Importance::Normal),
                     ]))),
+                    rules: None,
                     source_location: SourceLocation {
                         line: 11,
                         column: 9,
@@ -207,6 +209,7 @@ fn test_parse_stylesheet() {
                                                        ::get_initial_specified_value()])),
                          Importance::Normal),
                     ]))),
+                    rules: None,
                     source_location: SourceLocation {
                         line: 15,
                         column: 9,
@@ -317,6 +320,33 @@ impl ParseErrorReporter for TestingErrorReporter {
 }
 
 
+#[test]
+fn test_keyframes_animating_custom_properties() {
+    let css = r"
+    @keyframes foo {
+        from { --x: 0px; }
+        50% { --y: red; }
+        to { --x: 10px; }
+    }";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, media, lock,
+                                          None, None, QuirksMode::NoQuirks, 0);
+    let guard = stylesheet.shared_lock.read();
+    let rules = stylesheet.contents.rules.read_with(&guard);
+    let rule = match rules.0[0] {
+        CssRule::Keyframes(ref rule) => rule.read_with(&guard),
+        _ => panic!("Expected a @keyframes rule"),
+    };
+
+    // Animations of custom properties alone still have keyframes.
+    let animation = KeyframesAnimation::from_keyframes(&rule.keyframes, None, &guard);
+    assert!(animation.properties_changed.is_empty());
+    assert_eq!(animation.custom_properties_changed, vec![Atom::from("x"), Atom::from("y")]);
+    assert_eq!(animation.steps.len(), 3);
+}
+
 #[test]
 fn test_report_error_stylesheet() {
     PREFS.set("layout.viewport.enabled", PrefValue::Boolean(true));
@@ -370,6 +400,51 @@ fn test_report_error_stylesheet() {
     assert_eq!(error_reporter.errors.borrow()[0].url, url);
 }
 
+#[test]
+fn test_collecting_reporter() {
+    let css = r"
+    div {
+        color: red;
+        invalid: true;
+    }
+    @invalid;
+    ";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let error_reporter = CollectingReporter::with_source(css, 0);
+
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    Stylesheet::from_str(css, url, Origin::UserAgent, media, lock,
+                         None, Some(&error_reporter), QuirksMode::NoQuirks, 0,
+                         AllowImportRules::Yes);
+
+    let diagnostics = error_reporter.into_diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+
+    let unknown_property = &diagnostics[0];
+    assert_eq!(unknown_property.code, "unsupported-property-declaration");
+    assert_eq!(unknown_property.reason, Some("unknown-property"));
+    assert_eq!(unknown_property.severity, Severity::Error);
+    assert_eq!(unknown_property.source.as_ref().unwrap(), "invalid: true;");
+    assert_eq!(unknown_property.selectors.as_ref().unwrap(), "div");
+    assert_eq!((unknown_property.range.start.line, unknown_property.range.start.column), (3, 9));
+    assert_eq!((unknown_property.range.end.line, unknown_property.range.end.column), (3, 23));
+
+    assert_eq!(diagnostics[1].code, "invalid-rule");
+    assert_eq!(diagnostics[1].reason, Some("unsupported-at-rule"));
+    assert_eq!(diagnostics[1].source.as_ref().unwrap(), "@invalid");
+    assert_eq!((diagnostics[1].range.start.line, diagnostics[1].range.start.column), (5, 5));
+
+    let host = ContextualParseError::NeverMatchingHostSelector(":host:hover".to_owned());
+    assert_eq!(host.code(), "never-matching-host-selector");
+    assert_eq!(host.severity(), Severity::Warning);
+
+    let json = serde_json::to_value(unknown_property).unwrap();
+    assert_eq!(json["code"], "unsupported-property-declaration");
+    assert_eq!(json["severity"], "error");
+    assert_eq!(json["range"]["end_column"], 23);
+}
+
 #[test]
 fn test_no_report_unrecognized_vendor_properties() {
     let css = r"
@@ -429,3 +504,180 @@ fn test_source_url() {
         assert_eq!(*url_opt, test.1);
     }
 }
+
+#[test]
+fn test_nested_style_rules() {
+    PREFS.set("layout.css.nesting.enabled", PrefValue::Boolean(true));
+    let css = r"
+    .card {
+        color: red;
+        > .title { color: blue; }
+        &:hover { color: green; }
+        .footer & { color: black; }
+        @media screen {
+            color: yellow;
+            .icon { color: white; }
+        }
+    }";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, media, lock,
+                                          None, None, QuirksMode::NoQuirks, 0);
+    let guard = stylesheet.shared_lock.read();
+    let rules = stylesheet.contents.rules.read_with(&guard);
+    assert_eq!(rules.0.len(), 1);
+
+    let mut serialized = String::new();
+    rules.0[0].to_css(&guard, &mut serialized).unwrap();
+    assert_eq!(serialized, "\
+.card {
+  color: red;
+  & > .title { color: blue; }
+  &:hover { color: green; }
+  .footer & { color: black; }
+  @media screen {
+    & { color: yellow; }
+    & .icon { color: white; }
+  }
+}");
+}
+
+#[test]
+fn test_report_error_nested_rules() {
+    PREFS.set("layout.css.nesting.enabled", PrefValue::Boolean(true));
+    let css = r"
+    div {
+        color: red;
+        @invalid;
+        display: invalid;
+        &:hover { color: blue; }
+    }";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let error_reporter = TestingErrorReporter::new();
+
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    Stylesheet::from_str(css, url, Origin::Author, media, lock,
+                         None, Some(&error_reporter), QuirksMode::NoQuirks, 0);
+
+    // Errors in nested rules are reported in source order with the ones in
+    // declarations.
+    error_reporter.assert_messages_contain(&[
+        (3, 17, "Invalid rule: '@invalid"),
+        (4, 18, "Unsupported property declaration: 'display: invalid;'"),
+    ]);
+}
+
+#[test]
+fn test_scope_rule() {
+    PREFS.set("layout.css.at-scope.enabled", PrefValue::Boolean(true));
+    let css = r"
+    @scope (.card) to (.content) {
+        .title { color: blue; }
+        > .header { color: green; }
+        :scope { color: red; }
+        & .footer { color: black; }
+    }
+    @scope to (.limit) {
+        p { color: white; }
+    }
+    @scope (.a) ( { p { color: yellow; } }";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, media, lock,
+                                          None, None, QuirksMode::NoQuirks, 0);
+    let guard = stylesheet.shared_lock.read();
+    let rules = stylesheet.contents.rules.read_with(&guard);
+    assert_eq!(rules.0.len(), 2);
+
+    let mut serialized = String::new();
+    rules.0[0].to_css(&guard, &mut serialized).unwrap();
+    assert_eq!(serialized, "\
+@scope (.card) to (& .content) {
+  & .title { color: blue; }
+  & > .header { color: green; }
+  :scope { color: red; }
+  & .footer { color: black; }
+}");
+
+    let mut serialized = String::new();
+    rules.0[1].to_css(&guard, &mut serialized).unwrap();
+    assert_eq!(serialized, "\
+@scope to (& .limit) {
+  & p { color: white; }
+}");
+}
+
+#[test]
+fn test_starting_style_rule() {
+    PREFS.set("layout.css.nesting.enabled", PrefValue::Boolean(true));
+    PREFS.set("layout.css.starting-style-at-rules.enabled", PrefValue::Boolean(true));
+    let css = r"
+    @starting-style {
+        p { opacity: 0; }
+    }
+    div {
+        opacity: 1;
+        @starting-style { opacity: 0; }
+    }";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, media, lock,
+                                          None, None, QuirksMode::NoQuirks, 0);
+    let guard = stylesheet.shared_lock.read();
+    let rules = stylesheet.contents.rules.read_with(&guard);
+    assert_eq!(rules.0.len(), 2);
+
+    let mut serialized = String::new();
+    rules.0[0].to_css(&guard, &mut serialized).unwrap();
+    assert_eq!(serialized, "\
+@starting-style {
+  p { opacity: 0; }
+}");
+
+    let mut serialized = String::new();
+    rules.0[1].to_css(&guard, &mut serialized).unwrap();
+    assert_eq!(serialized, "\
+div {
+  opacity: 1;
+  @starting-style {
+    & { opacity: 0; }
+  }
+}");
+}
+
+#[test]
+fn test_container_style_query() {
+    PREFS.set("layout.css.container-queries.enabled", PrefValue::Boolean(true));
+    PREFS.set("layout.css.style-queries.enabled", PrefValue::Boolean(true));
+    let css = r"
+    @container style(--theme: dark) { p { color: white; } }
+    @container card style((--a) and (not (--b: 1px))) { p { color: red; } }
+    @container (width > 10px) or style(--c) { p { color: blue; } }
+    @container style(width: 10px) { p { color: green; } }";
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, media, lock,
+                                          None, None, QuirksMode::NoQuirks, 0);
+    let guard = stylesheet.shared_lock.read();
+    let rules = stylesheet.contents.rules.read_with(&guard);
+    assert_eq!(rules.0.len(), 4);
+
+    let serialized = rules.0.iter().map(|rule| {
+        let mut serialized = String::new();
+        rule.to_css(&guard, &mut serialized).unwrap();
+        serialized.lines().next().unwrap().to_owned()
+    }).collect::<Vec<_>>();
+    assert_eq!(serialized, [
+        "@container style(--theme: dark) {",
+        "@container card style((--a) and (not (--b: 1px))) {",
+        "@container (width > 10px) or style(--c) {",
+        // Standard properties aren't supported yet, so this is a
+        // <general-enclosed>.
+        "@container style(width: 10px) {",
+    ]);
+}
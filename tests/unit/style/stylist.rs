@@ -2,23 +2,49 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use cssparser::SourceLocation;
-use euclid::Scale;
+use cssparser::{SourceLocation, RGBA};
+use dom::ElementState;
+use euclid::{Point2D, Rect, Scale, Vector2D};
 use euclid::Size2D;
+use mock_dom::MockElement;
+use selectors::matching::{MatchingContext, MatchingForInvalidation, MatchingMode};
+use selectors::matching::{NeedsSelectorFlags, SelectorCaches};
 use selectors::parser::{AncestorHashes, Selector};
+use serde_json::json;
 use servo_arc::Arc;
 use servo_atoms::Atom;
-use style::context::QuirksMode;
-use style::media_queries::{Device, MediaType};
-use style::properties::{PropertyDeclarationBlock, PropertyDeclaration};
-use style::properties::{longhands, Importance};
+use servo_config::prefs::{PREFS, PrefValue};
+use servo_url::ServoUrl;
+use style::animation::{maybe_start_animations, DocumentAnimationSet};
+use style::animation::{ElementAnimationSet, ScrollState};
+use style::applicable_declarations::{ApplicableDeclarationList, ScopeProximity};
+use style::context::{CascadeInputs, QuirksMode, RegisteredSpeculativePainter};
+use style::context::{RegisteredSpeculativePainters, SharedStyleContext, StyleSystemOptions};
+use style::context::{StyleContext, ThreadLocalStyleContext};
+use style::dom::{TElement, TNode};
+use style::invalidation::element::invalidation_map::{DependencyInvalidationKind, InvalidationMap};
+use style::invalidation::element::relative_selector::invalidate_relative_selectors_for_removal;
+use style::invalidation::element::restyle_hints::RestyleHint;
+use style::media_queries::{Device, MediaList, MediaType};
+use style::properties::{ComputedValues, PropertyDeclarationBlock, PropertyDeclaration};
+use style::properties::{longhands, parse_style_attribute, Importance, LonghandId};
+use style::properties::animated_properties::AnimationValue;
 use style::selector_map::SelectorMap;
-use style::selector_parser::{SelectorImpl, SelectorParser};
-use style::shared_lock::SharedRwLock;
-use style::stylesheets::StyleRule;
-use style::stylist::{Stylist, Rule};
+use style::selector_parser::{PseudoElement, SelectorImpl, SelectorParser};
+use style::servo::selector_parser::{ServoElementSnapshot, SnapshotMap};
+use style::shared_lock::{SharedRwLock, StylesheetGuards};
+use style::sharing::{SharingMissReason, StyleSharingStatistics};
+use style::style_resolver::{PseudoElementResolution, StyleResolverForElement};
+use style::stylesheets::{AllowImportRules, CssRuleType, DocumentStyleSheet, Origin, StyleRule, Stylesheet};
+use style::stylesheets::UserAgentStylesheets;
+use style::stylist::{RuleInclusion, Stylist, Rule};
 use style::stylist::needs_revalidation_for_testing;
 use style::thread_state::{self, ThreadState};
+use style::traversal_flags::TraversalFlags;
+use style::values::CustomIdent;
+use style::values::computed::Color as ComputedColor;
+use style::values::generics::CounterStyle;
+use style_traits::ToCss;
 
 /// Helper method to get some Rules from selector strings.
 /// Each sublist of the result contains the Rules for one StyleRule.
@@ -156,6 +182,53 @@ fn test_revalidation_selectors() {
     }
 }
 
+#[test]
+fn test_relative_selector_invalidation_map() {
+    let selectors = parse_selectors(&[
+        "div:has(> img)",
+        ".a:has(~ .b) span",
+        "main:has(:is(.c .d))",
+    ]);
+    let mut map = InvalidationMap::new();
+    let mut relative_map = InvalidationMap::new();
+    for selector in &selectors {
+        map.note_selector(selector, QuirksMode::NoQuirks, &mut relative_map).expect("OOM");
+    }
+
+    // The outer selectors don't depend on anything inside :has().
+    assert!(map.class_to_selector.get(&Atom::from("b"), QuirksMode::NoQuirks).is_none());
+    assert!(map.relative_selectors.is_empty());
+    assert!(map.class_to_selector.get(&Atom::from("a"), QuirksMode::NoQuirks).is_some());
+
+    // Every relative selector gets a dependency on its rightmost compound, so
+    // that insertions and removals can invalidate its anchors, pointing to the
+    // compound of the outer selector with the :has().
+    assert_eq!(relative_map.relative_selectors.len(), 3);
+    for dependency in &relative_map.relative_selectors {
+        assert!(dependency.selector.is_relative());
+        assert_eq!(dependency.selector_offset, 0);
+        assert!(dependency.parent.is_some());
+    }
+    let outer = relative_map.relative_selectors[0].parent.as_ref().unwrap();
+    assert_eq!(outer.invalidation_kind(), DependencyInvalidationKind::Element);
+    let outer = relative_map.relative_selectors[1].parent.as_ref().unwrap();
+    assert_eq!(outer.invalidation_kind(), DependencyInvalidationKind::Descendants);
+
+    let deps = relative_map.class_to_selector.get(&Atom::from("b"), QuirksMode::NoQuirks).unwrap();
+    assert_eq!(deps.len(), 1);
+    assert!(deps[0].selector.is_relative());
+
+    // A non-rightmost compound of a selector list nested in the relative
+    // selector depends on the compound of the relative selector containing
+    // the list.
+    let deps = relative_map.class_to_selector.get(&Atom::from("c"), QuirksMode::NoQuirks).unwrap();
+    assert_eq!(deps.len(), 1);
+    assert!(!deps[0].selector.is_relative());
+    assert!(deps[0].parent.as_ref().unwrap().selector.is_relative());
+
+    assert!(relative_map.class_to_selector.get(&Atom::from("d"), QuirksMode::NoQuirks).is_some());
+}
+
 #[test]
 fn test_rule_ordering_same_specificity() {
     let (rules_list, _) = get_mock_rules(&["a.intro", "img.sidebar"]);
@@ -199,3 +272,477 @@ fn test_stylist_rule_tree_accessors() {
     stylist.rule_tree();
     stylist.rule_tree().root();
 }
+
+fn document_sheet(lock: &SharedRwLock, css: &str, origin: Origin) -> DocumentStyleSheet {
+    let url = ServoUrl::parse("about::test").unwrap();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    DocumentStyleSheet(Arc::new(Stylesheet::from_str(css, url, origin, media, lock.clone(), None, None,
+                                                     QuirksMode::NoQuirks, 0, AllowImportRules::Yes)))
+}
+
+fn stylist_with_document_sheets(lock: &SharedRwLock, sheets: Vec<DocumentStyleSheet>) -> Stylist {
+    let mut stylist = mock_stylist();
+    let guard = lock.read();
+    for sheet in sheets {
+        stylist.append_stylesheet(sheet, &guard);
+    }
+    stylist.flush(&StylesheetGuards::same(&guard), None::<MockElement>, None);
+    stylist
+}
+
+fn stylist_with_sheets(lock: &SharedRwLock, sheets: &[(&str, Origin)]) -> Stylist {
+    stylist_with_document_sheets(lock, sheets.iter().map(|&(css, origin)| document_sheet(lock, css, origin)).collect())
+}
+
+fn initial_style(stylist: &Stylist, guards: &StylesheetGuards) -> Arc<ComputedValues> {
+    stylist.style_for_anonymous::<MockElement>(guards, &PseudoElement::ServoAnonymousBlock,
+                                               stylist.device().default_computed_values())
+}
+
+#[test]
+fn test_first_letter_property_restriction() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("div::first-letter { float: left; position: absolute }", Origin::Author),
+    ]);
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    let element = MockElement::new(&lock, "div", None, &[]);
+    let parent = initial_style(&stylist, &guards);
+    assert!(PseudoElement::FirstLetter.is_lazy() && PseudoElement::FirstLine.is_lazy());
+
+    let mut declarations = ApplicableDeclarationList::new();
+    let mut selector_caches = SelectorCaches::default();
+    let mut context = MatchingContext::new(MatchingMode::ForStatelessPseudoElement, None,
+                                           &mut selector_caches, QuirksMode::NoQuirks,
+                                           NeedsSelectorFlags::No, MatchingForInvalidation::No);
+    stylist.push_applicable_declarations(element, Some(&PseudoElement::FirstLetter), None, None,
+                                         Default::default(), RuleInclusion::All,
+                                         &mut declarations, &mut context);
+    assert_eq!(declarations.len(), 1);
+
+    let inputs = CascadeInputs {
+        rules: Some(stylist.rule_tree().compute_rule_node(&mut declarations, &guards)),
+        ..Default::default()
+    };
+    let style = stylist.compute_pseudo_element_style_with_inputs(
+        inputs, &PseudoElement::FirstLetter, &guards, Some(&*parent), Some(&*parent), Some(element));
+
+    assert_eq!(style.get_box().clone_float(), longhands::float::computed_value::T::Left);
+    assert_eq!(style.get_box().clone_position(), longhands::position::computed_value::T::Static,
+               "position doesn't apply to ::first-letter");
+}
+
+#[test]
+fn test_scope_proximity_wins_over_specificity() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    PREFS.set("layout.css.at-scope.enabled", PrefValue::Boolean(true));
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("@scope (.near) { div { color: green } } \
+          @scope (.far) { div.x { color: red } }", Origin::Author),
+    ]);
+    let far = MockElement::new(&lock, "div", None, &["far"]);
+    let near = MockElement::new(&lock, "div", None, &["near"]);
+    let element = MockElement::new(&lock, "div", None, &["x"]);
+    far.append_child(near);
+    near.append_child(element);
+
+    let mut declarations = ApplicableDeclarationList::new();
+    let mut selector_caches = SelectorCaches::default();
+    let mut context = MatchingContext::new(MatchingMode::Normal, None, &mut selector_caches,
+                                           QuirksMode::NoQuirks, NeedsSelectorFlags::No,
+                                           MatchingForInvalidation::No);
+    stylist.push_applicable_declarations(element, None, None, None, Default::default(),
+                                         RuleInclusion::All, &mut declarations, &mut context);
+    assert_eq!(declarations.len(), 2);
+
+    // The rule with the nearer scoping root comes last, even though it's less
+    // specific and comes first in the sheet.
+    let (far_rule, near_rule) = (&declarations[0], &declarations[1]);
+    assert!(near_rule.specificity < far_rule.specificity);
+    assert_eq!(near_rule.scope_proximity, ScopeProximity::new(1));
+    assert_eq!(far_rule.scope_proximity, ScopeProximity::new(2));
+}
+
+#[test]
+fn test_lazy_placeholder_style() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let user_agent_sheets = UserAgentStylesheets::new(
+        lock.clone(), vec![], document_sheet(&lock, "", Origin::UserAgent));
+    let mut sheets = user_agent_sheets.user_or_user_agent_stylesheets;
+    sheets.push(document_sheet(
+        &lock, "input::placeholder { opacity: 1; position: absolute; overflow-wrap: anywhere }", Origin::Author));
+    let stylist = stylist_with_document_sheets(&lock, sheets);
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    let input = MockElement::new(&lock, "input", None, &[]);
+    let parent = initial_style(&stylist, &guards);
+
+    let style = stylist.lazily_compute_pseudo_element_style(
+        &guards, input, &PseudoElement::Placeholder, RuleInclusion::All, &parent, &parent,
+        /* is_probe = */ true, None).expect("::placeholder should match");
+
+    assert_eq!(style.get_effects().clone_opacity(), 1.);
+    assert_eq!(style.get_box().clone_position(), longhands::position::computed_value::T::Static,
+               "position doesn't apply to ::placeholder");
+    assert_eq!(style.get_inherited_text().clone_overflow_wrap(),
+               longhands::overflow_wrap::computed_value::T::Normal,
+               "the user-agent !important rule should win");
+
+    let div = MockElement::new(&lock, "div", None, &[]);
+    assert!(stylist.lazily_compute_pseudo_element_style(
+        &guards, div, &PseudoElement::Placeholder, RuleInclusion::All, &parent, &parent,
+        /* is_probe = */ true, None).is_none());
+}
+
+#[test]
+fn test_lazy_marker_style() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("li::marker { direction: rtl; position: absolute }", Origin::Author),
+    ]);
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    let li = MockElement::new(&lock, "li", None, &[]);
+    let parent = initial_style(&stylist, &guards);
+
+    let style = stylist.lazily_compute_pseudo_element_style(
+        &guards, li, &PseudoElement::Marker, RuleInclusion::All, &parent, &parent,
+        /* is_probe = */ false, None).unwrap();
+
+    assert_eq!(style.get_inherited_box().clone_direction(),
+               longhands::direction::computed_value::T::Rtl);
+    assert_eq!(style.get_box().clone_position(), longhands::position::computed_value::T::Static,
+               "position doesn't apply to ::marker");
+}
+
+#[test]
+fn test_registered_property_font_relative_units() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    PREFS.set("layout.css.properties-and-values.enabled", PrefValue::Boolean(true));
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("@property --x { syntax: '<length>'; inherits: false; initial-value: 0px }", Origin::Author),
+    ]);
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    let url = ServoUrl::parse("about::test").unwrap();
+    let style_for = |parent: &ComputedValues, css: &str| {
+        let block = parse_style_attribute(css, &url, None, QuirksMode::NoQuirks, CssRuleType::Style);
+        stylist.compute_for_declarations::<MockElement>(&guards, parent, Arc::new(lock.wrap(block)))
+    };
+    let x = |style: &ComputedValues| {
+        style.custom_properties().unwrap().get(&Atom::from("x")).unwrap().to_css_string()
+    };
+
+    let parent = style_for(stylist.device().default_computed_values(), "font-size: 20px");
+    assert_eq!(x(&parent), "0px");
+    let child = style_for(&parent, "--x: 2em");
+    assert_eq!(x(&child), "40px", "em should resolve against the font of the element");
+}
+
+#[test]
+fn test_relative_color_from_currentcolor() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    PREFS.set("layout.css.more_color_4.enabled", PrefValue::Boolean(true));
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[]);
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    let url = ServoUrl::parse("about::test").unwrap();
+    let block = parse_style_attribute("color: blue; border-top-color: rgb(from currentcolor r g b / 50%)",
+                                      &url, None, QuirksMode::NoQuirks, CssRuleType::Style);
+    let style = stylist.compute_for_declarations::<MockElement>(
+        &guards, stylist.device().default_computed_values(), Arc::new(lock.wrap(block)));
+    assert_eq!(style.get_border().clone_border_top_color(),
+               ComputedColor::rgba(RGBA::new(0, 0, 255, 0.5)));
+}
+
+#[test]
+fn test_counter_style_registry() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("@counter-style marks { system: cyclic; symbols: '*' }", Origin::User),
+        ("@counter-style marks { system: cyclic; symbols: '+' }", Origin::Author),
+        ("@counter-style stars { system: cyclic; symbols: '*' }", Origin::User),
+        ("@counter-style few { system: fixed; symbols: a; fallback: stars }", Origin::UserAgent),
+        ("@counter-style more-stars { system: extends stars; suffix: '!' }", Origin::Author),
+        ("@counter-style few-marks { system: extends few; pad: 2 '0' }", Origin::Author),
+    ]);
+    let registry = stylist.counter_style_registry();
+    let style = |name: &str| CounterStyle::Name(CustomIdent(Atom::from(name)));
+    let counter = |name: &str, value| registry.counter_representation(&style(name), value);
+
+    assert_eq!(counter("marks", 1), "+");
+    assert_eq!(counter("stars", 1), "*");
+    assert_eq!(counter("lower-roman", 1), "i");
+    assert_eq!(counter("more-stars", 2), "*");
+    assert_eq!(registry.marker_representation(&style("more-stars"), 1), "*!");
+    assert_eq!(counter("few-marks", 1), "0a");
+    assert_eq!(counter("few-marks", 2), "*");
+}
+
+#[test]
+fn test_revalidation_selectors_at() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("div:first-child { color: red } div:empty { color: blue } div:nth-child(3) { color: green }",
+         Origin::Author),
+    ]);
+    let body = MockElement::new(&lock, "body", None, &[]);
+    let first = MockElement::new(&lock, "div", None, &[]);
+    let second = MockElement::new(&lock, "div", None, &[]);
+    body.append_child(first);
+    body.append_child(second);
+
+    let matches = |element| stylist.match_revalidation_selectors(
+        element, None, &mut SelectorCaches::default(), NeedsSelectorFlags::No);
+    let (for_first, for_second) = (matches(first), matches(second));
+    assert_eq!(for_first.len(), 3);
+    let indices: Vec<usize> = (0..for_first.len())
+        .filter(|i| for_first.get(*i) != for_second.get(*i))
+        .collect();
+    let failures = stylist.revalidation_selectors_at(second, &indices);
+    assert_eq!(failures, vec!["div:first-child".to_owned()]);
+
+    let mut statistics = StyleSharingStatistics::default();
+    statistics.rejections.insert(SharingMissReason::Revalidation, 1);
+    statistics.revalidation_failures = failures.into_iter().map(|s| (s, 1)).collect();
+    assert_eq!(serde_json::to_value(&statistics).unwrap()["revalidation_failures"],
+               json!({ "div:first-child": 1 }));
+}
+
+struct NoPainters;
+
+impl RegisteredSpeculativePainters for NoPainters {
+    fn get(&self, _name: &Atom) -> Option<&dyn RegisteredSpeculativePainter> {
+        None
+    }
+}
+
+/// Gives `elements` a style, with no pending restyle hint.
+fn mark_styled(stylist: &Stylist, lock: &SharedRwLock, elements: &[MockElement]) {
+    let guard = lock.read();
+    let style = initial_style(stylist, &StylesheetGuards::same(&guard));
+    for e in elements {
+        let mut data = unsafe { e.ensure_data() };
+        data.styles.primary = Some(style.clone());
+        data.hint = RestyleHint::empty();
+    }
+}
+
+/// Styles `elements`, then runs state invalidation for `element` having
+/// changed from `old_state` to its current state.
+fn invalidate_state_change(stylist: &Stylist, lock: &SharedRwLock, elements: &[MockElement],
+                           element: MockElement, old_state: ElementState) {
+    mark_styled(stylist, lock, elements);
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+
+    let mut snapshot = ServoElementSnapshot::new();
+    snapshot.state = Some(old_state);
+    let mut snapshot_map = SnapshotMap::new();
+    snapshot_map.insert(TNode::opaque(&element), snapshot);
+    element.set_has_snapshot();
+
+    let shared_context = SharedStyleContext {
+        stylist,
+        visited_styles_enabled: false,
+        options: StyleSystemOptions::default(),
+        guards,
+        current_time_for_animations: 0.,
+        traversal_flags: TraversalFlags::empty(),
+        snapshot_map: &snapshot_map,
+        animations: DocumentAnimationSet::default(),
+        registered_speculative_painters: &NoPainters,
+    };
+    element.mutate_data().unwrap().invalidate_style_if_needed(
+        element, &shared_context, None, &mut SelectorCaches::default());
+}
+
+fn restyle_hint(element: MockElement) -> RestyleHint {
+    element.borrow_data().unwrap().hint
+}
+
+#[test]
+fn test_focus_within_invalidation() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("form:focus-within > input { color: red }", Origin::Author),
+    ]);
+    let form = MockElement::new(&lock, "form", None, &[]);
+    let input = MockElement::new(&lock, "input", None, &[]);
+    let div = MockElement::new(&lock, "div", None, &[]);
+    form.append_child(input);
+    form.append_child(div);
+
+    form.set_state(ElementState::FOCUS_WITHIN);
+    invalidate_state_change(&stylist, &lock, &[form, input, div], form, ElementState::empty());
+    assert!(!restyle_hint(form).contains(RestyleHint::RESTYLE_SELF));
+    assert!(restyle_hint(input).contains(RestyleHint::RESTYLE_SELF));
+    assert!(restyle_hint(div).is_empty());
+
+    form.set_state(ElementState::FOCUS_WITHIN | ElementState::HOVER);
+    invalidate_state_change(&stylist, &lock, &[form, input, div], form, ElementState::FOCUS_WITHIN);
+    assert!(restyle_hint(input).is_empty(), ":hover changes don't affect the rule");
+}
+
+#[test]
+fn test_dir_invalidation() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("p:dir(rtl) { color: red }", Origin::Author),
+    ]);
+    let p = MockElement::new(&lock, "p", None, &[]);
+    let div = MockElement::new(&lock, "div", None, &[]);
+
+    p.set_state(ElementState::RTL);
+    invalidate_state_change(&stylist, &lock, &[p], p, ElementState::LTR);
+    assert!(restyle_hint(p).contains(RestyleHint::RESTYLE_SELF));
+
+    div.set_state(ElementState::RTL);
+    invalidate_state_change(&stylist, &lock, &[div], div, ElementState::LTR);
+    assert!(restyle_hint(div).is_empty());
+}
+
+#[test]
+fn test_relative_selector_removal_invalidation() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        (".a:has(> .b) { color: red }", Origin::Author),
+        (".x:has(+ .y) { color: red }", Origin::Author),
+    ]);
+    let body = MockElement::new(&lock, "body", None, &[]);
+    let a = MockElement::new(&lock, "div", None, &["a"]);
+    let b = MockElement::new(&lock, "div", None, &["b"]);
+    let x = MockElement::new(&lock, "div", None, &["x"]);
+    let removed = MockElement::new(&lock, "div", None, &[]);
+    let y = MockElement::new(&lock, "div", None, &["y"]);
+    body.append_child(a);
+    a.append_child(b);
+    body.append_child(x);
+    body.append_child(removed);
+    body.append_child(y);
+    let elements = [body, a, b, x, removed, y];
+
+    mark_styled(&stylist, &lock, &elements);
+    invalidate_relative_selectors_for_removal(b, &stylist);
+    a.remove_child(b);
+    assert!(restyle_hint(a).contains(RestyleHint::RESTYLE_SELF));
+    assert!(restyle_hint(body).is_empty());
+
+    // Removing the element between .x and .y makes .y the next sibling of .x,
+    // which doesn't affect the other previous siblings.
+    mark_styled(&stylist, &lock, &elements);
+    invalidate_relative_selectors_for_removal(removed, &stylist);
+    body.remove_child(removed);
+    assert!(restyle_hint(x).contains(RestyleHint::RESTYLE_SELF));
+    assert!(restyle_hint(a).is_empty());
+    assert!(restyle_hint(y).is_empty());
+    assert!(restyle_hint(body).is_empty());
+}
+
+/// Starts the animations of `element` for a style with the given declarations,
+/// and returns the opacity they animate it to at the current scroll position.
+fn scroll_driven_opacity(stylist: &Stylist, lock: &SharedRwLock, animations: &DocumentAnimationSet,
+                         element: MockElement, declarations: &str) -> Option<f32> {
+    let guard = lock.read();
+    let guards = StylesheetGuards::same(&guard);
+    let url = ServoUrl::parse("about::test").unwrap();
+    let block = parse_style_attribute(declarations, &url, None, QuirksMode::NoQuirks, CssRuleType::Style);
+    let style = stylist.compute_for_declarations::<MockElement>(
+        &guards, stylist.device().default_computed_values(), Arc::new(lock.wrap(block)));
+
+    let snapshot_map = SnapshotMap::new();
+    let shared_context = SharedStyleContext {
+        stylist,
+        visited_styles_enabled: false,
+        options: StyleSystemOptions::default(),
+        guards,
+        current_time_for_animations: 0.,
+        traversal_flags: TraversalFlags::empty(),
+        snapshot_map: &snapshot_map,
+        animations: animations.clone(),
+        registered_speculative_painters: &NoPainters,
+    };
+    let mut thread_local = ThreadLocalStyleContext::new();
+    let mut context = StyleContext { shared: &shared_context, thread_local: &mut thread_local };
+    let mut resolver = StyleResolverForElement::new(
+        element, &mut context, RuleInclusion::All, PseudoElementResolution::IfApplicable);
+    let mut set = ElementAnimationSet::default();
+    maybe_start_animations(element, &shared_context, &style, &mut set, &mut resolver);
+    assert_eq!(set.animations.len(), 1);
+
+    match set.get_value_map_for_active_animations(0.)?.get(&LonghandId::Opacity)? {
+        AnimationValue::Opacity(value) => Some(*value),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_scroll_driven_animations() {
+    thread_state::initialize(ThreadState::LAYOUT);
+    let lock = SharedRwLock::new();
+    let stylist = stylist_with_sheets(&lock, &[
+        ("@keyframes fade { from { opacity: 0 } to { opacity: 1 } }", Origin::Author),
+    ]);
+    let body = MockElement::new(&lock, "body", None, &[]);
+    let subject = MockElement::new(&lock, "div", None, &[]);
+    body.append_child(subject);
+    mark_styled(&stylist, &lock, &[body]);
+
+    // The body is the nearest scroll container of the subject, which is 50px
+    // tall and 200px below the top of the scrollable overflow area.
+    let animations = DocumentAnimationSet::default();
+    animations.set_scroll_state(body.as_node().opaque(), ScrollState {
+        offset: Vector2D::new(0., 60.),
+        scroll_range: Vector2D::new(0., 200.),
+        scrollport_size: Size2D::new(100., 100.),
+    });
+    animations.set_view_timeline_subject_rect(
+        subject.as_node().opaque(), Rect::new(Point2D::new(0., 200.), Size2D::new(100., 50.)));
+    let opacity = |declarations: &str| {
+        let opacity = scroll_driven_opacity(&stylist, &lock, &animations, subject,
+                                            &format!("animation: fade linear; {}", declarations));
+        opacity.map(|opacity| (opacity * 100.).round() / 100.)
+    };
+
+    assert_eq!(opacity("animation-timeline: scroll()"), Some(0.3));
+    assert_eq!(opacity("animation-timeline: scroll(); animation-range: 20px 100px"), Some(0.5));
+    assert_eq!(opacity("animation-timeline: scroll(); animation-range: 100px 200px"), None);
+    assert_eq!(opacity("animation-timeline: scroll(); animation-range: 100px 200px; \
+                        animation-fill-mode: backwards"), Some(0.));
+    assert_eq!(opacity("animation-timeline: scroll(); animation-iteration-count: 2; \
+                        animation-direction: alternate; animation-range: 0px 100px"), Some(0.8));
+
+    // The subject enters the scrollport at 100px, is fully visible from 150px
+    // to 200px and leaves it at 250px.
+    assert_eq!(opacity("animation-timeline: view()"), None);
+    animations.set_scroll_state(body.as_node().opaque(), ScrollState {
+        offset: Vector2D::new(0., 160.),
+        scroll_range: Vector2D::new(0., 200.),
+        scrollport_size: Size2D::new(100., 100.),
+    });
+    assert_eq!(opacity("animation-timeline: view()"), Some(0.4));
+    assert_eq!(opacity("animation-timeline: view(); animation-range: contain"), Some(0.2));
+    assert_eq!(opacity("animation-timeline: view(); animation-range: entry 50% exit 0%"), Some(0.47));
+    assert_eq!(opacity("animation-timeline: view(20px)"), Some(0.36));
+    assert_eq!(opacity("animation-timeline: view(); animation-range: entry"), None);
+
+    // The iterations of an animation that repeats forever have no length, so
+    // it's after the last one, which goes forwards.
+    assert_eq!(opacity("animation-timeline: view(); animation-iteration-count: infinite"), None);
+    assert_eq!(opacity("animation-timeline: view(); animation-iteration-count: infinite; \
+                        animation-fill-mode: forwards; animation-direction: alternate-reverse"), Some(1.));
+    assert_eq!(opacity("animation-timeline: view(); animation-iteration-count: infinite; \
+                        animation-fill-mode: forwards; animation-direction: reverse"), Some(0.));
+}